@@ -50,10 +50,8 @@ fn main() {
     let name = "example-bezier".to_string();
     let config = Config::default();
 
-    let mut swin = {
-        let wattrs = config.to_window_attributes().unwrap();
-        niw::SingleWindow::<State, ()>::from_config(wattrs).unwrap()
-    };
+    let mut swin =
+        niw::SingleWindow::<State, ()>::from_config_winit(&config.winit).unwrap();
 
     let screen = pollster::block_on(Screen::new(
         name.clone(),
@@ -80,7 +78,7 @@ fn main() {
 }
 
 fn on_redraw_requested(
-    _: &Window,
+    _: Option<&Window>,
     state: &mut State,
     _event: &mut Event<()>,
 ) -> Option<ControlFlow> {
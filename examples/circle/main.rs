@@ -62,6 +62,7 @@ impl State {
             transforms: &Transforms::empty(),
             device: self.render.as_device(),
             queue: self.render.as_queue(),
+            cache: self.render.as_cache(),
         };
         let mut target = self.render.to_color_target();
         self.domr.redraw(&context, &mut encoder, &mut target).unwrap();
@@ -79,11 +80,8 @@ fn main() {
     let name = "example-circle".to_string();
     let config = Config::default();
 
-    let mut swin = {
-        let wattrs = config.to_window_attributes().unwrap();
-        info!("winit::Window size {:?}", wattrs.inner_size);
-        niw::SingleWindow::<State, ()>::from_config(wattrs).unwrap()
-    };
+    let mut swin =
+        niw::SingleWindow::<State, ()>::from_config_winit(&config.winit).unwrap();
 
     let screen = pollster::block_on(Screen::new(
         name.clone(),
@@ -115,7 +113,7 @@ fn main() {
 }
 
 fn on_redraw_requested(
-    _: &Window,
+    _: Option<&Window>,
     state: &mut State,
     _event: &mut Event<()>,
 ) -> Option<ControlFlow> {
@@ -124,7 +122,7 @@ fn on_redraw_requested(
 }
 
 fn on_win_resized(
-    _: &Window,
+    _: Option<&Window>,
     state: &mut State,
     event: &mut Event<()>,
 ) -> Option<ControlFlow> {
@@ -144,7 +142,7 @@ fn on_win_resized(
 }
 
 fn on_win_scale_factor_changed(
-    _: &Window,
+    _: Option<&Window>,
     state: &mut State,
     event: &mut Event<()>,
 ) -> Option<ControlFlow> {
@@ -175,8 +173,11 @@ fn make_dom(opts: &Opt, render: &Render, format: wgpu::TextureFormat) -> dom::Do
             fill: opts.fill,
             ..circle::Attributes::default()
         };
-        let device = render.as_device();
-        shape::Shape::new_circle(circle::Circle::new(attrs, device, format)).into()
+        let transforms = Transforms::empty();
+        let screen = render.as_screen();
+        let context = screen.to_context(&transforms);
+        let circle = circle::Circle::new(attrs, &context, format);
+        shape::Shape::new_circle(circle).into()
     };
     let mut win = win::Win::new(vec![shape]);
     win.resize(render.to_extent3d().into(), Some(render.to_scale_factor()));
@@ -42,6 +42,7 @@ impl State {
             transforms: &Transforms::empty(),
             device: &screen.device,
             queue: &screen.queue,
+            cache: &screen.cache,
         };
         let mut target = self.render.to_color_target();
         target.view_port = Viewport {
@@ -73,10 +74,8 @@ fn main() {
     let name = "example-cls".to_string();
     let config = Config::default();
 
-    let mut swin = {
-        let wattrs = config.to_window_attributes().unwrap();
-        niw::SingleWindow::<State, ()>::from_config(wattrs).unwrap()
-    };
+    let mut swin =
+        niw::SingleWindow::<State, ()>::from_config_winit(&config.winit).unwrap();
 
     let screen = pollster::block_on(Screen::new(
         name.clone(),
@@ -118,7 +117,7 @@ fn main() {
 }
 
 fn on_redraw_requested(
-    _: &Window,
+    _: Option<&Window>,
     state: &mut State,
     _event: &mut Event<()>,
 ) -> Option<ControlFlow> {
@@ -5,6 +5,15 @@ use winit::{
     window::Window,
 };
 
+use std::{
+    fs, path,
+    sync::{
+        atomic::{AtomicBool, Ordering::SeqCst},
+        Arc,
+    },
+    thread, time,
+};
+
 use gpgpu::{niw, Config, Render, Screen};
 
 const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
@@ -13,12 +22,28 @@ const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
 pub struct Opt {
     #[structopt(long = "event")]
     event_name: Option<String>,
+
+    /// Record all events to file, as JSON lines.
+    #[structopt(long = "record")]
+    record: Option<String>,
+
+    /// Export event statistics on exit, as JSON or CSV based on file extension.
+    #[structopt(long = "export")]
+    export: Option<String>,
+
+    /// Redraw only on user events, posted by a worker thread every `ticker` secs.
+    #[structopt(long = "on-demand")]
+    on_demand: bool,
+
+    #[structopt(long = "ticker", default_value = "2")]
+    ticker: u64,
 }
 
 struct State {
     opts: Opt,
     render: Render,
     events_log: niw::EventsLog,
+    dirty: Arc<AtomicBool>,
 }
 
 impl AsMut<Render> for State {
@@ -35,10 +60,8 @@ fn main() {
     let name = "example-event-loop".to_string();
     let config = Config::default();
 
-    let mut swin = {
-        let wattrs = config.to_window_attributes().unwrap();
-        niw::SingleWindow::<State, ()>::from_config(wattrs).unwrap()
-    };
+    let mut swin =
+        niw::SingleWindow::<State, ()>::from_config_winit(&config.winit).unwrap();
 
     let screen = pollster::block_on(Screen::new(
         name.clone(),
@@ -54,10 +77,34 @@ fn main() {
         opts: opts.clone(),
         render,
         events_log: niw::EventsLog::default(),
+        dirty: swin.to_dirty_flag(),
     };
 
+    if opts.on_demand {
+        swin.set_redraw_mode(niw::RedrawMode::OnDemand);
+
+        let proxy = swin.to_event_proxy();
+        let ticker = time::Duration::from_secs(opts.ticker);
+        thread::spawn(move || loop {
+            thread::sleep(ticker);
+            if proxy.post(()).is_err() {
+                break;
+            }
+        });
+    }
+    swin.to_timers()
+        .write()
+        .set_interval("stats", time::Duration::from_secs(1))
+        .unwrap();
+
+    if let Some(loc) = opts.record.as_ref() {
+        swin.record(loc).unwrap();
+    }
+
     swin.on_win_keyboard_input(Box::new(on_win_keyboard_input))
         .on_redraw_requested(Box::new(on_redraw_requested))
+        .on_user_event(Box::new(on_user_event))
+        .on_timer("stats", Box::new(on_stats_timer))
         .on_event(Box::new(on_event));
 
     println!("Press Esc to exit");
@@ -65,7 +112,7 @@ fn main() {
 }
 
 fn on_win_keyboard_input(
-    _: &Window,
+    _: Option<&Window>,
     state: &mut State,
     event: &mut Event<()>,
 ) -> Option<ControlFlow> {
@@ -81,6 +128,9 @@ fn on_win_keyboard_input(
                 ..
             } => {
                 state.events_log.pretty_print();
+                if let Some(loc) = state.opts.export.as_ref() {
+                    export_stats(&state.events_log, loc)
+                }
                 Some(ControlFlow::Exit)
             }
             _ => None,
@@ -90,7 +140,7 @@ fn on_win_keyboard_input(
 }
 
 fn on_redraw_requested(
-    _: &Window,
+    _: Option<&Window>,
     state: &mut State,
     _event: &mut Event<()>,
 ) -> Option<ControlFlow> {
@@ -107,7 +157,30 @@ fn on_redraw_requested(
     None
 }
 
-fn on_event(_: &Window, state: &mut State, event: &mut Event<()>) -> Option<ControlFlow> {
+fn on_user_event(
+    _: Option<&Window>,
+    state: &mut State,
+    _event: &mut Event<()>,
+) -> Option<ControlFlow> {
+    state.dirty.store(true, SeqCst);
+    None
+}
+
+fn on_stats_timer(
+    _: Option<&Window>,
+    state: &mut State,
+    _event: &mut Event<()>,
+) -> Option<ControlFlow> {
+    let name = "Event::RedrawRequested";
+    println!("redraw rate {:.1}/s", state.events_log.to_rate(name));
+    None
+}
+
+fn on_event(
+    _: Option<&Window>,
+    state: &mut State,
+    event: &mut Event<()>,
+) -> Option<ControlFlow> {
     state.events_log.append(event);
     match state.opts.event_name.as_ref() {
         Some(event_name) if &niw::to_event_name(event).to_string() == event_name => {
@@ -117,3 +190,12 @@ fn on_event(_: &Window, state: &mut State, event: &mut Event<()>) -> Option<Cont
     }
     None
 }
+
+fn export_stats(events_log: &niw::EventsLog, loc: &str) {
+    let data = match path::Path::new(loc).extension().and_then(|x| x.to_str()) {
+        Some("csv") => events_log.to_csv(),
+        _ => events_log.to_json().unwrap(),
+    };
+    fs::write(loc, data).unwrap();
+    println!("event statistics exported to {:?}", loc);
+}
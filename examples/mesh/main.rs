@@ -0,0 +1,393 @@
+use cgmath::{Deg, Point3, Vector3};
+use log::info;
+use structopt::StructOpt;
+use winit::{
+    event::{Event, VirtualKeyCode, WindowEvent},
+    event_loop::ControlFlow,
+    window::Window,
+};
+
+use std::{path, sync::Arc};
+
+use gpgpu::{
+    model, niw,
+    primv::{clear, frame_graph, mesh},
+    util::{self, Spinlock},
+    Config, Context, Perspective, Render, Screen, Transforms,
+};
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+#[derive(Clone, StructOpt)]
+pub struct Opt {
+    #[structopt(long = "rotate", default_value = "0.5,1,0", use_delimiter = true)]
+    rotate: Vec<f32>,
+
+    /// One of flat, gouraud, phong.
+    #[structopt(long = "shading", default_value = "phong")]
+    shading: String,
+
+    /// Use a point light instead of directional light.
+    #[structopt(long = "point")]
+    point: bool,
+
+    /// Model file to render, obj, ply, stl, gltf or glb, defaults to a cube.
+    #[structopt(long = "model")]
+    model: Option<path::PathBuf>,
+
+    /// Image file to use as texture.
+    #[structopt(long = "texture")]
+    texture: Option<path::PathBuf>,
+
+    #[structopt(long = "msaa", default_value = "1")]
+    msaa: u32,
+
+    #[structopt(long = "fps", default_value = "60")]
+    fps: f64,
+
+    /// Show frame times overlay, toggle with F3.
+    #[structopt(long = "overlay")]
+    overlay: bool,
+
+    /// Save frame profiles in Chrome trace format, every 300 frames.
+    #[structopt(long = "trace")]
+    trace: Option<path::PathBuf>,
+}
+
+struct State {
+    opts: Opt,
+    render: Render,
+    rotate_by: Vec<f32>,
+    paused: bool,
+    eye: Point3<f32>,
+    p: Perspective<Deg<f32>>,
+    mesh: mesh::Mesh,
+    frame_rate: util::FrameRate,
+    gpu_timer: Option<util::GpuTimer>,
+    overlay: Option<frame_graph::FrameGraph>,
+    input: Arc<Spinlock<niw::InputState>>,
+}
+
+impl AsMut<Render> for State {
+    fn as_mut(&mut self) -> &mut Render {
+        &mut self.render
+    }
+}
+
+impl State {
+    fn redraw(&mut self) {
+        if !self.frame_rate.is_redraw() {
+            return;
+        }
+        self.frame_rate.begin_frame();
+
+        // arrow keys orbit the model, wheel zooms in and out.
+        {
+            let input = self.input.read();
+            if input.is_key_pressed(VirtualKeyCode::Left) {
+                self.rotate_by[1] -= 2.0;
+            }
+            if input.is_key_pressed(VirtualKeyCode::Right) {
+                self.rotate_by[1] += 2.0;
+            }
+            if input.is_key_pressed(VirtualKeyCode::Up) {
+                self.rotate_by[0] -= 2.0;
+            }
+            if input.is_key_pressed(VirtualKeyCode::Down) {
+                self.rotate_by[0] += 2.0;
+            }
+            let (_, lines) = input.to_wheel_lines();
+            self.eye.z = (self.eye.z - lines * 0.25).max(0.5);
+        }
+
+        let mut transforms = Transforms::empty();
+        transforms
+            .rotate_x_by(Deg(self.rotate_by[0]))
+            .rotate_y_by(Deg(self.rotate_by[1]))
+            .rotate_z_by(Deg(self.rotate_by[2]))
+            .look_at_rh(self.eye, Point3::new(0.0, 0.0, 0.0), Vector3::unit_y())
+            .perspective_by(self.p);
+
+        let mut encoder = {
+            let desc = wgpu::CommandEncoderDescriptor {
+                label: Some("examples/mesh:command-encoder"),
+            };
+            self.render.as_device().create_command_encoder(&desc)
+        };
+        if let Some(gpu_timer) = self.gpu_timer.as_mut() {
+            gpu_timer.begin(&mut encoder)
+        }
+
+        let context = Context {
+            transforms: &transforms,
+            device: self.render.as_device(),
+            queue: self.render.as_queue(),
+            cache: self.render.as_cache(),
+        };
+        let mut target = self.render.to_color_target();
+        clear::Clear::new(wgpu::Color::BLACK)
+            .redraw(&context, &mut encoder, &mut target)
+            .unwrap();
+        let profiler = self.render.to_profiler();
+        if let Some(profiler) = profiler.as_ref() {
+            let mut profiler = profiler.lock().unwrap();
+            profiler.begin_scope("examples/mesh:mesh", &mut encoder);
+        }
+        self.mesh.redraw(&context, &mut encoder, &mut target).unwrap();
+        if let Some(profiler) = profiler.as_ref() {
+            profiler.lock().unwrap().end_scope(&mut encoder);
+        }
+        if let Some(overlay) = self.overlay.as_mut() {
+            overlay.update(&self.frame_rate);
+            overlay.redraw(&context, &mut encoder, &mut target).unwrap();
+        }
+
+        if let Some(gpu_timer) = self.gpu_timer.as_mut() {
+            gpu_timer.end(&mut encoder);
+        }
+        self.render.submit(encoder).unwrap();
+        if let Some(gpu_timer) = self.gpu_timer.as_mut() {
+            gpu_timer.submitted();
+            for gpu in gpu_timer.collect(self.render.as_device()) {
+                self.frame_rate.add_gpu_time(gpu)
+            }
+        }
+
+        if !self.paused {
+            self.rotate_by[0] += self.opts.rotate[0];
+            self.rotate_by[1] += self.opts.rotate[1];
+            self.rotate_by[2] += self.opts.rotate[2];
+        }
+
+        self.frame_rate.end_frame();
+        if self.frame_rate.total() % 300 == 0 {
+            info!("{}", self.frame_rate.to_stats());
+            if let (Some(loc), Some(profiler)) = (self.opts.trace.as_ref(), profiler) {
+                profiler.lock().unwrap().save_chrome_trace(loc).unwrap();
+            }
+        }
+    }
+}
+
+fn main() {
+    env_logger::init();
+
+    let mut opts = Opt::from_args();
+    opts.rotate = match opts.rotate.as_slice() {
+        [] => vec![0.0, 0.0, 0.0],
+        [x] => vec![*x, 0.0, 0.0],
+        [x, y] => vec![*x, *y, 0.0],
+        [x, y, z, ..] => vec![*x, *y, *z],
+    };
+
+    let name = "example-mesh".to_string();
+    let config = Config::default();
+
+    let mut swin =
+        niw::SingleWindow::<State, ()>::from_config_winit(&config.winit).unwrap();
+
+    let screen = pollster::block_on(Screen::new(
+        name.clone(),
+        swin.as_window(),
+        Config::default(),
+    ))
+    .unwrap();
+
+    let mut render = Render::new(screen, FORMAT);
+    render.set_msaa(opts.msaa).set_depth(DEPTH_FORMAT);
+
+    let eye = Point3::new(0.0, 0.0, 3.0);
+    let mesh = {
+        let (vertices, indices) = match opts.model.as_ref() {
+            Some(loc) => {
+                let model::Model { vertices, indices } =
+                    model::Model::from_file(loc).unwrap();
+                (vertices, indices)
+            }
+            None => make_cube(),
+        };
+        let target = render.to_pipeline_target();
+        let (transforms, screen) = (Transforms::empty(), render.as_screen());
+        let context = screen.to_context(&transforms);
+        let mut mesh = mesh::Mesh::new(vertices, indices, &context, target).unwrap();
+
+        let shading = match opts.shading.as_str() {
+            "flat" => mesh::Shading::Flat,
+            "gouraud" => mesh::Shading::Gouraud,
+            _ => mesh::Shading::Phong,
+        };
+        let light = match opts.point {
+            true => mesh::Light::Point {
+                position: Point3::new(2.0, 2.0, 2.0),
+                color: wgpu::Color::WHITE,
+            },
+            false => mesh::Light::Directional {
+                direction: Vector3::new(-1.0, -1.0, -1.0),
+                color: wgpu::Color::WHITE,
+            },
+        };
+        mesh.set_lighting(mesh::Lighting {
+            light,
+            shading,
+            eye,
+            ..mesh::Lighting::default()
+        });
+
+        if let Some(file) = opts.texture.as_ref() {
+            let img = image::open(file).unwrap().to_rgba8();
+            mesh.set_texture(render.as_device(), render.as_queue(), &img);
+        }
+        mesh
+    };
+
+    let state = {
+        let p = Perspective {
+            fov: Deg(90.0),
+            aspect: render.as_screen().to_aspect_ratio(),
+            near: 0.1,
+            far: 100.0,
+        };
+
+        let mut frame_rate = util::FrameRate::new();
+        frame_rate.set_target_fps(Some(opts.fps));
+        let gpu_timer = util::GpuTimer::new(render.as_device(), render.as_queue());
+        if gpu_timer.is_none() {
+            info!("TIMESTAMP_QUERY not supported, GPU times are not measured");
+        }
+        let overlay = match opts.overlay {
+            true => {
+                let (transforms, screen) = (Transforms::empty(), render.as_screen());
+                let context = screen.to_context(&transforms);
+                let target = render.to_pipeline_target();
+                Some(frame_graph::FrameGraph::new(target, &context))
+            }
+            false => None,
+        };
+
+        render.start();
+        State {
+            opts: opts.clone(),
+            render,
+            rotate_by: vec![0.0, 0.0, 0.0],
+            paused: false,
+            eye,
+            p,
+            mesh,
+            frame_rate,
+            gpu_timer,
+            overlay,
+            input: swin.to_input_state(),
+        }
+    };
+
+    let mut bindings = config.bindings.clone();
+    bindings
+        .bind("pause", vec!["Space".parse().unwrap()])
+        .bind("reset", vec!["R".parse().unwrap(), "Ctrl+Home".parse().unwrap()])
+        .bind("overlay", vec!["F3".parse().unwrap()]);
+
+    swin.set_bindings(bindings)
+        .on_action("pause", Box::new(on_pause))
+        .on_action("reset", Box::new(on_reset))
+        .on_action("overlay", Box::new(on_overlay))
+        .on_win_resized(Box::new(on_win_resized))
+        .on_redraw_requested(Box::new(on_redraw_requested));
+
+    info!("Press arrow keys to rotate, scroll to zoom, Space to pause, R to reset");
+    info!("F3 to toggle frame times overlay");
+    info!("Esc to exit");
+    swin.run(state);
+}
+
+// unit cube centered at origin, with 4 vertices per face so that each face
+// gets its own normal and texture-coordinates.
+fn make_cube() -> (Vec<mesh::Vertex>, Vec<u32>) {
+    let faces: [([f32; 3], [f32; 3], [f32; 3], [f32; 4]); 6] = [
+        ([0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [1.0, 0.2, 0.2, 1.0]),
+        ([0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.2, 1.0, 0.2, 1.0]),
+        ([1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0], [0.2, 0.2, 1.0, 1.0]),
+        ([-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0], [1.0, 1.0, 0.2, 1.0]),
+        ([0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [1.0, 0.2, 1.0, 1.0]),
+        ([0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.2, 1.0, 1.0, 1.0]),
+    ];
+
+    let mut vertices = vec![];
+    let mut indices = vec![];
+    for (n, u, v, color) in faces.iter() {
+        let base = vertices.len() as u32;
+        for (su, sv) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+            let position = [
+                (n[0] + su * u[0] + sv * v[0]) * 0.5,
+                (n[1] + su * u[1] + sv * v[1]) * 0.5,
+                (n[2] + su * u[2] + sv * v[2]) * 0.5,
+            ];
+            vertices.push(mesh::Vertex {
+                position,
+                normal: *n,
+                uv: [(su + 1.0) / 2.0, (1.0 - sv) / 2.0],
+                color: *color,
+            });
+        }
+        indices.extend_from_slice(&[base, base + 1, base + 2, base, base + 2, base + 3]);
+    }
+
+    (vertices, indices)
+}
+
+fn on_redraw_requested(
+    _: Option<&Window>,
+    state: &mut State,
+    _event: &mut Event<()>,
+) -> Option<ControlFlow> {
+    state.redraw();
+    None
+}
+
+fn on_pause(
+    _: Option<&Window>,
+    state: &mut State,
+    _event: &mut Event<()>,
+) -> Option<ControlFlow> {
+    state.paused = !state.paused;
+    None
+}
+
+fn on_reset(
+    _: Option<&Window>,
+    state: &mut State,
+    _event: &mut Event<()>,
+) -> Option<ControlFlow> {
+    state.rotate_by = vec![0.0, 0.0, 0.0];
+    state.eye = Point3::new(0.0, 0.0, 3.0);
+    None
+}
+
+fn on_overlay(
+    _: Option<&Window>,
+    state: &mut State,
+    _event: &mut Event<()>,
+) -> Option<ControlFlow> {
+    state.overlay = match state.overlay.take() {
+        Some(_) => None,
+        None => {
+            let (transforms, screen) = (Transforms::empty(), state.render.as_screen());
+            let context = screen.to_context(&transforms);
+            let target = state.render.to_pipeline_target();
+            Some(frame_graph::FrameGraph::new(target, &context))
+        }
+    };
+    None
+}
+
+fn on_win_resized(
+    _: Option<&Window>,
+    state: &mut State,
+    event: &mut Event<()>,
+) -> Option<ControlFlow> {
+    if let Event::WindowEvent { event: WindowEvent::Resized(_), .. } = event {
+        state.p.aspect = state.render.as_screen().to_aspect_ratio();
+    }
+
+    None
+}
@@ -0,0 +1,123 @@
+use log::info;
+use winit::{
+    dpi::LogicalSize,
+    event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent},
+    event_loop::ControlFlow,
+    window::WindowAttributes,
+};
+
+use std::collections::HashMap;
+
+use gpgpu::{niw, primv::clear, Config, Context, Transforms};
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+type Windows = niw::Windows<State, ()>;
+
+struct State {
+    colors: HashMap<String, wgpu::Color>,
+}
+
+fn main() {
+    env_logger::init();
+
+    let config = Config::default();
+    let mut mwin = niw::MultiWindow::<State, ()>::new(config.clone(), FORMAT);
+
+    let mut handlers = make_handlers();
+    handlers.on_win_keyboard_input(Box::new(on_canvas_keyboard_input));
+    mwin.add_window_config("canvas", &config.winit, handlers).unwrap();
+
+    let state = {
+        let mut colors = HashMap::new();
+        colors.insert("canvas".to_string(), wgpu::Color::BLACK);
+        colors.insert("inspector".to_string(), wgpu::Color::BLUE);
+        colors.insert("palette".to_string(), wgpu::Color::GREEN);
+        State { colors }
+    };
+
+    println!("Press I to open inspector, P to open palette, Esc to exit");
+    mwin.run(state);
+}
+
+fn make_handlers() -> niw::WindowHandlers<State, ()> {
+    let mut handlers = niw::WindowHandlers::default();
+    handlers.on_redraw_requested(Box::new(on_redraw_requested));
+    handlers
+}
+
+fn on_canvas_keyboard_input(
+    windows: &mut Windows,
+    _state: &mut State,
+    event: &mut Event<()>,
+) -> Option<ControlFlow> {
+    let name = match event {
+        Event::WindowEvent {
+            event:
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(code),
+                            ..
+                        },
+                    ..
+                },
+            ..
+        } => match code {
+            VirtualKeyCode::I => "inspector",
+            VirtualKeyCode::P => "palette",
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    match windows.find(name) {
+        Some(wid) => windows.close(wid),
+        None => {
+            let mut wattrs = WindowAttributes::default();
+            wattrs.title = name.to_string();
+            wattrs.inner_size = Some(LogicalSize::new(320, 480).into());
+            windows.open(name, wattrs, make_handlers());
+        }
+    }
+    info!("{} windows open", windows.len());
+
+    None
+}
+
+fn on_redraw_requested(
+    windows: &mut Windows,
+    state: &mut State,
+    event: &mut Event<()>,
+) -> Option<ControlFlow> {
+    let wid = match event {
+        Event::RedrawRequested(wid) => *wid,
+        _ => return None,
+    };
+    let color = match windows.to_name(wid) {
+        Some(name) => state.colors[&name],
+        None => return None,
+    };
+    let render = windows.as_render_mut(wid).unwrap();
+
+    let mut encoder = {
+        let desc = wgpu::CommandEncoderDescriptor {
+            label: Some("examples/multi_window:command-encoder"),
+        };
+        render.as_device().create_command_encoder(&desc)
+    };
+
+    let context = Context {
+        transforms: &Transforms::empty(),
+        device: render.as_device(),
+        queue: render.as_queue(),
+        cache: render.as_cache(),
+    };
+    let mut target = render.to_color_target();
+    clear::Clear::new(color).redraw(&context, &mut encoder, &mut target).unwrap();
+
+    render.submit(encoder).unwrap();
+
+    None
+}
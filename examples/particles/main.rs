@@ -0,0 +1,164 @@
+use log::info;
+use rand::random;
+use structopt::StructOpt;
+use winit::{
+    event::{Event, WindowEvent},
+    event_loop::ControlFlow,
+    window::Window,
+};
+
+use gpgpu::{niw, primv::batch, util, Config, Context, Render, Screen, Transforms};
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+#[derive(StructOpt)]
+pub struct Opt {
+    #[structopt(short = "n", default_value = "100000")]
+    n_particles: usize,
+
+    #[structopt(long = "radius", default_value = "2")]
+    radius: f32,
+}
+
+struct State {
+    render: Render,
+    batch: batch::Batch,
+    velocities: Vec<(f32, f32)>,
+    frame_rate: util::FrameRate,
+}
+
+impl AsMut<Render> for State {
+    fn as_mut(&mut self) -> &mut Render {
+        &mut self.render
+    }
+}
+
+impl State {
+    fn redraw(&mut self) {
+        if !self.frame_rate.is_redraw() {
+            return;
+        }
+
+        let extent: gpgpu::Extent = self.render.to_extent3d().into();
+        let n = self.batch.len();
+        for (attrs, (dx, dy)) in
+            self.batch.as_mut_range(0..n).iter_mut().zip(self.velocities.iter_mut())
+        {
+            attrs.center.x += *dx;
+            attrs.center.y += *dy;
+            if attrs.center.x < 0.0 || attrs.center.x > extent.width {
+                *dx = -*dx;
+            }
+            if attrs.center.y < 0.0 || attrs.center.y > extent.height {
+                *dy = -*dy;
+            }
+        }
+
+        let mut encoder = {
+            let desc = wgpu::CommandEncoderDescriptor {
+                label: Some("examples/particles:command-encoder"),
+            };
+            self.render.as_device().create_command_encoder(&desc)
+        };
+
+        let context = Context {
+            transforms: &Transforms::empty(),
+            device: self.render.as_device(),
+            queue: self.render.as_queue(),
+            cache: self.render.as_cache(),
+        };
+        let mut target = self.render.to_color_target();
+        gpgpu::primv::clear::Clear::new(wgpu::Color::BLACK)
+            .redraw(&context, &mut encoder, &mut target)
+            .unwrap();
+        self.batch.redraw(&context, &mut encoder, &mut target).unwrap();
+
+        self.render.submit(encoder).unwrap();
+
+        self.frame_rate.next_frame_after(10_000);
+    }
+}
+
+fn main() {
+    env_logger::init();
+
+    let opts = Opt::from_args();
+    let name = "example-particles".to_string();
+    let config = Config::default();
+
+    let mut swin =
+        niw::SingleWindow::<State, ()>::from_config_winit(&config.winit).unwrap();
+
+    let screen = pollster::block_on(Screen::new(
+        name.clone(),
+        swin.as_window(),
+        Config::default(),
+    ))
+    .unwrap();
+
+    let mut render = Render::new(screen, FORMAT);
+
+    let state = {
+        let extent: gpgpu::Extent = render.to_extent3d().into();
+        let mut batch = {
+            let transforms = Transforms::empty();
+            let screen = render.as_screen();
+            let context = screen.to_context(&transforms);
+            batch::Batch::new(&context, FORMAT)
+        };
+        batch.resize(extent, None);
+        batch.extend((0..opts.n_particles).map(|_| batch::Attributes {
+            center:
+                (random::<f32>() * extent.width, random::<f32>() * extent.height).into(),
+            radius: opts.radius,
+            fill: true,
+            fg: wgpu::Color {
+                r: random::<f64>(),
+                g: random::<f64>(),
+                b: random::<f64>(),
+                a: 1.0,
+            },
+            ..batch::Attributes::default()
+        }));
+        let velocities = (0..opts.n_particles)
+            .map(|_| (random::<f32>() * 4.0 - 2.0, random::<f32>() * 4.0 - 2.0))
+            .collect();
+
+        render.start();
+        State {
+            render,
+            batch,
+            velocities,
+            frame_rate: util::FrameRate::new(),
+        }
+    };
+
+    swin.on_win_resized(Box::new(on_win_resized))
+        .on_redraw_requested(Box::new(on_redraw_requested));
+
+    info!("Press Esc to exit");
+    swin.run(state);
+}
+
+fn on_redraw_requested(
+    _: Option<&Window>,
+    state: &mut State,
+    _event: &mut Event<()>,
+) -> Option<ControlFlow> {
+    state.redraw();
+    None
+}
+
+fn on_win_resized(
+    _: Option<&Window>,
+    state: &mut State,
+    event: &mut Event<()>,
+) -> Option<ControlFlow> {
+    if let Event::WindowEvent { event: WindowEvent::Resized(_), .. } = event {
+        let extent = state.render.to_extent3d();
+        info!("win_resized: extent:{:?}", extent);
+        state.batch.resize(extent.into(), None);
+    }
+
+    None
+}
@@ -7,14 +7,16 @@ use winit::{
     window::Window,
 };
 
-use std::{fs, path, time};
+use std::{path, time};
 
 use gpgpu::{
-    niw, primv::wireframe, Config, Context, Perspective, Render, Screen, Transforms,
+    model, niw, primv::wireframe, Config, Context, Perspective, Render, Screen,
+    Transforms,
 };
 
 const SSAA: f32 = 1.0;
 const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
 
 #[derive(Clone, StructOpt)]
 pub struct Opt {
@@ -24,8 +26,20 @@ pub struct Opt {
     #[structopt(long = "vertices")]
     vertices: path::PathBuf,
 
+    /// For model files, keep only edges whose adjacent faces bend by more
+    /// than this angle, in degrees.
+    #[structopt(long = "feature")]
+    feature: Option<f32>,
+
     #[structopt(long = "save")]
     save: Option<path::PathBuf>,
+
+    #[structopt(long = "msaa", default_value = "1")]
+    msaa: u32,
+
+    /// Reload shader and vertices file when they change, for development.
+    #[structopt(long = "hot-reload")]
+    hot_reload: bool,
 }
 
 struct State {
@@ -78,6 +92,7 @@ impl State {
             transforms: &transforms,
             device: &screen.device,
             queue: &screen.queue,
+            cache: &screen.cache,
         };
         self.wireframe.redraw(&context, &mut encoder, &mut target).unwrap();
 
@@ -108,10 +123,8 @@ fn main() {
     let name = "example-triangle".to_string();
     let config = Config::default();
 
-    let mut swin = {
-        let wattrs = config.to_window_attributes().unwrap();
-        niw::SingleWindow::<State, ()>::from_config(wattrs).unwrap()
-    };
+    let mut swin =
+        niw::SingleWindow::<State, ()>::from_config_winit(&config.winit).unwrap();
 
     let screen = pollster::block_on(Screen::new(
         name.clone(),
@@ -120,16 +133,32 @@ fn main() {
     ))
     .unwrap();
 
-    let wireframe = {
-        let data = fs::read(opts.vertices.clone()).unwrap();
-        wireframe::Wireframe::from_bytes(&data, FORMAT, &screen.device).unwrap()
-    };
-
     let mut render = Render::new_super_sampled(screen, SSAA, FORMAT);
+    render.set_msaa(opts.msaa).set_depth(DEPTH_FORMAT);
     if let Some(loc) = opts.save.clone() {
         render.save_gif(loc, FORMAT);
     }
 
+    let mut wireframe = {
+        let target = render.to_pipeline_target();
+        let transforms = Transforms::empty();
+        let screen = render.as_screen();
+        let context = screen.to_context(&transforms);
+        match (opts.feature, model::Format::from_path(&opts.vertices)) {
+            (Some(angle), Some(_)) => {
+                let model = model::Model::from_file(&opts.vertices).unwrap();
+                let edges = wireframe::Edges::Feature(Deg(angle));
+                model.to_wireframe(edges, &context, target).unwrap()
+            }
+            (_, _) => {
+                wireframe::Wireframe::from_file(&opts.vertices, target, &context).unwrap()
+            }
+        }
+    };
+    if opts.hot_reload {
+        wireframe.hot_reload();
+    }
+
     let state = {
         let p = Perspective {
             fov: Deg(90.0),
@@ -164,7 +193,7 @@ fn main() {
 }
 
 fn on_redraw_requested(
-    _: &Window,
+    _: Option<&Window>,
     state: &mut State,
     _event: &mut Event<()>,
 ) -> Option<ControlFlow> {
@@ -173,7 +202,7 @@ fn on_redraw_requested(
 }
 
 fn on_win_resized(
-    _: &Window,
+    _: Option<&Window>,
     state: &mut State,
     event: &mut Event<()>,
 ) -> Option<ControlFlow> {
@@ -191,7 +220,7 @@ fn on_win_resized(
 }
 
 fn on_win_keyboard_input(
-    _: &Window,
+    _: Option<&Window>,
     state: &mut State,
     event: &mut Event<()>,
 ) -> Option<ControlFlow> {
@@ -5,11 +5,13 @@ use std::{any::type_name, fmt, path, result};
 
 use gpgpu::{
     err_at,
+    primv::wireframe,
     util::{self, PrettyPrint},
     Error, Result, DEFAULT_SCALE_FACTOR,
 };
 
 mod info;
+mod render;
 
 #[derive(Clone, StructOpt)]
 pub struct Opt {
@@ -61,6 +63,37 @@ enum SubCommand {
         #[structopt(long = "args", use_delimiter = true)]
         args: Vec<f32>,
     },
+    /// Compose transforms from an expression, like
+    /// "translate(1,0,0) * rotate_y(30deg) * perspective(60deg,1.6,0.1,100)"
+    Compose {
+        #[structopt(long = "wireframe")]
+        loc: Option<path::PathBuf>,
+
+        expr: String,
+    },
+    /// Render wireframe, along with grid and axes, into an image file
+    Render {
+        #[structopt(long = "wireframe")]
+        loc: Option<path::PathBuf>,
+
+        /// Transform expression, refer compose subcommand
+        #[structopt(long = "mvp", default_value = "identity()")]
+        mvp: String,
+
+        /// Number of grid cells on either side of the origin
+        #[structopt(long = "grid", default_value = "10")]
+        grid: u32,
+
+        #[structopt(long = "size", default_value = "800,600", use_delimiter = true)]
+        size: Vec<u32>,
+
+        /// Use the CPU rasterizer even if an adapter is available
+        #[structopt(long = "cpu")]
+        cpu: bool,
+
+        #[structopt(long = "out")]
+        out: path::PathBuf,
+    },
 }
 
 fn main() {
@@ -75,6 +108,8 @@ fn main() {
         SubCommand::Translate { .. } => handle_translate(&opts),
         SubCommand::Rotate { .. } => handle_rotate(&opts),
         SubCommand::Perspective { .. } => handle_perspective(&opts),
+        SubCommand::Compose { .. } => handle_compose(&opts),
+        SubCommand::Render { .. } => handle_render(&opts),
     };
 
     res.map_err(|e| println!("Error {}", e)).ok();
@@ -236,6 +271,91 @@ fn handle_perspective(opts: &Opt) -> Result<()> {
     Ok(())
 }
 
+fn handle_compose(opts: &Opt) -> Result<()> {
+    use cgmath::{Euler, SquareMatrix};
+
+    let (loc, expr) = match &opts.subcmd {
+        SubCommand::Compose { loc, expr } => (loc, expr),
+        _ => unreachable!(),
+    };
+
+    let mat = gpgpu::compose(expr)?;
+
+    println!("Matrix");
+    mat.print();
+    println!();
+
+    println!("Inverse");
+    match mat.invert() {
+        Some(inv) => inv.print(),
+        None => println!("singular matrix"),
+    }
+    println!();
+
+    println!("Decomposition");
+    match gpgpu::decompose(&mat) {
+        Ok(trs) => {
+            let euler = Euler::from(trs.rotate);
+            let (t, s) = (trs.translate, trs.scale);
+            println!("translate: {:.4} {:.4} {:.4}", t.x, t.y, t.z);
+            println!(
+                "rotate:    {:.4} {:.4} {:.4} (degrees, about x, y, z)",
+                Deg::from(euler.x).0,
+                Deg::from(euler.y).0,
+                Deg::from(euler.z).0
+            );
+            println!("scale:     {:.4} {:.4} {:.4}", s.x, s.y, s.z);
+        }
+        Err(err) => println!("{}", err),
+    }
+
+    if let Some(loc) = loc {
+        let mut in_verts = load_from_file(loc)?;
+
+        println!();
+
+        println!("Input");
+        println!("{}", in_verts);
+
+        println!();
+
+        println!("Output");
+        println!("{}", in_verts.transform_mut(mat));
+    }
+
+    Ok(())
+}
+
+fn handle_render(opts: &Opt) -> Result<()> {
+    let (loc, mvp, grid, size, cpu, out) = match &opts.subcmd {
+        SubCommand::Render { loc, mvp, grid, size, cpu, out } => {
+            (loc, mvp, *grid, size, *cpu, out)
+        }
+        _ => unreachable!(),
+    };
+
+    let size = match size.as_slice() {
+        [width, height] if *width > 0 && *height > 0 => (*width, *height),
+        _ => err_at!(Invalid, msg: "invalid size {:?}, expected width,height", size)?,
+    };
+    if size.0 > render::MAX_SIZE || size.1 > render::MAX_SIZE {
+        err_at!(Invalid, msg: "size {:?} exceeds {}", size, render::MAX_SIZE)?
+    }
+    if grid > render::MAX_GRID {
+        err_at!(Invalid, msg: "grid {} exceeds {}", grid, render::MAX_GRID)?
+    }
+    let mvp = gpgpu::compose(mvp)?;
+    let vertices = match loc {
+        Some(loc) => wireframe::load_vertices(loc)?,
+        None => vec![],
+    };
+
+    render::render_wireframe(vertices, mvp, grid, size, out, cpu)?;
+    println!("Rendered to {:?}", out);
+
+    Ok(())
+}
+
 fn handle_angle(opts: &Opt) -> Result<()> {
     let rows = vec![info::AngleProperty::new_deg()];
     util::make_table(&rows).print_tty(!opts.force_color);
@@ -297,20 +417,10 @@ fn load_from_file<P>(loc: P) -> Result<Vertices>
 where
     P: AsRef<path::Path>,
 {
-    use std::{fs, str::from_utf8};
-
-    let data = err_at!(IOError, fs::read(loc))?;
-
-    let txt = err_at!(IOError, from_utf8(&data))?;
-    let mut vertices: Vec<Vertex> = vec![];
-    for line in txt.lines() {
-        Vertex::from_text_line(line)?.map(|v| vertices.push(v));
-    }
-
-    Ok(Vertices(vertices))
+    Ok(Vertices(wireframe::load_vertices(loc)?))
 }
 
-struct Vertices(Vec<Vertex>);
+struct Vertices(Vec<wireframe::Vertex>);
 
 impl fmt::Display for Vertices {
     fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
@@ -330,44 +440,3 @@ impl Vertices {
         self
     }
 }
-
-struct Vertex {
-    position: [f32; 4],
-    #[allow(dead_code)]
-    color: [f32; 4],
-}
-
-impl Vertex {
-    fn new(position: &[f32], color: &[f32]) -> Result<Vertex> {
-        let position: [f32; 4] = match position {
-            [x, y, z] => Point3::from((*x, *y, *z)).to_homogeneous().into(),
-            [x, y] => Point3::from((*x, *y, 0.0)).to_homogeneous().into(),
-            _ => err_at!(Invalid, msg: "invalid position {:?}", position)?,
-        };
-        let color: [f32; 4] = match color {
-            [r, g, b, a] => [*r, *g, *b, *a],
-            [r, g, b] => [*r, *g, *b, 1.0],
-            [] => [1.0, 1.0, 1.0, 1.0],
-            _ => err_at!(Invalid, msg: "invalid color {:?}", color)?,
-        };
-
-        Ok(Vertex { position, color })
-    }
-
-    fn from_text_line(txt: &str) -> Result<Option<Vertex>> {
-        match txt.split(";").collect::<Vec<&str>>().as_slice() {
-            ["", ""] | [""] | [] => Ok(None),
-            [pos, ""] => Some(Vertex::from_position(&util::parse_csv(pos)?)).transpose(),
-            [pos, color] => {
-                Some(Vertex::new(&util::parse_csv(pos)?, &util::parse_csv(color)?))
-                    .transpose()
-            }
-            [pos] => Some(Vertex::from_position(&util::parse_csv(pos)?)).transpose(),
-            _ => err_at!(Invalid, msg: "invalid vertex {}", txt),
-        }
-    }
-
-    fn from_position(position: &[f32]) -> Result<Vertex> {
-        Self::new(position, &[1.0, 1.0, 1.0, 1.0])
-    }
-}
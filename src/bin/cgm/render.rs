@@ -0,0 +1,294 @@
+use cgmath::{Matrix4, Vector4};
+use log::{info, warn};
+
+use std::path;
+
+use gpgpu::{
+    err_at,
+    primv::wireframe::{Vertex, Wireframe},
+    Cache, ColorTarget, Context, DepthTarget, Error, Result, SaveFile, Transforms,
+};
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// Upper bound on `--grid`, each cell adds four vertices to the line-list.
+pub const MAX_GRID: u32 = 1000;
+/// Upper bound on either dimension of `--size`, the CPU rasterizer holds
+/// 8 bytes per pixel.
+pub const MAX_SIZE: u32 = 16384;
+
+const GRID_COLOR: [f32; 4] = [0.25, 0.25, 0.25, 1.0];
+const AXES_COLOR: [[f32; 4]; 3] =
+    [[1.0, 0.0, 0.0, 1.0], [0.0, 1.0, 0.0, 1.0], [0.0, 0.0, 1.0, 1.0]];
+
+/// Render line-list of `vertices`, along with a grid of `grid` cells on either
+/// side of the origin on the xz-plane and the axes, transformed by `mvp` into
+/// an image file at `loc`. Rendering falls back to a CPU rasterizer if there
+/// is no adapter, or if `cpu` is true.
+pub fn render_wireframe(
+    vertices: Vec<Vertex>,
+    mvp: Matrix4<f32>,
+    grid: u32,
+    (width, height): (u32, u32),
+    loc: &path::Path,
+    cpu: bool,
+) -> Result<()> {
+    let mut vertices = vertices;
+    vertices.extend(to_grid_axes(grid));
+    vertices
+        .iter_mut()
+        .for_each(|v| v.position = (mvp * Vector4::from(v.position)).into());
+
+    let extent = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+
+    let adapter = match cpu {
+        true => None,
+        false => {
+            let instance = wgpu::Instance::new(wgpu::Backends::all());
+            let options = wgpu::RequestAdapterOptions::default();
+            pollster::block_on(instance.request_adapter(&options))
+        }
+    };
+
+    match adapter {
+        Some(adapter) => {
+            info!("rendering with adapter {:?}", adapter.get_info().name);
+            render_gpu(&adapter, vertices, extent, loc)
+        }
+        None => {
+            if !cpu {
+                warn!("no adapter found, falling back to cpu rasterizer");
+            }
+            render_cpu(&vertices, extent, loc)
+        }
+    }
+}
+
+fn render_gpu(
+    adapter: &wgpu::Adapter,
+    vertices: Vec<Vertex>,
+    extent: wgpu::Extent3d,
+    loc: &path::Path,
+) -> Result<()> {
+    let max = adapter.limits().max_texture_dimension_2d;
+    if extent.width > max || extent.height > max {
+        err_at!(
+            Invalid,
+            msg: "size {}x{} exceeds adapter limit {}", extent.width, extent.height, max
+        )?
+    }
+
+    let (device, queue) = {
+        let desc = wgpu::DeviceDescriptor {
+            label: Some("cgm/render"),
+            features: wgpu::Features::empty(),
+            limits: wgpu::Limits::downlevel_defaults().using_resolution(adapter.limits()),
+        };
+        err_at!(Fatal, pollster::block_on(adapter.request_device(&desc, None)))?
+    };
+
+    let texture = to_texture(&device, extent, FORMAT, "cgm/render:color");
+    let mut target = {
+        let depth = to_texture(&device, extent, DEPTH_FORMAT, "cgm/render:depth");
+        let depth = DepthTarget {
+            format: DEPTH_FORMAT,
+            view: depth.create_view(&wgpu::TextureViewDescriptor::default()),
+        };
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        ColorTarget {
+            depth: Some(depth),
+            ..ColorTarget::new(FORMAT, view)
+        }
+    };
+
+    let cache = Cache::default();
+    let context = Context {
+        transforms: &Transforms::empty(),
+        device: &device,
+        queue: &queue,
+        cache: &cache,
+    };
+    // vertices are already in clip-space.
+    let mut wireframe = Wireframe::new(vertices, target.to_pipeline_target(), &context);
+
+    let mut save_file = SaveFile::new_bmp(loc.to_path_buf(), &device, extent, FORMAT);
+
+    let mut encoder = {
+        let desc = wgpu::CommandEncoderDescriptor { label: Some("cgm/render:encoder") };
+        device.create_command_encoder(&desc)
+    };
+    wireframe.redraw(&context, &mut encoder, &mut target)?;
+    save_file.load_from_texture(&mut encoder, &device, &texture)?;
+    queue.submit(Some(encoder.finish()));
+
+    save_file.capture(&device)?;
+    save_file.save_to_file()?;
+
+    Ok(())
+}
+
+fn to_texture(
+    device: &wgpu::Device,
+    size: wgpu::Extent3d,
+    format: wgpu::TextureFormat,
+    label: &str,
+) -> wgpu::Texture {
+    let desc = wgpu::TextureDescriptor {
+        label: Some(label),
+        size,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+    };
+    device.create_texture(&desc)
+}
+
+fn render_cpu(
+    vertices: &[Vertex],
+    extent: wgpu::Extent3d,
+    loc: &path::Path,
+) -> Result<()> {
+    let (pixels, _) = rasterize(vertices, extent);
+
+    let imgbuf: image::RgbaImage =
+        match image::ImageBuffer::from_vec(extent.width, extent.height, pixels) {
+            Some(imgbuf) => imgbuf,
+            None => err_at!(Fatal, msg: "invalid image buffer {:?}", extent)?,
+        };
+    err_at!(IOError, imgbuf.save(loc), "file:{:?}", loc)?;
+    info!("Saving to image file, {:?}", loc);
+
+    Ok(())
+}
+
+// Rasterize line-list of clip-space `vertices`, same as the wireframe
+// pipeline, with depth test and sRGB encoding of the interpolated colors.
+// Return the RGBA pixels and the depth buffer, row-major from top-left.
+fn rasterize(vertices: &[Vertex], extent: wgpu::Extent3d) -> (Vec<u8>, Vec<f32>) {
+    let (width, height) = (extent.width as usize, extent.height as usize);
+    // opaque black, same as the default Style::bg that wireframe clears with.
+    let mut pixels = [0_u8, 0, 0, 255].repeat(width * height);
+    let mut depths = vec![1.0_f32; width * height];
+
+    for line in vertices.chunks_exact(2) {
+        let (a, b) = match clip_line(&line[0], &line[1]) {
+            Some(val) => val,
+            None => continue,
+        };
+
+        // viewport transform, pixel centers are at half-integer coordinates.
+        let to_window = |v: &Vertex| {
+            let [x, y, z, w] = v.position;
+            let (x, y) = ((x / w + 1.0) / 2.0, (1.0 - y / w) / 2.0);
+            (x * width as f32, y * height as f32, z / w)
+        };
+        let (ax, ay, az) = to_window(&a);
+        let (bx, by, bz) = to_window(&b);
+
+        let steps = (bx - ax).abs().max((by - ay).abs()).ceil().max(1.0) as usize;
+        for i in 0..=steps {
+            let t = i as f32 / steps as f32;
+            let (x, y) = (ax + (bx - ax) * t, ay + (by - ay) * t);
+            let (x, y) = (x.floor() as isize, y.floor() as isize);
+            if x < 0 || y < 0 || x >= width as isize || y >= height as isize {
+                continue;
+            }
+
+            let off = (y as usize) * width + (x as usize);
+            let z = az + (bz - az) * t;
+            if z >= depths[off] {
+                continue;
+            }
+            depths[off] = z;
+
+            for c in 0..4 {
+                let val = a.color[c] + (b.color[c] - a.color[c]) * t;
+                let val = if c < 3 { to_srgb(val) } else { val.clamp(0.0, 1.0) };
+                pixels[off * 4 + c] = (val * 255.0).round() as u8;
+            }
+        }
+    }
+
+    (pixels, depths)
+}
+
+// Clip segment `a`-`b` against the clip-space volume, `-w <= x,y <= w` and
+// `0 <= z <= w`. Return None if the segment is outside the volume.
+fn clip_line(a: &Vertex, b: &Vertex) -> Option<(Vertex, Vertex)> {
+    let distances = |v: &Vertex| {
+        let [x, y, z, w] = v.position;
+        [w + x, w - x, w + y, w - y, z, w - z]
+    };
+    let (da, db) = (distances(a), distances(b));
+
+    let (mut t0, mut t1) = (0.0_f32, 1.0_f32);
+    for (da, db) in da.into_iter().zip(db.into_iter()) {
+        match (da < 0.0, db < 0.0) {
+            (true, true) => return None,
+            (true, false) => t0 = t0.max(da / (da - db)),
+            (false, true) => t1 = t1.min(da / (da - db)),
+            (false, false) => (),
+        }
+    }
+    if t0 > t1 {
+        return None;
+    }
+
+    let lerp = |t: f32| {
+        let mut v = *a;
+        for i in 0..4 {
+            v.position[i] += (b.position[i] - a.position[i]) * t;
+            v.color[i] += (b.color[i] - a.color[i]) * t;
+        }
+        v
+    };
+
+    Some((lerp(t0), lerp(t1)))
+}
+
+fn to_srgb(val: f32) -> f32 {
+    let val = val.clamp(0.0, 1.0);
+    match val <= 0.0031308 {
+        true => val * 12.92,
+        false => 1.055 * val.powf(1.0 / 2.4) - 0.055,
+    }
+}
+
+// Line-list for grid on the xz-plane and the x, y, z axes.
+fn to_grid_axes(grid: u32) -> Vec<Vertex> {
+    let vertex = |position: [f32; 3], color: [f32; 4]| {
+        let [x, y, z] = position;
+        Vertex { position: [x, y, z, 1.0], color }
+    };
+
+    let n = grid as f32;
+    let mut vertices = vec![];
+
+    let len = n.max(1.0);
+    for (axis, color) in AXES_COLOR.iter().enumerate() {
+        let mut end = [0.0; 3];
+        end[axis] = len;
+        vertices.push(vertex([0.0, 0.0, 0.0], *color));
+        vertices.push(vertex(end, *color));
+    }
+
+    let grid = grid as i64;
+    for i in -grid..=grid {
+        let i = i as f32;
+        // grid lines through the origin stop where the positive axes start.
+        let end = if i == 0.0 { 0.0 } else { n };
+        vertices.push(vertex([i, 0.0, -n], GRID_COLOR));
+        vertices.push(vertex([i, 0.0, end], GRID_COLOR));
+        vertices.push(vertex([-n, 0.0, i], GRID_COLOR));
+        vertices.push(vertex([end, 0.0, i], GRID_COLOR));
+    }
+
+    vertices
+}
+
+#[cfg(test)]
+#[path = "render_test.rs"]
+mod render_test;
@@ -0,0 +1,87 @@
+use super::*;
+
+const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+const BLUE: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+fn vertex(position: [f32; 4], color: [f32; 4]) -> Vertex {
+    Vertex { position, color }
+}
+
+#[test]
+fn test_clip_line() {
+    // inside the volume, unchanged.
+    let (a, b) = (vertex([-0.5, 0.0, 0.5, 1.0], RED), vertex([0.5, 0.0, 0.5, 1.0], BLUE));
+    let (ca, cb) = clip_line(&a, &b).unwrap();
+    assert_eq!(ca.position, a.position);
+    assert_eq!(cb.position, b.position);
+
+    // right of the volume, and behind the near plane.
+    let (a, b) = (vertex([2.0, 0.0, 0.5, 1.0], RED), vertex([3.0, 0.0, 0.5, 1.0], RED));
+    assert!(clip_line(&a, &b).is_none());
+    let (a, b) = (vertex([0.0, 0.0, -0.5, 1.0], RED), vertex([0.0, 0.0, -0.1, 1.0], RED));
+    assert!(clip_line(&a, &b).is_none());
+
+    // crossing the left plane half-way, color is interpolated along.
+    let (a, b) = (vertex([-2.0, 0.0, 0.5, 1.0], RED), vertex([0.0, 0.0, 0.5, 1.0], BLUE));
+    let (ca, cb) = clip_line(&a, &b).unwrap();
+    assert_eq!(ca.position, [-1.0, 0.0, 0.5, 1.0]);
+    assert_eq!(ca.color, [0.5, 0.0, 0.5, 1.0]);
+    assert_eq!(cb.position, b.position);
+    assert_eq!(cb.color, b.color);
+}
+
+#[test]
+fn test_to_srgb() {
+    assert_eq!(to_srgb(0.0), 0.0);
+    assert!((to_srgb(1.0) - 1.0).abs() < 1e-6);
+    assert!((to_srgb(0.001) - 0.01292).abs() < 1e-6);
+    assert!((to_srgb(0.5) - 0.735357).abs() < 1e-5);
+    // out of range values are clamped.
+    assert_eq!(to_srgb(-1.0), 0.0);
+    assert!((to_srgb(2.0) - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn test_rasterize() {
+    let extent = wgpu::Extent3d { width: 8, height: 4, depth_or_array_layers: 1 };
+    let off = |x: usize, y: usize| y * 8 + x;
+
+    // horizontal segment from window (1,2) to (7,2), and a segment behind it.
+    let vertices = vec![
+        vertex([-0.75, 0.0, 0.5, 1.0], RED),
+        vertex([0.75, 0.0, 0.5, 1.0], RED),
+        vertex([-1.0, 0.0, 0.9, 1.0], BLUE),
+        vertex([1.0, 0.0, 0.9, 1.0], BLUE),
+    ];
+    let (pixels, depths) = rasterize(&vertices, extent);
+    assert_eq!(pixels.len(), 8 * 4 * 4);
+    assert_eq!(depths.len(), 8 * 4);
+
+    for y in 0..4 {
+        for x in 0..8 {
+            let o = off(x, y);
+            let (pixel, depth) = (&pixels[o * 4..o * 4 + 4], depths[o]);
+            match (x, y) {
+                (0, 2) => {
+                    assert_eq!(pixel, &[0, 0, 255, 255], "{},{}", x, y);
+                    assert_eq!(depth, 0.9, "{},{}", x, y);
+                }
+                (_, 2) => {
+                    assert_eq!(pixel, &[255, 0, 0, 255], "{},{}", x, y);
+                    assert_eq!(depth, 0.5, "{},{}", x, y);
+                }
+                (_, _) => {
+                    assert_eq!(pixel, &[0, 0, 0, 255], "{},{}", x, y);
+                    assert_eq!(depth, 1.0, "{},{}", x, y);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_to_grid_axes() {
+    assert_eq!(to_grid_axes(0).len(), 3 * 2 + 4);
+    assert_eq!(to_grid_axes(10).len(), 3 * 2 + 21 * 4);
+    assert_eq!(to_grid_axes(MAX_GRID).len(), 3 * 2 + (2 * MAX_GRID as usize + 1) * 4);
+}
@@ -52,6 +52,7 @@ impl State {
             transforms: &Transforms::empty(),
             device: &screen.device,
             queue: &screen.queue,
+            cache: &screen.cache,
         };
         let target = self.render.to_color_target();
 
@@ -77,10 +78,8 @@ pub fn handle_raster(opts: Opt) -> Result<()> {
         config.winit.inner_size = Some(size);
     };
 
-    let mut swin = {
-        let wattrs = config.to_window_attributes().unwrap();
-        niw::SingleWindow::<State, ()>::from_config(wattrs).unwrap()
-    };
+    let mut swin =
+        niw::SingleWindow::<State, ()>::from_config_winit(&config.winit).unwrap();
 
     let font = fonts::FontFile::new(loc)?;
 
@@ -109,7 +108,7 @@ pub fn handle_raster(opts: Opt) -> Result<()> {
 }
 
 fn on_redraw_requested(
-    _: &Window,
+    _: Option<&Window>,
     state: &mut State,
     _event: &mut Event<()>,
 ) -> Option<ControlFlow> {
@@ -118,7 +117,7 @@ fn on_redraw_requested(
 }
 
 fn on_win_resized(
-    _: &Window,
+    _: Option<&Window>,
     state: &mut State,
     event: &mut Event<()>,
 ) -> Option<ControlFlow> {
@@ -136,7 +135,7 @@ fn on_win_resized(
     None
 }
 fn on_win_scale_factor_changed(
-    _: &Window,
+    _: Option<&Window>,
     state: &mut State,
     event: &mut Event<()>,
 ) -> Option<ControlFlow> {
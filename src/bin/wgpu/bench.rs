@@ -0,0 +1,404 @@
+use prettytable::{cell, row};
+use serde_json::{json, Value};
+
+use std::time;
+
+use gpgpu::{
+    err_at,
+    util::{self, JsonRow, PrettyRow},
+    Error, Result,
+};
+
+use crate::Opt;
+
+// Shall match LOOPS in bench.wgsl.
+const KERNEL_LOOPS: u64 = 256;
+// Number of vec4<f32> elements processed by ALU kernels.
+const KERNEL_ELEMENTS: u64 = 1 << 20;
+const WORKGROUP_SIZE: u64 = 64;
+// Number of dispatches, per iteration, to measure dispatch overhead.
+const DISPATCHES: u32 = 1000;
+const TEXTURE_SIZE: u32 = 2048;
+// Upper bound for `--size`, in mega-bytes. wgpu 0.12 does not expose a buffer
+// size limit, and buffers are allocated on host as well.
+const MAX_SIZE: u64 = 1024;
+
+// Micro-benchmark result, with one column for each adapter. Column is None if
+// the benchmark could not be run on that adapter.
+pub struct Bench {
+    name: &'static str,
+    unit: &'static str,
+    adapters: Vec<Option<f64>>,
+}
+
+impl Bench {
+    fn new(name: &'static str, unit: &'static str) -> Bench {
+        Bench { name, unit, adapters: vec![] }
+    }
+}
+
+impl PrettyRow for Bench {
+    fn to_format() -> prettytable::format::TableFormat {
+        *prettytable::format::consts::FORMAT_CLEAN
+    }
+
+    fn to_head() -> prettytable::Row {
+        row![Fy => "Name", "Unit"]
+    }
+
+    fn to_row(&self) -> prettytable::Row {
+        let mut row = row![self.name, self.unit];
+        self.adapters.iter().for_each(|val| match val {
+            Some(val) => row.add_cell(cell![format!("{:.3}", val)]),
+            None => row.add_cell(cell!["-"]),
+        });
+        row
+    }
+}
+
+impl JsonRow for Bench {
+    fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "unit": self.unit,
+            "adapters": self.adapters,
+        })
+    }
+}
+
+// Run micro-benchmarks on each adapter, buffer benchmarks transfer `size`
+// mega-bytes, and each benchmark is repeated `iterations` times.
+pub fn info_bench(size: u64, iterations: u32, opts: &Opt) -> Result<Value> {
+    if size == 0 || iterations == 0 {
+        err_at!(Invalid, msg: "size and iterations must be non-zero")?
+    }
+    if size > MAX_SIZE {
+        err_at!(Invalid, msg: "size {}MB exceeds {}MB", size, MAX_SIZE)?
+    }
+
+    let instance = wgpu::Instance::new(wgpu::Backends::all());
+    let adapters: Vec<wgpu::Adapter> =
+        instance.enumerate_adapters(wgpu::Backends::all()).collect();
+
+    let mut benches = vec![
+        Bench::new("buffer_upload", "GB/s"),
+        Bench::new("buffer_download", "GB/s"),
+        Bench::new("texture_copy", "GB/s"),
+        Bench::new("dispatch_overhead", "us"),
+        Bench::new("fma_f32", "GFLOPS"),
+        Bench::new("mad_u32", "GIOPS"),
+        Bench::new("sin_f32", "Gsin/s"),
+    ];
+
+    for adapter in adapters.iter() {
+        let results = match Bencher::new(adapter, size << 20, iterations) {
+            Ok(bencher) => bencher.run(),
+            Err(err) => {
+                log::warn!("bench {:?}: {}", adapter.get_info().name, err);
+                vec![None; benches.len()]
+            }
+        };
+        benches.iter_mut().zip(results).for_each(|(b, r)| b.adapters.push(r));
+    }
+
+    if opts.json {
+        let infos: Vec<wgpu::AdapterInfo> =
+            adapters.iter().map(|a| a.get_info()).collect();
+        let val = json!({
+            "adapters": util::make_json(&infos),
+            "benches": util::make_json(&benches),
+        });
+        return Ok(val);
+    }
+
+    let mut table = prettytable::Table::new();
+    let mut titles = Bench::to_head();
+    for a in adapters.iter() {
+        let mut name = a.get_info().name.clone();
+        name.truncate(10);
+        titles.add_cell(cell![Fy -> name]);
+    }
+    table.set_titles(titles);
+    benches.iter().for_each(|r| {
+        table.add_row(r.to_row());
+    });
+    table.set_format(Bench::to_format());
+    table.print_tty(opts.force_color);
+
+    Ok(Value::Null)
+}
+
+struct Bencher {
+    device: wgpu::Device,
+    queue: wgpu::Queue,
+    compute: bool,
+    size: u64,
+    iterations: u32,
+}
+
+impl Bencher {
+    fn new(adapter: &wgpu::Adapter, size: u64, iterations: u32) -> Result<Bencher> {
+        let limits = adapter.limits();
+        let desc = wgpu::DeviceDescriptor {
+            label: Some("wgpu/bench"),
+            features: wgpu::Features::empty(),
+            limits: limits.clone(),
+        };
+        let (device, queue) =
+            err_at!(Fatal, pollster::block_on(adapter.request_device(&desc, None)))?;
+
+        let compute = adapter
+            .get_downlevel_properties()
+            .flags
+            .contains(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+            && u64::from(limits.max_storage_buffer_binding_size) >= KERNEL_ELEMENTS * 16;
+
+        Ok(Bencher { device, queue, compute, size, iterations })
+    }
+
+    // Return results in the same order as benches listed in `info_bench`.
+    fn run(&self) -> Vec<Option<f64>> {
+        let kernels = [("fma_f32", 32), ("mad_u32", 32), ("sin_f32", 16)];
+
+        let mut results = vec![
+            self.buffer_upload(),
+            self.buffer_download(),
+            self.texture_copy(),
+            self.dispatch_overhead(),
+        ];
+        for (entry_point, ops) in kernels.iter() {
+            results.push(self.kernel(entry_point, *ops))
+        }
+
+        results
+    }
+
+    fn buffer_upload(&self) -> Option<f64> {
+        let buffer = self.create_buffer(self.size, wgpu::BufferUsages::COPY_DST);
+        let data = vec![0xA5_u8; self.size as usize];
+
+        let elapsed = self.measure(|| {
+            self.queue.write_buffer(&buffer, 0, &data);
+            self.queue.submit(None);
+        });
+
+        Some(to_giga(self.size * u64::from(self.iterations), elapsed))
+    }
+
+    fn buffer_download(&self) -> Option<f64> {
+        use wgpu::BufferUsages;
+
+        let src = self.create_buffer(self.size, BufferUsages::COPY_SRC);
+        let dst = self
+            .create_buffer(self.size, BufferUsages::COPY_DST | BufferUsages::MAP_READ);
+
+        let mut ok = true;
+        let elapsed = self.measure(|| {
+            let mut encoder = self.create_encoder();
+            encoder.copy_buffer_to_buffer(&src, 0, &dst, 0, self.size);
+            self.queue.submit(Some(encoder.finish()));
+
+            let slice = dst.slice(..);
+            let request = slice.map_async(wgpu::MapMode::Read);
+            self.device.poll(wgpu::Maintain::Wait);
+            match pollster::block_on(request) {
+                Ok(()) => {
+                    drop(slice.get_mapped_range());
+                    dst.unmap();
+                }
+                Err(_) => ok = false,
+            }
+        });
+
+        ok.then(|| to_giga(self.size * u64::from(self.iterations), elapsed))
+    }
+
+    fn texture_copy(&self) -> Option<f64> {
+        let src = self.create_texture(wgpu::TextureUsages::COPY_SRC);
+        let dst = self.create_texture(wgpu::TextureUsages::COPY_DST);
+        let extent = wgpu::Extent3d {
+            width: TEXTURE_SIZE,
+            height: TEXTURE_SIZE,
+            depth_or_array_layers: 1,
+        };
+
+        let elapsed = self.measure(|| {
+            let mut encoder = self.create_encoder();
+            encoder.copy_texture_to_texture(
+                src.as_image_copy(),
+                dst.as_image_copy(),
+                extent,
+            );
+            self.queue.submit(Some(encoder.finish()));
+        });
+
+        let bytes = u64::from(TEXTURE_SIZE * TEXTURE_SIZE * 4);
+        Some(to_giga(bytes * u64::from(self.iterations), elapsed))
+    }
+
+    fn dispatch_overhead(&self) -> Option<f64> {
+        let (pipeline, bind_group) = self.create_pipeline("empty")?;
+
+        let elapsed = self.measure(|| {
+            let mut encoder = self.create_encoder();
+            {
+                let desc =
+                    wgpu::ComputePassDescriptor { label: Some("wgpu/bench:empty") };
+                let mut pass = encoder.begin_compute_pass(&desc);
+                pass.set_pipeline(&pipeline);
+                pass.set_bind_group(0, &bind_group, &[]);
+                (0..DISPATCHES).for_each(|_| pass.dispatch(1, 1, 1));
+            }
+            self.queue.submit(Some(encoder.finish()));
+        });
+
+        let n = f64::from(DISPATCHES) * f64::from(self.iterations);
+        Some(elapsed.as_secs_f64() * 1_000_000.0 / n)
+    }
+
+    fn kernel(&self, entry_point: &str, ops_per_loop: u64) -> Option<f64> {
+        let (pipeline, bind_group) = self.create_pipeline(entry_point)?;
+        let workgroups = (KERNEL_ELEMENTS / WORKGROUP_SIZE) as u32;
+
+        let elapsed = self.measure(|| {
+            let mut encoder = self.create_encoder();
+            {
+                let desc = wgpu::ComputePassDescriptor { label: Some(entry_point) };
+                let mut pass = encoder.begin_compute_pass(&desc);
+                pass.set_pipeline(&pipeline);
+                pass.set_bind_group(0, &bind_group, &[]);
+                pass.dispatch(workgroups, 1, 1);
+            }
+            self.queue.submit(Some(encoder.finish()));
+        });
+
+        let ops = KERNEL_ELEMENTS * KERNEL_LOOPS * ops_per_loop;
+        Some(to_giga(ops * u64::from(self.iterations), elapsed))
+    }
+
+    // Run `f` once to warm up, then `iterations` times, waiting for the GPU to
+    // finish after each run, return time taken by the later.
+    fn measure<F>(&self, mut f: F) -> time::Duration
+    where
+        F: FnMut(),
+    {
+        f();
+        self.device.poll(wgpu::Maintain::Wait);
+
+        let start = time::Instant::now();
+        for _ in 0..self.iterations {
+            f();
+            self.device.poll(wgpu::Maintain::Wait);
+        }
+        start.elapsed()
+    }
+
+    fn create_encoder(&self) -> wgpu::CommandEncoder {
+        let desc = wgpu::CommandEncoderDescriptor { label: Some("wgpu/bench:encoder") };
+        self.device.create_command_encoder(&desc)
+    }
+
+    fn create_buffer(&self, size: u64, usage: wgpu::BufferUsages) -> wgpu::Buffer {
+        let desc = wgpu::BufferDescriptor {
+            label: Some("wgpu/bench:buffer"),
+            size,
+            usage,
+            mapped_at_creation: false,
+        };
+        self.device.create_buffer(&desc)
+    }
+
+    fn create_texture(&self, usage: wgpu::TextureUsages) -> wgpu::Texture {
+        let desc = wgpu::TextureDescriptor {
+            label: Some("wgpu/bench:texture"),
+            size: wgpu::Extent3d {
+                width: TEXTURE_SIZE,
+                height: TEXTURE_SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage,
+        };
+        self.device.create_texture(&desc)
+    }
+
+    // Return None if adapter does not support compute shaders.
+    fn create_pipeline(
+        &self,
+        entry_point: &str,
+    ) -> Option<(wgpu::ComputePipeline, wgpu::BindGroup)> {
+        if !self.compute {
+            return None;
+        }
+
+        let bind_group_layout = {
+            let entry = wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::COMPUTE,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage { read_only: false },
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            };
+            let desc = wgpu::BindGroupLayoutDescriptor {
+                label: Some("wgpu/bench:bind-group-layout"),
+                entries: &[entry],
+            };
+            self.device.create_bind_group_layout(&desc)
+        };
+
+        let pipeline_layout = {
+            let desc = wgpu::PipelineLayoutDescriptor {
+                label: Some("wgpu/bench:pipeline-layout"),
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            };
+            self.device.create_pipeline_layout(&desc)
+        };
+
+        let module = {
+            let text = include_str!("bench.wgsl");
+            let desc = wgpu::ShaderModuleDescriptor {
+                label: Some("wgpu/bench:shader"),
+                source: wgpu::ShaderSource::Wgsl(text.into()),
+            };
+            self.device.create_shader_module(&desc)
+        };
+
+        let pipeline = {
+            let desc = wgpu::ComputePipelineDescriptor {
+                label: Some(entry_point),
+                layout: Some(&pipeline_layout),
+                module: &module,
+                entry_point,
+            };
+            self.device.create_compute_pipeline(&desc)
+        };
+
+        let buffer =
+            self.create_buffer(KERNEL_ELEMENTS * 16, wgpu::BufferUsages::STORAGE);
+        let bind_group = {
+            let desc = wgpu::BindGroupDescriptor {
+                label: Some("wgpu/bench:bind-group"),
+                layout: &bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+            };
+            self.device.create_bind_group(&desc)
+        };
+
+        Some((pipeline, bind_group))
+    }
+}
+
+// Return `n` per second, in giga units.
+fn to_giga(n: u64, elapsed: time::Duration) -> f64 {
+    (n as f64) / elapsed.as_secs_f64() / 1_000_000_000.0
+}
@@ -0,0 +1,59 @@
+// Built-in kernels for `wgpu bench`. Each invocation works on one element of
+// `data`, LOOPS and the number of statements in each loop shall match
+// KERNEL_LOOPS and the ops-per-loop in bench.rs.
+
+struct Data {
+    values: array<vec4<f32>>;
+};
+
+[[group(0), binding(0)]] var<storage, read_write> data: Data;
+
+let LOOPS: u32 = 256u;
+
+// Does nothing, to measure dispatch overhead.
+[[stage(compute), workgroup_size(64)]]
+fn empty([[builtin(global_invocation_id)]] id: vec3<u32>) {
+}
+
+// 4 x vec4 multiply-add per loop, 32 flops.
+[[stage(compute), workgroup_size(64)]]
+fn fma_f32([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    var a = data.values[id.x];
+    let b = vec4<f32>(0.9999, 0.9998, 0.9997, 0.9996);
+    let c = vec4<f32>(0.0001, 0.0002, 0.0003, 0.0004);
+    for (var i: u32 = 0u; i < LOOPS; i = i + 1u) {
+        a = a * b + c;
+        a = a * b + c;
+        a = a * b + c;
+        a = a * b + c;
+    }
+    data.values[id.x] = a;
+}
+
+// 4 x vec4 integer multiply-add per loop, 32 int-ops.
+[[stage(compute), workgroup_size(64)]]
+fn mad_u32([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    var a = vec4<u32>(data.values[id.x]);
+    let b = vec4<u32>(1664525u, 22695477u, 1103515245u, 134775813u);
+    let c = vec4<u32>(1013904223u, 1u, 12345u, 1u);
+    for (var i: u32 = 0u; i < LOOPS; i = i + 1u) {
+        a = a * b + c;
+        a = a * b + c;
+        a = a * b + c;
+        a = a * b + c;
+    }
+    data.values[id.x] = vec4<f32>(a);
+}
+
+// 4 x vec4 sine per loop, 16 transcendentals.
+[[stage(compute), workgroup_size(64)]]
+fn sin_f32([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    var a = data.values[id.x];
+    for (var i: u32 = 0u; i < LOOPS; i = i + 1u) {
+        a = sin(a);
+        a = sin(a);
+        a = sin(a);
+        a = sin(a);
+    }
+    data.values[id.x] = a;
+}
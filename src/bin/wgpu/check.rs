@@ -0,0 +1,129 @@
+use colored::Colorize;
+use serde_json::{json, Value};
+
+use std::{fs, path};
+
+use gpgpu::{
+    err_at,
+    pretty::{AdapterReport, Profile},
+    util, Error, Result,
+};
+
+use crate::Opt;
+
+// Return capabilities of every adapter, saved by `wgpu --json report` for
+// `wgpu diff`.
+pub fn info_capabilities(_opts: &Opt) -> Result<Value> {
+    let reports = to_adapter_reports();
+    err_at!(FailConvert, serde_json::to_value(&reports))
+}
+
+// Check every adapter against profile at `loc`, list what passes and what fails.
+// Return true along with the listing, if every adapter passes.
+pub fn check_profile(loc: &path::Path, opts: &Opt) -> Result<(Value, bool)> {
+    let profile = Profile::from_file(loc)?;
+    let reports = to_adapter_reports();
+
+    let mut items = vec![];
+    let mut all_pass = !reports.is_empty();
+    for report in reports.iter() {
+        let failures = profile.check(report);
+        let pass = failures.is_empty();
+        all_pass = all_pass && pass;
+
+        if opts.json {
+            items.push(json!({
+                "adapter": report.name,
+                "backend": report.backend,
+                "pass": pass,
+                "failures": util::make_json(&failures),
+            }));
+            continue;
+        }
+
+        let status = match pass {
+            true => "PASS".green(),
+            false => "FAIL".red(),
+        };
+        println!("{} {:?} ({})", status, report.name, report.backend);
+        if !pass {
+            util::make_table(&failures).print_tty(opts.force_color);
+        }
+        println!();
+    }
+    if reports.is_empty() && !opts.json {
+        println!("{}", "No adapter found".red());
+    }
+
+    Ok((Value::Array(items), all_pass))
+}
+
+// Compare adapter `a` in report `old` with adapter `b` in report `new`.
+pub fn diff_reports(
+    old: &path::Path,
+    new: &path::Path,
+    a: usize,
+    b: usize,
+    opts: &Opt,
+) -> Result<Value> {
+    let old = nth_report(&load_adapter_reports(old)?, a, old)?;
+    let new = nth_report(&load_adapter_reports(new)?, b, new)?;
+    let diffs = old.diff(&new);
+
+    if opts.json {
+        let val = json!({
+            "old": old.name,
+            "new": new.name,
+            "differences": util::make_json(&diffs),
+        });
+        return Ok(val);
+    }
+
+    println!("{:?} ({}) -> {:?} ({})", old.name, old.backend, new.name, new.backend);
+    match diffs.len() {
+        0 => println!("{}", "No difference".green()),
+        _ => {
+            util::make_table(&diffs).print_tty(opts.force_color);
+        }
+    }
+
+    Ok(Value::Null)
+}
+
+fn to_adapter_reports() -> Vec<AdapterReport> {
+    let instance = wgpu::Instance::new(wgpu::Backends::all());
+    instance
+        .enumerate_adapters(wgpu::Backends::all())
+        .map(|a| AdapterReport::from(&a))
+        .collect()
+}
+
+// Load adapter reports from JSON file, either saved by `wgpu --json report`,
+// or a list of adapter reports, or a single adapter report.
+fn load_adapter_reports(loc: &path::Path) -> Result<Vec<AdapterReport>> {
+    let data = err_at!(IOError, fs::read(loc))?;
+    let mut val: Value =
+        err_at!(FailConvert, serde_json::from_slice(&data), "{:?}", loc)?;
+
+    let val = match val.get_mut("capabilities") {
+        Some(caps) => caps.take(),
+        None => val,
+    };
+    let val = match val {
+        Value::Array(_) => val,
+        val => Value::Array(vec![val]),
+    };
+
+    err_at!(FailConvert, serde_json::from_value(val), "{:?}", loc)
+}
+
+fn nth_report(
+    reports: &[AdapterReport],
+    n: usize,
+    loc: &path::Path,
+) -> Result<AdapterReport> {
+    match reports.get(n) {
+        Some(report) => Ok(report.clone()),
+        None => err_at!(Invalid, msg: "no adapter {} in {:?}", n, loc),
+    }
+}
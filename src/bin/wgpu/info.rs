@@ -1,5 +1,6 @@
 use colored::Colorize;
 use prettytable::cell;
+use serde_json::{json, Value};
 use winit::{
     event_loop::EventLoop,
     monitor::{MonitorHandle, VideoMode},
@@ -7,14 +8,17 @@ use winit::{
 };
 
 use gpgpu::{
-    err_at, util,
+    err_at,
     util::{self, PrettyRow},
     Config, Error, Result,
 };
 
 use crate::Opt;
 
-pub fn info_global_report(opts: &Opt) -> Result<()> {
+// Each `info_*` function prints its table, or with `--json` returns the same
+// data as JSON value without printing.
+
+pub fn info_global_report(opts: &Opt) -> Result<Value> {
     let inst = wgpu::Instance::new(util::wgpu_backend().into());
     let gr = inst.generate_report();
     let mut srs: Vec<gpgpu::pretty::StorageReport> =
@@ -45,15 +49,19 @@ pub fn info_global_report(opts: &Opt) -> Result<()> {
     #[cfg(target_os = "macos")]
     gr.metal.as_ref().map(|hr| extend_hub_report(hr));
 
+    if opts.json {
+        return Ok(util::make_json(&srs));
+    }
     util::make_table(&srs).print_tty(!opts.force_color);
-    Ok(())
+    Ok(Value::Null)
 }
 
-pub fn info_queue(_opts: &Opt) -> Result<()> {
+pub fn info_queue(opts: &Opt) -> Result<Value> {
     let inst = wgpu::Instance::new(util::wgpu_backend().into());
     let adapters: Vec<wgpu::Adapter> =
         inst.enumerate_adapters(wgpu::Backends::all()).collect();
 
+    let mut queues = vec![];
     for adapter in adapters.iter() {
         let desc = wgpu::DeviceDescriptor {
             label: Some("gpgpu"),
@@ -65,14 +73,14 @@ pub fn info_queue(_opts: &Opt) -> Result<()> {
                 pollster::block_on(async { adapter.request_device(&desc, None).await });
             err_at!(Fatal, r)
         }?;
-        println!(
-            "Queue timestamp period for {:?} is {}ns",
-            adapter.get_info().name,
-            queue.get_timestamp_period()
-        );
+        let (name, period) = (adapter.get_info().name, queue.get_timestamp_period());
+        match opts.json {
+            true => queues.push(json!({"adapter": name, "timestamp_period": period})),
+            false => println!("Queue timestamp period for {:?} is {}ns", name, period),
+        }
     }
 
-    Ok(())
+    Ok(Value::Array(queues))
 }
 
 // List monitors or show video modes for primary monitor or monitor chosen by index `n`.
@@ -81,7 +89,7 @@ pub fn info_window(
     n: Option<usize>,
     opts: &Opt,
     config: &Config,
-) -> Result<()> {
+) -> Result<Value> {
     let eloop = EventLoop::new();
     let window = {
         let mut wb = WindowBuilder::new();
@@ -89,16 +97,42 @@ pub fn info_window(
         err_at!(Fatal, wb.build(&eloop))?
     };
 
-    println!(" Primary monitor: {:?}", window.current_monitor().map(|m| m.name()));
+    let monitors: Vec<MonitorHandle> = window.available_monitors().collect();
+
+    if opts.json {
+        let primary = window.primary_monitor().and_then(|m| m.name());
+        let current = window.current_monitor().and_then(|m| m.name());
+        let val = match n {
+            Some(n) if modes => {
+                let modes = to_monitor(&monitors, n)?.video_modes();
+                let modes = modes.collect::<Vec<VideoMode>>();
+                json!({ "video_modes": util::make_json(&modes) })
+            }
+            None if modes => {
+                let modes = match window.primary_monitor() {
+                    Some(primary) => primary.video_modes().collect::<Vec<VideoMode>>(),
+                    None => vec![],
+                };
+                json!({ "video_modes": util::make_json(&modes) })
+            }
+            _ => json!({
+                "primary": primary,
+                "current": current,
+                "monitors": util::make_json(&monitors),
+            }),
+        };
+        return Ok(val);
+    }
+
+    println!(" Primary monitor: {:?}", window.primary_monitor().map(|m| m.name()));
     println!(" Current monitor: {:?}", window.current_monitor().map(|m| m.name()));
     println!();
 
-    let monitors: Vec<MonitorHandle> = window.available_monitors().collect();
-
     match n {
         Some(n) if modes => {
             // show video modes for monitor index `n`
-            let modes = monitors[n].video_modes().collect::<Vec<VideoMode>>();
+            let modes = to_monitor(&monitors, n)?.video_modes();
+            let modes = modes.collect::<Vec<VideoMode>>();
             util::make_table(&modes).print_tty(!opts.force_color);
         }
         None if modes => match window.primary_monitor() {
@@ -121,21 +155,34 @@ pub fn info_window(
         }
     }
 
-    Ok(())
+    Ok(Value::Null)
+}
+
+fn to_monitor(monitors: &[MonitorHandle], n: usize) -> Result<&MonitorHandle> {
+    match monitors.get(n) {
+        Some(mh) => Ok(mh),
+        None => {
+            err_at!(Invalid, msg: "monitor {} not found, {} available", n, monitors.len())
+        }
+    }
 }
 
-pub fn info_adapters(opts: &Opt) -> Result<()> {
+pub fn info_adapters(opts: &Opt) -> Result<Value> {
     let instance = wgpu::Instance::new(wgpu::Backends::all());
     let adapters: Vec<wgpu::Adapter> =
         instance.enumerate_adapters(wgpu::Backends::all()).collect();
 
     let infos: Vec<wgpu::AdapterInfo> = adapters.iter().map(|a| a.get_info()).collect();
+    if opts.json {
+        return Ok(util::make_json(&infos));
+    }
     util::make_table(&infos).print_tty(opts.force_color);
 
-    Ok(())
+    Ok(Value::Null)
 }
 
-pub fn info_features(opts: &Opt) -> Result<()> {
+// Adapter columns in JSON output are in the same order as `info_adapters`.
+pub fn info_features(opts: &Opt) -> Result<Value> {
     let mut features = gpgpu::pretty::features();
 
     let instance = wgpu::Instance::new(wgpu::Backends::all());
@@ -145,6 +192,9 @@ pub fn info_features(opts: &Opt) -> Result<()> {
     adapters.iter().for_each(|a| {
         gpgpu::pretty::add_adapter_to_features(&mut features, a.features())
     });
+    if opts.json {
+        return Ok(util::make_json(&features));
+    }
 
     let mut table = prettytable::Table::new();
     let table = match features.len() {
@@ -168,10 +218,11 @@ pub fn info_features(opts: &Opt) -> Result<()> {
 
     table.print_tty(opts.force_color);
 
-    Ok(())
+    Ok(Value::Null)
 }
 
-pub fn info_limits(opts: &Opt) -> Result<()> {
+// Adapter columns in JSON output are in the same order as `info_adapters`.
+pub fn info_limits(opts: &Opt) -> Result<Value> {
     let mut limits = gpgpu::pretty::limits();
 
     let instance = wgpu::Instance::new(wgpu::Backends::all());
@@ -181,6 +232,9 @@ pub fn info_limits(opts: &Opt) -> Result<()> {
     adapters
         .iter()
         .for_each(|a| gpgpu::pretty::add_adapter_to_limits(&mut limits, a.limits()));
+    if opts.json {
+        return Ok(util::make_json(&limits));
+    }
 
     let mut table = prettytable::Table::new();
     let table = match limits.len() {
@@ -204,12 +258,15 @@ pub fn info_limits(opts: &Opt) -> Result<()> {
 
     table.print_tty(opts.force_color);
 
-    Ok(())
+    Ok(Value::Null)
 }
 
-pub fn info_texture_formats(opts: &Opt) -> Result<()> {
+pub fn info_texture_formats(opts: &Opt) -> Result<Value> {
     let info = gpgpu::pretty::texture_formats_info();
+    if opts.json {
+        return Ok(util::make_json(&info));
+    }
     util::make_table(&info).print_tty(opts.force_color);
 
-    Ok(())
+    Ok(Value::Null)
 }
@@ -1,22 +1,33 @@
+mod bench;
+mod check;
 mod info;
+mod shader;
 
 use colored::Colorize;
+use serde_json::{json, Value};
 use structopt::StructOpt;
 
 use std::{path, process::exit};
 
-use gpgpu::{Config, Error, Result};
+use gpgpu::{err_at, util, Config, Error, Result};
 
+use bench::info_bench;
+use check::{check_profile, diff_reports, info_capabilities};
 use info::{
     info_adapters, info_features, info_global_report, info_limits, info_queue,
     info_texture_formats, info_window,
 };
+use shader::info_shader;
 
 #[derive(Clone, StructOpt)]
 pub struct Opt {
     #[structopt(long = "force-color")]
     force_color: bool,
 
+    /// Print the report as JSON, instead of tables.
+    #[structopt(long = "json")]
+    json: bool,
+
     #[structopt(long = "config")]
     config_loc: Option<path::PathBuf>,
 
@@ -46,6 +57,54 @@ pub enum SubCommand {
     Limits,
     /// List Texture formats.
     Formats,
+    /// Check adapters against a capability profile, list what passes and fails.
+    /// Exit with status 2 if there is no adapter or if any adapter fails.
+    Check {
+        /// Profile in toml, with required features, limits and texture formats.
+        #[structopt(long = "require")]
+        require: path::PathBuf,
+    },
+    /// Run micro-benchmarks on each adapter.
+    Bench {
+        /// size of buffers, in mega-bytes, for upload and download benchmarks,
+        /// at most 1024.
+        #[structopt(long = "size", default_value = "64")]
+        size: u64,
+
+        /// number of times each benchmark is repeated.
+        #[structopt(long = "iterations", default_value = "10")]
+        iterations: u32,
+    },
+    /// Validate WGSL shaders, list entry points and bindings, or translate them.
+    Shader {
+        files: Vec<path::PathBuf>,
+
+        /// translate shader to spirv, glsl or msl.
+        #[structopt(long = "to")]
+        to: Option<String>,
+
+        /// entry point to translate, required for glsl.
+        #[structopt(long = "entry")]
+        entry: Option<String>,
+
+        /// write translated shader to file, instead of stdout.
+        #[structopt(long = "out")]
+        out: Option<path::PathBuf>,
+    },
+    /// Compare adapter capabilities from two reports saved by `--json report`.
+    Diff {
+        old: path::PathBuf,
+
+        new: path::PathBuf,
+
+        /// index of the adapter in old report.
+        #[structopt(long = "old-adapter", default_value = "0")]
+        old_adapter: usize,
+
+        /// index of the adapter in new report.
+        #[structopt(long = "new-adapter", default_value = "0")]
+        new_adapter: usize,
+    },
 }
 
 fn main() {
@@ -64,93 +123,156 @@ fn main() {
         None => Config::default(),
     };
 
+    // set when `check` finds an adapter missing required capabilities.
+    let mut check_failed = false;
+
     let res = match &opts.subcmd {
         SubCommand::Report => handle_report(opts.clone(), &config),
         SubCommand::Backend => {
-            println!("{:?} backend is used", gpgpu::backend());
-            Ok(())
+            let backend = util::wgpu_backend_to_string(util::wgpu_backend());
+            match opts.json {
+                true => Ok(json!({ "backend": backend })),
+                false => {
+                    println!("{} backend is used", backend);
+                    Ok(Value::Null)
+                }
+            }
         }
         SubCommand::Window { modes, n } => info_window(*modes, *n, &opts, &config),
-        SubCommand::Features => handle_features(opts),
-        SubCommand::Limits => handle_limits(opts),
-        SubCommand::Formats => handle_formats(opts),
+        SubCommand::Features => handle_features(opts.clone()),
+        SubCommand::Limits => handle_limits(opts.clone()),
+        SubCommand::Formats => handle_formats(opts.clone()),
+        SubCommand::Check { require } => {
+            check_profile(require, &opts).map(|(val, pass)| {
+                check_failed = !pass;
+                val
+            })
+        }
+        SubCommand::Bench { size, iterations } => info_bench(*size, *iterations, &opts),
+        SubCommand::Shader { files, to, entry, out } => {
+            info_shader(files, to.as_deref(), entry.as_deref(), out.as_deref(), &opts)
+        }
+        SubCommand::Diff { old, new, old_adapter, new_adapter } => {
+            diff_reports(old, new, *old_adapter, *new_adapter, &opts)
+        }
     };
 
-    res.map_err(|err: Error| println!("unexpected error: {}", err)).ok();
+    let res = res.and_then(|val| match opts.json {
+        true => {
+            println!("{}", err_at!(FailConvert, serde_json::to_string_pretty(&val))?);
+            Ok(())
+        }
+        false => Ok(()),
+    });
+
+    if let Err(err) = res {
+        match opts.json {
+            true => eprintln!("unexpected error: {}", err),
+            false => println!("unexpected error: {}", err),
+        }
+        exit(1);
+    }
+    if check_failed {
+        exit(2);
+    }
+}
+
+// Print section heading for tables, skipped for JSON output.
+fn heading(opts: &Opt, title: &str) {
+    if !opts.json {
+        println!("{}", title.red());
+        println!("{}", "-".repeat(title.len()).red());
+    }
+}
+
+// Print section separator for tables, skipped for JSON output.
+fn separator(opts: &Opt) {
+    if !opts.json {
+        println!();
+    }
 }
 
-fn handle_report(opts: Opt, config: &Config) -> Result<()> {
-    println!();
-    println!("{}", "Monitors:".red());
-    println!("{}", "---------".red());
-    info_window(false, None, &opts, config)?;
-    println!();
-
-    println!("{}", "Global Memory Report:".red());
-    println!("{}", "---------------------".red());
-    info_global_report(&opts)?;
-    println!();
-
-    println!("{}", "Adapters:".red());
-    println!("{}", "--------".red());
-    info_adapters(&opts)?;
-    println!();
-
-    println!("{}", "Queue:".red());
-    println!("{}", "------".red());
-    info_queue(&opts)?;
-    println!();
-
-    Ok(())
+fn handle_report(opts: Opt, config: &Config) -> Result<Value> {
+    separator(&opts);
+    heading(&opts, "Monitors:");
+    let monitors = info_window(false, None, &opts, config)?;
+    separator(&opts);
+
+    heading(&opts, "Global Memory Report:");
+    let storage = info_global_report(&opts)?;
+    separator(&opts);
+
+    heading(&opts, "Adapters:");
+    let adapters = info_adapters(&opts)?;
+    separator(&opts);
+
+    heading(&opts, "Queue:");
+    let queues = info_queue(&opts)?;
+    separator(&opts);
+
+    let capabilities = match opts.json {
+        true => info_capabilities(&opts)?,
+        false => Value::Null,
+    };
+
+    let val = json!({
+        "monitors": monitors,
+        "storage": storage,
+        "adapters": adapters,
+        "queues": queues,
+        "capabilities": capabilities,
+    });
+    Ok(val)
 }
 
-fn handle_features(opts: Opt) -> Result<()> {
-    println!("{}", "Adapters:".red());
-    println!("{}", "--------".red());
-    info_adapters(&opts)?;
-    println!();
+fn handle_features(opts: Opt) -> Result<Value> {
+    heading(&opts, "Adapters:");
+    let adapters = info_adapters(&opts)?;
+    separator(&opts);
 
-    println!("{}", "Features:".red());
-    println!("{}", "---------".red());
-    info_features(&opts)?;
-    println!();
+    heading(&opts, "Features:");
+    let features = info_features(&opts)?;
+    separator(&opts);
 
-    Ok(())
+    Ok(json!({ "adapters": adapters, "features": features }))
 }
 
-fn handle_limits(opts: Opt) -> Result<()> {
-    println!("{}", "Adapters:".red());
-    println!("{}", "--------".red());
-    info_adapters(&opts)?;
-    println!();
+fn handle_limits(opts: Opt) -> Result<Value> {
+    heading(&opts, "Adapters:");
+    let adapters = info_adapters(&opts)?;
+    separator(&opts);
 
-    println!("{}", "Limits:".red());
-    println!("{}", "-------".red());
-    info_limits(&opts)?;
-    println!();
+    heading(&opts, "Limits:");
+    let limits = info_limits(&opts)?;
+    separator(&opts);
 
-    Ok(())
+    Ok(json!({ "adapters": adapters, "limits": limits }))
 }
 
-fn handle_formats(opts: Opt) -> Result<()> {
-    println!("{}", "TextureUsages:".red());
-    println!("{}", "--------------".red());
-    for item in gpgpu::pretty::texture_usages().iter() {
-        println!(" {}: {}", item.1, item.2)
+fn handle_formats(opts: Opt) -> Result<Value> {
+    let usages = gpgpu::pretty::texture_usages();
+    let flags = gpgpu::pretty::texture_format_flags();
+
+    heading(&opts, "TextureUsages:");
+    if !opts.json {
+        usages.iter().for_each(|item| println!(" {}: {}", item.1, item.2));
     }
-    println!();
+    separator(&opts);
 
-    println!("{}", "TextureFormatFeatureFlags:".red());
-    println!("{}", "--------------------------".red());
-    for item in gpgpu::pretty::texture_format_flags().iter() {
-        println!(" {}: {}", item.1, item.2)
+    heading(&opts, "TextureFormatFeatureFlags:");
+    if !opts.json {
+        flags.iter().for_each(|item| println!(" {}: {}", item.1, item.2));
     }
-    println!();
+    separator(&opts);
 
-    println!("{}", "TextureFormats:".red());
-    println!("{}", "---------------".red());
-    info_texture_formats(&opts)?;
-    println!();
+    heading(&opts, "TextureFormats:");
+    let formats = info_texture_formats(&opts)?;
+    separator(&opts);
 
-    Ok(())
+    let val = json!({
+        "texture_usages": usages.iter().map(|item| item.2).collect::<Vec<&str>>(),
+        "texture_format_flags": flags.iter().map(|item| item.2).collect::<Vec<&str>>(),
+        "texture_formats": formats,
+    });
+    Ok(val)
 }
@@ -0,0 +1,85 @@
+use colored::Colorize;
+use serde_json::{json, Value};
+
+use std::{fs, path};
+
+use gpgpu::{err_at, shader::Shader, util, Error, Result};
+
+use crate::Opt;
+
+// Validate WGSL `files`, list their entry points and bindings, or translate
+// them to `to` language. Fail on the first invalid file.
+pub fn info_shader(
+    files: &[path::PathBuf],
+    to: Option<&str>,
+    entry: Option<&str>,
+    out: Option<&path::Path>,
+    opts: &Opt,
+) -> Result<Value> {
+    let mut items = vec![];
+
+    for loc in files.iter() {
+        let shader = Shader::from_file(loc)?;
+
+        match to {
+            Some(to) => items.push(translate(&shader, loc, to, entry, out, opts)?),
+            None if opts.json => items.push(json!({
+                "file": loc,
+                "entry_points": util::make_json(&shader.to_entry_points()),
+                "bindings": util::make_json(&shader.to_bindings()),
+            })),
+            None => {
+                println!("{} {:?}", "Ok".green(), loc);
+                println!();
+                util::make_table(&shader.to_entry_points()).print_tty(opts.force_color);
+                println!();
+                util::make_table(&shader.to_bindings()).print_tty(opts.force_color);
+                println!();
+            }
+        }
+    }
+
+    Ok(Value::Array(items))
+}
+
+fn translate(
+    shader: &Shader,
+    loc: &path::Path,
+    to: &str,
+    entry: Option<&str>,
+    out: Option<&path::Path>,
+    opts: &Opt,
+) -> Result<Value> {
+    let text = match to {
+        "spirv" => {
+            let words = shader.to_spirv()?;
+            if let Some(out) = out {
+                let bytes: Vec<u8> = words.iter().flat_map(|w| w.to_le_bytes()).collect();
+                err_at!(IOError, fs::write(out, bytes), "file:{:?}", out)?;
+                return Ok(json!({ "file": loc, "spirv": out }));
+            }
+            let words: Vec<String> =
+                words.iter().map(|w| format!("{:#010x}", w)).collect();
+            words.join("\n")
+        }
+        "glsl" => match entry {
+            Some(entry) => shader.to_glsl(entry)?,
+            None => err_at!(Invalid, msg: "--entry is required for glsl")?,
+        },
+        "msl" => shader.to_msl()?,
+        _ => err_at!(Invalid, msg: "cannot translate to {:?}, spirv|glsl|msl", to)?,
+    };
+
+    if let Some(out) = out {
+        err_at!(IOError, fs::write(out, &text), "file:{:?}", out)?;
+        return Ok(json!({ "file": loc, to: out }));
+    }
+
+    match opts.json {
+        true => Ok(json!({ "file": loc, to: text })),
+        false => {
+            println!("{}", text);
+            Ok(Value::Null)
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::{
+    collections::HashMap,
+    hash::Hash,
+    num::NonZeroU8,
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    shader::{Permutation, Permutations, Preprocessor},
+    Error, PipelineTarget, Result,
+};
+
+/// Device scoped cache of wgpu objects, that primitives share with each other.
+///
+/// Shader modules are cached by their source name, source text and defines,
+/// bind-group layouts by their entries, samplers by their descriptor, and
+/// render pipelines by their label, shader permutation and [PipelineTarget].
+/// A cache shall be used with a single device, [crate::Screen] holds one for
+/// its device, and primitives fetch from it through [crate::Context].
+pub struct Cache {
+    modules: Permutations,
+    layouts: Mutex<HashMap<Vec<wgpu::BindGroupLayoutEntry>, Arc<wgpu::BindGroupLayout>>>,
+    samplers: Mutex<HashMap<SamplerKey, Arc<wgpu::Sampler>>>,
+    pipelines: Mutex<HashMap<PipelineKey, Arc<wgpu::RenderPipeline>>>,
+}
+
+/// Number of objects in [Cache], refer [Cache::to_stats].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub modules: usize,
+    pub layouts: usize,
+    pub samplers: usize,
+    pub pipelines: usize,
+}
+
+// Pipeline label, hash of its shader permutation and its target.
+type PipelineKey = (String, u64, PipelineTarget);
+
+// Hashable form of wgpu::SamplerDescriptor, without the label.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct SamplerKey {
+    address_modes: [wgpu::AddressMode; 3],
+    filters: [wgpu::FilterMode; 3],
+    lod_clamps: [u32; 2],
+    compare: Option<wgpu::CompareFunction>,
+    anisotropy_clamp: Option<NonZeroU8>,
+    border_color: Option<wgpu::SamplerBorderColor>,
+}
+
+impl<'a> From<&wgpu::SamplerDescriptor<'a>> for SamplerKey {
+    fn from(desc: &wgpu::SamplerDescriptor<'a>) -> SamplerKey {
+        SamplerKey {
+            address_modes: [
+                desc.address_mode_u,
+                desc.address_mode_v,
+                desc.address_mode_w,
+            ],
+            filters: [desc.mag_filter, desc.min_filter, desc.mipmap_filter],
+            lod_clamps: [desc.lod_min_clamp.to_bits(), desc.lod_max_clamp.to_bits()],
+            compare: desc.compare,
+            anisotropy_clamp: desc.anisotropy_clamp,
+            border_color: desc.border_color,
+        }
+    }
+}
+
+impl Default for Cache {
+    fn default() -> Cache {
+        Cache::new(Preprocessor::default())
+    }
+}
+
+impl Cache {
+    /// Create an empty cache, shader sources are processed with `preprocessor`.
+    pub fn new(preprocessor: Preprocessor) -> Cache {
+        Cache {
+            modules: Permutations::new(preprocessor),
+            layouts: Mutex::new(HashMap::new()),
+            samplers: Mutex::new(HashMap::new()),
+            pipelines: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Return shader module for WGSL `text` compiled with `defines`, refer
+    /// [Permutations::to_permutation].
+    pub fn to_permutation(
+        &self,
+        device: &wgpu::Device,
+        name: &str,
+        text: &str,
+        defines: &[(&str, &str)],
+    ) -> Result<Arc<Permutation>> {
+        self.modules.to_permutation(device, name, text, defines)
+    }
+
+    /// Return bind-group layout for `entries`, `label` is used only when the
+    /// layout is created.
+    pub fn to_bind_group_layout(
+        &self,
+        device: &wgpu::Device,
+        label: Option<&str>,
+        entries: &[wgpu::BindGroupLayoutEntry],
+    ) -> Result<Arc<wgpu::BindGroupLayout>> {
+        let mut layouts = err_at!(Fatal, self.layouts.lock())?;
+        let val = layouts.entry(entries.to_vec()).or_insert_with(|| {
+            let desc = wgpu::BindGroupLayoutDescriptor { label, entries };
+            Arc::new(device.create_bind_group_layout(&desc))
+        });
+
+        Ok(Arc::clone(val))
+    }
+
+    /// Return sampler for `desc`, label is ignored while matching.
+    pub fn to_sampler(
+        &self,
+        device: &wgpu::Device,
+        desc: &wgpu::SamplerDescriptor,
+    ) -> Result<Arc<wgpu::Sampler>> {
+        let mut samplers = err_at!(Fatal, self.samplers.lock())?;
+        let val = samplers
+            .entry(SamplerKey::from(desc))
+            .or_insert_with(|| Arc::new(device.create_sampler(desc)));
+
+        Ok(Arc::clone(val))
+    }
+
+    /// Return render pipeline identified by `label`, shader `permutation` and
+    /// `target`, calling `f` to create the pipeline if it is not cached.
+    /// Labels shall identify the rest of the pipeline descriptor, like its
+    /// primitive and depth states.
+    pub fn to_render_pipeline<F>(
+        &self,
+        label: &str,
+        permutation: &Permutation,
+        target: &PipelineTarget,
+        f: F,
+    ) -> Result<Arc<wgpu::RenderPipeline>>
+    where
+        F: FnOnce() -> Result<wgpu::RenderPipeline>,
+    {
+        let key = (label.to_string(), permutation.to_hash(), *target);
+        if let Some(val) = err_at!(Fatal, self.pipelines.lock())?.get(&key) {
+            return Ok(Arc::clone(val));
+        }
+
+        // create outside the lock, `f` might fetch other objects from cache.
+        let val = Arc::new(f()?);
+        let mut pipelines = err_at!(Fatal, self.pipelines.lock())?;
+        Ok(Arc::clone(pipelines.entry(key).or_insert(val)))
+    }
+
+    pub fn to_stats(&self) -> CacheStats {
+        CacheStats {
+            modules: self.modules.len(),
+            layouts: to_len(&self.layouts),
+            samplers: to_len(&self.samplers),
+            pipelines: to_len(&self.pipelines),
+        }
+    }
+
+    /// Drop all cached objects, objects already fetched by primitives stay
+    /// alive until they are dropped.
+    pub fn clear(&self) -> Result<()> {
+        self.modules.clear()?;
+        err_at!(Fatal, self.layouts.lock())?.clear();
+        err_at!(Fatal, self.samplers.lock())?.clear();
+        err_at!(Fatal, self.pipelines.lock())?.clear();
+        Ok(())
+    }
+}
+
+fn to_len<K, V>(m: &Mutex<HashMap<K, V>>) -> usize
+where
+    K: Eq + Hash,
+{
+    m.lock().map(|m| m.len()).unwrap_or(0)
+}
+
+#[cfg(test)]
+#[path = "cache_test.rs"]
+mod cache_test;
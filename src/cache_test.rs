@@ -0,0 +1,31 @@
+use super::*;
+
+#[test]
+fn test_sampler_key() {
+    let desc = wgpu::SamplerDescriptor {
+        label: Some("one"),
+        mag_filter: wgpu::FilterMode::Linear,
+        ..Default::default()
+    };
+    let key = SamplerKey::from(&desc);
+
+    let other = wgpu::SamplerDescriptor { label: Some("two"), ..desc.clone() };
+    assert!(key == SamplerKey::from(&other), "labels shall be ignored");
+
+    let other = wgpu::SamplerDescriptor {
+        min_filter: wgpu::FilterMode::Linear,
+        ..desc.clone()
+    };
+    assert!(key != SamplerKey::from(&other));
+
+    let other = wgpu::SamplerDescriptor { lod_max_clamp: 10.0, ..desc };
+    assert!(key != SamplerKey::from(&other));
+}
+
+#[test]
+fn test_cache_stats() {
+    let cache = Cache::default();
+    assert_eq!(cache.to_stats(), CacheStats::default());
+    cache.clear().unwrap();
+    assert_eq!(cache.to_stats(), CacheStats::default());
+}
@@ -8,13 +8,11 @@ use wgpu::{Instance, RequestAdapterOptions};
 use winit::window::{Window, WindowAttributes};
 
 use serde::Deserialize;
-use winit::{dpi, window};
+use winit::{dpi, event_loop::EventLoopWindowTarget, window};
 
-use std::{convert::TryInto, ffi, path};
+use std::{convert::TryInto, path};
 
-#[allow(unused_imports)]
-use crate::niw;
-use crate::{util, Error, Result};
+use crate::{niw, util, Error, Result};
 
 /// Configuration type for initializing gpgpu crate.
 #[derive(Clone)]
@@ -29,6 +27,8 @@ pub struct Config {
     pub adapter_options: ConfigAdapter,
     /// Refer to [ConfigWinit] for details
     pub winit: ConfigWinit,
+    /// Key and mouse bindings, refer [niw::Bindings] for details.
+    pub bindings: niw::Bindings,
 }
 
 impl Default for Config {
@@ -39,6 +39,7 @@ impl Default for Config {
             present_mode: wgpu::PresentMode::Fifo,
             adapter_options: ConfigAdapter::default(),
             winit: ConfigWinit::default(),
+            bindings: niw::Bindings::default(),
         }
     }
 }
@@ -66,6 +67,9 @@ impl Default for ConfigAdapter {
 /// [ConfigWinit] can be converted to [WindowAttributes], via TryFrom/TryInto trait,
 /// to create a winit window with desired attributes. Get Started with
 /// `ConfigWinit::default()`
+///
+/// Attributes that can only be set on a created window, like cursor and IME
+/// settings, are applied via [ConfigWinit::apply_window].
 #[derive(Clone, Debug)]
 pub struct ConfigWinit {
     pub title: String,
@@ -82,10 +86,67 @@ pub struct ConfigWinit {
     pub max_inner_size: Option<Vec<f64>>,
     pub min_inner_size: Option<Vec<f64>>,
     pub position: Option<Vec<f64>>,
-    // TODO: ime_position
-    // TODO: cursor_icon
-    // TODO: fullscreen
-    // TODO: window_icon: Option<ffi::OsString>,
+    /// Fullscreen configuration, the configured monitor and video mode are
+    /// resolved by [ConfigWinit::to_window_attributes]. Plain conversion to
+    /// [WindowAttributes] has no monitors to resolve against, and falls back
+    /// to borderless fullscreen on the current monitor.
+    pub fullscreen: Option<ConfigFullscreen>,
+    /// Logical position of the IME candidate box, applied via
+    /// [ConfigWinit::apply_window].
+    pub ime_position: Option<Vec<f64>>,
+    /// Cursor icon, in toml configuration file this is the CSS cursor name,
+    /// like `"crosshair"` or `"ew-resize"`, refer [to_cursor_icon].
+    pub cursor_icon: Option<window::CursorIcon>,
+    /// Grab the cursor, preventing it from leaving the window.
+    pub cursor_grab: bool,
+    /// Path to an image file, loaded as the window icon.
+    pub window_icon: Option<path::PathBuf>,
+}
+
+/// Fullscreen configuration for [ConfigWinit].
+///
+/// In toml configuration file, fullscreen is configured under the
+/// `[winit.fullscreen]` table:
+///
+/// ```toml
+/// [winit.fullscreen]
+/// mode = "exclusive"   # or "borderless"
+/// monitor = "DP-1"     # monitor name, or its index as integer
+/// size = [1920, 1080]  # video mode, applicable for exclusive mode
+/// bit_depth = 32
+/// refresh_rate = 60
+/// ```
+///
+/// Monitors and video modes are resolved against the monitors available to
+/// the event-loop, refer [niw::to_fullscreen_at] and [niw::to_fullscreen].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConfigFullscreen {
+    pub mode: FullscreenMode,
+    /// Monitor to use, defaults to window's current monitor.
+    pub monitor: Option<MonitorSelect>,
+    /// Width and height of the video mode, in physical pixels, defaults to
+    /// monitor's size.
+    pub size: Option<Vec<u32>>,
+    /// Bit depth of the video mode, defaults to the highest available.
+    pub bit_depth: Option<u16>,
+    /// Refresh rate of the video mode in Hz, defaults to the highest available.
+    pub refresh_rate: Option<u16>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FullscreenMode {
+    /// Window covers the whole monitor, without changing its video mode.
+    #[default]
+    Borderless,
+    /// Window takes over the monitor, changing its video mode.
+    Exclusive,
+}
+
+/// Select a monitor by name or by index into the list of available monitors.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MonitorSelect {
+    Index(usize),
+    Name(String),
 }
 
 impl Default for ConfigWinit {
@@ -108,6 +169,11 @@ impl Default for ConfigWinit {
             max_inner_size: None,
             min_inner_size: None,
             position: None,
+            fullscreen: None,
+            ime_position: None,
+            cursor_icon: None,
+            cursor_grab: false,
+            window_icon: None,
         }
     }
 }
@@ -150,12 +216,14 @@ impl Config {
 
 // Local type that is friendly for converting from toml Value.
 #[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct TomlConfig {
     web: Option<bool>,
-    trace_path: Option<ffi::OsString>,
+    trace_path: Option<String>,
     present_mode: Option<String>,
     adapter_options: Option<TomlConfigAdapter>,
     winit: Option<TomlConfigWinit>,
+    bindings: Option<toml::Value>,
 }
 
 impl TryFrom<TomlConfig> for Config {
@@ -187,7 +255,10 @@ impl TryFrom<TomlConfig> for Config {
             c.adapter_options.force_fallback_adapter = val;
         }
         if let Some(val) = toml_config.winit {
-            c.winit = val.into()
+            c.winit = val.try_into()?
+        }
+        if let Some(val) = toml_config.bindings {
+            c.bindings = niw::Bindings::from_toml(val)?
         }
 
         Ok(c)
@@ -196,6 +267,7 @@ impl TryFrom<TomlConfig> for Config {
 
 // Local type that is friendly for converting from toml Value.
 #[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct TomlConfigAdapter {
     power_preference: Option<String>,
     force_fallback_adapter: Option<bool>,
@@ -203,6 +275,7 @@ struct TomlConfigAdapter {
 
 // local type friendly to toml text/value.
 #[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 struct TomlConfigWinit {
     title: Option<String>,
     visible: Option<bool>,
@@ -218,10 +291,22 @@ struct TomlConfigWinit {
     max_inner_size: Option<Vec<f64>>,
     min_inner_size: Option<Vec<f64>>,
     position: Option<Vec<f64>>,
-    // TODO: ime_position
-    // TODO: cursor_icon
-    // TODO: fullscreen
-    // TODO: window_icon: Option<ffi::OsString>,
+    fullscreen: Option<TomlConfigFullscreen>,
+    ime_position: Option<Vec<f64>>,
+    cursor_icon: Option<String>,
+    cursor_grab: Option<bool>,
+    window_icon: Option<String>,
+}
+
+// local type friendly to toml text/value.
+#[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TomlConfigFullscreen {
+    mode: Option<String>,
+    monitor: Option<toml::Value>,
+    size: Option<Vec<u32>>,
+    bit_depth: Option<u16>,
+    refresh_rate: Option<u16>,
 }
 
 macro_rules! from_toml {
@@ -239,10 +324,24 @@ macro_rules! from_toml {
     };
 }
 
-impl From<TomlConfigWinit> for ConfigWinit {
-    fn from(toml_config: TomlConfigWinit) -> ConfigWinit {
+impl TryFrom<TomlConfigWinit> for ConfigWinit {
+    type Error = Error;
+
+    fn try_from(toml_config: TomlConfigWinit) -> Result<ConfigWinit> {
         let c = ConfigWinit::default();
-        ConfigWinit {
+        let fullscreen = match toml_config.fullscreen {
+            Some(val) => Some(val.try_into()?),
+            None => c.fullscreen,
+        };
+        let cursor_icon = match toml_config.cursor_icon.as_deref() {
+            Some(name) => Some(to_cursor_icon(name)?),
+            None => c.cursor_icon,
+        };
+        let window_icon = match toml_config.window_icon {
+            Some(val) if !val.is_empty() => Some(val.into()),
+            _ => c.window_icon,
+        };
+        let val = ConfigWinit {
             title: from_toml!(toml_config, title, c),
             visible: from_toml!(toml_config, visible, c),
             transparent: from_toml!(toml_config, transparent, c),
@@ -257,7 +356,50 @@ impl From<TomlConfigWinit> for ConfigWinit {
             max_inner_size: from_toml!(opt, toml_config, max_inner_size, c),
             min_inner_size: from_toml!(opt, toml_config, min_inner_size, c),
             position: from_toml!(opt, toml_config, position, c),
+            fullscreen,
+            ime_position: from_toml!(opt, toml_config, ime_position, c),
+            cursor_icon,
+            cursor_grab: from_toml!(toml_config, cursor_grab, c),
+            window_icon,
+        };
+
+        Ok(val)
+    }
+}
+
+impl TryFrom<TomlConfigFullscreen> for ConfigFullscreen {
+    type Error = Error;
+
+    fn try_from(toml_config: TomlConfigFullscreen) -> Result<ConfigFullscreen> {
+        let mode = match toml_config.mode.as_deref() {
+            Some("borderless") | None => FullscreenMode::Borderless,
+            Some("exclusive") => FullscreenMode::Exclusive,
+            Some(s) => err_at!(Invalid, msg: "fullscreen mode {}", s)?,
+        };
+        let monitor = match toml_config.monitor {
+            Some(toml::Value::String(name)) => Some(MonitorSelect::Name(name)),
+            Some(toml::Value::Integer(n)) if n >= 0 => {
+                Some(MonitorSelect::Index(n as usize))
+            }
+            Some(val) => err_at!(Invalid, msg: "fullscreen monitor {}", val)?,
+            None => None,
+        };
+        match toml_config.size.as_ref() {
+            Some(size) if size.len() != 2 => {
+                err_at!(Invalid, msg: "size invalid {:?}", size)?
+            }
+            _ => (),
         }
+
+        let val = ConfigFullscreen {
+            mode,
+            monitor,
+            size: toml_config.size,
+            bit_depth: toml_config.bit_depth,
+            refresh_rate: toml_config.refresh_rate,
+        };
+
+        Ok(val)
     }
 }
 
@@ -271,14 +413,17 @@ impl TryFrom<ConfigWinit> for window::WindowAttributes {
             max_inner_size: config.to_max_inner_size()?,
             position: config.to_position()?,
             resizable: config.resizable,
-            fullscreen: None, // TODO fetch from config,
+            fullscreen: config
+                .fullscreen
+                .as_ref()
+                .map(|_| window::Fullscreen::Borderless(None)),
             title: config.title.clone(),
             maximized: config.maximized,
             visible: config.visible,
             transparent: config.transparent,
             decorations: config.decorations,
             always_on_top: config.always_on_top,
-            window_icon: None, // TODO fetch from config,
+            window_icon: config.to_window_icon()?,
         };
 
         Ok(val)
@@ -290,7 +435,65 @@ impl ConfigWinit {
     pub fn from_toml(val: toml::Value) -> Result<ConfigWinit> {
         let toml_config: TomlConfigWinit =
             err_at!(FailConvert, toml::from_str(&val.to_string()))?;
-        Ok(toml_config.into())
+        toml_config.try_into()
+    }
+
+    /// Return window-attributes to create a window on `target`, same as the
+    /// [WindowAttributes] conversion, but with the fullscreen monitor and video
+    /// mode resolved, refer [niw::to_fullscreen_at].
+    pub fn to_window_attributes<T>(
+        &self,
+        target: &EventLoopWindowTarget<T>,
+    ) -> Result<window::WindowAttributes> {
+        let mut attrs: window::WindowAttributes = self.clone().try_into()?;
+        attrs.fullscreen =
+            self.fullscreen.as_ref().map(|config| niw::to_fullscreen_at(target, config));
+
+        Ok(attrs)
+    }
+
+    /// Apply attributes that can only be set on a created `window`, that is,
+    /// cursor position, visibility, icon and grab, and IME position. Windows
+    /// created from ConfigWinit by [niw::SingleWindow] and [niw::MultiWindow]
+    /// have these applied already.
+    pub fn apply_window(&self, window: &Window) -> Result<()> {
+        self.apply_to(window)
+    }
+
+    fn apply_to<W: WindowSetter>(&self, window: &W) -> Result<()> {
+        if let Some(pos) = &self.cursor_position {
+            let pos = to_logical_position(pos.as_slice())?;
+            window.set_cursor_position(pos)?;
+        }
+        window.set_cursor_visible(self.cursor_visible);
+        if let Some(icon) = self.cursor_icon {
+            window.set_cursor_icon(icon);
+        }
+        if self.cursor_grab {
+            window.set_cursor_grab(true)?;
+        }
+        if let Some(pos) = &self.ime_position {
+            window.set_ime_position(to_logical_position(pos.as_slice())?);
+        }
+        Ok(())
+    }
+
+    fn to_window_icon(&self) -> Result<Option<window::Icon>> {
+        let loc = match &self.window_icon {
+            Some(loc) => loc,
+            None => return Ok(None),
+        };
+        let img = err_at!(FailConvert, image::open(loc), "window_icon {:?}", loc)?;
+        let img = img.to_rgba8();
+        let (width, height) = img.dimensions();
+        let icon = err_at!(
+            FailConvert,
+            window::Icon::from_rgba(img.into_raw(), width, height),
+            "window_icon {:?}",
+            loc
+        )?;
+
+        Ok(Some(icon))
     }
 
     fn to_inner_size(&self) -> Result<Option<dpi::Size>> {
@@ -342,6 +545,89 @@ pub fn to_logical_position(pos: &[f64]) -> Result<dpi::Position> {
     }
 }
 
+// Setters for attributes applied on a created window, refer
+// [ConfigWinit::apply_window].
+trait WindowSetter {
+    fn set_cursor_position(&self, pos: dpi::Position) -> Result<()>;
+
+    fn set_cursor_visible(&self, visible: bool);
+
+    fn set_cursor_icon(&self, icon: window::CursorIcon);
+
+    fn set_cursor_grab(&self, grab: bool) -> Result<()>;
+
+    fn set_ime_position(&self, pos: dpi::Position);
+}
+
+impl WindowSetter for Window {
+    fn set_cursor_position(&self, pos: dpi::Position) -> Result<()> {
+        err_at!(Fatal, Window::set_cursor_position(self, pos))
+    }
+
+    fn set_cursor_visible(&self, visible: bool) {
+        Window::set_cursor_visible(self, visible)
+    }
+
+    fn set_cursor_icon(&self, icon: window::CursorIcon) {
+        Window::set_cursor_icon(self, icon)
+    }
+
+    fn set_cursor_grab(&self, grab: bool) -> Result<()> {
+        err_at!(Fatal, Window::set_cursor_grab(self, grab))
+    }
+
+    fn set_ime_position(&self, pos: dpi::Position) {
+        Window::set_ime_position(self, pos)
+    }
+}
+
+/// Convert CSS cursor name, like `"crosshair"` or `"ew-resize"`, into
+/// [window::CursorIcon].
+pub fn to_cursor_icon(name: &str) -> Result<window::CursorIcon> {
+    use window::CursorIcon::*;
+
+    let val = match name {
+        "default" => Default,
+        "crosshair" => Crosshair,
+        "hand" | "pointer" => Hand,
+        "arrow" => Arrow,
+        "move" => Move,
+        "text" => Text,
+        "wait" => Wait,
+        "help" => Help,
+        "progress" => Progress,
+        "not-allowed" => NotAllowed,
+        "context-menu" => ContextMenu,
+        "cell" => Cell,
+        "vertical-text" => VerticalText,
+        "alias" => Alias,
+        "copy" => Copy,
+        "no-drop" => NoDrop,
+        "grab" => Grab,
+        "grabbing" => Grabbing,
+        "all-scroll" => AllScroll,
+        "zoom-in" => ZoomIn,
+        "zoom-out" => ZoomOut,
+        "e-resize" => EResize,
+        "n-resize" => NResize,
+        "ne-resize" => NeResize,
+        "nw-resize" => NwResize,
+        "s-resize" => SResize,
+        "se-resize" => SeResize,
+        "sw-resize" => SwResize,
+        "w-resize" => WResize,
+        "ew-resize" => EwResize,
+        "ns-resize" => NsResize,
+        "nesw-resize" => NeswResize,
+        "nwse-resize" => NwseResize,
+        "col-resize" => ColResize,
+        "row-resize" => RowResize,
+        _ => err_at!(Invalid, msg: "cursor_icon {}", name)?,
+    };
+
+    Ok(val)
+}
+
 fn power_preference(s: &str) -> Result<wgpu::PowerPreference> {
     let val = match s {
         "low_power" => wgpu::PowerPreference::LowPower,
@@ -351,3 +637,7 @@ fn power_preference(s: &str) -> Result<wgpu::PowerPreference> {
 
     Ok(val)
 }
+
+#[cfg(test)]
+#[path = "config_test.rs"]
+mod config_test;
@@ -0,0 +1,120 @@
+use super::*;
+
+#[test]
+fn test_config_file() {
+    let loc: path::PathBuf = [env!("CARGO_MANIFEST_DIR"), "config.toml"].iter().collect();
+    let config = Config::from_file(loc).unwrap();
+
+    assert_eq!(config.present_mode, wgpu::PresentMode::Fifo);
+    assert_eq!(config.adapter_options.power_preference, wgpu::PowerPreference::LowPower);
+    assert_eq!(config.winit.ime_position, Some(vec![0.0, 0.0]));
+    assert_eq!(config.winit.cursor_icon, Some(window::CursorIcon::Default));
+    assert!(!config.winit.cursor_grab);
+    assert_eq!(config.winit.window_icon, None);
+}
+
+#[test]
+fn test_config_winit_toml() {
+    let val: toml::Value = toml::from_str(
+        r#"
+        title = "test"
+        always_on_top = true
+        cursor_icon = "ew-resize"
+        cursor_grab = true
+        ime_position = [10, 20]
+        window_icon = "icon.png"
+        "#,
+    )
+    .unwrap();
+    let config = ConfigWinit::from_toml(val).unwrap();
+
+    assert_eq!(config.title, "test");
+    assert!(config.always_on_top);
+    assert_eq!(config.cursor_icon, Some(window::CursorIcon::EwResize));
+    assert!(config.cursor_grab);
+    assert_eq!(config.ime_position, Some(vec![10.0, 20.0]));
+    assert_eq!(config.window_icon, Some(path::PathBuf::from("icon.png")));
+}
+
+#[test]
+fn test_config_winit_unknown_key() {
+    let val: toml::Value = toml::from_str("alway_on_top = true").unwrap();
+    let err = ConfigWinit::from_toml(val).unwrap_err().to_string();
+    assert!(err.contains("alway_on_top"), "{}", err);
+    assert!(err.contains("always_on_top"), "{}", err);
+
+    let val: toml::Value = toml::from_str("cursor_icon = \"pointy\"").unwrap();
+    assert!(ConfigWinit::from_toml(val).is_err());
+}
+
+#[test]
+fn test_config_window_icon() {
+    let config = ConfigWinit {
+        window_icon: Some("not-found.png".into()),
+        ..ConfigWinit::default()
+    };
+    assert!(window::WindowAttributes::try_from(config).is_err());
+}
+
+#[derive(Default)]
+struct MockWindow {
+    calls: std::cell::RefCell<Vec<String>>,
+}
+
+impl WindowSetter for MockWindow {
+    fn set_cursor_position(&self, pos: dpi::Position) -> Result<()> {
+        self.calls.borrow_mut().push(format!("cursor_position {:?}", pos));
+        Ok(())
+    }
+
+    fn set_cursor_visible(&self, visible: bool) {
+        self.calls.borrow_mut().push(format!("cursor_visible {}", visible))
+    }
+
+    fn set_cursor_icon(&self, icon: window::CursorIcon) {
+        self.calls.borrow_mut().push(format!("cursor_icon {:?}", icon))
+    }
+
+    fn set_cursor_grab(&self, grab: bool) -> Result<()> {
+        self.calls.borrow_mut().push(format!("cursor_grab {}", grab));
+        Ok(())
+    }
+
+    fn set_ime_position(&self, pos: dpi::Position) {
+        self.calls.borrow_mut().push(format!("ime_position {:?}", pos))
+    }
+}
+
+#[test]
+fn test_config_apply_window() {
+    let window = MockWindow::default();
+    ConfigWinit::default().apply_to(&window).unwrap();
+    assert_eq!(*window.calls.borrow(), vec!["cursor_visible true".to_string()]);
+
+    let config = ConfigWinit {
+        cursor_position: Some(vec![1.0, 2.0]),
+        cursor_visible: false,
+        cursor_icon: Some(window::CursorIcon::Crosshair),
+        cursor_grab: true,
+        ime_position: Some(vec![10.0, 20.0]),
+        ..ConfigWinit::default()
+    };
+    let window = MockWindow::default();
+    config.apply_to(&window).unwrap();
+
+    let pos = |x, y| format!("{:?}", to_logical_position(&[x, y]).unwrap());
+    let calls = vec![
+        format!("cursor_position {}", pos(1.0, 2.0)),
+        "cursor_visible false".to_string(),
+        "cursor_icon Crosshair".to_string(),
+        "cursor_grab true".to_string(),
+        format!("ime_position {}", pos(10.0, 20.0)),
+    ];
+    assert_eq!(*window.calls.borrow(), calls);
+
+    let config = ConfigWinit {
+        ime_position: Some(vec![10.0]),
+        ..ConfigWinit::default()
+    };
+    assert!(config.apply_to(&MockWindow::default()).is_err());
+}
@@ -98,28 +98,36 @@ impl error::Error for Error {}
 /// Type alias for Result return type, used by this package.
 pub type Result<T> = result::Result<T, Error>;
 
+mod cache;
 mod config;
 mod layout;
 mod render;
 mod save;
 mod screen;
 mod style;
+mod target;
 mod transforms;
 
 pub mod dom;
 pub mod fonts;
+pub mod model;
 pub mod niw;
 pub mod pretty;
 pub mod primv;
+pub mod shader;
 pub mod util;
 
-pub use config::{Config, ConfigAdapter, ConfigWinit};
+pub use cache::{Cache, CacheStats};
+pub use config::{
+    Config, ConfigAdapter, ConfigFullscreen, ConfigWinit, FullscreenMode, MonitorSelect,
+};
 pub use layout::{BoxVertex, Extent, Origin, Rect, Resize, State, Viewport};
 pub use render::Render;
 pub use save::SaveFile;
 pub use screen::Screen;
 pub use style::{to_rgba8unorm_color, Border, Style, StyleBorder};
-pub use transforms::{Camera, Ortho, Perspective, Transforms};
+pub use target::{Blend, ColorTarget, DepthTarget, PipelineTarget};
+pub use transforms::{compose, decompose, Camera, Ortho, Perspective, Transforms, Trs};
 
 pub const CLEAR_COLOR: wgpu::Color = wgpu::Color { r: 0.0, g: 0.0, b: 0.0, a: 1.0 };
 
@@ -131,10 +139,5 @@ pub struct Context<'a> {
     pub transforms: &'a Transforms,
     pub device: &'a wgpu::Device,
     pub queue: &'a wgpu::Queue,
-}
-
-pub struct ColorTarget {
-    pub format: wgpu::TextureFormat,
-    pub view: wgpu::TextureView,
-    pub view_port: Viewport,
+    pub cache: &'a Cache,
 }
@@ -0,0 +1,154 @@
+//! glTF 2.0 loader, for .gltf and binary .glb files.
+
+use cgmath::{Matrix, Matrix3, Matrix4, Point3, SquareMatrix, Transform, Vector3};
+use log::warn;
+
+use std::path;
+
+use crate::{model::Model, Error, Result};
+
+/// Load meshes from the default scene, or the first scene, with node
+/// transforms applied. If the document does not have scenes, all meshes are
+/// loaded as is.
+pub fn from_bytes(data: &[u8], base: Option<&path::Path>) -> Result<Model> {
+    let ::gltf::Gltf { document, blob } =
+        err_at!(Invalid, ::gltf::Gltf::from_slice(data), "gltf")?;
+    let buffers =
+        err_at!(IOError, ::gltf::import_buffers(&document, base, blob), "gltf")?;
+
+    let mut model = Model::default();
+    match document.default_scene().or_else(|| document.scenes().next()) {
+        Some(scene) => {
+            for node in scene.nodes() {
+                add_node(&node, Matrix4::identity(), &buffers, &mut model)?;
+            }
+        }
+        None => {
+            for mesh in document.meshes() {
+                add_mesh(&mesh, Matrix4::identity(), &buffers, &mut model)?;
+            }
+        }
+    }
+
+    model.fix_normals();
+    Ok(model)
+}
+
+fn add_node(
+    node: &::gltf::Node,
+    parent: Matrix4<f32>,
+    buffers: &[::gltf::buffer::Data],
+    model: &mut Model,
+) -> Result<()> {
+    let transform = parent * Matrix4::from(node.transform().matrix());
+    if let Some(mesh) = node.mesh() {
+        add_mesh(&mesh, transform, buffers, model)?;
+    }
+    for child in node.children() {
+        add_node(&child, transform, buffers, model)?;
+    }
+    Ok(())
+}
+
+fn add_mesh(
+    mesh: &::gltf::Mesh,
+    transform: Matrix4<f32>,
+    buffers: &[::gltf::buffer::Data],
+    model: &mut Model,
+) -> Result<()> {
+    use ::gltf::mesh::{util::ReadIndices, Mode};
+
+    // normals are transformed by the inverse-transpose of the model matrix.
+    let normal_matrix = {
+        let m3 = Matrix3::from_cols(
+            transform.x.truncate(),
+            transform.y.truncate(),
+            transform.z.truncate(),
+        );
+        m3.invert().map(|m| m.transpose()).unwrap_or(m3)
+    };
+
+    let name = mesh.name().unwrap_or("");
+    for primitive in mesh.primitives() {
+        let mode = primitive.mode();
+        if !matches!(mode, Mode::Triangles | Mode::TriangleStrip | Mode::TriangleFan) {
+            warn!("gltf: skip mesh {}:{:?} in mode {:?}", mesh.index(), name, mode);
+            continue;
+        }
+
+        let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+        let positions: Vec<[f32; 3]> = match reader.read_positions() {
+            Some(positions) => positions.collect(),
+            None => err_at!(
+                Invalid,
+                msg: "gltf: mesh {}:{:?} primitive {} without positions",
+                mesh.index(),
+                name,
+                primitive.index()
+            )?,
+        };
+        let n = positions.len();
+        let normals: Option<Vec<[f32; 3]>> = reader.read_normals().map(|n| n.collect());
+        let uvs: Option<Vec<[f32; 2]>> =
+            reader.read_tex_coords(0).map(|t| t.into_f32().collect());
+        let colors: Option<Vec<[f32; 4]>> =
+            reader.read_colors(0).map(|c| c.into_rgba_f32().collect());
+        let base_color =
+            primitive.material().pbr_metallic_roughness().base_color_factor();
+
+        let base = model.vertices.len() as u32;
+        for (i, position) in positions.into_iter().enumerate() {
+            let mut vertex = super::default_vertex();
+            vertex.position = transform.transform_point(Point3::from(position)).into();
+            if let Some(normal) = normals.as_ref().and_then(|ns| ns.get(i)) {
+                vertex.normal = (normal_matrix * Vector3::from(*normal)).into();
+            }
+            if let Some(uv) = uvs.as_ref().and_then(|uvs| uvs.get(i)) {
+                vertex.uv = *uv;
+            }
+            let color = colors.as_ref().and_then(|cs| cs.get(i)).copied();
+            let color = color.unwrap_or([1.0, 1.0, 1.0, 1.0]);
+            vertex.color = [
+                color[0] * base_color[0],
+                color[1] * base_color[1],
+                color[2] * base_color[2],
+                color[3] * base_color[3],
+            ];
+            model.vertices.push(vertex);
+        }
+
+        let indices: Vec<u32> = match reader.read_indices() {
+            Some(ReadIndices::U8(iter)) => iter.map(u32::from).collect(),
+            Some(ReadIndices::U16(iter)) => iter.map(u32::from).collect(),
+            Some(ReadIndices::U32(iter)) => iter.collect(),
+            None => (0..(n as u32)).collect(),
+        };
+        if let Some(index) = indices.iter().find(|i| (**i as usize) >= n) {
+            err_at!(
+                Invalid,
+                msg: "gltf: mesh {}:{:?} index {} out of {} vertices",
+                mesh.index(),
+                name,
+                index,
+                n
+            )?
+        }
+
+        let indices: Vec<u32> = indices.into_iter().map(|i| base + i).collect();
+        match mode {
+            Mode::TriangleStrip => {
+                for (i, tri) in indices.windows(3).enumerate() {
+                    // every other triangle flips winding order.
+                    match i % 2 {
+                        0 => model.indices.extend_from_slice(&[tri[0], tri[1], tri[2]]),
+                        _ => model.indices.extend_from_slice(&[tri[1], tri[0], tri[2]]),
+                    }
+                }
+            }
+            Mode::TriangleFan => super::triangulate(&indices, &mut model.indices),
+            _ => indices.chunks_exact(3).for_each(|t| model.indices.extend_from_slice(t)),
+        }
+    }
+
+    Ok(())
+}
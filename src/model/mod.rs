@@ -0,0 +1,154 @@
+//! Module implement loaders for 3D model files.
+//!
+//! Following formats are supported:
+//!
+//! | format  | extension    | notes                                             |
+//! |---------|--------------|---------------------------------------------------|
+//! | OBJ     | .obj         | Wavefront OBJ, diffuse colors from MTL libraries  |
+//! | PLY     | .ply         | ascii, binary_little_endian, binary_big_endian    |
+//! | STL     | .stl         | ascii and binary                                  |
+//! | glTF    | .gltf, .glb  | meshes, node transforms, base color factors       |
+//!
+//! All loaders produce a [Model], an indexed triangle-list that can be used to
+//! construct a [mesh::Mesh] primitive or a [wireframe::Wireframe] primitive. Parse errors are
+//! reported with the line number, for text formats, or the element index, for
+//! binary formats, where the error was detected.
+
+use std::{ffi, path};
+
+use crate::{
+    primv::{mesh, wireframe},
+    Context, Error, PipelineTarget, Result,
+};
+
+mod gltf;
+mod obj;
+mod ply;
+mod stl;
+
+/// Model file formats.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Format {
+    Obj,
+    Ply,
+    Stl,
+    Gltf,
+}
+
+impl Format {
+    /// Detect model format from the file extension, return None if `loc` does
+    /// not have a known extension.
+    pub fn from_path<P>(loc: P) -> Option<Format>
+    where
+        P: AsRef<path::Path>,
+    {
+        let ext = loc.as_ref().extension().and_then(ffi::OsStr::to_str)?;
+        match ext.to_lowercase().as_str() {
+            "obj" => Some(Format::Obj),
+            "ply" => Some(Format::Ply),
+            "stl" => Some(Format::Stl),
+            "gltf" | "glb" => Some(Format::Gltf),
+            _ => None,
+        }
+    }
+}
+
+/// Indexed triangle-list loaded from a model file.
+///
+/// Vertices without normals in the source file get smooth normals computed via
+/// [mesh::compute_normals]. Vertices without color default to white.
+#[derive(Clone, Debug, Default)]
+pub struct Model {
+    pub vertices: Vec<mesh::Vertex>,
+    pub indices: Vec<u32>,
+}
+
+impl Model {
+    /// Load model from file, format is detected from file extension. Files
+    /// referred by the model, like MTL libraries and glTF buffers, are
+    /// resolved relative to the model's directory.
+    pub fn from_file<P>(loc: P) -> Result<Model>
+    where
+        P: AsRef<path::Path>,
+    {
+        use std::fs;
+
+        let loc = loc.as_ref();
+        let format = match Format::from_path(loc) {
+            Some(format) => format,
+            None => err_at!(Invalid, msg: "unknown model format {:?}", loc)?,
+        };
+
+        let data = err_at!(IOError, fs::read(loc), "{:?}", loc)?;
+        Self::from_bytes(&data, format, loc.parent())
+    }
+
+    /// Load model from `data` encoded in `format`. External references are
+    /// resolved relative to `base`, and fail if `base` is None.
+    pub fn from_bytes(
+        data: &[u8],
+        format: Format,
+        base: Option<&path::Path>,
+    ) -> Result<Model> {
+        match format {
+            Format::Obj => obj::from_bytes(data, base),
+            Format::Ply => ply::from_bytes(data),
+            Format::Stl => stl::from_bytes(data),
+            Format::Gltf => gltf::from_bytes(data, base),
+        }
+    }
+
+    pub fn num_triangles(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Construct a mesh primitive for this model.
+    pub fn to_mesh<T>(&self, context: &Context, target: T) -> Result<mesh::Mesh>
+    where
+        T: Into<PipelineTarget>,
+    {
+        mesh::Mesh::new(self.vertices.clone(), self.indices.clone(), context, target)
+    }
+
+    /// Construct a wireframe primitive from edges of this model.
+    pub fn to_wireframe<T>(
+        &self,
+        edges: wireframe::Edges,
+        context: &Context,
+        target: T,
+    ) -> Result<wireframe::Wireframe>
+    where
+        T: Into<PipelineTarget>,
+    {
+        wireframe::Wireframe::from_model(self, edges, target, context)
+    }
+
+    // loaders leave normal as ZERO when the source does not carry them.
+    fn fix_normals(&mut self) {
+        let missing = self.vertices.iter().any(|v| v.normal == [0.0, 0.0, 0.0]);
+        if missing {
+            let mut vertices = self.vertices.clone();
+            mesh::compute_normals(&mut vertices, &self.indices);
+            for (v, n) in self.vertices.iter_mut().zip(vertices) {
+                if v.normal == [0.0, 0.0, 0.0] {
+                    v.normal = n.normal;
+                }
+            }
+        }
+    }
+}
+
+fn default_vertex() -> mesh::Vertex {
+    mesh::Vertex { normal: [0.0, 0.0, 0.0], ..mesh::Vertex::default() }
+}
+
+// Triangulate a convex polygon as a fan around its first vertex.
+fn triangulate(polygon: &[u32], indices: &mut Vec<u32>) {
+    for i in 1..polygon.len().saturating_sub(1) {
+        indices.extend_from_slice(&[polygon[0], polygon[i], polygon[i + 1]]);
+    }
+}
+
+#[cfg(test)]
+#[path = "model_test.rs"]
+mod model_test;
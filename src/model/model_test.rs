@@ -0,0 +1,217 @@
+use super::*;
+
+#[test]
+fn test_obj() {
+    let data = "
+# quad with negative indices
+v 0 0 0
+v 1 0 0
+v 1 1 0 1 0 0
+v 0 1 0
+vt 0 0
+vn 0 0 1
+f 1/1/1 2/1/1 3/1/1 -1/1/1
+";
+    let model = Model::from_bytes(data.as_bytes(), Format::Obj, None).unwrap();
+    assert_eq!(model.vertices.len(), 4);
+    assert_eq!(model.indices, vec![0, 1, 2, 0, 2, 3]);
+    assert_eq!(model.vertices[2].color, [1.0, 0.0, 0.0, 1.0]);
+    assert_eq!(model.vertices[0].uv, [0.0, 1.0]);
+    assert_eq!(model.vertices[0].normal, [0.0, 0.0, 1.0]);
+
+    let data = "v 0 0 0\nv 1 0 0\nf 1 2 3\n";
+    let err = Model::from_bytes(data.as_bytes(), Format::Obj, None).unwrap_err();
+    assert!(err.to_string().contains("obj line 3"), "{}", err);
+}
+
+#[test]
+fn test_obj_mtl() {
+    let dir = std::env::temp_dir().join("gpgpu-obj-mtl-test");
+    std::fs::create_dir_all(&dir).unwrap();
+    let mtl = "
+newmtl red
+Kd 1 0 0
+d 0.5
+newmtl spectral
+Kd spectral file.rfl
+newmtl xyz
+Kd xyz 0.5 0.5 0.5
+Tr x
+";
+    std::fs::write(dir.join("test.mtl"), mtl).unwrap();
+
+    let data = "mtllib test.mtl
+v 0 0 0
+v 1 0 0
+v 0 1 0
+v 0 0 1
+usemtl red
+f 1 2 3
+usemtl spectral
+f 1 2 4
+usemtl xyz
+f 1 3 4
+";
+    let model = Model::from_bytes(data.as_bytes(), Format::Obj, Some(&dir)).unwrap();
+    assert_eq!(model.num_triangles(), 3);
+    let colors: Vec<[f32; 4]> =
+        model.indices.iter().map(|i| model.vertices[*i as usize].color).collect();
+    assert_eq!(colors[0], [1.0, 0.0, 0.0, 0.5]);
+    // unsupported forms are skipped, leaving the default color.
+    assert_eq!(colors[3], [1.0, 1.0, 1.0, 1.0]);
+    assert_eq!(colors[6], [1.0, 1.0, 1.0, 1.0]);
+}
+
+#[test]
+fn test_ply() {
+    let data = "ply
+format ascii 1.0
+comment made by hand
+element vertex 3
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0 255 0 0
+1 0 0 0 255 0
+0 1 0 0 0 255
+3 0 1 2
+";
+    let model = Model::from_bytes(data.as_bytes(), Format::Ply, None).unwrap();
+    assert_eq!(model.indices, vec![0, 1, 2]);
+    assert_eq!(model.vertices[1].color, [0.0, 1.0, 0.0, 1.0]);
+    assert_eq!(model.vertices[0].normal, [0.0, 0.0, 1.0]);
+
+    let mut data = b"ply
+format binary_big_endian 1.0
+element vertex 3
+property float x
+property float y
+property float z
+element face 1
+property list uchar int vertex_indices
+end_header
+"
+    .to_vec();
+    for v in [0.0_f32, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+        data.extend_from_slice(&v.to_be_bytes());
+    }
+    data.push(3);
+    for i in [0_i32, 1, 2] {
+        data.extend_from_slice(&i.to_be_bytes());
+    }
+    let model = Model::from_bytes(&data, Format::Ply, None).unwrap();
+    assert_eq!(model.indices, vec![0, 1, 2]);
+    assert_eq!(model.vertices[2].position, [0.0, 1.0, 0.0]);
+
+    // counts in the header are not trusted for allocation.
+    let data = "ply\nformat binary_little_endian 1.0\nelement vertex 18446744073709551615
+property float x\nproperty float y\nproperty float z\nend_header\n";
+    let err = Model::from_bytes(data.as_bytes(), Format::Ply, None).unwrap_err();
+    assert!(err.to_string().contains("truncated vertex 0"), "{}", err);
+
+    let mut data = b"ply
+format binary_little_endian 1.0
+element extra 1
+property list uint float x
+end_header
+"
+    .to_vec();
+    data.extend_from_slice(&u32::MAX.to_le_bytes());
+    let err = Model::from_bytes(&data, Format::Ply, None).unwrap_err();
+    assert!(err.to_string().contains("truncated extra 0"), "{}", err);
+
+    let data = "ply\nformat ascii 1.0\nelement face 1
+property list uchar int vertex_indices\nend_header\n1e300 0 1 2\n";
+    let err = Model::from_bytes(data.as_bytes(), Format::Ply, None).unwrap_err();
+    assert!(err.to_string().contains("ply line 6: missing vertex_indices"), "{}", err);
+
+    let header = "ply\nformat ascii 1.0\nelement vertex 3
+property float x\nproperty float y\nproperty float z
+element face 2\nproperty list uchar float vertex_indices\nend_header
+0 0 0\n1 0 0\n0 1 0\n3 0 1 2\n";
+    for (face, msg) in [
+        ("3 0 -1 2", "ply: face 1 with invalid index -1"),
+        ("3 0 1 1.5", "ply: face 1 with invalid index 1.5"),
+        ("3 0 1 1e10", "ply: face 1 with invalid index 10000000000"),
+    ] {
+        let data = format!("{}{}\n", header, face);
+        let err = Model::from_bytes(data.as_bytes(), Format::Ply, None).unwrap_err();
+        assert!(err.to_string().contains(msg), "{}", err);
+    }
+
+    let data = "ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\nend_heade\n";
+    let err = Model::from_bytes(data.as_bytes(), Format::Ply, None).unwrap_err();
+    assert!(err.to_string().contains("ply line 5"), "{}", err);
+}
+
+#[test]
+fn test_stl() {
+    let data = "solid test
+  facet normal 0 0 0
+    outer loop
+      vertex 0 0 0
+      vertex 1 0 0
+      vertex 0 1 0
+    endloop
+  endfacet
+endsolid test
+";
+    let model = Model::from_bytes(data.as_bytes(), Format::Stl, None).unwrap();
+    assert_eq!(model.indices, vec![0, 1, 2]);
+    assert_eq!(model.vertices[0].normal, [0.0, 0.0, 1.0]);
+
+    // binary header starting with "solid", like some exporters do.
+    let mut data = b"solid".to_vec();
+    data.resize(80, 0);
+    data.extend_from_slice(&1_u32.to_le_bytes());
+    for v in [0.0_f32, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0] {
+        data.extend_from_slice(&v.to_le_bytes());
+    }
+    data.extend_from_slice(&[0, 0]);
+    let model = Model::from_bytes(&data, Format::Stl, None).unwrap();
+    assert_eq!(model.vertices.len(), 3);
+    assert_eq!(model.vertices[1].position, [1.0, 0.0, 0.0]);
+
+    let data = "solid test\n  facet normal 0 0\n";
+    let err = Model::from_bytes(data.as_bytes(), Format::Stl, None).unwrap_err();
+    assert!(err.to_string().contains("stl line 2"), "{}", err);
+}
+
+#[test]
+fn test_gltf() {
+    let data = r#"{
+  "asset": {"version": "2.0"},
+  "scene": 0,
+  "scenes": [{"nodes": [0]}],
+  "nodes": [{"mesh": 0, "translation": [0, 0, 1]}],
+  "meshes": [{"primitives": [{"attributes": {"POSITION": 0}, "indices": 1, "material": 0}]}],
+  "materials": [{"pbrMetallicRoughness": {"baseColorFactor": [1, 0, 0, 1]}}],
+  "buffers": [{
+    "byteLength": 44,
+    "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAACAPwAAAAAAAAAAAAAAAAAAgD8AAAAAAAABAAIAAAA="
+  }],
+  "bufferViews": [
+    {"buffer": 0, "byteOffset": 0, "byteLength": 36},
+    {"buffer": 0, "byteOffset": 36, "byteLength": 6}
+  ],
+  "accessors": [
+    {"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+     "min": [0, 0, 0], "max": [1, 1, 0]},
+    {"bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR"}
+  ]
+}"#;
+    let model = Model::from_bytes(data.as_bytes(), Format::Gltf, None).unwrap();
+    assert_eq!(model.indices, vec![0, 1, 2]);
+    assert_eq!(model.vertices[1].position, [1.0, 0.0, 1.0]);
+    assert_eq!(model.vertices[1].color, [1.0, 0.0, 0.0, 1.0]);
+
+    let data = "{\n  \"asset\": {\"version\": \"2.0\"},\n  \"scenes\": [\n}";
+    let err = Model::from_bytes(data.as_bytes(), Format::Gltf, None).unwrap_err();
+    assert!(err.to_string().contains("line 4"), "{}", err);
+}
@@ -0,0 +1,245 @@
+//! Wavefront OBJ loader, with diffuse colors from MTL libraries.
+
+use log::warn;
+
+use std::{collections::HashMap, path};
+
+use crate::{model::Model, Error, Result};
+
+type Color = [f32; 4];
+
+/// Position, uv, normal and color indices identifying an unique vertex.
+type VertexKey = (usize, Option<usize>, Option<usize>, [u32; 4]);
+
+pub fn from_bytes(data: &[u8], base: Option<&path::Path>) -> Result<Model> {
+    use std::str::from_utf8;
+
+    let txt = err_at!(FailConvert, from_utf8(data), "obj")?;
+
+    let mut positions: Vec<[f32; 3]> = vec![];
+    let mut colors: Vec<Option<Color>> = vec![];
+    let mut normals: Vec<[f32; 3]> = vec![];
+    let mut uvs: Vec<[f32; 2]> = vec![];
+
+    let mut materials: HashMap<String, Color> = HashMap::new();
+    let mut material: Option<Color> = None;
+
+    // unique combination of position, uv, normal and material is a vertex.
+    let mut cache: HashMap<VertexKey, u32> = HashMap::new();
+    let mut model = Model::default();
+
+    for (lineno, line) in txt.lines().enumerate().map(|(i, l)| (i + 1, l)) {
+        let line = match line.find('#') {
+            Some(off) => &line[..off],
+            None => line,
+        };
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => {
+                let vals = parse_floats(&args, lineno)?;
+                match vals.as_slice() {
+                    [x, y, z] | [x, y, z, _] => {
+                        positions.push([*x, *y, *z]);
+                        colors.push(None);
+                    }
+                    [x, y, z, r, g, b] => {
+                        positions.push([*x, *y, *z]);
+                        colors.push(Some([*r, *g, *b, 1.0]));
+                    }
+                    _ => {
+                        err_at!(Invalid, msg: "obj line {}: invalid vertex {:?}", lineno, line)?
+                    }
+                }
+            }
+            "vn" => match parse_floats(&args, lineno)?.as_slice() {
+                [x, y, z] => normals.push([*x, *y, *z]),
+                _ => {
+                    err_at!(Invalid, msg: "obj line {}: invalid normal {:?}", lineno, line)?
+                }
+            },
+            "vt" => match parse_floats(&args, lineno)?.as_slice() {
+                // OBJ has texture origin at bottom-left, wgpu at top-left.
+                [u] => uvs.push([*u, 1.0]),
+                [u, v] | [u, v, _] => uvs.push([*u, 1.0 - *v]),
+                _ => err_at!(Invalid, msg: "obj line {}: invalid uv {:?}", lineno, line)?,
+            },
+            "f" => {
+                if args.len() < 3 {
+                    err_at!(Invalid, msg: "obj line {}: face with {} vertices", lineno, args.len())?
+                }
+
+                let mut polygon = Vec::with_capacity(args.len());
+                for arg in args.iter() {
+                    let (p, t, n) = parse_face_vertex(
+                        arg,
+                        lineno,
+                        (positions.len(), uvs.len(), normals.len()),
+                    )?;
+                    let color = colors[p].or(material).unwrap_or([1.0, 1.0, 1.0, 1.0]);
+                    let key = (p, t, n, color.map(f32::to_bits));
+                    let index = match cache.get(&key) {
+                        Some(index) => *index,
+                        None => {
+                            let index = model.vertices.len() as u32;
+                            let mut vertex = super::default_vertex();
+                            vertex.position = positions[p];
+                            vertex.color = color;
+                            if let Some(t) = t {
+                                vertex.uv = uvs[t];
+                            }
+                            if let Some(n) = n {
+                                vertex.normal = normals[n];
+                            }
+                            model.vertices.push(vertex);
+                            cache.insert(key, index);
+                            index
+                        }
+                    };
+                    polygon.push(index);
+                }
+                super::triangulate(&polygon, &mut model.indices);
+            }
+            "mtllib" => match base {
+                Some(base) => {
+                    for name in args.iter() {
+                        let loc = base.join(name);
+                        match load_mtl(&loc) {
+                            Ok(mtls) => materials.extend(mtls),
+                            Err(Error::IOError(_, err)) => {
+                                warn!(
+                                    "obj line {}: skip mtllib {:?}, {}",
+                                    lineno, loc, err
+                                )
+                            }
+                            Err(err) => return Err(err),
+                        }
+                    }
+                }
+                None => {
+                    warn!("obj line {}: skip mtllib {:?}, no base path", lineno, args)
+                }
+            },
+            "usemtl" => {
+                let name = args.join(" ");
+                material = materials.get(&name).copied();
+                if material.is_none() {
+                    warn!("obj line {}: unknown material {:?}", lineno, name);
+                }
+            }
+            // groups, objects, smoothing groups, lines, points and free-form
+            // geometry are not applicable to triangle meshes.
+            _ => (),
+        }
+    }
+
+    model.fix_normals();
+    Ok(model)
+}
+
+/// Parse MTL library, return diffuse color, with dissolve as alpha, for each
+/// material.
+fn load_mtl(loc: &path::Path) -> Result<HashMap<String, Color>> {
+    use std::{fs, str::from_utf8};
+
+    let data = err_at!(IOError, fs::read(loc), "{:?}", loc)?;
+    let txt = err_at!(FailConvert, from_utf8(&data), "{:?}", loc)?;
+
+    let mut materials = HashMap::new();
+    let mut current: Option<(String, Color)> = None;
+
+    for (lineno, line) in txt.lines().enumerate().map(|(i, l)| (i + 1, l)) {
+        let line = match line.find('#') {
+            Some(off) => &line[..off],
+            None => line,
+        };
+        let mut tokens = line.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let args: Vec<&str> = tokens.collect();
+
+        match (keyword, current.as_mut()) {
+            ("newmtl", _) => {
+                if let Some((name, color)) = current.take() {
+                    materials.insert(name, color);
+                }
+                current = Some((args.join(" "), [1.0, 1.0, 1.0, 1.0]));
+            }
+            ("Kd", Some((_, color))) => match parse_floats(&args, lineno).as_deref() {
+                Ok([r, g, b]) => *color = [*r, *g, *b, color[3]],
+                // spectral and xyz forms are not supported.
+                _ => warn!("{:?} line {}: skip Kd {:?}", loc, lineno, line),
+            },
+            ("d", Some((_, color))) => match parse_floats(&args, lineno).as_deref() {
+                Ok([d]) => color[3] = *d,
+                _ => warn!("{:?} line {}: skip d {:?}", loc, lineno, line),
+            },
+            ("Tr", Some((_, color))) => match parse_floats(&args, lineno).as_deref() {
+                Ok([tr]) => color[3] = 1.0 - *tr,
+                _ => warn!("{:?} line {}: skip Tr {:?}", loc, lineno, line),
+            },
+            ("Kd" | "d" | "Tr", None) => {
+                err_at!(Invalid, msg: "{:?} line {}: {} before newmtl", loc, lineno, keyword)?
+            }
+            _ => (),
+        }
+    }
+
+    if let Some((name, color)) = current.take() {
+        materials.insert(name, color);
+    }
+
+    Ok(materials)
+}
+
+fn parse_floats(args: &[&str], lineno: usize) -> Result<Vec<f32>> {
+    let mut vals = Vec::with_capacity(args.len());
+    for arg in args.iter() {
+        vals.push(err_at!(FailConvert, arg.parse::<f32>(), "obj line {}", lineno)?);
+    }
+    Ok(vals)
+}
+
+// Parse `v`, `v/vt`, `v//vn` or `v/vt/vn` and return zero-based indices. OBJ
+// indices are one-based and negative indices are relative to the end of the
+// list parsed so far.
+fn parse_face_vertex(
+    arg: &str,
+    lineno: usize,
+    (n_pos, n_uv, n_normal): (usize, usize, usize),
+) -> Result<(usize, Option<usize>, Option<usize>)> {
+    let resolve = |s: &str, n: usize| -> Result<Option<usize>> {
+        if s.is_empty() {
+            return Ok(None);
+        }
+        let i = err_at!(FailConvert, s.parse::<isize>(), "obj line {}", lineno)?;
+        let index = match i {
+            i if i > 0 && (i as usize) <= n => (i as usize) - 1,
+            i if i < 0 && i.unsigned_abs() <= n => n - i.unsigned_abs(),
+            _ => err_at!(Invalid, msg: "obj line {}: index {} out of {}", lineno, i, n)?,
+        };
+        Ok(Some(index))
+    };
+
+    let parts: Vec<&str> = arg.split('/').collect();
+    let (p, t, n) = match parts.as_slice() {
+        [p] => (resolve(p, n_pos)?, None, None),
+        [p, t] => (resolve(p, n_pos)?, resolve(t, n_uv)?, None),
+        [p, t, n] => (resolve(p, n_pos)?, resolve(t, n_uv)?, resolve(n, n_normal)?),
+        _ => err_at!(Invalid, msg: "obj line {}: invalid face vertex {:?}", lineno, arg)?,
+    };
+
+    match p {
+        Some(p) => Ok((p, t, n)),
+        None => {
+            err_at!(Invalid, msg: "obj line {}: face vertex without position", lineno)
+        }
+    }
+}
@@ -0,0 +1,427 @@
+//! Stanford PLY loader, for ascii, binary_little_endian and binary_big_endian
+//! encodings.
+
+use std::convert::TryInto;
+
+use crate::{model::Model, Error, Result};
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Encoding {
+    Ascii,
+    LittleEndian,
+    BigEndian,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn from_name(name: &str) -> Option<Scalar> {
+        let val = match name {
+            "char" | "int8" => Scalar::I8,
+            "uchar" | "uint8" => Scalar::U8,
+            "short" | "int16" => Scalar::I16,
+            "ushort" | "uint16" => Scalar::U16,
+            "int" | "int32" => Scalar::I32,
+            "uint" | "uint32" => Scalar::U32,
+            "float" | "float32" => Scalar::F32,
+            "double" | "float64" => Scalar::F64,
+            _ => return None,
+        };
+        Some(val)
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        }
+    }
+
+    // color components are normalized when stored as integers.
+    fn to_color(self, val: f64) -> f32 {
+        match self {
+            Scalar::U8 | Scalar::I8 => (val / 255.0) as f32,
+            Scalar::U16 | Scalar::I16 => (val / 65535.0) as f32,
+            _ => val as f32,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Property {
+    Scalar {
+        name: String,
+        ty: Scalar,
+    },
+    List {
+        name: String,
+        count: Scalar,
+        item: Scalar,
+    },
+}
+
+#[derive(Clone, Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+impl Element {
+    // Minimum number of bytes for a single element in the body, counts in the
+    // header are not trusted beyond what the body can hold.
+    fn min_size(&self, encoding: Encoding) -> usize {
+        let size: usize = self
+            .properties
+            .iter()
+            .map(|p| match (p, encoding) {
+                // a digit and a separator.
+                (_, Encoding::Ascii) => 2,
+                (Property::Scalar { ty, .. }, _) => ty.size(),
+                (Property::List { count, .. }, _) => count.size(),
+            })
+            .sum();
+        size.max(1)
+    }
+}
+
+/// Property values of a single element, lists are flattened.
+type Values = Vec<Vec<f64>>;
+
+pub fn from_bytes(data: &[u8]) -> Result<Model> {
+    let (encoding, elements, offset, lineno) = parse_header(data)?;
+
+    let mut model = Model::default();
+    let mut reader = match encoding {
+        Encoding::Ascii => {
+            let txt = err_at!(FailConvert, std::str::from_utf8(&data[offset..]), "ply")?;
+            Reader::Ascii { lines: txt.lines(), lineno }
+        }
+        encoding => Reader::Binary { data, offset, encoding },
+    };
+
+    for element in elements.iter() {
+        match element.name.as_str() {
+            "vertex" => {
+                let n = (data.len() - offset) / element.min_size(encoding);
+                model.vertices.reserve(element.count.min(n));
+                read_vertices(&mut reader, element, &mut model)?
+            }
+            "face" => read_faces(&mut reader, element, &mut model)?,
+            _ => {
+                for i in 0..element.count {
+                    reader.read_element(element, i)?;
+                }
+            }
+        }
+    }
+
+    let n = model.vertices.len();
+    if let Some(index) = model.indices.iter().find(|i| (**i as usize) >= n) {
+        err_at!(Invalid, msg: "ply: face index {} out of {} vertices", index, n)?
+    }
+
+    model.fix_normals();
+    Ok(model)
+}
+
+fn read_vertices(
+    reader: &mut Reader,
+    element: &Element,
+    model: &mut Model,
+) -> Result<()> {
+    let find = |names: &[&str]| -> Option<(usize, Scalar)> {
+        element.properties.iter().enumerate().find_map(|(i, p)| match p {
+            Property::Scalar { name, ty } if names.contains(&name.as_str()) => {
+                Some((i, *ty))
+            }
+            _ => None,
+        })
+    };
+
+    let position = match (find(&["x"]), find(&["y"]), find(&["z"])) {
+        (Some(x), Some(y), Some(z)) => [x.0, y.0, z.0],
+        _ => err_at!(Invalid, msg: "ply: vertex element without x, y, z properties")?,
+    };
+    let normal = match (find(&["nx"]), find(&["ny"]), find(&["nz"])) {
+        (Some(x), Some(y), Some(z)) => Some([x.0, y.0, z.0]),
+        _ => None,
+    };
+    let uv = match (find(&["u", "s", "texture_u"]), find(&["v", "t", "texture_v"])) {
+        (Some(u), Some(v)) => Some([u.0, v.0]),
+        _ => None,
+    };
+    let rgb = match (
+        find(&["red", "diffuse_red"]),
+        find(&["green", "diffuse_green"]),
+        find(&["blue", "diffuse_blue"]),
+    ) {
+        (Some(r), Some(g), Some(b)) => Some([r, g, b]),
+        _ => None,
+    };
+    let alpha = find(&["alpha"]);
+
+    for i in 0..element.count {
+        let values = reader.read_element(element, i)?;
+        let val = |j: usize| values[j][0];
+
+        let mut vertex = super::default_vertex();
+        vertex.position = position.map(|j| val(j) as f32);
+        if let Some(normal) = normal {
+            vertex.normal = normal.map(|j| val(j) as f32);
+        }
+        if let Some([u, v]) = uv {
+            vertex.uv = [val(u) as f32, 1.0 - val(v) as f32];
+        }
+        if let Some(rgb) = rgb {
+            let [r, g, b] = rgb.map(|(j, ty)| ty.to_color(val(j)));
+            let a = alpha.map(|(j, ty)| ty.to_color(val(j))).unwrap_or(1.0);
+            vertex.color = [r, g, b, a];
+        }
+        model.vertices.push(vertex);
+    }
+
+    Ok(())
+}
+
+fn read_faces(reader: &mut Reader, element: &Element, model: &mut Model) -> Result<()> {
+    let j = element.properties.iter().position(|p| {
+        matches!(p, Property::List { name, .. }
+            if name == "vertex_indices" || name == "vertex_index")
+    });
+    let j = match j {
+        Some(j) => j,
+        None => err_at!(Invalid, msg: "ply: face element without vertex_indices")?,
+    };
+
+    for i in 0..element.count {
+        let values = reader.read_element(element, i)?;
+        let mut polygon: Vec<u32> = Vec::with_capacity(values[j].len());
+        for index in values[j].iter() {
+            // `as u32` would saturate negative and truncate fractional indices.
+            if *index < 0.0 || index.fract() != 0.0 || *index > f64::from(u32::MAX) {
+                err_at!(Invalid, msg: "ply: face {} with invalid index {}", i, index)?
+            }
+            polygon.push(*index as u32);
+        }
+        if polygon.len() < 3 {
+            err_at!(Invalid, msg: "ply: face {} with {} vertices", i, polygon.len())?
+        }
+        super::triangulate(&polygon, &mut model.indices);
+    }
+
+    Ok(())
+}
+
+// Return encoding, elements, byte offset of body and line number of the last
+// header line.
+fn parse_header(data: &[u8]) -> Result<(Encoding, Vec<Element>, usize, usize)> {
+    let mut encoding = None;
+    let mut elements: Vec<Element> = vec![];
+
+    let mut offset = 0;
+    let mut lineno = 0;
+    loop {
+        let end = match data[offset..].iter().position(|b| *b == b'\n') {
+            Some(end) => offset + end,
+            None => err_at!(Invalid, msg: "ply line {}: missing end_header", lineno + 1)?,
+        };
+        lineno += 1;
+        let line = err_at!(FailConvert, std::str::from_utf8(&data[offset..end]))?;
+        offset = end + 1;
+
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["ply"] if lineno == 1 => (),
+            _ if lineno == 1 => err_at!(Invalid, msg: "ply line 1: not a ply file")?,
+            ["format", enc, "1.0"] => {
+                encoding = match *enc {
+                    "ascii" => Some(Encoding::Ascii),
+                    "binary_little_endian" => Some(Encoding::LittleEndian),
+                    "binary_big_endian" => Some(Encoding::BigEndian),
+                    _ => err_at!(Invalid, msg: "ply line {}: format {:?}", lineno, enc)?,
+                }
+            }
+            ["comment", ..] | ["obj_info", ..] | [] => (),
+            ["element", name, count] => {
+                let count = err_at!(FailConvert, count.parse(), "ply line {}", lineno)?;
+                let name = name.to_string();
+                elements.push(Element { name, count, properties: vec![] });
+            }
+            ["property", ty, name] => {
+                let ty = match Scalar::from_name(ty) {
+                    Some(ty) => ty,
+                    None => err_at!(Invalid, msg: "ply line {}: type {:?}", lineno, ty)?,
+                };
+                let name = name.to_string();
+                match elements.last_mut() {
+                    Some(e) => e.properties.push(Property::Scalar { name, ty }),
+                    None => {
+                        err_at!(Invalid, msg: "ply line {}: property before element", lineno)?
+                    }
+                }
+            }
+            ["property", "list", count, item, name] => {
+                let (count, item) = match (
+                    Scalar::from_name(count),
+                    Scalar::from_name(item),
+                ) {
+                    (Some(count), Some(item)) => (count, item),
+                    _ => {
+                        err_at!(Invalid, msg: "ply line {}: invalid list {:?}", lineno, line)?
+                    }
+                };
+                let name = name.to_string();
+                match elements.last_mut() {
+                    Some(e) => e.properties.push(Property::List { name, count, item }),
+                    None => {
+                        err_at!(Invalid, msg: "ply line {}: property before element", lineno)?
+                    }
+                }
+            }
+            ["end_header"] => break,
+            _ => err_at!(Invalid, msg: "ply line {}: invalid header {:?}", lineno, line)?,
+        }
+    }
+
+    match encoding {
+        Some(encoding) => Ok((encoding, elements, offset, lineno)),
+        None => err_at!(Invalid, msg: "ply: missing format in header"),
+    }
+}
+
+enum Reader<'a> {
+    Ascii {
+        lines: std::str::Lines<'a>,
+        lineno: usize,
+    },
+    Binary {
+        data: &'a [u8],
+        offset: usize,
+        encoding: Encoding,
+    },
+}
+
+impl<'a> Reader<'a> {
+    // read the i-th element, return one vector of values for every property.
+    fn read_element(&mut self, element: &Element, i: usize) -> Result<Values> {
+        match self {
+            Reader::Ascii { lines, lineno } => {
+                let line = loop {
+                    *lineno += 1;
+                    match lines.next() {
+                        Some(line) if line.trim().is_empty() => continue,
+                        Some(line) => break line,
+                        None => err_at!(
+                            Invalid,
+                            msg: "ply line {}: missing {} {}", lineno, element.name, i
+                        )?,
+                    }
+                };
+                Self::parse_ascii(line, *lineno, element)
+            }
+            Reader::Binary { data, offset, encoding } => {
+                let values = Self::parse_binary(data, offset, *encoding, element);
+                match values {
+                    Some(values) => Ok(values),
+                    None => err_at!(
+                        Invalid,
+                        msg: "ply: truncated {} {} at byte {}", element.name, i, offset
+                    ),
+                }
+            }
+        }
+    }
+
+    fn parse_ascii(line: &str, lineno: usize, element: &Element) -> Result<Values> {
+        let mut tokens = line.split_whitespace();
+        let mut next = |name: &str| -> Result<f64> {
+            match tokens.next() {
+                Some(tok) => {
+                    err_at!(FailConvert, tok.parse::<f64>(), "ply line {}", lineno)
+                }
+                None => err_at!(Invalid, msg: "ply line {}: missing {}", lineno, name),
+            }
+        };
+
+        let mut values = Vec::with_capacity(element.properties.len());
+        for property in element.properties.iter() {
+            match property {
+                Property::Scalar { name, .. } => values.push(vec![next(name)?]),
+                Property::List { name, .. } => {
+                    let n = next(name)? as usize;
+                    let mut items = vec![];
+                    for _ in 0..n {
+                        items.push(next(name)?);
+                    }
+                    values.push(items);
+                }
+            }
+        }
+
+        Ok(values)
+    }
+
+    fn parse_binary(
+        data: &[u8],
+        offset: &mut usize,
+        encoding: Encoding,
+        element: &Element,
+    ) -> Option<Values> {
+        let mut read = |ty: Scalar| -> Option<f64> {
+            let bytes = data.get(*offset..(*offset + ty.size()))?;
+            *offset += ty.size();
+            let le = encoding == Encoding::LittleEndian;
+            macro_rules! decode {
+                ($t:ty) => {{
+                    let arr = bytes.try_into().ok()?;
+                    if le {
+                        <$t>::from_le_bytes(arr) as f64
+                    } else {
+                        <$t>::from_be_bytes(arr) as f64
+                    }
+                }};
+            }
+            let val = match ty {
+                Scalar::I8 => decode!(i8),
+                Scalar::U8 => decode!(u8),
+                Scalar::I16 => decode!(i16),
+                Scalar::U16 => decode!(u16),
+                Scalar::I32 => decode!(i32),
+                Scalar::U32 => decode!(u32),
+                Scalar::F32 => decode!(f32),
+                Scalar::F64 => decode!(f64),
+            };
+            Some(val)
+        };
+
+        let mut values = Vec::with_capacity(element.properties.len());
+        for property in element.properties.iter() {
+            match property {
+                Property::Scalar { ty, .. } => values.push(vec![read(*ty)?]),
+                Property::List { count, item, .. } => {
+                    let n = read(*count)? as usize;
+                    let mut items = vec![];
+                    for _ in 0..n {
+                        items.push(read(*item)?);
+                    }
+                    values.push(items);
+                }
+            }
+        }
+
+        Some(values)
+    }
+}
@@ -0,0 +1,115 @@
+//! STL loader, for ascii and binary encodings.
+
+use std::convert::TryInto;
+
+use crate::{model::Model, Error, Result};
+
+const HEADER_SIZE: usize = 84;
+const TRIANGLE_SIZE: usize = 50;
+
+pub fn from_bytes(data: &[u8]) -> Result<Model> {
+    // binary files are allowed to start with "solid" as well, hence the size
+    // of the file decides the encoding.
+    let n = data
+        .get(80..HEADER_SIZE)
+        .map(|n| u32::from_le_bytes(n.try_into().unwrap()) as usize);
+
+    let mut model = match n {
+        Some(n) if HEADER_SIZE + (n * TRIANGLE_SIZE) == data.len() => from_binary(data)?,
+        Some(n) if std::str::from_utf8(data).is_err() => err_at!(
+            Invalid,
+            msg: "stl: binary with {} triangles, expected {} bytes, found {}",
+            n,
+            HEADER_SIZE + (n * TRIANGLE_SIZE),
+            data.len()
+        )?,
+        _ => from_ascii(data)?,
+    };
+    model.fix_normals();
+
+    Ok(model)
+}
+
+fn from_binary(data: &[u8]) -> Result<Model> {
+    let mut model = Model::default();
+
+    let read = |off: usize| -> [f32; 3] {
+        let f =
+            |i: usize| f32::from_le_bytes(data[off + i..off + i + 4].try_into().unwrap());
+        [f(0), f(4), f(8)]
+    };
+
+    for off in (HEADER_SIZE..data.len()).step_by(TRIANGLE_SIZE) {
+        let normal = read(off);
+        for i in 0..3 {
+            let mut vertex = super::default_vertex();
+            vertex.position = read(off + 12 + (i * 12));
+            vertex.normal = normal;
+            model.indices.push(model.vertices.len() as u32);
+            model.vertices.push(vertex);
+        }
+    }
+
+    Ok(model)
+}
+
+fn from_ascii(data: &[u8]) -> Result<Model> {
+    let txt = err_at!(FailConvert, std::str::from_utf8(data), "stl")?;
+
+    let mut model = Model::default();
+    let mut normal = [0.0, 0.0, 0.0];
+    let mut facet: Vec<[f32; 3]> = vec![];
+    let mut in_solid = false;
+
+    let parse = |args: &[&str], lineno: usize| -> Result<[f32; 3]> {
+        let mut vals = [0.0; 3];
+        match args {
+            [x, y, z] => {
+                for (val, arg) in vals.iter_mut().zip([x, y, z]) {
+                    *val =
+                        err_at!(FailConvert, arg.parse::<f32>(), "stl line {}", lineno)?;
+                }
+            }
+            _ => {
+                err_at!(Invalid, msg: "stl line {}: expected 3 values {:?}", lineno, args)?
+            }
+        }
+        Ok(vals)
+    };
+
+    for (lineno, line) in txt.lines().enumerate().map(|(i, l)| (i + 1, l)) {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            [] => (),
+            ["solid", ..] => in_solid = true,
+            _ if !in_solid => {
+                err_at!(Invalid, msg: "stl line {}: expected solid", lineno)?
+            }
+            ["facet", "normal", args @ ..] => {
+                normal = parse(args, lineno)?;
+                facet.clear();
+            }
+            ["outer", "loop"] => (),
+            ["vertex", args @ ..] => facet.push(parse(args, lineno)?),
+            ["endloop"] => (),
+            ["endfacet"] => {
+                if facet.len() < 3 {
+                    err_at!(Invalid, msg: "stl line {}: facet with {} vertices", lineno, facet.len())?
+                }
+                let base = model.vertices.len() as u32;
+                let polygon: Vec<u32> = (base..base + facet.len() as u32).collect();
+                for position in facet.drain(..) {
+                    let mut vertex = super::default_vertex();
+                    vertex.position = position;
+                    vertex.normal = normal;
+                    model.vertices.push(vertex);
+                }
+                super::triangulate(&polygon, &mut model.indices);
+            }
+            ["endsolid", ..] => in_solid = false,
+            _ => err_at!(Invalid, msg: "stl line {}: invalid {:?}", lineno, line)?,
+        }
+    }
+
+    Ok(model)
+}
@@ -0,0 +1,461 @@
+use winit::event::{
+    ElementState, Event, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta,
+    VirtualKeyCode, WindowEvent,
+};
+
+use std::{collections::BTreeMap, fmt, result, str::FromStr};
+
+use crate::{Error, Result};
+
+/// Key, mouse-button or wheel motion that can be part of a [Chord].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Trigger {
+    Key(VirtualKeyCode),
+    Mouse(MouseButton),
+    WheelUp,
+    WheelDown,
+    WheelLeft,
+    WheelRight,
+}
+
+/// A trigger along with the modifiers that shall be held down, like
+/// `Ctrl+Shift+S`, `MouseLeft+Alt` or `WheelUp`.
+///
+/// Chords are parsed from `+` separated, case-insensitive, names. Modifiers are
+/// `Ctrl`, `Shift`, `Alt` and `Logo`, with `Control`, `Option`, `Super`, `Cmd`
+/// and `Meta` as aliases. Keys are named after [VirtualKeyCode] variants, with
+/// aliases like `Esc`, `Enter`, `Backspace` and `0`-`9`. Mouse buttons are
+/// `MouseLeft`, `MouseRight`, `MouseMiddle` and `Mouse<n>`, wheel motions are
+/// `WheelUp`, `WheelDown`, `WheelLeft` and `WheelRight`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Chord {
+    pub modifiers: ModifiersState,
+    pub trigger: Trigger,
+}
+
+impl FromStr for Chord {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Chord> {
+        let mut modifiers = ModifiersState::empty();
+        let mut trigger = None;
+
+        for token in s.split('+').map(|t| t.trim()) {
+            let name = token.to_lowercase();
+            match name.as_str() {
+                "ctrl" | "control" => modifiers |= ModifiersState::CTRL,
+                "shift" => modifiers |= ModifiersState::SHIFT,
+                "alt" | "option" => modifiers |= ModifiersState::ALT,
+                "logo" | "super" | "cmd" | "meta" => modifiers |= ModifiersState::LOGO,
+                _ if trigger.is_some() => {
+                    err_at!(Invalid, msg: "chord {:?} with more than one key", s)?
+                }
+                _ => trigger = Some(to_trigger(&name, s)?),
+            }
+        }
+
+        match trigger {
+            Some(trigger) => Ok(Chord { modifiers, trigger }),
+            None => err_at!(Invalid, msg: "chord {:?} without key", s),
+        }
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
+        let mods = [
+            (ModifiersState::CTRL, "Ctrl"),
+            (ModifiersState::SHIFT, "Shift"),
+            (ModifiersState::ALT, "Alt"),
+            (ModifiersState::LOGO, "Logo"),
+        ];
+        for (m, name) in mods.iter() {
+            if self.modifiers.contains(*m) {
+                write!(f, "{}+", name)?;
+            }
+        }
+
+        match self.trigger {
+            Trigger::Key(key) => write!(f, "{:?}", key),
+            Trigger::Mouse(MouseButton::Left) => write!(f, "MouseLeft"),
+            Trigger::Mouse(MouseButton::Right) => write!(f, "MouseRight"),
+            Trigger::Mouse(MouseButton::Middle) => write!(f, "MouseMiddle"),
+            Trigger::Mouse(MouseButton::Other(n)) => write!(f, "Mouse{}", n),
+            Trigger::WheelUp => write!(f, "WheelUp"),
+            Trigger::WheelDown => write!(f, "WheelDown"),
+            Trigger::WheelLeft => write!(f, "WheelLeft"),
+            Trigger::WheelRight => write!(f, "WheelRight"),
+        }
+    }
+}
+
+impl Chord {
+    /// Return the chord triggered by `event`, with `modifiers` held down. Only
+    /// key-press, button-press and wheel events trigger a chord.
+    pub fn from_event<E>(event: &Event<E>, modifiers: ModifiersState) -> Option<Chord> {
+        let trigger = match event {
+            Event::WindowEvent { event, .. } => match event {
+                WindowEvent::KeyboardInput {
+                    input:
+                        KeyboardInput {
+                            state: ElementState::Pressed,
+                            virtual_keycode: Some(key),
+                            ..
+                        },
+                    ..
+                } => Trigger::Key(*key),
+                WindowEvent::MouseInput {
+                    state: ElementState::Pressed, button, ..
+                } => Trigger::Mouse(*button),
+                WindowEvent::MouseWheel { delta, .. } => {
+                    let (x, y) = match delta {
+                        MouseScrollDelta::LineDelta(x, y) => (*x as f64, *y as f64),
+                        MouseScrollDelta::PixelDelta(pos) => (pos.x, pos.y),
+                    };
+                    match (x, y) {
+                        (_, y) if y > 0.0 => Trigger::WheelUp,
+                        (_, y) if y < 0.0 => Trigger::WheelDown,
+                        (x, _) if x > 0.0 => Trigger::WheelRight,
+                        (x, _) if x < 0.0 => Trigger::WheelLeft,
+                        (_, _) => return None,
+                    }
+                }
+                _ => return None,
+            },
+            _ => return None,
+        };
+
+        Some(Chord { modifiers, trigger })
+    }
+}
+
+/// Type maps chords to named actions.
+///
+/// Bindings are loaded from the `[bindings]` section of the configuration
+/// file, refer [crate::Config::from_file], where each action is bound to one
+/// or more chords. Actions can be re-bound for a context, like an editor or a
+/// modal dialog, in the `[bindings.context.<name>]` sub-section. While a
+/// context is active, refer [Bindings::set_context], its bindings override the
+/// default bindings for the same action.
+///
+/// ```toml
+/// [bindings]
+/// quit = ["Ctrl+Q", "Escape"]
+/// save = "Ctrl+Shift+S"
+/// pan = "MouseLeft+Alt"
+/// zoom_in = "WheelUp"
+///
+/// [bindings.context.editor]
+/// save = "Ctrl+S"
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Bindings {
+    actions: BTreeMap<String, Vec<Chord>>,
+    contexts: BTreeMap<String, BTreeMap<String, Vec<Chord>>>,
+    context: Option<String>,
+}
+
+impl Bindings {
+    /// Convert the `[bindings]` section from toml to Bindings.
+    pub fn from_toml(val: toml::Value) -> Result<Bindings> {
+        let table = match val {
+            toml::Value::Table(table) => table,
+            val => err_at!(Invalid, msg: "bindings not a table {}", val)?,
+        };
+
+        let mut bindings = Bindings::default();
+        for (name, val) in table.into_iter() {
+            match (name.as_str(), val) {
+                ("context", toml::Value::Table(contexts)) => {
+                    for (context, val) in contexts.into_iter() {
+                        let actions = match val {
+                            toml::Value::Table(actions) => actions,
+                            val => err_at!(
+                                Invalid,
+                                msg: "bindings.context.{} not a table {}",
+                                context,
+                                val
+                            )?,
+                        };
+                        let mut map = BTreeMap::new();
+                        for (action, val) in actions.into_iter() {
+                            map.insert(action.clone(), to_chords(&action, val)?);
+                        }
+                        bindings.contexts.insert(context, map);
+                    }
+                }
+                (_, val) => {
+                    let chords = to_chords(&name, val)?;
+                    bindings.actions.insert(name, chords);
+                }
+            }
+        }
+
+        Ok(bindings)
+    }
+
+    /// Bind `action` to `chords` in the default context, replacing existing
+    /// chords for the action.
+    pub fn bind(&mut self, action: &str, chords: Vec<Chord>) -> &mut Self {
+        self.actions.insert(action.to_string(), chords);
+        self
+    }
+
+    /// Bind `action` to `chords` in `context`, overriding the default binding
+    /// for the action while `context` is active.
+    pub fn bind_in(
+        &mut self,
+        context: &str,
+        action: &str,
+        chords: Vec<Chord>,
+    ) -> &mut Self {
+        let map = self.contexts.entry(context.to_string()).or_default();
+        map.insert(action.to_string(), chords);
+        self
+    }
+
+    /// Activate `context`, None shall fall back to the default bindings.
+    pub fn set_context(&mut self, context: Option<&str>) -> &mut Self {
+        self.context = context.map(|s| s.to_string());
+        self
+    }
+
+    pub fn to_context(&self) -> Option<String> {
+        self.context.clone()
+    }
+
+    /// Return chords bound to `action` in the active context.
+    pub fn to_chords(&self, action: &str) -> Vec<Chord> {
+        let overrides = self.as_overrides();
+        match overrides.and_then(|m| m.get(action)) {
+            Some(chords) => chords.clone(),
+            None => self.actions.get(action).cloned().unwrap_or_default(),
+        }
+    }
+
+    /// Return the action bound to `chord` in the active context.
+    pub fn to_action(&self, chord: &Chord) -> Option<String> {
+        let overrides = self.as_overrides();
+        if let Some(map) = overrides {
+            for (action, chords) in map.iter() {
+                if chords.contains(chord) {
+                    return Some(action.clone());
+                }
+            }
+        }
+
+        self.actions
+            .iter()
+            // actions re-bound in the active context shall not be triggered by
+            // their default chords.
+            .filter(|(action, _)| {
+                !overrides.map(|m| m.contains_key(*action)).unwrap_or(false)
+            })
+            .find(|(_, chords)| chords.contains(chord))
+            .map(|(action, _)| action.clone())
+    }
+
+    /// Return the action triggered by `event` in the active context.
+    pub fn to_event_action<E>(
+        &self,
+        event: &Event<E>,
+        modifiers: ModifiersState,
+    ) -> Option<String> {
+        self.to_action(&Chord::from_event(event, modifiers)?)
+    }
+
+    fn as_overrides(&self) -> Option<&BTreeMap<String, Vec<Chord>>> {
+        self.contexts.get(self.context.as_ref()?)
+    }
+}
+
+fn to_chords(action: &str, val: toml::Value) -> Result<Vec<Chord>> {
+    match val {
+        toml::Value::String(s) => Ok(vec![s.parse()?]),
+        toml::Value::Array(vals) => {
+            let mut chords = vec![];
+            for val in vals.into_iter() {
+                match val {
+                    toml::Value::String(s) => chords.push(s.parse()?),
+                    val => {
+                        err_at!(Invalid, msg: "binding {} invalid chord {}", action, val)?
+                    }
+                }
+            }
+            Ok(chords)
+        }
+        val => err_at!(Invalid, msg: "binding {} invalid chord {}", action, val),
+    }
+}
+
+fn to_trigger(name: &str, chord: &str) -> Result<Trigger> {
+    let trigger = match name {
+        "mouseleft" => Trigger::Mouse(MouseButton::Left),
+        "mouseright" => Trigger::Mouse(MouseButton::Right),
+        "mousemiddle" => Trigger::Mouse(MouseButton::Middle),
+        "wheelup" => Trigger::WheelUp,
+        "wheeldown" => Trigger::WheelDown,
+        "wheelleft" => Trigger::WheelLeft,
+        "wheelright" => Trigger::WheelRight,
+        "esc" => Trigger::Key(VirtualKeyCode::Escape),
+        "enter" => Trigger::Key(VirtualKeyCode::Return),
+        "backspace" => Trigger::Key(VirtualKeyCode::Back),
+        "del" => Trigger::Key(VirtualKeyCode::Delete),
+        "ins" => Trigger::Key(VirtualKeyCode::Insert),
+        "pgup" => Trigger::Key(VirtualKeyCode::PageUp),
+        "pgdown" => Trigger::Key(VirtualKeyCode::PageDown),
+        name if name.starts_with("mouse") => match name[5..].parse::<u16>() {
+            Ok(n) => Trigger::Mouse(MouseButton::Other(n)),
+            Err(_) => {
+                err_at!(Invalid, msg: "chord {:?} unknown button {:?}", chord, name)?
+            }
+        },
+        name => {
+            let key = KEYS.iter().find(|(n, _)| {
+                n.eq_ignore_ascii_case(name)
+                    || (n.starts_with("Key") && n[3..].eq_ignore_ascii_case(name))
+            });
+            match key {
+                Some((_, key)) => Trigger::Key(*key),
+                None => {
+                    err_at!(Invalid, msg: "chord {:?} unknown key {:?}", chord, name)?
+                }
+            }
+        }
+    };
+
+    Ok(trigger)
+}
+
+macro_rules! keys {
+    ($($key:ident),*) => {
+        &[$((stringify!($key), VirtualKeyCode::$key)),*]
+    };
+}
+
+const KEYS: &[(&str, VirtualKeyCode)] = keys!(
+    Key1,
+    Key2,
+    Key3,
+    Key4,
+    Key5,
+    Key6,
+    Key7,
+    Key8,
+    Key9,
+    Key0,
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    S,
+    T,
+    U,
+    V,
+    W,
+    X,
+    Y,
+    Z,
+    Escape,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    F13,
+    F14,
+    F15,
+    F16,
+    F17,
+    F18,
+    F19,
+    F20,
+    F21,
+    F22,
+    F23,
+    F24,
+    Snapshot,
+    Scroll,
+    Pause,
+    Insert,
+    Home,
+    Delete,
+    End,
+    PageDown,
+    PageUp,
+    Left,
+    Up,
+    Right,
+    Down,
+    Back,
+    Return,
+    Space,
+    Compose,
+    Caret,
+    Numlock,
+    Numpad0,
+    Numpad1,
+    Numpad2,
+    Numpad3,
+    Numpad4,
+    Numpad5,
+    Numpad6,
+    Numpad7,
+    Numpad8,
+    Numpad9,
+    NumpadAdd,
+    NumpadDivide,
+    NumpadDecimal,
+    NumpadComma,
+    NumpadEnter,
+    NumpadEquals,
+    NumpadMultiply,
+    NumpadSubtract,
+    Apostrophe,
+    Apps,
+    Asterisk,
+    At,
+    Backslash,
+    Capital,
+    Colon,
+    Comma,
+    Equals,
+    Grave,
+    LBracket,
+    Minus,
+    Period,
+    Plus,
+    RBracket,
+    Semicolon,
+    Slash,
+    Tab,
+    Underline,
+    Copy,
+    Paste,
+    Cut
+);
+
+#[cfg(test)]
+#[path = "bindings_test.rs"]
+mod bindings_test;
@@ -0,0 +1,78 @@
+use winit::{event::DeviceId, window::WindowId};
+
+use super::*;
+
+#[test]
+fn test_chord_parse() {
+    let chord: Chord = "Ctrl+Shift+S".parse().unwrap();
+    assert_eq!(chord.modifiers, ModifiersState::CTRL | ModifiersState::SHIFT);
+    assert_eq!(chord.trigger, Trigger::Key(VirtualKeyCode::S));
+    assert_eq!(chord.to_string(), "Ctrl+Shift+S");
+
+    let chord: Chord = "mouseleft + alt".parse().unwrap();
+    assert_eq!(chord.modifiers, ModifiersState::ALT);
+    assert_eq!(chord.trigger, Trigger::Mouse(MouseButton::Left));
+
+    let chord: Chord = "1".parse().unwrap();
+    assert_eq!(chord.trigger, Trigger::Key(VirtualKeyCode::Key1));
+    let chord: Chord = "Esc".parse().unwrap();
+    assert_eq!(chord.trigger, Trigger::Key(VirtualKeyCode::Escape));
+    let chord: Chord = "WheelUp".parse().unwrap();
+    assert_eq!(chord.to_string(), "WheelUp");
+
+    assert!("Ctrl+Shift".parse::<Chord>().is_err());
+    assert!("Ctrl+A+B".parse::<Chord>().is_err());
+    assert!("Ctrl+Blah".parse::<Chord>().is_err());
+}
+
+#[test]
+fn test_bindings_context() {
+    let val: toml::Value = toml::from_str(
+        r#"
+quit = ["Ctrl+Q", "Escape"]
+save = "Ctrl+Shift+S"
+
+[context.editor]
+save = "Ctrl+S"
+"#,
+    )
+    .unwrap();
+    let mut bindings = Bindings::from_toml(val).unwrap();
+
+    let ctrl_s: Chord = "Ctrl+S".parse().unwrap();
+    let ctrl_shift_s: Chord = "Ctrl+Shift+S".parse().unwrap();
+    let esc: Chord = "Escape".parse().unwrap();
+
+    assert_eq!(bindings.to_action(&esc), Some("quit".to_string()));
+    assert_eq!(bindings.to_action(&ctrl_shift_s), Some("save".to_string()));
+    assert_eq!(bindings.to_action(&ctrl_s), None);
+
+    bindings.set_context(Some("editor"));
+    assert_eq!(bindings.to_action(&ctrl_s), Some("save".to_string()));
+    assert_eq!(bindings.to_action(&ctrl_shift_s), None);
+    assert_eq!(bindings.to_action(&esc), Some("quit".to_string()));
+    assert_eq!(bindings.to_chords("save"), vec![ctrl_s]);
+
+    let val: toml::Value = toml::from_str("save = 10").unwrap();
+    assert!(Bindings::from_toml(val).is_err());
+}
+
+#[test]
+fn test_bindings_event() {
+    let mut bindings = Bindings::default();
+    bindings.bind("zoom_in", vec!["WheelUp".parse().unwrap()]);
+
+    #[allow(deprecated)]
+    let event: Event<()> = Event::WindowEvent {
+        window_id: unsafe { WindowId::dummy() },
+        event: WindowEvent::MouseWheel {
+            device_id: unsafe { DeviceId::dummy() },
+            delta: MouseScrollDelta::LineDelta(0.0, 1.0),
+            phase: winit::event::TouchPhase::Moved,
+            modifiers: ModifiersState::empty(),
+        },
+    };
+    let modifiers = ModifiersState::empty();
+    assert_eq!(bindings.to_event_action(&event, modifiers), Some("zoom_in".to_string()));
+    assert_eq!(bindings.to_event_action(&event, ModifiersState::CTRL), None);
+}
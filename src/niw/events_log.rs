@@ -1,37 +1,332 @@
+use serde::Serialize;
 use winit::event::{DeviceEvent, Event, StartCause, WindowEvent};
 
-use std::{collections::BTreeMap, time};
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Write,
+    time,
+};
 
+use crate::{Error, Result};
+
+/// Number of buckets in [LatencyHistogram], bucket `i` counts latencies upto
+/// `2^i` microseconds, and the last bucket counts everything beyond that.
+pub const LATENCY_BUCKETS: usize = 24;
+
+/// Type collects statistics on events handled by an event-loop.
+///
+/// For every event name, refer [to_event_name], this keeps a count, a rate
+/// computed over a sliding window and a histogram of latencies, where latency
+/// is the time elapsed since the last [Event::NewEvents]. Counts are also
+/// broken down per window and per device. Statistics can be printed to stdout
+/// or exported as JSON or CSV.
 pub struct EventsLog {
-    event_names: BTreeMap<String, u64>,
+    event_names: BTreeMap<&'static str, EventStat>,
+    windows: BTreeMap<String, BTreeMap<&'static str, u64>>,
+    devices: BTreeMap<String, BTreeMap<&'static str, u64>>,
+    recent: VecDeque<(time::Instant, &'static str)>,
+    rate_window: time::Duration,
+    new_events: Option<time::Instant>,
     genesis: time::Instant,
 }
 
+#[derive(Clone, Default)]
+struct EventStat {
+    count: u64,
+    latency: LatencyHistogram,
+}
+
+/// Histogram of latencies with power-of-two buckets in microseconds.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LatencyHistogram {
+    buckets: [u64; LATENCY_BUCKETS],
+    count: u64,
+    total: time::Duration,
+    max: time::Duration,
+}
+
 impl Default for EventsLog {
     fn default() -> EventsLog {
+        EventsLog::new(time::Duration::from_secs(1))
+    }
+}
+
+impl EventsLog {
+    /// Create a new log, event rates are computed over the last `rate_window`.
+    pub fn new(rate_window: time::Duration) -> EventsLog {
         EventsLog {
             event_names: BTreeMap::new(),
+            windows: BTreeMap::new(),
+            devices: BTreeMap::new(),
+            recent: VecDeque::new(),
+            rate_window,
+            new_events: None,
             genesis: time::Instant::now(),
         }
     }
+
+    pub fn append<T>(&mut self, event: &Event<T>) {
+        self.append_at(event, time::Instant::now())
+    }
+
+    fn append_at<T>(&mut self, event: &Event<T>, now: time::Instant) {
+        let name = to_event_name(event);
+
+        let stat = self.event_names.entry(name).or_default();
+        stat.count += 1;
+        match (event, self.new_events) {
+            (Event::NewEvents(_), _) => self.new_events = Some(now),
+            (_, Some(start)) => stat.latency.add(now.saturating_duration_since(start)),
+            (_, None) => (),
+        }
+
+        match event {
+            Event::WindowEvent { window_id, .. } | Event::RedrawRequested(window_id) => {
+                let id = format!("{:?}", window_id);
+                *self.windows.entry(id).or_default().entry(name).or_default() += 1;
+            }
+            Event::DeviceEvent { device_id, .. } => {
+                let id = format!("{:?}", device_id);
+                *self.devices.entry(id).or_default().entry(name).or_default() += 1;
+            }
+            _ => (),
+        }
+
+        self.recent.push_back((now, name));
+        self.expire(now);
+    }
+
+    fn expire(&mut self, now: time::Instant) {
+        while let Some((at, _)) = self.recent.front() {
+            match now.checked_duration_since(*at) {
+                Some(elapsed) if elapsed >= self.rate_window => {
+                    self.recent.pop_front();
+                }
+                _ => break,
+            }
+        }
+    }
 }
 
 impl EventsLog {
-    pub fn append<T>(&mut self, event: &Event<T>) {
-        let name = to_event_name(event).to_string();
+    /// Return the number of times event `name` was logged.
+    pub fn to_count(&self, name: &str) -> u64 {
+        self.event_names.get(name).map(|s| s.count).unwrap_or(0)
+    }
+
+    /// Return the rate of event `name`, per second, over the sliding window
+    /// ending now.
+    pub fn to_rate(&self, name: &str) -> f64 {
+        self.to_rate_at(name, time::Instant::now())
+    }
+
+    // Entries are expired only when events are appended, hence skip entries
+    // that fell out of the window since the last event.
+    fn to_rate_at(&self, name: &str, now: time::Instant) -> f64 {
+        let n = self
+            .recent
+            .iter()
+            .filter(|(at, n)| match now.checked_duration_since(*at) {
+                Some(elapsed) => *n == name && elapsed < self.rate_window,
+                None => *n == name,
+            })
+            .count();
+        (n as f64) / self.rate_window.as_secs_f64()
+    }
+
+    /// Return the rate of event `name`, per second, since this log was created.
+    pub fn to_average_rate(&self, name: &str) -> f64 {
+        let secs = self.genesis.elapsed().as_secs_f64();
+        match secs {
+            secs if secs > 0.0 => (self.to_count(name) as f64) / secs,
+            _ => 0.0,
+        }
+    }
+
+    /// Return the latency histogram of event `name`.
+    pub fn to_latency(&self, name: &str) -> Option<&LatencyHistogram> {
+        self.event_names.get(name).map(|s| &s.latency)
+    }
+
+    /// Return per window event counts, windows are identified by the debug
+    /// representation of their [winit::window::WindowId].
+    pub fn to_window_counts(&self) -> &BTreeMap<String, BTreeMap<&'static str, u64>> {
+        &self.windows
+    }
 
-        let n = self.event_names.get(&name).map(|v| *v).unwrap_or(0);
-        self.event_names.insert(name, n + 1);
+    /// Return per device event counts, devices are identified by the debug
+    /// representation of their [winit::event::DeviceId].
+    pub fn to_device_counts(&self) -> &BTreeMap<String, BTreeMap<&'static str, u64>> {
+        &self.devices
     }
 
     pub fn pretty_print(&self) {
-        let duration = self.genesis.elapsed();
-        for (key, value) in self.event_names.iter() {
-            let rate = match duration.as_secs() {
-                secs if secs > 0 => value / secs,
-                _ => *value,
-            };
-            println!("{:030} : {:10}/{}", key, value, rate)
+        println!(
+            "{:40} {:>10} {:>10} {:>10} {:>10} {:>10} {:>10}",
+            "event", "count", "rate/s", "avg/s", "p50(us)", "p99(us)", "max(us)"
+        );
+        for (name, stat) in self.event_names.iter() {
+            let lt = &stat.latency;
+            println!(
+                "{:40} {:>10} {:>10.1} {:>10.1} {:>10} {:>10} {:>10}",
+                name,
+                stat.count,
+                self.to_rate(name),
+                self.to_average_rate(name),
+                lt.to_percentile(50.0).as_micros(),
+                lt.to_percentile(99.0).as_micros(),
+                lt.to_max().as_micros(),
+            )
+        }
+
+        let breakdowns = [("window", &self.windows), ("device", &self.devices)];
+        for (what, breakdown) in breakdowns.iter() {
+            for (id, counts) in breakdown.iter() {
+                println!("{} {}", what, id);
+                for (name, count) in counts.iter() {
+                    println!("    {:36} {:>10}", name, count)
+                }
+            }
+        }
+    }
+
+    /// Export statistics as JSON.
+    pub fn to_json(&self) -> Result<String> {
+        let stats = EventsStats {
+            elapsed_us: self.genesis.elapsed().as_micros() as u64,
+            rate_window_us: self.rate_window.as_micros() as u64,
+            events: self
+                .event_names
+                .iter()
+                .map(|(name, stat)| EventStats {
+                    name,
+                    count: stat.count,
+                    rate: self.to_rate(name),
+                    average_rate: self.to_average_rate(name),
+                    latency: LatencyStats::from(&stat.latency),
+                })
+                .collect(),
+            windows: &self.windows,
+            devices: &self.devices,
+        };
+
+        err_at!(FailConvert, serde_json::to_string_pretty(&stats))
+    }
+
+    /// Export per event statistics as CSV, with a header row. Latencies are
+    /// in microseconds.
+    pub fn to_csv(&self) -> String {
+        let mut s = "event,count,rate,average_rate,mean_us,p50_us,p95_us,p99_us,max_us\n"
+            .to_string();
+        for (name, stat) in self.event_names.iter() {
+            let lt = LatencyStats::from(&stat.latency);
+            writeln!(
+                s,
+                "{},{},{:.3},{:.3},{},{},{},{},{}",
+                name,
+                stat.count,
+                self.to_rate(name),
+                self.to_average_rate(name),
+                lt.mean_us,
+                lt.p50_us,
+                lt.p95_us,
+                lt.p99_us,
+                lt.max_us
+            )
+            .ok();
+        }
+        s
+    }
+}
+
+impl LatencyHistogram {
+    pub fn add(&mut self, latency: time::Duration) {
+        let micros = latency.as_micros();
+        let bucket = (0..LATENCY_BUCKETS - 1)
+            .find(|i| micros <= (1_u128 << i))
+            .unwrap_or(LATENCY_BUCKETS - 1);
+
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.total += latency;
+        self.max = self.max.max(latency);
+    }
+
+    pub fn to_count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn to_mean(&self) -> time::Duration {
+        match self.count {
+            0 => time::Duration::ZERO,
+            n => time::Duration::from_nanos((self.total.as_nanos() / n as u128) as u64),
+        }
+    }
+
+    pub fn to_max(&self) -> time::Duration {
+        self.max
+    }
+
+    /// Return the upper bound of the bucket containing the `p`th percentile,
+    /// `p` is in the range 0.0 to 100.0. Never exceeds the maximum latency.
+    pub fn to_percentile(&self, p: f64) -> time::Duration {
+        let target = ((self.count as f64) * p / 100.0).ceil().max(1.0) as u64;
+
+        let mut acc = 0;
+        for (i, n) in self.buckets.iter().enumerate().take(LATENCY_BUCKETS - 1) {
+            acc += n;
+            if acc >= target {
+                return time::Duration::from_micros(1 << i).min(self.max);
+            }
+        }
+        self.max
+    }
+
+    /// Return latency counts per bucket, refer [LATENCY_BUCKETS].
+    pub fn as_buckets(&self) -> &[u64] {
+        &self.buckets
+    }
+}
+
+#[derive(Serialize)]
+struct EventsStats<'a> {
+    elapsed_us: u64,
+    rate_window_us: u64,
+    events: Vec<EventStats<'a>>,
+    windows: &'a BTreeMap<String, BTreeMap<&'static str, u64>>,
+    devices: &'a BTreeMap<String, BTreeMap<&'static str, u64>>,
+}
+
+#[derive(Serialize)]
+struct EventStats<'a> {
+    name: &'a str,
+    count: u64,
+    rate: f64,
+    average_rate: f64,
+    latency: LatencyStats,
+}
+
+#[derive(Serialize)]
+struct LatencyStats {
+    count: u64,
+    mean_us: u64,
+    p50_us: u64,
+    p95_us: u64,
+    p99_us: u64,
+    max_us: u64,
+    buckets: Vec<u64>,
+}
+
+impl<'a> From<&'a LatencyHistogram> for LatencyStats {
+    fn from(lt: &'a LatencyHistogram) -> LatencyStats {
+        LatencyStats {
+            count: lt.count,
+            mean_us: lt.to_mean().as_micros() as u64,
+            p50_us: lt.to_percentile(50.0).as_micros() as u64,
+            p95_us: lt.to_percentile(95.0).as_micros() as u64,
+            p99_us: lt.to_percentile(99.0).as_micros() as u64,
+            max_us: lt.max.as_micros() as u64,
+            buckets: lt.buckets.to_vec(),
         }
     }
 }
@@ -42,7 +337,7 @@ pub fn to_event_name<T>(event: &Event<T>) -> &'static str {
         Event::NewEvents(cause) => match cause {
             StartCause::ResumeTimeReached { .. } => "Event::NewEvents(ResumeTimeReached)",
             StartCause::WaitCancelled { .. } => "Event::NewEvents(WaitCancelled)",
-            StartCause::Poll => "Event::NewEvents(Pool)",
+            StartCause::Poll => "Event::NewEvents(Poll)",
             StartCause::Init => "Event::NewEvents(Init)",
         },
         Event::WindowEvent { event, .. } => match event {
@@ -82,8 +377,12 @@ pub fn to_event_name<T>(event: &Event<T>) -> &'static str {
         Event::Suspended => "Event::Suspended",
         Event::Resumed => "Event::Resumed",
         Event::MainEventsCleared => "Event::MainEventsCleared",
-        Event::RedrawRequested(_) => "Event::RedrawEventsCleared",
+        Event::RedrawRequested(_) => "Event::RedrawRequested",
         Event::RedrawEventsCleared => "Event::RedrawEventsCleared",
         Event::LoopDestroyed => "Event::LoopDestroyed",
     }
 }
+
+#[cfg(test)]
+#[path = "events_log_test.rs"]
+mod events_log_test;
@@ -0,0 +1,85 @@
+use winit::{event::DeviceId, window::WindowId};
+
+use super::*;
+
+#[test]
+fn test_latency_histogram() {
+    let mut lt = LatencyHistogram::default();
+    assert_eq!(lt.to_percentile(99.0), time::Duration::ZERO);
+
+    for micros in 1..=100 {
+        lt.add(time::Duration::from_micros(micros));
+    }
+    assert_eq!(lt.to_count(), 100);
+    assert_eq!(lt.to_mean(), time::Duration::from_nanos(50_500));
+    assert_eq!(lt.to_max(), time::Duration::from_micros(100));
+    assert_eq!(lt.to_percentile(50.0), time::Duration::from_micros(64));
+    assert_eq!(lt.to_percentile(99.0), time::Duration::from_micros(100));
+    assert_eq!(lt.as_buckets()[..8], [1, 1, 2, 4, 8, 16, 32, 36]);
+
+    lt.add(time::Duration::from_secs(3600));
+    assert_eq!(lt.as_buckets()[LATENCY_BUCKETS - 1], 1);
+    assert_eq!(lt.to_percentile(100.0), time::Duration::from_secs(3600));
+
+    // count beyond u32::MAX must not wrap.
+    lt.count = u64::from(u32::MAX) + 2;
+    lt.total = time::Duration::from_nanos(lt.count * 10);
+    assert_eq!(lt.to_mean(), time::Duration::from_nanos(10));
+}
+
+#[test]
+fn test_events_log() {
+    let mut log = EventsLog::new(time::Duration::from_millis(100));
+    let window_id = unsafe { WindowId::dummy() };
+    let device_id = unsafe { DeviceId::dummy() };
+
+    let start = time::Instant::now();
+    let at = |millis| start + time::Duration::from_millis(millis);
+
+    for frame in 0..10_u64 {
+        let t = frame * 20;
+        let events: Vec<(u64, Event<()>)> = vec![
+            (t, Event::NewEvents(StartCause::Poll)),
+            (t + 1, Event::WindowEvent { window_id, event: WindowEvent::Focused(true) }),
+            (t + 2, Event::DeviceEvent { device_id, event: DeviceEvent::Added }),
+            (t + 5, Event::RedrawRequested(window_id)),
+        ];
+        for (millis, event) in events.iter() {
+            log.append_at(event, at(*millis))
+        }
+    }
+
+    let name = "Event::RedrawRequested";
+    assert_eq!(log.to_count(name), 10);
+    assert_eq!(log.to_count("Event::NewEvents(Poll)"), 10);
+    // events within the last 100ms, at 105, 125, 145, 165 and 185 millis.
+    assert_eq!(log.to_rate(name), 50.0);
+    // rate decays while no events are logged, at 165 and 185 millis.
+    assert_eq!(log.to_rate_at(name, at(250)), 20.0);
+    assert_eq!(log.to_rate_at(name, at(1000)), 0.0);
+
+    let lt = log.to_latency(name).unwrap();
+    assert_eq!(lt.to_count(), 10);
+    assert_eq!(lt.to_max(), time::Duration::from_millis(5));
+    assert_eq!(log.to_latency("Event::NewEvents(Poll)").unwrap().to_count(), 0);
+
+    let windows = log.to_window_counts();
+    assert_eq!(windows.len(), 1);
+    let counts = windows.values().next().unwrap();
+    assert_eq!(counts.get("WindowEvent::Focused"), Some(&10));
+    assert_eq!(counts.get(name), Some(&10));
+    let devices = log.to_device_counts();
+    assert_eq!(devices.values().next().unwrap().get("DeviceEvent::Added"), Some(&10));
+
+    let csv = log.to_csv();
+    let mut lines = csv.lines();
+    assert!(lines.next().unwrap().starts_with("event,count,rate"));
+    assert!(lines.any(|l| l.starts_with("Event::RedrawRequested,10,50.000,")));
+
+    let value: serde_json::Value = serde_json::from_str(&log.to_json().unwrap()).unwrap();
+    let events = value["events"].as_array().unwrap();
+    assert_eq!(events.len(), 4);
+    let redraw = events.iter().find(|e| e["name"] == name).unwrap();
+    assert_eq!(redraw["count"], 10);
+    assert_eq!(redraw["latency"]["max_us"], 5000);
+}
@@ -0,0 +1,215 @@
+use winit::{
+    dpi::{LogicalPosition, PhysicalPosition},
+    event::{
+        ElementState, Event, KeyboardInput, ModifiersState, MouseButton,
+        MouseScrollDelta, Touch, TouchPhase, VirtualKeyCode, WindowEvent,
+    },
+};
+
+use std::collections::{BTreeMap, HashSet};
+
+/// Type tracks the state of keyboard, mouse and touch input for a window.
+///
+/// [SingleWindow][crate::niw::SingleWindow] updates this state before calling
+/// the event handlers, refer [SingleWindow::to_input_state][to_input_state].
+/// `just_pressed` and `just_released` queries, and wheel deltas, are for the
+/// current frame and reset after handling [Event::RedrawEventsCleared].
+///
+/// [to_input_state]: crate::niw::SingleWindow::to_input_state
+#[derive(Clone, Debug)]
+pub struct InputState {
+    scale_factor: f64,
+    keys: HashSet<VirtualKeyCode>,
+    keys_pressed: HashSet<VirtualKeyCode>,
+    keys_released: HashSet<VirtualKeyCode>,
+    buttons: HashSet<MouseButton>,
+    buttons_pressed: HashSet<MouseButton>,
+    buttons_released: HashSet<MouseButton>,
+    cursor: Option<PhysicalPosition<f64>>,
+    wheel_lines: (f32, f32),
+    wheel_pixels: (f64, f64),
+    modifiers: ModifiersState,
+    touches: BTreeMap<u64, PhysicalPosition<f64>>,
+}
+
+impl Default for InputState {
+    fn default() -> InputState {
+        InputState::new(crate::DEFAULT_SCALE_FACTOR as f64)
+    }
+}
+
+impl InputState {
+    /// Create input state for a window with `scale_factor`, later changes to
+    /// scale factor are tracked via [WindowEvent::ScaleFactorChanged].
+    pub fn new(scale_factor: f64) -> InputState {
+        InputState {
+            scale_factor,
+            keys: HashSet::new(),
+            keys_pressed: HashSet::new(),
+            keys_released: HashSet::new(),
+            buttons: HashSet::new(),
+            buttons_pressed: HashSet::new(),
+            buttons_released: HashSet::new(),
+            cursor: None,
+            wheel_lines: (0.0, 0.0),
+            wheel_pixels: (0.0, 0.0),
+            modifiers: ModifiersState::empty(),
+            touches: BTreeMap::new(),
+        }
+    }
+
+    /// Update input state from `event`. Applications using their own event-loop
+    /// shall call this for every event targeting the window, and call
+    /// [InputState::end_frame] after each frame.
+    pub fn handle_event<E>(&mut self, event: &Event<E>) {
+        let event = match event {
+            Event::WindowEvent { event, .. } => event,
+            _ => return,
+        };
+
+        match event {
+            WindowEvent::KeyboardInput {
+                input: KeyboardInput { virtual_keycode: Some(key), state, .. },
+                ..
+            } => match state {
+                ElementState::Pressed if self.keys.insert(*key) => {
+                    self.keys_pressed.insert(*key);
+                }
+                // key repeat
+                ElementState::Pressed => (),
+                ElementState::Released => {
+                    self.keys.remove(key);
+                    self.keys_released.insert(*key);
+                }
+            },
+            WindowEvent::MouseInput { state, button, .. } => match state {
+                ElementState::Pressed => {
+                    self.buttons.insert(*button);
+                    self.buttons_pressed.insert(*button);
+                }
+                ElementState::Released => {
+                    self.buttons.remove(button);
+                    self.buttons_released.insert(*button);
+                }
+            },
+            WindowEvent::CursorMoved { position, .. } => self.cursor = Some(*position),
+            WindowEvent::CursorLeft { .. } => self.cursor = None,
+            WindowEvent::MouseWheel { delta, .. } => match delta {
+                MouseScrollDelta::LineDelta(x, y) => {
+                    self.wheel_lines.0 += x;
+                    self.wheel_lines.1 += y;
+                }
+                MouseScrollDelta::PixelDelta(pos) => {
+                    self.wheel_pixels.0 += pos.x;
+                    self.wheel_pixels.1 += pos.y;
+                }
+            },
+            WindowEvent::ModifiersChanged(modifiers) => self.modifiers = *modifiers,
+            WindowEvent::Touch(Touch { phase, location, id, .. }) => match phase {
+                TouchPhase::Started | TouchPhase::Moved => {
+                    self.touches.insert(*id, *location);
+                }
+                TouchPhase::Ended | TouchPhase::Cancelled => {
+                    self.touches.remove(id);
+                }
+            },
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                self.scale_factor = *scale_factor
+            }
+            // release events are not delivered while the window is out of
+            // focus, treat everything as released to avoid stuck keys.
+            WindowEvent::Focused(false) => {
+                self.keys_released.extend(self.keys.drain());
+                self.buttons_released.extend(self.buttons.drain());
+                self.modifiers = ModifiersState::empty();
+                self.touches.clear();
+            }
+            _ => (),
+        }
+    }
+
+    /// Reset per-frame state, like just-pressed keys and wheel deltas.
+    pub fn end_frame(&mut self) {
+        self.keys_pressed.clear();
+        self.keys_released.clear();
+        self.buttons_pressed.clear();
+        self.buttons_released.clear();
+        self.wheel_lines = (0.0, 0.0);
+        self.wheel_pixels = (0.0, 0.0);
+    }
+}
+
+impl InputState {
+    pub fn is_key_pressed(&self, key: VirtualKeyCode) -> bool {
+        self.keys.contains(&key)
+    }
+
+    /// Return true if `key` was pressed in this frame.
+    pub fn is_key_just_pressed(&self, key: VirtualKeyCode) -> bool {
+        self.keys_pressed.contains(&key)
+    }
+
+    /// Return true if `key` was released in this frame.
+    pub fn is_key_just_released(&self, key: VirtualKeyCode) -> bool {
+        self.keys_released.contains(&key)
+    }
+
+    pub fn is_button_pressed(&self, button: MouseButton) -> bool {
+        self.buttons.contains(&button)
+    }
+
+    /// Return true if `button` was pressed in this frame.
+    pub fn is_button_just_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_pressed.contains(&button)
+    }
+
+    /// Return true if `button` was released in this frame.
+    pub fn is_button_just_released(&self, button: MouseButton) -> bool {
+        self.buttons_released.contains(&button)
+    }
+
+    /// Iterate over keys that are currently held down.
+    pub fn iter_pressed_keys(&self) -> impl Iterator<Item = VirtualKeyCode> + '_ {
+        self.keys.iter().copied()
+    }
+
+    pub fn to_scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// Return cursor position in physical pixels, None if the cursor is
+    /// outside the window.
+    pub fn to_cursor_physical(&self) -> Option<PhysicalPosition<f64>> {
+        self.cursor
+    }
+
+    /// Return cursor position in logical pixels, None if the cursor is
+    /// outside the window.
+    pub fn to_cursor_logical(&self) -> Option<LogicalPosition<f64>> {
+        self.cursor.map(|pos| pos.to_logical(self.scale_factor))
+    }
+
+    /// Return horizontal and vertical wheel delta in lines, for this frame.
+    pub fn to_wheel_lines(&self) -> (f32, f32) {
+        self.wheel_lines
+    }
+
+    /// Return horizontal and vertical wheel delta in physical pixels, for this
+    /// frame. Touchpads usually report deltas in pixels.
+    pub fn to_wheel_pixels(&self) -> (f64, f64) {
+        self.wheel_pixels
+    }
+
+    pub fn to_modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    /// Return active touch points, as (touch-id, position) in physical pixels.
+    pub fn to_touches(&self) -> Vec<(u64, PhysicalPosition<f64>)> {
+        self.touches.iter().map(|(id, pos)| (*id, *pos)).collect()
+    }
+}
+
+#[cfg(test)]
+#[path = "input_state_test.rs"]
+mod input_state_test;
@@ -0,0 +1,91 @@
+use winit::{event::DeviceId, window::WindowId};
+
+use super::*;
+
+fn window_event(event: WindowEvent<'static>) -> Event<'static, ()> {
+    let window_id = unsafe { WindowId::dummy() };
+    Event::WindowEvent { window_id, event }
+}
+
+#[allow(deprecated)]
+fn key(key: VirtualKeyCode, state: ElementState) -> Event<'static, ()> {
+    window_event(WindowEvent::KeyboardInput {
+        device_id: unsafe { DeviceId::dummy() },
+        input: KeyboardInput {
+            scancode: 0,
+            state,
+            virtual_keycode: Some(key),
+            modifiers: ModifiersState::empty(),
+        },
+        is_synthetic: false,
+    })
+}
+
+#[test]
+fn test_input_keys() {
+    let mut input = InputState::new(2.0);
+
+    input.handle_event(&key(VirtualKeyCode::A, ElementState::Pressed));
+    assert!(input.is_key_pressed(VirtualKeyCode::A));
+    assert!(input.is_key_just_pressed(VirtualKeyCode::A));
+
+    input.end_frame();
+    assert!(input.is_key_pressed(VirtualKeyCode::A));
+    assert!(!input.is_key_just_pressed(VirtualKeyCode::A));
+
+    // key repeat is not a fresh press.
+    input.handle_event(&key(VirtualKeyCode::A, ElementState::Pressed));
+    assert!(!input.is_key_just_pressed(VirtualKeyCode::A));
+
+    input.handle_event(&key(VirtualKeyCode::A, ElementState::Released));
+    assert!(!input.is_key_pressed(VirtualKeyCode::A));
+    assert!(input.is_key_just_released(VirtualKeyCode::A));
+
+    input.handle_event(&key(VirtualKeyCode::B, ElementState::Pressed));
+    input.handle_event(&window_event(WindowEvent::Focused(false)));
+    assert!(!input.is_key_pressed(VirtualKeyCode::B));
+    assert!(input.is_key_just_released(VirtualKeyCode::B));
+}
+
+#[test]
+fn test_input_mouse() {
+    let device_id = unsafe { DeviceId::dummy() };
+    let mut input = InputState::new(2.0);
+
+    #[allow(deprecated)]
+    input.handle_event(&window_event(WindowEvent::CursorMoved {
+        device_id,
+        position: PhysicalPosition::new(100.0, 50.0),
+        modifiers: ModifiersState::empty(),
+    }));
+    assert_eq!(input.to_cursor_physical(), Some(PhysicalPosition::new(100.0, 50.0)));
+    assert_eq!(input.to_cursor_logical(), Some(LogicalPosition::new(50.0, 25.0)));
+
+    #[allow(deprecated)]
+    input.handle_event(&window_event(WindowEvent::MouseInput {
+        device_id,
+        state: ElementState::Pressed,
+        button: MouseButton::Left,
+        modifiers: ModifiersState::empty(),
+    }));
+    assert!(input.is_button_just_pressed(MouseButton::Left));
+
+    #[allow(deprecated)]
+    for _ in 0..2 {
+        input.handle_event(&window_event(WindowEvent::MouseWheel {
+            device_id,
+            delta: MouseScrollDelta::LineDelta(0.0, 1.0),
+            phase: TouchPhase::Moved,
+            modifiers: ModifiersState::empty(),
+        }));
+    }
+    assert_eq!(input.to_wheel_lines(), (0.0, 2.0));
+
+    input.end_frame();
+    assert_eq!(input.to_wheel_lines(), (0.0, 0.0));
+    assert!(input.is_button_pressed(MouseButton::Left));
+    assert!(!input.is_button_just_pressed(MouseButton::Left));
+
+    input.handle_event(&window_event(WindowEvent::CursorLeft { device_id }));
+    assert_eq!(input.to_cursor_physical(), None);
+}
@@ -5,6 +5,32 @@
 //! surface for `wgpu`. Internally `SingleWindow` uses [winit] for `event_loop` and
 //! `window-handle`. This is suitable for applications required only one window.
 //!
+//! Keyboard, mouse and touch state is tracked by [SingleWindow] in an [InputState],
+//! refer [SingleWindow::to_input_state], so that handlers can query which keys
+//! and buttons are held down, cursor position and wheel deltas without
+//! maintaining that state by themselves.
+//!
+//! Chords, like `Ctrl+S` or `MouseLeft+Alt`, can be bound to named actions using
+//! [Bindings], typically loaded from the configuration file. Handlers subscribed
+//! via [SingleWindow::on_action] are called when the bound chord is triggered.
+//!
+//! By default [SingleWindow] redraws continuously. Applications that are idle
+//! most of the time can use [RedrawMode::OnDemand] to redraw only when their
+//! state is dirty, and use [Timers] for periodic work, so that the event-loop
+//! sleeps in between. Worker threads can post user events into the event-loop
+//! using an [EventProxy].
+//!
+//! The full stream of events, along with timestamps, can be recorded to a file
+//! using [SingleWindow::record]. Recorded events can be replayed through the
+//! same handlers using [SingleWindow::replay], without a window, which is
+//! useful to reproduce user-reported bugs and to run interaction tests on
+//! machines without a display.
+//!
+//! Applications requiring more than one window, like a main canvas along with
+//! inspector and palette windows, can use [MultiWindow]. Windows can be opened and
+//! closed while the event-loop is running, and all of them render using the same
+//! `wgpu` device. Handlers are subscribed per window via [WindowHandlers].
+//!
 //! For an exhaustive list of all possible events, refer [Event]. Application can
 //! subscribe handlers for individual events using the [SingleWindow] instance.
 //! Following is the list of individual events and its corresponding handler types.
@@ -49,12 +75,33 @@
 //! |  [DeviceEvent::Key]                   | on_device_key([Handler])
 //! |  [DeviceEvent::Text]                  | on_device_text([Handler])
 
+mod bindings;
 mod events_log;
+mod input_state;
+mod monitor;
+mod multi_window;
 mod pretty;
+mod proxy;
+mod record;
 mod single_window;
+mod timers;
 
-pub use events_log::{to_event_name, EventsLog};
+pub use bindings::{Bindings, Chord, Trigger};
+pub use events_log::{to_event_name, EventsLog, LatencyHistogram, LATENCY_BUCKETS};
+pub use input_state::InputState;
+pub use monitor::{
+    find_monitor, get_monitor_info, move_to_monitor, set_fullscreen, to_fullscreen,
+    to_fullscreen_at, to_monitors, to_video_mode, toggle_fullscreen, Monitor,
+    VideoModeInfo,
+};
+pub use multi_window::{MultiHandler, MultiWindow, WindowHandlers, Windows};
+pub use proxy::EventProxy;
+pub use record::{
+    load_records, Cause, Record, RecordDeviceEvent, RecordEvent, RecordWindowEvent,
+    Recorder, TouchForce,
+};
 pub use single_window::{Handler, SingleWindow};
+pub use timers::{RedrawMode, Timers};
 
 #[allow(unused_imports)]
 use winit::{
@@ -68,47 +115,3 @@ use winit::{
 
 #[allow(unused_imports)]
 use std::path::PathBuf;
-use std::{fmt, result};
-
-use crate::{Error, Result};
-
-pub struct Monitor {
-    pub name: String,
-    pub size: PhysicalSize<u32>,
-    pub scale_factor: f64,
-}
-
-impl fmt::Display for Monitor {
-    fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
-        write!(
-            f,
-            "MONITOR: {:?}, physical_size:{}x{} scale_factor:{}",
-            self.name, self.size.width, self.size.height, self.scale_factor
-        )
-    }
-}
-
-impl Monitor {
-    pub fn to_logical_size(&self) -> LogicalSize<u32> {
-        self.size.to_logical(self.scale_factor)
-    }
-}
-
-pub fn get_monitor_info() -> Result<Monitor> {
-    let ev = EventLoop::new();
-    let mh = match ev.primary_monitor() {
-        Some(mh) => mh,
-        None => match ev.available_monitors().next() {
-            Some(mh) => mh.clone(),
-            None => err_at!(Invalid, msg: "Cannot find a monitor, check the cables")?,
-        },
-    };
-
-    let val = Monitor {
-        name: mh.name().unwrap_or("unnamed-monitor".to_string()),
-        size: mh.size(),
-        scale_factor: mh.scale_factor(),
-    };
-
-    Ok(val)
-}
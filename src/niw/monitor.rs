@@ -0,0 +1,304 @@
+use log::warn;
+use winit::{
+    dpi::{LogicalSize, PhysicalPosition, PhysicalSize},
+    event_loop::{EventLoop, EventLoopWindowTarget},
+    monitor::{MonitorHandle, VideoMode},
+    window::{Fullscreen, Window},
+};
+
+use std::{fmt, result};
+
+use crate::{ConfigFullscreen, Error, FullscreenMode, MonitorSelect, Result};
+
+/// Monitor connected to the machine.
+pub struct Monitor {
+    pub name: String,
+    pub size: PhysicalSize<u32>,
+    pub scale_factor: f64,
+    /// Index of this monitor in the list of available monitors, refer
+    /// [MonitorSelect::Index].
+    pub index: usize,
+    pub position: PhysicalPosition<i32>,
+    pub primary: bool,
+    pub video_modes: Vec<VideoModeInfo>,
+}
+
+/// Plain copy of [VideoMode] attributes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VideoModeInfo {
+    pub size: PhysicalSize<u32>,
+    pub bit_depth: u16,
+    pub refresh_rate: u16,
+}
+
+impl fmt::Display for Monitor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
+        write!(
+            f,
+            "MONITOR: {:?}, physical_size:{}x{} scale_factor:{}",
+            self.name, self.size.width, self.size.height, self.scale_factor
+        )
+    }
+}
+
+impl fmt::Display for VideoModeInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
+        write!(
+            f,
+            "{}x{}@{}Hz {}bit",
+            self.size.width, self.size.height, self.refresh_rate, self.bit_depth
+        )
+    }
+}
+
+impl<'a> From<&'a VideoMode> for VideoModeInfo {
+    fn from(mode: &'a VideoMode) -> VideoModeInfo {
+        VideoModeInfo {
+            size: mode.size(),
+            bit_depth: mode.bit_depth(),
+            refresh_rate: mode.refresh_rate(),
+        }
+    }
+}
+
+impl Monitor {
+    pub fn to_logical_size(&self) -> LogicalSize<u32> {
+        self.size.to_logical(self.scale_factor)
+    }
+
+    fn from_handle(mh: &MonitorHandle, index: usize, primary: bool) -> Monitor {
+        Monitor {
+            name: mh.name().unwrap_or_else(|| "unnamed-monitor".to_string()),
+            size: mh.size(),
+            scale_factor: mh.scale_factor(),
+            index,
+            position: mh.position(),
+            primary,
+            video_modes: mh.video_modes().map(|m| VideoModeInfo::from(&m)).collect(),
+        }
+    }
+}
+
+/// Return the primary monitor, or the first available monitor if there is
+/// no primary monitor.
+pub fn get_monitor_info() -> Result<Monitor> {
+    let ev = EventLoop::new();
+    let monitors: Vec<MonitorHandle> = ev.available_monitors().collect();
+
+    let (mh, primary) = match ev.primary_monitor() {
+        Some(mh) => (mh, true),
+        None => match monitors.first() {
+            Some(mh) => (mh.clone(), false),
+            None => err_at!(Invalid, msg: "Cannot find a monitor, check the cables")?,
+        },
+    };
+    let index = monitors.iter().position(|m| *m == mh).unwrap_or(0);
+
+    Ok(Monitor::from_handle(&mh, index, primary))
+}
+
+/// Return monitors available to `window`, in the order of their index.
+pub fn to_monitors(window: &Window) -> Vec<Monitor> {
+    let primary = window.primary_monitor();
+    window
+        .available_monitors()
+        .enumerate()
+        .map(|(i, mh)| Monitor::from_handle(&mh, i, Some(&mh) == primary.as_ref()))
+        .collect()
+}
+
+/// Return the monitor selected by `select`, names are matched exactly, and
+/// failing that, as a case-insensitive substring.
+pub fn find_monitor(window: &Window, select: &MonitorSelect) -> Option<MonitorHandle> {
+    let monitors: Vec<MonitorHandle> = window.available_monitors().collect();
+    let names: Vec<Option<String>> = monitors.iter().map(|m| m.name()).collect();
+    match_monitor(select, &names).map(|i| monitors[i].clone())
+}
+
+/// Resolve `config` to [Fullscreen] for `window`.
+///
+/// If the configured monitor is not available, window's current monitor is
+/// used. If the configured video mode is not available on the monitor,
+/// exclusive fullscreen falls back to borderless fullscreen.
+pub fn to_fullscreen(window: &Window, config: &ConfigFullscreen) -> Fullscreen {
+    let monitors: Vec<MonitorHandle> = window.available_monitors().collect();
+    let current = window.current_monitor().or_else(|| window.primary_monitor());
+    resolve_fullscreen(&monitors, current, config)
+}
+
+/// Resolve `config` to [Fullscreen] before creating a window, typically used
+/// to set [WindowAttributes::fullscreen][attrs].
+///
+/// Same as [to_fullscreen], except that the primary monitor stands in for
+/// window's current monitor.
+///
+/// [attrs]: winit::window::WindowAttributes::fullscreen
+pub fn to_fullscreen_at<T>(
+    target: &EventLoopWindowTarget<T>,
+    config: &ConfigFullscreen,
+) -> Fullscreen {
+    let monitors: Vec<MonitorHandle> = target.available_monitors().collect();
+    let primary = target.primary_monitor().or_else(|| monitors.first().cloned());
+    resolve_fullscreen(&monitors, primary, config)
+}
+
+/// Switch `window` to fullscreen as per `config`, or to windowed mode if
+/// `config` is None.
+pub fn set_fullscreen(window: &Window, config: Option<&ConfigFullscreen>) {
+    window.set_fullscreen(config.map(|c| to_fullscreen(window, c)))
+}
+
+/// Toggle `window` between windowed mode and fullscreen as per `config`,
+/// return true if the window is fullscreen after the toggle.
+pub fn toggle_fullscreen(window: &Window, config: &ConfigFullscreen) -> bool {
+    match window.fullscreen() {
+        Some(_) => {
+            window.set_fullscreen(None);
+            false
+        }
+        None => {
+            set_fullscreen(window, Some(config));
+            true
+        }
+    }
+}
+
+/// Move `window` to the monitor selected by `select`. Fullscreen windows stay
+/// fullscreen, in the same mode, on the new monitor. Windowed windows are
+/// centered on the new monitor.
+pub fn move_to_monitor(window: &Window, select: &MonitorSelect) -> Result<()> {
+    let mh = match find_monitor(window, select) {
+        Some(mh) => mh,
+        None => err_at!(Invalid, msg: "monitor {:?} not found", select)?,
+    };
+
+    match window.fullscreen() {
+        Some(Fullscreen::Exclusive(mode)) => {
+            let config = ConfigFullscreen {
+                mode: FullscreenMode::Exclusive,
+                monitor: Some(select.clone()),
+                size: Some(vec![mode.size().width, mode.size().height]),
+                bit_depth: Some(mode.bit_depth()),
+                refresh_rate: Some(mode.refresh_rate()),
+            };
+            set_fullscreen(window, Some(&config))
+        }
+        Some(Fullscreen::Borderless(_)) => {
+            window.set_fullscreen(Some(Fullscreen::Borderless(Some(mh))))
+        }
+        None => {
+            let (pos, size, outer) = (mh.position(), mh.size(), window.outer_size());
+            let x = pos.x + (size.width.saturating_sub(outer.width) / 2) as i32;
+            let y = pos.y + (size.height.saturating_sub(outer.height) / 2) as i32;
+            window.set_outer_position(PhysicalPosition::new(x, y))
+        }
+    }
+
+    Ok(())
+}
+
+/// Return the video mode `window` is displayed in. For exclusive fullscreen
+/// this is the mode that was set, otherwise winit does not tell the monitor's
+/// current mode and the best mode matching the monitor's size is returned.
+pub fn to_video_mode(window: &Window) -> Option<VideoModeInfo> {
+    match window.fullscreen() {
+        Some(Fullscreen::Exclusive(mode)) => Some(VideoModeInfo::from(&mode)),
+        _ => {
+            let mh = window.current_monitor()?;
+            let infos: Vec<VideoModeInfo> =
+                mh.video_modes().map(|m| VideoModeInfo::from(&m)).collect();
+            let i = match_video_mode(&infos, mh.size(), None, None)?;
+            Some(infos[i])
+        }
+    }
+}
+
+// Resolve `config` among `monitors`, using `current` if there is no monitor
+// configured or if the configured monitor is not available.
+fn resolve_fullscreen(
+    monitors: &[MonitorHandle],
+    current: Option<MonitorHandle>,
+    config: &ConfigFullscreen,
+) -> Fullscreen {
+    let monitor = match config.monitor.as_ref() {
+        Some(select) => {
+            let names: Vec<Option<String>> = monitors.iter().map(|m| m.name()).collect();
+            match match_monitor(select, &names) {
+                Some(i) => Some(monitors[i].clone()),
+                None => {
+                    warn!("monitor {:?} not found, using current monitor", select);
+                    current
+                }
+            }
+        }
+        None => current,
+    };
+
+    match (config.mode, monitor) {
+        (FullscreenMode::Borderless, monitor) => Fullscreen::Borderless(monitor),
+        (FullscreenMode::Exclusive, Some(mh)) => {
+            let size = match config.size.as_deref() {
+                Some([width, height]) => PhysicalSize::new(*width, *height),
+                _ => mh.size(),
+            };
+            let modes: Vec<VideoMode> = mh.video_modes().collect();
+            let infos: Vec<VideoModeInfo> =
+                modes.iter().map(VideoModeInfo::from).collect();
+            match match_video_mode(&infos, size, config.bit_depth, config.refresh_rate) {
+                Some(i) => Fullscreen::Exclusive(modes[i].clone()),
+                None => {
+                    warn!(
+                        "video mode {}x{} not available on {:?}, using borderless",
+                        size.width,
+                        size.height,
+                        mh.name()
+                    );
+                    Fullscreen::Borderless(Some(mh))
+                }
+            }
+        }
+        (FullscreenMode::Exclusive, None) => {
+            warn!("no monitor for exclusive fullscreen, using borderless");
+            Fullscreen::Borderless(None)
+        }
+    }
+}
+
+fn match_monitor(select: &MonitorSelect, names: &[Option<String>]) -> Option<usize> {
+    match select {
+        MonitorSelect::Index(i) if *i < names.len() => Some(*i),
+        MonitorSelect::Index(_) => None,
+        MonitorSelect::Name(name) => {
+            let name = name.as_str();
+            let lower = name.to_lowercase();
+            names.iter().position(|n| n.as_deref() == Some(name)).or_else(|| {
+                names.iter().position(|n| match n {
+                    Some(n) => n.to_lowercase().contains(&lower),
+                    None => false,
+                })
+            })
+        }
+    }
+}
+
+// Among modes matching `size`, and `bit_depth` and `refresh_rate` if given,
+// return the one with highest refresh rate and bit depth.
+fn match_video_mode(
+    modes: &[VideoModeInfo],
+    size: PhysicalSize<u32>,
+    bit_depth: Option<u16>,
+    refresh_rate: Option<u16>,
+) -> Option<usize> {
+    modes
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| m.size == size)
+        .filter(|(_, m)| bit_depth.is_none_or(|b| b == m.bit_depth))
+        .filter(|(_, m)| refresh_rate.is_none_or(|r| r == m.refresh_rate))
+        .max_by_key(|(_, m)| (m.refresh_rate, m.bit_depth))
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+#[path = "monitor_test.rs"]
+mod monitor_test;
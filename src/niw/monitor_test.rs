@@ -0,0 +1,75 @@
+use super::*;
+
+fn mode(width: u32, height: u32, bit_depth: u16, refresh_rate: u16) -> VideoModeInfo {
+    VideoModeInfo {
+        size: PhysicalSize::new(width, height),
+        bit_depth,
+        refresh_rate,
+    }
+}
+
+#[test]
+fn test_match_monitor() {
+    let names = vec![
+        Some("DP-1".to_string()),
+        None,
+        Some("HDMI-A-1".to_string()),
+        Some("hdmi-a-1".to_string()),
+    ];
+
+    assert_eq!(match_monitor(&MonitorSelect::Index(1), &names), Some(1));
+    assert_eq!(match_monitor(&MonitorSelect::Index(4), &names), None);
+
+    let select = MonitorSelect::Name("hdmi-a-1".to_string());
+    assert_eq!(match_monitor(&select, &names), Some(3));
+    let select = MonitorSelect::Name("HDMI".to_string());
+    assert_eq!(match_monitor(&select, &names), Some(2));
+    let select = MonitorSelect::Name("dp".to_string());
+    assert_eq!(match_monitor(&select, &names), Some(0));
+    let select = MonitorSelect::Name("eDP-1".to_string());
+    assert_eq!(match_monitor(&select, &names), None);
+}
+
+#[test]
+fn test_match_video_mode() {
+    let modes = vec![
+        mode(1920, 1080, 24, 60),
+        mode(1920, 1080, 32, 60),
+        mode(1920, 1080, 32, 144),
+        mode(1280, 720, 32, 60),
+    ];
+    let fhd = PhysicalSize::new(1920, 1080);
+
+    assert_eq!(match_video_mode(&modes, fhd, None, None), Some(2));
+    assert_eq!(match_video_mode(&modes, fhd, None, Some(60)), Some(1));
+    assert_eq!(match_video_mode(&modes, fhd, Some(24), None), Some(0));
+    assert_eq!(match_video_mode(&modes, fhd, Some(24), Some(144)), None);
+
+    let hd = PhysicalSize::new(1280, 720);
+    assert_eq!(match_video_mode(&modes, hd, None, None), Some(3));
+    let qhd = PhysicalSize::new(2560, 1440);
+    assert_eq!(match_video_mode(&modes, qhd, None, None), None);
+    assert_eq!(match_video_mode(&[], fhd, None, None), None);
+}
+
+#[test]
+fn test_video_mode_display() {
+    assert_eq!(mode(1920, 1080, 32, 60).to_string(), "1920x1080@60Hz 32bit");
+}
+
+#[test]
+fn test_resolve_fullscreen() {
+    // without monitors, every configuration falls back to borderless on the
+    // current monitor.
+    let mut config = ConfigFullscreen {
+        mode: FullscreenMode::Exclusive,
+        monitor: Some(MonitorSelect::Name("DP-1".to_string())),
+        size: Some(vec![1920, 1080]),
+        ..ConfigFullscreen::default()
+    };
+    assert_eq!(resolve_fullscreen(&[], None, &config), Fullscreen::Borderless(None));
+
+    config.mode = FullscreenMode::Borderless;
+    config.monitor = Some(MonitorSelect::Index(1));
+    assert_eq!(resolve_fullscreen(&[], None, &config), Fullscreen::Borderless(None));
+}
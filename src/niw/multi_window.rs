@@ -0,0 +1,622 @@
+use log::{debug, info, trace, warn};
+use winit::{
+    event::{ElementState, Event, KeyboardInput, VirtualKeyCode, WindowEvent},
+    event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
+    window::{Window, WindowAttributes, WindowBuilder, WindowId},
+};
+
+use std::{collections::BTreeMap, fmt};
+
+use crate::{Config, ConfigWinit, Error, Render, Result, Screen};
+
+/// Type to handle events in [MultiWindow], with the set of open windows and
+/// state `S`. Window specific handlers can learn the target window from the
+/// `window_id` carried by the event.
+pub type MultiHandler<S, E> =
+    Box<dyn FnMut(&mut Windows<S, E>, &mut S, &mut Event<E>) -> Option<ControlFlow>>;
+
+/// Type manages an event-loop and a set of windows, useful for applications
+/// with a main canvas and auxiliary windows like inspectors and palettes.
+///
+/// Each window gets its own [Screen] and [Render], all of them sharing the same
+/// [wgpu::Device] and [wgpu::Queue], and its own table of [WindowHandlers].
+/// Windows can be added before starting the event-loop, using
+/// [MultiWindow::add_window], and opened or closed while the event-loop is
+/// running, using [Windows::open] and [Windows::close]. Event-loop exits when
+/// the last window is closed.
+pub struct MultiWindow<S, E = ()>
+where
+    E: 'static,
+{
+    event_loop: Option<EventLoop<E>>,
+    windows: Windows<S, E>,
+    on_event: MultiHandler<S, E>,
+    on_new_events: MultiHandler<S, E>,
+    on_user_event: MultiHandler<S, E>,
+    on_main_events_cleared: MultiHandler<S, E>,
+    on_device_event: MultiHandler<S, E>,
+    on_loop_destroyed: MultiHandler<S, E>,
+    exit_on_esc: bool,
+}
+
+/// Handlers for events targeting a single window in [MultiWindow]. Window
+/// events not listed here can be handled via [WindowHandlers::on_event].
+pub struct WindowHandlers<S, E>
+where
+    E: 'static,
+{
+    on_event: MultiHandler<S, E>,
+    on_redraw_requested: MultiHandler<S, E>,
+    on_resized: MultiHandler<S, E>,
+    on_close_requested: MultiHandler<S, E>,
+    on_focused: MultiHandler<S, E>,
+    on_keyboard_input: MultiHandler<S, E>,
+    on_cursor_moved: MultiHandler<S, E>,
+    on_mouse_wheel: MultiHandler<S, E>,
+    on_mouse_input: MultiHandler<S, E>,
+    on_dropped_file: MultiHandler<S, E>,
+}
+
+impl<S, E> Default for WindowHandlers<S, E> {
+    fn default() -> Self {
+        WindowHandlers {
+            on_event: Box::new(|_, _, _| None),
+            on_redraw_requested: Box::new(|_, _, _| None),
+            on_resized: Box::new(|_, _, _| None),
+            on_close_requested: Box::new(|_, _, _| None),
+            on_focused: Box::new(|_, _, _| None),
+            on_keyboard_input: Box::new(|_, _, _| None),
+            on_cursor_moved: Box::new(|_, _, _| None),
+            on_mouse_wheel: Box::new(|_, _, _| None),
+            on_mouse_input: Box::new(|_, _, _| None),
+            on_dropped_file: Box::new(|_, _, _| None),
+        }
+    }
+}
+
+/// Set of open windows in [MultiWindow], passed to every handler.
+pub struct Windows<S, E>
+where
+    E: 'static,
+{
+    config: Config,
+    color_format: wgpu::TextureFormat,
+    msaa: u32,
+    depth_format: Option<wgpu::TextureFormat>,
+    entries: BTreeMap<WindowId, Entry>,
+    handlers: BTreeMap<WindowId, WindowHandlers<S, E>>,
+    requests: Vec<Request<S, E>>,
+}
+
+// Fields are dropped in declaration order, `render` owns the surface created
+// from `window`, hence it must be declared, and dropped, before `window`.
+struct Entry {
+    name: String,
+    render: Render,
+    window: Window,
+}
+
+enum Request<S, E>
+where
+    E: 'static,
+{
+    Open {
+        name: String,
+        spec: Box<Spec>,
+        handlers: Box<WindowHandlers<S, E>>,
+    },
+    Close(WindowId),
+}
+
+// Window to open, from attributes or from configuration.
+enum Spec {
+    Attrs(WindowAttributes),
+    Config(ConfigWinit),
+}
+
+impl<S, E> MultiWindow<S, E>
+where
+    E: 'static,
+{
+    /// Create an event-loop without any window. `config` is used for
+    /// selecting the adapter when the first window is added, and every window
+    /// renders into a color texture of `color_format`.
+    pub fn new(config: Config, color_format: wgpu::TextureFormat) -> Self {
+        let windows = Windows {
+            config,
+            color_format,
+            msaa: 1,
+            depth_format: None,
+            entries: BTreeMap::new(),
+            handlers: BTreeMap::new(),
+            requests: vec![],
+        };
+
+        MultiWindow {
+            event_loop: Some(EventLoop::<E>::with_user_event()),
+            windows,
+            on_event: Box::new(|_, _, _| None),
+            on_new_events: Box::new(|_, _, _| None),
+            on_user_event: Box::new(|_, _, _| None),
+            on_main_events_cleared: Box::new(|_, _, _| None),
+            on_device_event: Box::new(|_, _, _| None),
+            on_loop_destroyed: Box::new(|_, _, _| None),
+            exit_on_esc: true,
+        }
+    }
+
+    /// Exit the event-loop, closing all windows, on Esc key.
+    pub fn set_exit_on_esc(&mut self, val: bool) -> &mut Self {
+        self.exit_on_esc = val;
+        self
+    }
+
+    /// Sample count for [Render] of windows created from now on, refer
+    /// [Render::set_msaa].
+    pub fn set_msaa(&mut self, sample_count: u32) -> &mut Self {
+        self.windows.msaa = sample_count;
+        self
+    }
+
+    /// Depth format for [Render] of windows created from now on, refer
+    /// [Render::set_depth].
+    pub fn set_depth(&mut self, format: wgpu::TextureFormat) -> &mut Self {
+        self.windows.depth_format = Some(format);
+        self
+    }
+
+    pub fn as_event_loop(&self) -> &EventLoop<E> {
+        self.event_loop.as_ref().unwrap()
+    }
+
+    pub fn as_windows(&self) -> &Windows<S, E> {
+        &self.windows
+    }
+
+    pub fn as_windows_mut(&mut self) -> &mut Windows<S, E> {
+        &mut self.windows
+    }
+
+    /// Create a new window before starting the event-loop. Window `name` shall
+    /// be unique across the set of windows.
+    pub fn add_window(
+        &mut self,
+        name: &str,
+        attrs: WindowAttributes,
+        handlers: WindowHandlers<S, E>,
+    ) -> Result<WindowId> {
+        let event_loop = self.event_loop.as_ref().unwrap();
+        let spec = Spec::Attrs(attrs);
+        self.windows.create(event_loop, name.to_string(), spec, handlers)
+    }
+
+    /// Same as [MultiWindow::add_window], but create the window as per
+    /// `config`, refer [ConfigWinit::to_window_attributes] and
+    /// [ConfigWinit::apply_window].
+    pub fn add_window_config(
+        &mut self,
+        name: &str,
+        config: &ConfigWinit,
+        handlers: WindowHandlers<S, E>,
+    ) -> Result<WindowId> {
+        let event_loop = self.event_loop.as_ref().unwrap();
+        let spec = Spec::Config(config.clone());
+        self.windows.create(event_loop, name.to_string(), spec, handlers)
+    }
+
+    pub fn run(mut self, mut state: S) -> !
+    where
+        S: 'static,
+        E: fmt::Debug,
+    {
+        let event_loop = self.event_loop.take().unwrap();
+        let mut windows = self.windows;
+
+        debug!("starting the multi-window event_loop ...");
+
+        event_loop.run(
+            move |mut evnt: Event<E>,
+                  target: &EventLoopWindowTarget<E>,
+                  cf: &mut ControlFlow| {
+                log_event(&evnt);
+
+                if let Some(val) = (self.on_event)(&mut windows, &mut state, &mut evnt) {
+                    *cf = val
+                }
+
+                let wid = match &evnt {
+                    Event::WindowEvent { window_id, .. } => Some(*window_id),
+                    Event::RedrawRequested(window_id) => Some(*window_id),
+                    _ => None,
+                };
+
+                let res = match wid {
+                    Some(wid) if !windows.handlers.contains_key(&wid) => {
+                        trace!("event for closed window {:?}", wid);
+                        None
+                    }
+                    Some(wid) => {
+                        // handlers are taken out, so that they can mutate the
+                        // set of windows.
+                        let mut hs = windows.handlers.remove(&wid).unwrap();
+                        let exit = self.exit_on_esc && is_esc_pressed(&evnt);
+                        let res = windows.dispatch(&mut hs, &mut state, &mut evnt);
+                        windows.handlers.insert(wid, hs);
+                        match exit {
+                            true => Some(ControlFlow::Exit),
+                            false => res,
+                        }
+                    }
+                    None => {
+                        let handler = match &evnt {
+                            Event::NewEvents(_) => Some(&mut self.on_new_events),
+                            Event::UserEvent(_) => Some(&mut self.on_user_event),
+                            Event::MainEventsCleared => {
+                                // Locally handle few things here.
+                                windows
+                                    .entries
+                                    .values()
+                                    .for_each(|e| e.window.request_redraw());
+                                Some(&mut self.on_main_events_cleared)
+                            }
+                            Event::DeviceEvent { .. } => Some(&mut self.on_device_event),
+                            Event::LoopDestroyed => Some(&mut self.on_loop_destroyed),
+                            _ => None,
+                        };
+                        handler.and_then(|h| h(&mut windows, &mut state, &mut evnt))
+                    }
+                };
+                if let Some(val) = res {
+                    *cf = val
+                }
+
+                windows.handle_requests(target);
+                if windows.entries.is_empty() {
+                    *cf = ControlFlow::Exit;
+                }
+                if let ControlFlow::Exit = cf {
+                    windows.close_all();
+                }
+            },
+        );
+    }
+}
+
+impl<S, E> MultiWindow<S, E>
+where
+    E: 'static,
+{
+    /// Handler is called for every event, before the event specific handler.
+    pub fn on_event(&mut self, handler: MultiHandler<S, E>) -> &mut Self {
+        debug!("subcribed to on_event ...");
+        self.on_event = handler;
+        self
+    }
+
+    pub fn on_new_events(&mut self, handler: MultiHandler<S, E>) -> &mut Self {
+        debug!("subcribed to on_new_events ...");
+        self.on_new_events = handler;
+        self
+    }
+
+    pub fn on_user_event(&mut self, handler: MultiHandler<S, E>) -> &mut Self {
+        debug!("subcribed to on_user_event ...");
+        self.on_user_event = handler;
+        self
+    }
+
+    pub fn on_main_events_cleared(&mut self, handler: MultiHandler<S, E>) -> &mut Self {
+        debug!("subcribed to on_main_events_cleared ...");
+        self.on_main_events_cleared = handler;
+        self
+    }
+
+    /// Handler is called for all device events, irrespective of the window.
+    pub fn on_device_event(&mut self, handler: MultiHandler<S, E>) -> &mut Self {
+        debug!("subcribed to on_device_event ...");
+        self.on_device_event = handler;
+        self
+    }
+
+    pub fn on_loop_destroyed(&mut self, handler: MultiHandler<S, E>) -> &mut Self {
+        debug!("subcribed to on_loop_destroyed ...");
+        self.on_loop_destroyed = handler;
+        self
+    }
+}
+
+impl<S, E> WindowHandlers<S, E>
+where
+    E: 'static,
+{
+    /// Handler is called for every event targeting this window, before the
+    /// event specific handler.
+    pub fn on_event(&mut self, handler: MultiHandler<S, E>) -> &mut Self {
+        self.on_event = handler;
+        self
+    }
+
+    pub fn on_redraw_requested(&mut self, handler: MultiHandler<S, E>) -> &mut Self {
+        self.on_redraw_requested = handler;
+        self
+    }
+
+    pub fn on_win_resized(&mut self, handler: MultiHandler<S, E>) -> &mut Self {
+        self.on_resized = handler;
+        self
+    }
+
+    /// Window is closed after calling this handler.
+    pub fn on_win_close_requested(&mut self, handler: MultiHandler<S, E>) -> &mut Self {
+        self.on_close_requested = handler;
+        self
+    }
+
+    pub fn on_win_focused(&mut self, handler: MultiHandler<S, E>) -> &mut Self {
+        self.on_focused = handler;
+        self
+    }
+
+    pub fn on_win_keyboard_input(&mut self, handler: MultiHandler<S, E>) -> &mut Self {
+        self.on_keyboard_input = handler;
+        self
+    }
+
+    pub fn on_win_cursor_moved(&mut self, handler: MultiHandler<S, E>) -> &mut Self {
+        self.on_cursor_moved = handler;
+        self
+    }
+
+    pub fn on_win_mouse_wheel(&mut self, handler: MultiHandler<S, E>) -> &mut Self {
+        self.on_mouse_wheel = handler;
+        self
+    }
+
+    pub fn on_win_mouse_input(&mut self, handler: MultiHandler<S, E>) -> &mut Self {
+        self.on_mouse_input = handler;
+        self
+    }
+
+    pub fn on_win_dropped_file(&mut self, handler: MultiHandler<S, E>) -> &mut Self {
+        self.on_dropped_file = handler;
+        self
+    }
+}
+
+impl<S, E> Windows<S, E>
+where
+    E: 'static,
+{
+    /// Open a new window after the current event is handled. Window `name`
+    /// shall be unique across the set of windows.
+    pub fn open(
+        &mut self,
+        name: &str,
+        attrs: WindowAttributes,
+        handlers: WindowHandlers<S, E>,
+    ) {
+        let name = name.to_string();
+        self.requests.push(Request::Open {
+            name,
+            spec: Box::new(Spec::Attrs(attrs)),
+            handlers: Box::new(handlers),
+        })
+    }
+
+    /// Same as [Windows::open], but open the window as per `config`, refer
+    /// [ConfigWinit::to_window_attributes] and [ConfigWinit::apply_window].
+    pub fn open_config(
+        &mut self,
+        name: &str,
+        config: &ConfigWinit,
+        handlers: WindowHandlers<S, E>,
+    ) {
+        let name = name.to_string();
+        self.requests.push(Request::Open {
+            name,
+            spec: Box::new(Spec::Config(config.clone())),
+            handlers: Box::new(handlers),
+        })
+    }
+
+    /// Close window `wid` after the current event is handled.
+    pub fn close(&mut self, wid: WindowId) {
+        self.requests.push(Request::Close(wid))
+    }
+
+    /// Return the number of open windows.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Return the id of window with `name`.
+    pub fn find(&self, name: &str) -> Option<WindowId> {
+        self.entries.iter().find(|(_, e)| e.name == name).map(|(wid, _)| *wid)
+    }
+
+    /// Iterate over the ids of open windows.
+    pub fn iter(&self) -> impl Iterator<Item = WindowId> + '_ {
+        self.entries.keys().copied()
+    }
+
+    pub fn to_name(&self, wid: WindowId) -> Option<String> {
+        self.entries.get(&wid).map(|e| e.name.clone())
+    }
+
+    pub fn as_window(&self, wid: WindowId) -> Option<&Window> {
+        self.entries.get(&wid).map(|e| &e.window)
+    }
+
+    pub fn as_render(&self, wid: WindowId) -> Option<&Render> {
+        self.entries.get(&wid).map(|e| &e.render)
+    }
+
+    pub fn as_render_mut(&mut self, wid: WindowId) -> Option<&mut Render> {
+        self.entries.get_mut(&wid).map(|e| &mut e.render)
+    }
+
+    /// Return the device shared by all windows, None if there are no windows.
+    pub fn as_device(&self) -> Option<&wgpu::Device> {
+        self.entries.values().next().map(|e| e.render.as_device())
+    }
+
+    /// Return the queue shared by all windows, None if there are no windows.
+    pub fn as_queue(&self) -> Option<&wgpu::Queue> {
+        self.entries.values().next().map(|e| e.render.as_queue())
+    }
+
+    fn create(
+        &mut self,
+        target: &EventLoopWindowTarget<E>,
+        name: String,
+        spec: Spec,
+        handlers: WindowHandlers<S, E>,
+    ) -> Result<WindowId> {
+        if self.find(&name).is_some() {
+            err_at!(Invalid, msg: "window {:?} already open", name)?
+        }
+
+        let (attrs, config) = match spec {
+            Spec::Attrs(attrs) => (attrs, None),
+            Spec::Config(config) => (config.to_window_attributes(target)?, Some(config)),
+        };
+
+        let window = {
+            let mut wb = WindowBuilder::new();
+            wb.window = attrs;
+            err_at!(Fatal, wb.build(target))?
+        };
+        if let Some(config) = config {
+            config.apply_window(&window)?
+        }
+        let screen = match self.entries.values().next() {
+            Some(e) => e.render.as_screen().to_shared_screen(name.clone(), &window)?,
+            None => {
+                let config = self.config.clone();
+                pollster::block_on(Screen::new(name.clone(), &window, config))?
+            }
+        };
+
+        let mut render = Render::new(screen, self.color_format);
+        render.set_msaa(self.msaa);
+        if let Some(format) = self.depth_format {
+            render.set_depth(format);
+        }
+        render.start();
+
+        let wid = window.id();
+        info!("open window {:?} {:?}", name, wid);
+
+        self.entries.insert(wid, Entry { name, render, window });
+        self.handlers.insert(wid, handlers);
+
+        Ok(wid)
+    }
+
+    fn dispatch(
+        &mut self,
+        hs: &mut WindowHandlers<S, E>,
+        state: &mut S,
+        evnt: &mut Event<E>,
+    ) -> Option<ControlFlow> {
+        let res = (hs.on_event)(self, state, evnt);
+
+        let handler = match evnt {
+            Event::RedrawRequested(_) => Some(&mut hs.on_redraw_requested),
+            Event::WindowEvent { window_id, event } => match event {
+                WindowEvent::Resized(size) => {
+                    // Locally handle few things here.
+                    if let Some(render) = self.as_render_mut(*window_id) {
+                        render.resize(*size, None)
+                    }
+                    Some(&mut hs.on_resized)
+                }
+                WindowEvent::ScaleFactorChanged { new_inner_size, scale_factor } => {
+                    // Locally handle few things here.
+                    if let Some(render) = self.as_render_mut(*window_id) {
+                        render.resize(**new_inner_size, Some(*scale_factor))
+                    }
+                    None
+                }
+                WindowEvent::CloseRequested => {
+                    // Locally handle few things here.
+                    self.close(*window_id);
+                    Some(&mut hs.on_close_requested)
+                }
+                WindowEvent::Focused(_) => Some(&mut hs.on_focused),
+                WindowEvent::KeyboardInput { .. } => Some(&mut hs.on_keyboard_input),
+                WindowEvent::CursorMoved { .. } => Some(&mut hs.on_cursor_moved),
+                WindowEvent::MouseWheel { .. } => Some(&mut hs.on_mouse_wheel),
+                WindowEvent::MouseInput { .. } => Some(&mut hs.on_mouse_input),
+                WindowEvent::DroppedFile(_) => Some(&mut hs.on_dropped_file),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        match handler.and_then(|h| h(self, state, evnt)) {
+            Some(val) => Some(val),
+            None => res,
+        }
+    }
+
+    fn handle_requests(&mut self, target: &EventLoopWindowTarget<E>) {
+        for req in self.requests.drain(..).collect::<Vec<Request<S, E>>>() {
+            match req {
+                Request::Open { name, spec, handlers } => {
+                    if let Err(err) = self.create(target, name.clone(), *spec, *handlers)
+                    {
+                        warn!("open window {:?}: {}", name, err)
+                    }
+                }
+                Request::Close(wid) => self.remove(wid),
+            }
+        }
+    }
+
+    fn remove(&mut self, wid: WindowId) {
+        self.handlers.remove(&wid);
+        if let Some(mut entry) = self.entries.remove(&wid) {
+            info!("close window {:?} {:?}", entry.name, wid);
+            entry.render.stop().ok();
+        }
+    }
+
+    fn close_all(&mut self) {
+        let wids: Vec<WindowId> = self.entries.keys().copied().collect();
+        wids.into_iter().for_each(|wid| self.remove(wid));
+    }
+}
+
+fn is_esc_pressed<E>(evnt: &Event<E>) -> bool {
+    matches!(
+        evnt,
+        Event::WindowEvent {
+            event: WindowEvent::KeyboardInput {
+                input: KeyboardInput {
+                    state: ElementState::Pressed,
+                    virtual_keycode: Some(VirtualKeyCode::Escape),
+                    ..
+                },
+                ..
+            },
+            ..
+        }
+    )
+}
+
+fn log_event<E>(event: &Event<E>)
+where
+    E: fmt::Debug,
+{
+    match event {
+        Event::NewEvents(_) | Event::RedrawEventsCleared | Event::MainEventsCleared => {
+            trace!("event {:?}", event)
+        }
+        _ => debug!("event {:?}", event),
+    }
+}
@@ -1,7 +1,8 @@
 use prettytable::{cell, row};
+use serde_json::json;
 use winit::monitor::{MonitorHandle, VideoMode};
 
-use crate::util::PrettyRow;
+use crate::util::{JsonRow, PrettyRow};
 
 impl PrettyRow for MonitorHandle {
     fn to_format() -> prettytable::format::TableFormat {
@@ -48,3 +49,30 @@ impl PrettyRow for VideoMode {
         ]
     }
 }
+
+impl JsonRow for MonitorHandle {
+    fn to_json(&self) -> serde_json::Value {
+        let (size, post) = (self.size(), self.position());
+        let modes: Vec<VideoMode> = self.video_modes().collect();
+
+        json!({
+            "name": self.name(),
+            "position": [post.x, post.y],
+            "size": [size.width, size.height],
+            "scale_factor": self.scale_factor(),
+            "video_modes": crate::util::make_json(&modes),
+        })
+    }
+}
+
+impl JsonRow for VideoMode {
+    fn to_json(&self) -> serde_json::Value {
+        let size = self.size();
+
+        json!({
+            "size": [size.width, size.height],
+            "bit_depth": self.bit_depth(),
+            "refresh_rate": self.refresh_rate(),
+        })
+    }
+}
@@ -0,0 +1,38 @@
+use winit::event_loop::EventLoopProxy;
+
+use crate::{Error, Result};
+
+/// Type to post user events into a running event-loop, from any thread.
+///
+/// Posted events are delivered as [Event::UserEvent] and wake up the
+/// event-loop, even when it is waiting for events. Obtain a proxy using
+/// [SingleWindow::to_event_proxy][to_event_proxy] before running the
+/// event-loop, and clone it for every worker thread.
+///
+/// [Event::UserEvent]: winit::event::Event::UserEvent
+/// [to_event_proxy]: crate::niw::SingleWindow::to_event_proxy
+pub struct EventProxy<E>
+where
+    E: 'static,
+{
+    proxy: EventLoopProxy<E>,
+}
+
+impl<E> Clone for EventProxy<E> {
+    fn clone(&self) -> Self {
+        EventProxy { proxy: self.proxy.clone() }
+    }
+}
+
+impl<E> From<EventLoopProxy<E>> for EventProxy<E> {
+    fn from(proxy: EventLoopProxy<E>) -> EventProxy<E> {
+        EventProxy { proxy }
+    }
+}
+
+impl<E> EventProxy<E> {
+    /// Post `event` to the event-loop, fails if the event-loop has exited.
+    pub fn post(&self, event: E) -> Result<()> {
+        err_at!(IPCError, self.proxy.send_event(event))
+    }
+}
@@ -0,0 +1,525 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use winit::{
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::{
+        AxisId, ButtonId, DeviceEvent, DeviceId, ElementState, Event, Force,
+        KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta, StartCause, Touch,
+        TouchPhase, WindowEvent,
+    },
+    window::{Theme, WindowId},
+};
+
+use std::{
+    fs,
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+    time,
+};
+
+use crate::{Error, Result};
+
+/// Type is a single entry in an events recording, refer [Recorder].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Record<E> {
+    /// Time elapsed since the recording started, in microseconds.
+    pub elapsed: u64,
+    /// Frame number, incremented after every [Event::RedrawEventsCleared].
+    pub frame: u64,
+    pub event: RecordEvent<E>,
+}
+
+/// Owned and serializable mirror of [Event].
+///
+/// Window-id and device-id are not recorded, they are platform handles that
+/// can't be restored, replayed events use dummy ids.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RecordEvent<E> {
+    NewEvents(Cause),
+    Window(RecordWindowEvent),
+    Device(RecordDeviceEvent),
+    UserEvent(E),
+    Suspended,
+    Resumed,
+    MainEventsCleared,
+    RedrawRequested,
+    RedrawEventsCleared,
+    LoopDestroyed,
+}
+
+/// Mirror of [StartCause], instants are recorded as microseconds elapsed since
+/// the recording started, and rebased on the replay start when replaying.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Cause {
+    ResumeTimeReached {
+        start: u64,
+        requested_resume: u64,
+    },
+    WaitCancelled {
+        start: u64,
+        requested_resume: Option<u64>,
+    },
+    Poll,
+    Init,
+}
+
+/// Mirror of [WindowEvent], deprecated `modifiers` fields are not recorded.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RecordWindowEvent {
+    Resized(PhysicalSize<u32>),
+    Moved(PhysicalPosition<i32>),
+    CloseRequested,
+    Destroyed,
+    DroppedFile(PathBuf),
+    HoveredFile(PathBuf),
+    HoveredFileCancelled,
+    ReceivedCharacter(char),
+    Focused(bool),
+    KeyboardInput {
+        input: KeyboardInput,
+        is_synthetic: bool,
+    },
+    ModifiersChanged(ModifiersState),
+    CursorMoved {
+        position: PhysicalPosition<f64>,
+    },
+    CursorEntered,
+    CursorLeft,
+    MouseWheel {
+        delta: MouseScrollDelta,
+        phase: TouchPhase,
+    },
+    MouseInput {
+        state: ElementState,
+        button: MouseButton,
+    },
+    TouchpadPressure {
+        pressure: f32,
+        stage: i64,
+    },
+    AxisMotion {
+        axis: AxisId,
+        value: f64,
+    },
+    Touch {
+        phase: TouchPhase,
+        location: PhysicalPosition<f64>,
+        force: Option<TouchForce>,
+        id: u64,
+    },
+    ScaleFactorChanged {
+        scale_factor: f64,
+        new_inner_size: PhysicalSize<u32>,
+    },
+    ThemeChanged {
+        dark: bool,
+    },
+}
+
+/// Mirror of [Force].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TouchForce {
+    Calibrated {
+        force: f64,
+        max_possible_force: f64,
+        altitude_angle: Option<f64>,
+    },
+    Normalized(f64),
+}
+
+/// Mirror of [DeviceEvent].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum RecordDeviceEvent {
+    Added,
+    Removed,
+    MouseMotion {
+        delta: (f64, f64),
+    },
+    MouseWheel {
+        delta: MouseScrollDelta,
+    },
+    Motion {
+        axis: AxisId,
+        value: f64,
+    },
+    Button {
+        button: ButtonId,
+        state: ElementState,
+    },
+    Key(KeyboardInput),
+    Text {
+        codepoint: char,
+    },
+}
+
+impl<E> RecordEvent<E> {
+    /// Convert from [Event], instants are recorded relative to `genesis`.
+    pub fn from_event(event: &Event<E>, genesis: time::Instant) -> RecordEvent<E>
+    where
+        E: Clone,
+    {
+        let offset =
+            |at: &time::Instant| at.saturating_duration_since(genesis).as_micros() as u64;
+
+        match event {
+            Event::NewEvents(cause) => RecordEvent::NewEvents(match cause {
+                StartCause::ResumeTimeReached { start, requested_resume } => {
+                    Cause::ResumeTimeReached {
+                        start: offset(start),
+                        requested_resume: offset(requested_resume),
+                    }
+                }
+                StartCause::WaitCancelled { start, requested_resume } => {
+                    Cause::WaitCancelled {
+                        start: offset(start),
+                        requested_resume: requested_resume.as_ref().map(offset),
+                    }
+                }
+                StartCause::Poll => Cause::Poll,
+                StartCause::Init => Cause::Init,
+            }),
+            Event::WindowEvent { event, .. } => {
+                RecordEvent::Window(RecordWindowEvent::from_event(event))
+            }
+            Event::DeviceEvent { event, .. } => {
+                RecordEvent::Device(RecordDeviceEvent::from_event(event))
+            }
+            Event::UserEvent(event) => RecordEvent::UserEvent(event.clone()),
+            Event::Suspended => RecordEvent::Suspended,
+            Event::Resumed => RecordEvent::Resumed,
+            Event::MainEventsCleared => RecordEvent::MainEventsCleared,
+            Event::RedrawRequested(_) => RecordEvent::RedrawRequested,
+            Event::RedrawEventsCleared => RecordEvent::RedrawEventsCleared,
+            Event::LoopDestroyed => RecordEvent::LoopDestroyed,
+        }
+    }
+
+    /// Convert back to [Event], recorded instants are rebased on `genesis`.
+    /// [WindowEvent::ScaleFactorChanged] borrows its inner size, which is held
+    /// in `size`.
+    pub fn to_event<'a>(
+        &self,
+        size: &'a mut PhysicalSize<u32>,
+        genesis: time::Instant,
+    ) -> Event<'a, E>
+    where
+        E: Clone,
+    {
+        let instant = |offset: &u64| genesis + time::Duration::from_micros(*offset);
+
+        match self {
+            RecordEvent::NewEvents(cause) => Event::NewEvents(match cause {
+                Cause::ResumeTimeReached { start, requested_resume } => {
+                    StartCause::ResumeTimeReached {
+                        start: instant(start),
+                        requested_resume: instant(requested_resume),
+                    }
+                }
+                Cause::WaitCancelled { start, requested_resume } => {
+                    StartCause::WaitCancelled {
+                        start: instant(start),
+                        requested_resume: requested_resume.as_ref().map(instant),
+                    }
+                }
+                Cause::Poll => StartCause::Poll,
+                Cause::Init => StartCause::Init,
+            }),
+            RecordEvent::Window(event) => Event::WindowEvent {
+                window_id: unsafe { WindowId::dummy() },
+                event: event.to_event(size),
+            },
+            RecordEvent::Device(event) => Event::DeviceEvent {
+                device_id: unsafe { DeviceId::dummy() },
+                event: event.to_event(),
+            },
+            RecordEvent::UserEvent(event) => Event::UserEvent(event.clone()),
+            RecordEvent::Suspended => Event::Suspended,
+            RecordEvent::Resumed => Event::Resumed,
+            RecordEvent::MainEventsCleared => Event::MainEventsCleared,
+            RecordEvent::RedrawRequested => {
+                Event::RedrawRequested(unsafe { WindowId::dummy() })
+            }
+            RecordEvent::RedrawEventsCleared => Event::RedrawEventsCleared,
+            RecordEvent::LoopDestroyed => Event::LoopDestroyed,
+        }
+    }
+}
+
+impl RecordWindowEvent {
+    pub fn from_event(event: &WindowEvent) -> RecordWindowEvent {
+        use RecordWindowEvent as R;
+
+        match event {
+            WindowEvent::Resized(size) => R::Resized(*size),
+            WindowEvent::Moved(pos) => R::Moved(*pos),
+            WindowEvent::CloseRequested => R::CloseRequested,
+            WindowEvent::Destroyed => R::Destroyed,
+            WindowEvent::DroppedFile(loc) => R::DroppedFile(loc.clone()),
+            WindowEvent::HoveredFile(loc) => R::HoveredFile(loc.clone()),
+            WindowEvent::HoveredFileCancelled => R::HoveredFileCancelled,
+            WindowEvent::ReceivedCharacter(ch) => R::ReceivedCharacter(*ch),
+            WindowEvent::Focused(val) => R::Focused(*val),
+            WindowEvent::KeyboardInput { input, is_synthetic, .. } => {
+                R::KeyboardInput { input: *input, is_synthetic: *is_synthetic }
+            }
+            WindowEvent::ModifiersChanged(mods) => R::ModifiersChanged(*mods),
+            WindowEvent::CursorMoved { position, .. } => {
+                R::CursorMoved { position: *position }
+            }
+            WindowEvent::CursorEntered { .. } => R::CursorEntered,
+            WindowEvent::CursorLeft { .. } => R::CursorLeft,
+            WindowEvent::MouseWheel { delta, phase, .. } => {
+                R::MouseWheel { delta: *delta, phase: *phase }
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                R::MouseInput { state: *state, button: *button }
+            }
+            WindowEvent::TouchpadPressure { pressure, stage, .. } => {
+                R::TouchpadPressure { pressure: *pressure, stage: *stage }
+            }
+            WindowEvent::AxisMotion { axis, value, .. } => {
+                R::AxisMotion { axis: *axis, value: *value }
+            }
+            WindowEvent::Touch(Touch { phase, location, force, id, .. }) => R::Touch {
+                phase: *phase,
+                location: *location,
+                force: force.map(TouchForce::from),
+                id: *id,
+            },
+            WindowEvent::ScaleFactorChanged { scale_factor, new_inner_size } => {
+                R::ScaleFactorChanged {
+                    scale_factor: *scale_factor,
+                    new_inner_size: **new_inner_size,
+                }
+            }
+            WindowEvent::ThemeChanged(theme) => {
+                R::ThemeChanged { dark: matches!(theme, Theme::Dark) }
+            }
+        }
+    }
+
+    #[allow(deprecated)]
+    pub fn to_event<'a>(&self, size: &'a mut PhysicalSize<u32>) -> WindowEvent<'a> {
+        use RecordWindowEvent as R;
+
+        let device_id = unsafe { DeviceId::dummy() };
+        let modifiers = ModifiersState::empty();
+
+        match self {
+            R::Resized(size) => WindowEvent::Resized(*size),
+            R::Moved(pos) => WindowEvent::Moved(*pos),
+            R::CloseRequested => WindowEvent::CloseRequested,
+            R::Destroyed => WindowEvent::Destroyed,
+            R::DroppedFile(loc) => WindowEvent::DroppedFile(loc.clone()),
+            R::HoveredFile(loc) => WindowEvent::HoveredFile(loc.clone()),
+            R::HoveredFileCancelled => WindowEvent::HoveredFileCancelled,
+            R::ReceivedCharacter(ch) => WindowEvent::ReceivedCharacter(*ch),
+            R::Focused(val) => WindowEvent::Focused(*val),
+            R::KeyboardInput { input, is_synthetic } => WindowEvent::KeyboardInput {
+                device_id,
+                input: *input,
+                is_synthetic: *is_synthetic,
+            },
+            R::ModifiersChanged(mods) => WindowEvent::ModifiersChanged(*mods),
+            R::CursorMoved { position } => {
+                WindowEvent::CursorMoved { device_id, position: *position, modifiers }
+            }
+            R::CursorEntered => WindowEvent::CursorEntered { device_id },
+            R::CursorLeft => WindowEvent::CursorLeft { device_id },
+            R::MouseWheel { delta, phase } => WindowEvent::MouseWheel {
+                device_id,
+                delta: *delta,
+                phase: *phase,
+                modifiers,
+            },
+            R::MouseInput { state, button } => WindowEvent::MouseInput {
+                device_id,
+                state: *state,
+                button: *button,
+                modifiers,
+            },
+            R::TouchpadPressure { pressure, stage } => WindowEvent::TouchpadPressure {
+                device_id,
+                pressure: *pressure,
+                stage: *stage,
+            },
+            R::AxisMotion { axis, value } => {
+                WindowEvent::AxisMotion { device_id, axis: *axis, value: *value }
+            }
+            R::Touch { phase, location, force, id } => WindowEvent::Touch(Touch {
+                device_id,
+                phase: *phase,
+                location: *location,
+                force: force.map(Force::from),
+                id: *id,
+            }),
+            R::ScaleFactorChanged { scale_factor, new_inner_size } => {
+                *size = *new_inner_size;
+                WindowEvent::ScaleFactorChanged {
+                    scale_factor: *scale_factor,
+                    new_inner_size: size,
+                }
+            }
+            R::ThemeChanged { dark: true } => WindowEvent::ThemeChanged(Theme::Dark),
+            R::ThemeChanged { dark: false } => WindowEvent::ThemeChanged(Theme::Light),
+        }
+    }
+}
+
+impl RecordDeviceEvent {
+    pub fn from_event(event: &DeviceEvent) -> RecordDeviceEvent {
+        use RecordDeviceEvent as R;
+
+        match event {
+            DeviceEvent::Added => R::Added,
+            DeviceEvent::Removed => R::Removed,
+            DeviceEvent::MouseMotion { delta } => R::MouseMotion { delta: *delta },
+            DeviceEvent::MouseWheel { delta } => R::MouseWheel { delta: *delta },
+            DeviceEvent::Motion { axis, value } => {
+                R::Motion { axis: *axis, value: *value }
+            }
+            DeviceEvent::Button { button, state } => {
+                R::Button { button: *button, state: *state }
+            }
+            DeviceEvent::Key(input) => R::Key(*input),
+            DeviceEvent::Text { codepoint } => R::Text { codepoint: *codepoint },
+        }
+    }
+
+    pub fn to_event(&self) -> DeviceEvent {
+        use RecordDeviceEvent as R;
+
+        match self {
+            R::Added => DeviceEvent::Added,
+            R::Removed => DeviceEvent::Removed,
+            R::MouseMotion { delta } => DeviceEvent::MouseMotion { delta: *delta },
+            R::MouseWheel { delta } => DeviceEvent::MouseWheel { delta: *delta },
+            R::Motion { axis, value } => {
+                DeviceEvent::Motion { axis: *axis, value: *value }
+            }
+            R::Button { button, state } => {
+                DeviceEvent::Button { button: *button, state: *state }
+            }
+            R::Key(input) => DeviceEvent::Key(*input),
+            R::Text { codepoint } => DeviceEvent::Text { codepoint: *codepoint },
+        }
+    }
+}
+
+impl From<Force> for TouchForce {
+    fn from(force: Force) -> TouchForce {
+        match force {
+            Force::Calibrated { force, max_possible_force, altitude_angle } => {
+                TouchForce::Calibrated { force, max_possible_force, altitude_angle }
+            }
+            Force::Normalized(val) => TouchForce::Normalized(val),
+        }
+    }
+}
+
+impl From<TouchForce> for Force {
+    fn from(force: TouchForce) -> Force {
+        match force {
+            TouchForce::Calibrated { force, max_possible_force, altitude_angle } => {
+                Force::Calibrated { force, max_possible_force, altitude_angle }
+            }
+            TouchForce::Normalized(val) => Force::Normalized(val),
+        }
+    }
+}
+
+/// Type records the stream of events, along with timestamps, to a file.
+///
+/// Each event is written as a JSON encoded [Record], one per line. Records
+/// are flushed to disk at the end of every frame so that the recording is
+/// usable even when the application crashes. Use [load_records] to read them
+/// back, or [SingleWindow::replay][crate::niw::SingleWindow::replay] to feed
+/// them to the same handlers without a window.
+pub struct Recorder {
+    loc: PathBuf,
+    file: io::BufWriter<fs::File>,
+    genesis: time::Instant,
+    frame: u64,
+}
+
+impl Recorder {
+    /// Create a new recording at `loc`, an existing file shall be truncated.
+    pub fn new<P>(loc: P) -> Result<Recorder>
+    where
+        P: AsRef<Path>,
+    {
+        let loc = loc.as_ref().to_path_buf();
+        let file = err_at!(IOError, fs::File::create(&loc), "{:?}", loc)?;
+
+        let val = Recorder {
+            loc,
+            file: io::BufWriter::new(file),
+            genesis: time::Instant::now(),
+            frame: 0,
+        };
+
+        Ok(val)
+    }
+
+    pub fn append<E>(&mut self, event: &Event<E>) -> Result<()>
+    where
+        E: Clone + Serialize,
+    {
+        let record = Record {
+            elapsed: self.genesis.elapsed().as_micros() as u64,
+            frame: self.frame,
+            event: RecordEvent::from_event(event, self.genesis),
+        };
+
+        let line = err_at!(FailConvert, serde_json::to_string(&record))?;
+        err_at!(IOError, writeln!(self.file, "{}", line), "{:?}", self.loc)?;
+
+        match event {
+            Event::RedrawEventsCleared => {
+                self.frame += 1;
+                self.flush()
+            }
+            Event::LoopDestroyed => self.flush(),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn flush(&mut self) -> Result<()> {
+        err_at!(IOError, self.file.flush(), "{:?}", self.loc)
+    }
+
+    pub fn to_frame(&self) -> u64 {
+        self.frame
+    }
+}
+
+/// Load events recorded by [Recorder] from file `loc`.
+pub fn load_records<E, P>(loc: P) -> Result<Vec<Record<E>>>
+where
+    E: DeserializeOwned,
+    P: AsRef<Path>,
+{
+    let loc = loc.as_ref();
+    let file = err_at!(IOError, fs::File::open(loc), "{:?}", loc)?;
+
+    let mut records = vec![];
+    for (lineno, line) in io::BufReader::new(file).lines().enumerate() {
+        let line = err_at!(IOError, line, "{:?}", loc)?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record = err_at!(
+            FailConvert,
+            serde_json::from_str(&line),
+            "{:?}:{}",
+            loc,
+            lineno + 1
+        )?;
+        records.push(record)
+    }
+
+    Ok(records)
+}
+
+#[cfg(test)]
+#[path = "record_test.rs"]
+mod record_test;
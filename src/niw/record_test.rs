@@ -0,0 +1,319 @@
+use winit::{event::VirtualKeyCode, event_loop::ControlFlow};
+
+use std::{cell::RefCell, env, rc::Rc, sync::Arc};
+
+use super::*;
+use crate::niw::SingleWindow;
+
+fn window_event(event: WindowEvent<'static>) -> Event<'static, u32> {
+    let window_id = unsafe { WindowId::dummy() };
+    Event::WindowEvent { window_id, event }
+}
+
+#[allow(deprecated)]
+fn key(key: VirtualKeyCode, state: ElementState) -> Event<'static, u32> {
+    window_event(WindowEvent::KeyboardInput {
+        device_id: unsafe { DeviceId::dummy() },
+        input: KeyboardInput {
+            scancode: 0,
+            state,
+            virtual_keycode: Some(key),
+            modifiers: ModifiersState::empty(),
+        },
+        is_synthetic: false,
+    })
+}
+
+fn frame(recorder: &mut Recorder, events: Vec<Event<'static, u32>>) {
+    let mut frame: Vec<Event<u32>> = vec![Event::NewEvents(StartCause::Poll)];
+    frame.extend(events);
+    frame.push(Event::MainEventsCleared);
+    frame.push(Event::RedrawRequested(unsafe { WindowId::dummy() }));
+    frame.push(Event::RedrawEventsCleared);
+
+    for event in frame.iter() {
+        recorder.append(event).unwrap();
+    }
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_record_event() {
+    let device_id = unsafe { DeviceId::dummy() };
+    let events = vec![
+        Event::NewEvents(StartCause::Init),
+        Event::UserEvent(10),
+        key(VirtualKeyCode::A, ElementState::Pressed),
+        window_event(WindowEvent::MouseWheel {
+            device_id,
+            delta: MouseScrollDelta::PixelDelta(PhysicalPosition::new(1.0, 2.0)),
+            phase: TouchPhase::Moved,
+            modifiers: ModifiersState::empty(),
+        }),
+        window_event(WindowEvent::Touch(Touch {
+            device_id,
+            phase: TouchPhase::Started,
+            location: PhysicalPosition::new(10.0, 20.0),
+            force: Some(Force::Normalized(0.5)),
+            id: 3,
+        })),
+        window_event(WindowEvent::DroppedFile("a/b.obj".into())),
+        Event::DeviceEvent {
+            device_id,
+            event: DeviceEvent::MouseMotion { delta: (1.5, -1.5) },
+        },
+        Event::RedrawEventsCleared,
+    ];
+
+    let genesis = time::Instant::now();
+    for event in events.iter() {
+        let record = RecordEvent::from_event(event, genesis);
+        let text = serde_json::to_string(&record).unwrap();
+        let record: RecordEvent<u32> = serde_json::from_str(&text).unwrap();
+
+        let mut size = PhysicalSize::default();
+        let replayed = record.to_event(&mut size, genesis);
+        assert_eq!(
+            RecordEvent::from_event(&replayed, genesis),
+            RecordEvent::from_event(event, genesis)
+        );
+    }
+
+    let mut new_inner_size = PhysicalSize::new(800, 600);
+    let event: Event<u32> = Event::WindowEvent {
+        window_id: unsafe { WindowId::dummy() },
+        event: WindowEvent::ScaleFactorChanged {
+            scale_factor: 2.0,
+            new_inner_size: &mut new_inner_size,
+        },
+    };
+    let record = RecordEvent::from_event(&event, genesis);
+    let mut size = PhysicalSize::default();
+    match record.to_event(&mut size, genesis) {
+        Event::WindowEvent {
+            event: WindowEvent::ScaleFactorChanged { scale_factor, new_inner_size },
+            ..
+        } => {
+            assert_eq!(scale_factor, 2.0);
+            assert_eq!(*new_inner_size, PhysicalSize::new(800, 600));
+        }
+        event => panic!("unexpected {:?}", event),
+    }
+}
+
+#[test]
+fn test_record_start_cause() {
+    let genesis = time::Instant::now();
+    let (start, resume) = (
+        genesis + time::Duration::from_millis(5),
+        genesis + time::Duration::from_millis(4),
+    );
+
+    let event: Event<u32> = Event::NewEvents(StartCause::ResumeTimeReached {
+        start,
+        requested_resume: resume,
+    });
+    let record = RecordEvent::from_event(&event, genesis);
+    let cause = Cause::ResumeTimeReached { start: 5000, requested_resume: 4000 };
+    assert_eq!(record, RecordEvent::NewEvents(cause));
+
+    // instants are rebased on the replay start, keeping their offsets.
+    let replay = genesis + time::Duration::from_secs(10);
+    let mut size = PhysicalSize::default();
+    match record.to_event(&mut size, replay) {
+        Event::NewEvents(StartCause::ResumeTimeReached { start, requested_resume }) => {
+            assert_eq!(start, replay + time::Duration::from_millis(5));
+            assert_eq!(requested_resume, replay + time::Duration::from_millis(4));
+        }
+        event => panic!("unexpected {:?}", event),
+    }
+
+    let event: Event<u32> =
+        Event::NewEvents(StartCause::WaitCancelled { start, requested_resume: None });
+    let record = RecordEvent::from_event(&event, genesis);
+    let cause = Cause::WaitCancelled { start: 5000, requested_resume: None };
+    assert_eq!(record, RecordEvent::NewEvents(cause));
+}
+
+#[derive(Default)]
+struct State {
+    keys: Vec<VirtualKeyCode>,
+    frames: u64,
+    saves: u64,
+}
+
+#[test]
+fn test_record_replay() {
+    let loc = env::temp_dir().join("gpgpu-test-record-replay.jsonl");
+
+    let mut recorder = Recorder::new(&loc).unwrap();
+    frame(&mut recorder, vec![Event::UserEvent(1)]);
+    frame(&mut recorder, vec![key(VirtualKeyCode::S, ElementState::Pressed)]);
+    frame(&mut recorder, vec![key(VirtualKeyCode::S, ElementState::Released)]);
+    frame(&mut recorder, vec![key(VirtualKeyCode::Escape, ElementState::Pressed)]);
+    frame(&mut recorder, vec![key(VirtualKeyCode::Q, ElementState::Pressed)]);
+    assert_eq!(recorder.to_frame(), 5);
+    std::mem::drop(recorder);
+
+    let records: Vec<Record<u32>> = load_records(&loc).unwrap();
+    assert_eq!(records.len(), 25);
+    assert_eq!(records[5].frame, 1);
+    assert!(records.windows(2).all(|r| r[0].elapsed <= r[1].elapsed));
+
+    let just_pressed = Rc::new(RefCell::new(vec![]));
+
+    let mut swin = SingleWindow::<State, u32>::headless();
+    let input = swin.to_input_state();
+    let mut bindings = crate::niw::Bindings::default();
+    bindings.bind("save", vec!["S".parse().unwrap()]);
+    swin.set_bindings(bindings);
+    swin.on_win_keyboard_input(Box::new(|w, state, event| {
+        assert!(w.is_none());
+        if let Event::WindowEvent {
+            event: WindowEvent::KeyboardInput { input, .. },
+            ..
+        } = event
+        {
+            state.keys.push(input.virtual_keycode.unwrap())
+        }
+        None
+    }))
+    .on_redraw_requested(Box::new({
+        let just_pressed = Rc::clone(&just_pressed);
+        move |_, state, _| {
+            state.frames += 1;
+            let pressed = input.read().is_key_just_pressed(VirtualKeyCode::S);
+            just_pressed.borrow_mut().push(pressed);
+            None
+        }
+    }))
+    .on_action(
+        "save",
+        Box::new(|_, state, _| {
+            state.saves += 1;
+            None
+        }),
+    );
+
+    let state = swin
+        .replay_with(&loc, State::default(), time::Duration::ZERO, |_, _, _| ())
+        .unwrap();
+    // replay stops on escape, before the fourth frame is drawn.
+    assert_eq!(state.frames, 3);
+    assert_eq!(state.saves, 1);
+    assert_eq!(
+        state.keys,
+        vec![VirtualKeyCode::S, VirtualKeyCode::S, VirtualKeyCode::Escape]
+    );
+    assert_eq!(*just_pressed.borrow(), vec![false, true, false]);
+
+    let mut swin = SingleWindow::<State, u32>::headless();
+    swin.set_exit_on_esc(false).on_user_event(Box::new(|_, state, _| {
+        state.saves += 1;
+        Some(ControlFlow::Exit)
+    }));
+    let state = swin
+        .replay_with(&loc, State::default(), time::Duration::ZERO, |_, _, _| ())
+        .unwrap();
+    assert_eq!(state.saves, 1);
+    assert_eq!(state.frames, 0);
+
+    std::fs::remove_file(&loc).ok();
+}
+
+#[test]
+fn test_replay_resize() {
+    let loc = env::temp_dir().join("gpgpu-test-replay-resize.jsonl");
+
+    let mut recorder = Recorder::new(&loc).unwrap();
+    frame(
+        &mut recorder,
+        vec![window_event(WindowEvent::Resized(PhysicalSize::new(800, 600)))],
+    );
+    let mut new_inner_size = PhysicalSize::new(400, 300);
+    let event: Event<u32> = Event::WindowEvent {
+        window_id: unsafe { WindowId::dummy() },
+        event: WindowEvent::ScaleFactorChanged {
+            scale_factor: 2.0,
+            new_inner_size: &mut new_inner_size,
+        },
+    };
+    recorder.append(&event).unwrap();
+    std::mem::drop(recorder);
+
+    let mut swin = SingleWindow::<Vec<(PhysicalSize<u32>, Option<f64>)>, u32>::headless();
+    let dirty = swin.to_dirty_flag();
+    dirty.store(false, std::sync::atomic::Ordering::SeqCst);
+    swin.on_win_resized(Box::new(|_, sizes, _| {
+        // resized before the handlers are called.
+        assert_eq!(sizes.len(), 1);
+        None
+    }));
+
+    let on_resize =
+        |sizes: &mut Vec<_>, size, scale_factor| sizes.push((size, scale_factor));
+    let sizes = swin.replay_with(&loc, vec![], time::Duration::ZERO, on_resize).unwrap();
+    assert_eq!(
+        sizes,
+        vec![
+            (PhysicalSize::new(800, 600), None),
+            (PhysicalSize::new(400, 300), Some(2.0))
+        ]
+    );
+    assert!(dirty.load(std::sync::atomic::Ordering::SeqCst));
+
+    std::fs::remove_file(&loc).ok();
+}
+
+#[test]
+fn test_replay_timers() {
+    let loc = env::temp_dir().join("gpgpu-test-replay-timers.jsonl");
+
+    let mut recorder = Recorder::new(&loc).unwrap();
+    for _ in 0..10 {
+        frame(&mut recorder, vec![]);
+    }
+    std::mem::drop(recorder);
+
+    // frames and timers fired in each frame, timers are set from handlers on
+    // the virtual clock.
+    let mut swin = SingleWindow::<Vec<(u64, &'static str)>, u32>::headless();
+    let timers = swin.to_timers();
+    let frames = Rc::new(RefCell::new(0_u64));
+    swin.on_redraw_requested(Box::new({
+        let (timers, frames) = (Arc::clone(&timers), Rc::clone(&frames));
+        move |_, _, _| {
+            if *frames.borrow() == 0 {
+                let every = time::Duration::from_millis(30);
+                timers.write().set_interval("tick", every).unwrap();
+                let after = time::Duration::from_millis(45);
+                timers.write().set_timeout("once", after).unwrap();
+            }
+            *frames.borrow_mut() += 1;
+            None
+        }
+    }))
+    .on_timer("tick", {
+        let frames = Rc::clone(&frames);
+        Box::new(move |w, fired, _| {
+            assert!(w.is_none());
+            fired.push((*frames.borrow(), "tick"));
+            None
+        })
+    })
+    .on_timer("once", {
+        let frames = Rc::clone(&frames);
+        Box::new(move |_, fired, _| {
+            fired.push((*frames.borrow(), "once"));
+            None
+        })
+    });
+
+    let frame_time = time::Duration::from_millis(10);
+    let fired = swin.replay_with(&loc, vec![], frame_time, |_, _, _| ()).unwrap();
+    assert_eq!(fired, vec![(3, "tick"), (5, "once"), (6, "tick"), (9, "tick")]);
+    // back on the system clock after replay.
+    assert!(timers.read().to_now() <= time::Instant::now());
+
+    std::fs::remove_file(&loc).ok();
+}
@@ -1,24 +1,44 @@
-use log::{debug, info, trace, warn};
+use log::{debug, error, info, trace, warn};
+use serde::{de::DeserializeOwned, Serialize};
 use winit::{
+    dpi::PhysicalSize,
     event::{DeviceEvent, Event, StartCause, WindowEvent},
     event_loop::{ControlFlow, EventLoop, EventLoopWindowTarget},
-    window::{Window, WindowAttributes, WindowBuilder},
+    window::{Window, WindowAttributes, WindowBuilder, WindowId},
 };
 
-use std::fmt;
+use std::{
+    collections::BTreeMap,
+    fmt, path,
+    sync::{
+        atomic::{AtomicBool, Ordering::SeqCst},
+        Arc,
+    },
+    time,
+};
 
-#[allow(unused_imports)]
-use crate::ConfigWinit;
-use crate::{Error, Render, Result};
+use crate::{
+    niw::{
+        load_records, set_fullscreen, Bindings, EventProxy, InputState, Record,
+        Recorder, RedrawMode, Timers,
+    },
+    util::Spinlock,
+    ConfigFullscreen, ConfigWinit, Error, Render, Result,
+};
 
 /// Type to handle events with an event-argument `E`, window-target and state `S`.
+/// Window is None while replaying on a [SingleWindow::headless] instance, refer
+/// [SingleWindow::replay].
 pub type Handler<S, E> =
-    Box<dyn FnMut(&Window, &mut S, &mut Event<E>) -> Option<ControlFlow>>;
+    Box<dyn FnMut(Option<&Window>, &mut S, &mut Event<E>) -> Option<ControlFlow>>;
+
+type RecordFn<E> = Box<dyn FnMut(&Event<E>)>;
 
 /// Type instantiates an event-loop and an associated window, useful for single window
 /// applications.
 ///
 /// Can be constructed from [ConfigWinit] configuration, refer
+/// [SingleWindow::from_config_winit], or from [WindowAttributes], refer
 /// [SingleWindow::from_config]. This type parameterised over user-event `E` for
 /// [EventLoop]
 pub struct SingleWindow<S, E = ()>
@@ -31,6 +51,14 @@ where
     event_handlers: Option<EventHandlers<S, E>>,
     window_event_handlers: Option<WindowEventHandlers<S, E>>,
     device_event_handlers: Option<DeviceEventHandlers<S, E>>,
+    action_handlers: Option<BTreeMap<String, Handler<S, E>>>,
+    input: Arc<Spinlock<InputState>>,
+    bindings: Arc<Spinlock<Bindings>>,
+    recorder: Option<RecordFn<E>>,
+    timer_handlers: Option<BTreeMap<String, Handler<S, E>>>,
+    timers: Arc<Spinlock<Timers>>,
+    redraw_mode: RedrawMode,
+    dirty: Arc<AtomicBool>,
     exit_on_esc: bool,
 }
 
@@ -156,7 +184,27 @@ where
         E: Default,
     {
         let event_loop = EventLoop::<E>::with_user_event();
+        Self::from_event_loop(event_loop, attrs, None)
+    }
+
+    /// Create the window as per `config`, fullscreen monitor and video mode
+    /// are resolved before creating the window, refer
+    /// [ConfigWinit::to_window_attributes], and the rest of the attributes are
+    /// applied after, refer [ConfigWinit::apply_window].
+    pub fn from_config_winit(config: &ConfigWinit) -> Result<Self>
+    where
+        E: Default,
+    {
+        let event_loop = EventLoop::<E>::with_user_event();
+        let attrs = config.to_window_attributes(&event_loop)?;
+        Self::from_event_loop(event_loop, attrs, Some(config))
+    }
 
+    fn from_event_loop(
+        event_loop: EventLoop<E>,
+        attrs: WindowAttributes,
+        config: Option<&ConfigWinit>,
+    ) -> Result<Self> {
         info!("inner_size {:?}", attrs.inner_size);
 
         let window = {
@@ -164,6 +212,11 @@ where
             wb.window = attrs;
             err_at!(Fatal, wb.build(&event_loop))?
         };
+        if let Some(config) = config {
+            config.apply_window(&window)?
+        }
+
+        let input = Arc::new(Spinlock::new(InputState::new(window.scale_factor())));
 
         let val = SingleWindow {
             event_loop: Some(event_loop),
@@ -172,12 +225,60 @@ where
             event_handlers: Some(EventHandlers::default()),
             window_event_handlers: Some(WindowEventHandlers::default()),
             device_event_handlers: Some(DeviceEventHandlers::default()),
+            action_handlers: Some(BTreeMap::new()),
+            input,
+            bindings: Arc::new(Spinlock::new(Bindings::default())),
+            recorder: None,
+            timer_handlers: Some(BTreeMap::new()),
+            timers: Arc::new(Spinlock::new(Timers::default())),
+            redraw_mode: RedrawMode::default(),
+            dirty: Arc::new(AtomicBool::new(true)),
             exit_on_esc: true,
         };
 
         Ok(val)
     }
 
+    /// Create an instance without event-loop and window, handlers subscribed
+    /// to this instance can only be driven by [SingleWindow::replay].
+    pub fn headless() -> Self {
+        SingleWindow {
+            event_loop: None,
+            window: None,
+            on_event: Some(Box::new(|_, _, _| None)),
+            event_handlers: Some(EventHandlers::default()),
+            window_event_handlers: Some(WindowEventHandlers::default()),
+            device_event_handlers: Some(DeviceEventHandlers::default()),
+            action_handlers: Some(BTreeMap::new()),
+            input: Arc::new(Spinlock::new(InputState::default())),
+            bindings: Arc::new(Spinlock::new(Bindings::default())),
+            recorder: None,
+            timer_handlers: Some(BTreeMap::new()),
+            timers: Arc::new(Spinlock::new(Timers::default())),
+            redraw_mode: RedrawMode::default(),
+            dirty: Arc::new(AtomicBool::new(true)),
+            exit_on_esc: true,
+        }
+    }
+
+    /// Record all events handled by [SingleWindow::run] to file `loc`, refer
+    /// [Recorder] for details. Recorded events can be fed back to the same
+    /// handlers using [SingleWindow::replay].
+    pub fn record<P>(&mut self, loc: P) -> Result<&mut Self>
+    where
+        P: AsRef<path::Path>,
+        E: Clone + Serialize,
+    {
+        let mut recorder = Recorder::new(loc)?;
+        self.recorder = Some(Box::new(move |evnt: &Event<E>| {
+            if let Err(err) = recorder.append(evnt) {
+                error!("recording event {}", err)
+            }
+        }));
+
+        Ok(self)
+    }
+
     pub fn set_exit_on_esc(&mut self, val: bool) -> &mut Self {
         self.exit_on_esc = val;
         self
@@ -191,6 +292,67 @@ where
         self.window.as_ref().unwrap()
     }
 
+    /// Switch the window to fullscreen as per `config`, or to windowed mode if
+    /// `config` is None, refer [niw::set_fullscreen][crate::niw::set_fullscreen].
+    /// Windows created via [SingleWindow::from_config_winit] start fullscreen
+    /// as per [ConfigWinit::fullscreen].
+    pub fn set_fullscreen(&mut self, config: Option<&ConfigFullscreen>) -> &mut Self {
+        if let Some(window) = self.window.as_ref() {
+            set_fullscreen(window, config)
+        }
+        self
+    }
+
+    /// Return the input state of this window, updated by [SingleWindow::run]
+    /// before calling the handlers. Applications can keep a clone of this in
+    /// their state and query it from handlers.
+    pub fn to_input_state(&self) -> Arc<Spinlock<InputState>> {
+        Arc::clone(&self.input)
+    }
+
+    /// Set key and mouse bindings for this window, actions triggered by these
+    /// bindings are dispatched to handlers subscribed via
+    /// [SingleWindow::on_action]. Bindings are typically loaded along with
+    /// [crate::Config].
+    pub fn set_bindings(&mut self, bindings: Bindings) -> &mut Self {
+        *self.bindings.write() = bindings;
+        self
+    }
+
+    /// Return the bindings of this window, applications can keep a clone of this
+    /// in their state to switch context from handlers.
+    pub fn to_bindings(&self) -> Arc<Spinlock<Bindings>> {
+        Arc::clone(&self.bindings)
+    }
+
+    /// Set the redraw policy, default is [RedrawMode::Continuous]. Idle
+    /// applications can use [RedrawMode::OnDemand] so that the event-loop
+    /// sleeps until there is an event, a timer expires or a redraw is requested.
+    pub fn set_redraw_mode(&mut self, mode: RedrawMode) -> &mut Self {
+        self.redraw_mode = mode;
+        self
+    }
+
+    /// Return the dirty flag for [RedrawMode::OnDemand], handlers shall set
+    /// this to true when state changes, to request a redraw. The flag is
+    /// cleared when the redraw is requested. Ignored in continuous mode.
+    pub fn to_dirty_flag(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.dirty)
+    }
+
+    /// Return timers for this window, applications can keep a clone of this
+    /// in their state to set and cancel timers from handlers. Handlers for
+    /// expired timers are subscribed via [SingleWindow::on_timer].
+    pub fn to_timers(&self) -> Arc<Spinlock<Timers>> {
+        Arc::clone(&self.timers)
+    }
+
+    /// Return a proxy to post user events from worker threads. Shall be called
+    /// before [SingleWindow::run].
+    pub fn to_event_proxy(&self) -> EventProxy<E> {
+        EventProxy::from(self.as_event_loop().create_proxy())
+    }
+
     pub fn to_scale_factor(&self) -> f32 {
         self.window.as_ref().map(|w| w.scale_factor() as f32).unwrap_or(0.0)
     }
@@ -203,10 +365,6 @@ where
         let window = self.window.take().unwrap();
         let event_loop = self.event_loop.take().unwrap();
         let wid = window.id();
-        let mut on_event = self.on_event.take().unwrap();
-        let mut event_handlers = self.event_handlers.take().unwrap();
-        let mut window_event_handlers = self.window_event_handlers.take().unwrap();
-        let mut device_event_handlers = self.device_event_handlers.take().unwrap();
 
         debug!("starting the event_loop ...");
 
@@ -215,165 +373,382 @@ where
                   _: &EventLoopWindowTarget<E>,
                   cf: &mut ControlFlow| {
                 log_event(&evnt);
-                let mut no_op: Handler<S, E> = Box::new(|_, _, _| None);
-
-                (on_event)(&window, &mut state, &mut evnt);
-
-                let handler = match &evnt {
-                    Event::NewEvents(_) => &mut event_handlers.on_new_events,
-                    Event::UserEvent(_) => &mut event_handlers.on_user_event,
-                    Event::Suspended => &mut event_handlers.on_suspended,
-                    Event::Resumed => &mut event_handlers.on_resumed,
-                    Event::MainEventsCleared => {
-                        // Locally handle few things here.
-                        window.request_redraw();
-                        &mut event_handlers.on_main_events_cleared
-                    }
-                    Event::RedrawRequested(window_id) if window_id == &wid => {
-                        &mut event_handlers.on_redraw_requested
-                    }
-                    Event::RedrawRequested(window_id) => {
-                        warn!(
-                            "mismatch in window id {:?} != {:?} for {:?}",
-                            window_id, wid, evnt
-                        );
-                        &mut no_op
+
+                if let Some(recorder) = self.recorder.as_mut() {
+                    recorder(&evnt)
+                }
+
+                // Locally handle few things here.
+                match &evnt {
+                    Event::NewEvents(_) => {
+                        if let Some(val) =
+                            self.fire_timers(Some(&window), &mut state, &mut evnt)
+                        {
+                            *cf = val
+                        }
                     }
-                    Event::RedrawEventsCleared => {
-                        &mut event_handlers.on_redraw_events_cleared
+                    Event::MainEventsCleared => match self.redraw_mode {
+                        RedrawMode::Continuous => window.request_redraw(),
+                        RedrawMode::OnDemand if self.dirty.swap(false, SeqCst) => {
+                            window.request_redraw()
+                        }
+                        RedrawMode::OnDemand => (),
+                    },
+                    Event::RedrawEventsCleared
+                        if self.redraw_mode == RedrawMode::OnDemand
+                            && *cf != ControlFlow::Exit =>
+                    {
+                        *cf = self.to_control_flow()
                     }
-                    Event::LoopDestroyed => &mut event_handlers.on_loop_destroyed,
                     Event::WindowEvent { window_id, event } if window_id == &wid => {
                         match event {
                             WindowEvent::Resized(size) => {
-                                // Locally handle few things here.
                                 let render: &mut Render = state.as_mut();
                                 render.resize(*size, None);
-                                &mut window_event_handlers.on_resized
-                            }
-                            WindowEvent::Moved(_) => &mut window_event_handlers.on_moved,
-                            WindowEvent::CloseRequested => {
-                                // Locally handle few things here.
-                                let render: &mut Render = state.as_mut();
-                                render.stop().ok();
-                                *cf = ControlFlow::Exit;
-                                &mut window_event_handlers.on_close_requested
-                            }
-                            WindowEvent::Destroyed => {
-                                &mut window_event_handlers.on_destroyed
+                                self.dirty.store(true, SeqCst);
                             }
-                            WindowEvent::DroppedFile(_) => {
-                                &mut window_event_handlers.on_dropped_file
-                            }
-                            WindowEvent::HoveredFile(_) => {
-                                &mut window_event_handlers.on_hovered_file
-                            }
-                            WindowEvent::HoveredFileCancelled => {
-                                &mut window_event_handlers.on_hovered_file_cancelled
-                            }
-                            WindowEvent::ReceivedCharacter(_) => {
-                                &mut window_event_handlers.on_received_character
-                            }
-                            WindowEvent::Focused(_) => {
-                                &mut window_event_handlers.on_focused
-                            }
-                            WindowEvent::KeyboardInput { input, .. }
-                                if self.exit_on_esc =>
-                            {
-                                use winit::event::{
-                                    ElementState, KeyboardInput, VirtualKeyCode,
-                                };
-
-                                match input {
-                                    KeyboardInput {
-                                        state: ElementState::Pressed,
-                                        virtual_keycode: Some(VirtualKeyCode::Escape),
-                                        ..
-                                    } => {
-                                        // Locally handle few things here.
-                                        let render: &mut Render = state.as_mut();
-                                        render.stop().ok();
-                                        *cf = ControlFlow::Exit;
-                                        &mut window_event_handlers.on_keyboard_input
-                                    }
-                                    _ => &mut window_event_handlers.on_keyboard_input,
-                                }
-                            }
-                            WindowEvent::KeyboardInput { .. } => {
-                                &mut window_event_handlers.on_keyboard_input
-                            }
-                            WindowEvent::ModifiersChanged(_) => {
-                                &mut window_event_handlers.on_modifiers_changed
-                            }
-                            WindowEvent::CursorMoved { .. } => {
-                                &mut window_event_handlers.on_cursor_moved
-                            }
-                            WindowEvent::CursorEntered { .. } => {
-                                &mut window_event_handlers.on_cursor_entered
-                            }
-                            WindowEvent::CursorLeft { .. } => {
-                                &mut window_event_handlers.on_cursor_left
-                            }
-                            WindowEvent::MouseWheel { .. } => {
-                                &mut window_event_handlers.on_mouse_wheel
-                            }
-                            WindowEvent::MouseInput { .. } => {
-                                &mut window_event_handlers.on_mouse_input
-                            }
-                            WindowEvent::TouchpadPressure { .. } => {
-                                &mut window_event_handlers.on_touchpad_pressure
-                            }
-                            WindowEvent::AxisMotion { .. } => {
-                                &mut window_event_handlers.on_axis_motion
-                            }
-                            WindowEvent::Touch(_) => &mut window_event_handlers.on_touch,
                             WindowEvent::ScaleFactorChanged {
                                 new_inner_size,
                                 scale_factor,
                             } => {
-                                // Locally handle few things here.
                                 let render: &mut Render = state.as_mut();
                                 render.resize(**new_inner_size, Some(*scale_factor));
-                                &mut window_event_handlers.on_scale_factor_changed
-                            }
-                            WindowEvent::ThemeChanged(_) => {
-                                &mut window_event_handlers.on_theme_changed
+                                self.dirty.store(true, SeqCst);
                             }
+                            _ => (),
                         }
                     }
-                    Event::WindowEvent { window_id, .. } => {
-                        warn!(
-                            "mismatch in window id {:?} != {:?} for {:?}",
-                            window_id, wid, evnt
-                        );
-                        &mut no_op
+                    _ => (),
+                }
+                if self.is_exit_event(&evnt) {
+                    let render: &mut Render = state.as_mut();
+                    render.stop().ok();
+                    *cf = ControlFlow::Exit;
+                }
+
+                if let Some(val) =
+                    self.dispatch(Some(&window), Some(wid), &mut state, &mut evnt)
+                {
+                    *cf = val
+                }
+            },
+        );
+    }
+
+    /// Replay events recorded via [SingleWindow::record] from file `loc`.
+    ///
+    /// Events are fed through the same handlers that [SingleWindow::run] would
+    /// call, without an event-loop. Handlers are called with None for window
+    /// if this instance has none, refer [SingleWindow::headless]. Replayed
+    /// resize events resize the [Render] in `state`.
+    ///
+    /// Recorded timestamps are ignored, instead events are replayed as fast as
+    /// possible against a virtual clock that starts at the time of replay and
+    /// advances by `frame_time` after every frame. Timers, refer [Timers],
+    /// expire as per this clock and their handlers are called like in
+    /// [SingleWindow::run]. Replay stops when an exit event is replayed or when
+    /// a handler returns [ControlFlow::Exit], and returns the final `state`.
+    pub fn replay<P>(self, loc: P, state: S, frame_time: time::Duration) -> Result<S>
+    where
+        S: AsMut<Render>,
+        P: AsRef<path::Path>,
+        E: fmt::Debug + Clone + DeserializeOwned,
+    {
+        let on_resize = |state: &mut S, size, scale_factor| {
+            let render: &mut Render = state.as_mut();
+            render.resize(size, scale_factor)
+        };
+        self.replay_with(loc, state, frame_time, on_resize)
+    }
+
+    /// Same as [SingleWindow::replay], for states without a [Render], replayed
+    /// resize events are passed to `on_resize` along with the scale factor.
+    ///
+    /// Typically used with a [SingleWindow::headless] instance, to reproduce
+    /// bugs and to run interaction tests on machines without a display.
+    pub fn replay_with<P, F>(
+        mut self,
+        loc: P,
+        mut state: S,
+        frame_time: time::Duration,
+        mut on_resize: F,
+    ) -> Result<S>
+    where
+        P: AsRef<path::Path>,
+        F: FnMut(&mut S, PhysicalSize<u32>, Option<f64>),
+        E: fmt::Debug + Clone + DeserializeOwned,
+    {
+        let records: Vec<Record<E>> = load_records(loc)?;
+        let window = self.window.take();
+        let genesis = time::Instant::now();
+        // virtual clock for timers, advanced by `frame_time` after every frame.
+        let mut now = genesis;
+        self.timers.write().set_clock(Some(now));
+
+        debug!("replaying {} events ...", records.len());
+
+        for record in records.iter() {
+            let mut size = PhysicalSize::default();
+            let mut evnt = record.event.to_event(&mut size, genesis);
+            log_event(&evnt);
+
+            // Locally handle few things here, same as run().
+            let mut cf = None;
+            match &evnt {
+                Event::NewEvents(_) => {
+                    cf = self.fire_timers(window.as_ref(), &mut state, &mut evnt)
+                }
+                Event::WindowEvent { event, .. } => match event {
+                    WindowEvent::Resized(size) => {
+                        on_resize(&mut state, *size, None);
+                        self.dirty.store(true, SeqCst);
+                    }
+                    WindowEvent::ScaleFactorChanged { new_inner_size, scale_factor } => {
+                        on_resize(&mut state, **new_inner_size, Some(*scale_factor));
+                        self.dirty.store(true, SeqCst);
+                    }
+                    _ => (),
+                },
+                _ => (),
+            }
+
+            let exit = self.is_exit_event(&evnt);
+            if let Some(val) = self.dispatch(window.as_ref(), None, &mut state, &mut evnt)
+            {
+                cf = Some(val)
+            }
+            if exit || matches!(cf, Some(ControlFlow::Exit)) {
+                break;
+            }
+
+            if let Event::RedrawEventsCleared = &evnt {
+                now = match now.checked_add(frame_time) {
+                    Some(now) => now,
+                    None => {
+                        self.timers.write().set_clock(None);
+                        err_at!(Invalid, msg: "frame_time {:?} overflows", frame_time)?
                     }
-                    Event::DeviceEvent { event, .. } => match event {
-                        DeviceEvent::Added => &mut device_event_handlers.on_added,
-                        DeviceEvent::Removed => &mut device_event_handlers.on_removed,
-                        DeviceEvent::MouseMotion { .. } => {
-                            &mut device_event_handlers.on_mouse_motion
-                        }
-                        DeviceEvent::MouseWheel { .. } => {
-                            &mut device_event_handlers.on_mouse_wheel
-                        }
-                        DeviceEvent::Motion { .. } => {
-                            &mut device_event_handlers.on_motion
-                        }
-                        DeviceEvent::Button { .. } => {
-                            &mut device_event_handlers.on_button
-                        }
-                        DeviceEvent::Key(_) => &mut device_event_handlers.on_key,
-                        DeviceEvent::Text { .. } => &mut device_event_handlers.on_text,
-                    },
                 };
+                self.timers.write().set_clock(Some(now));
+            }
+        }
+
+        self.timers.write().set_clock(None);
 
-                match handler(&window, &mut state, &mut evnt) {
-                    Some(val) => *cf = val,
-                    None => (),
+        Ok(state)
+    }
+
+    // Call handlers for expired timers, with the event that woke up the
+    // event-loop. Return the control flow from the last handler that returned
+    // one.
+    fn fire_timers(
+        &mut self,
+        window: Option<&Window>,
+        state: &mut S,
+        evnt: &mut Event<E>,
+    ) -> Option<ControlFlow> {
+        let expired = {
+            let mut timers = self.timers.write();
+            let now = timers.to_now();
+            timers.expire(now)
+        };
+        let timer_handlers = self.timer_handlers.as_mut().unwrap();
+
+        let mut cf = None;
+        for name in expired.into_iter() {
+            match timer_handlers.get_mut(&name) {
+                Some(handler) => {
+                    if let Some(val) = handler(window, state, evnt) {
+                        cf = Some(val)
+                    }
+                }
+                None => trace!("no handler for timer {:?}", name),
+            }
+        }
+
+        cf
+    }
+
+    // Control flow for on-demand redraw, poll if a redraw is pending, else
+    // wait for events or the next timer deadline.
+    fn to_control_flow(&self) -> ControlFlow {
+        if self.dirty.load(SeqCst) {
+            ControlFlow::Poll
+        } else {
+            match self.timers.read().to_deadline() {
+                Some(deadline) => ControlFlow::WaitUntil(deadline),
+                None => ControlFlow::Wait,
+            }
+        }
+    }
+
+    fn is_exit_event(&self, evnt: &Event<E>) -> bool {
+        use winit::event::{ElementState, KeyboardInput, VirtualKeyCode};
+
+        match evnt {
+            Event::WindowEvent { event: WindowEvent::CloseRequested, .. } => true,
+            Event::WindowEvent {
+                event:
+                    WindowEvent::KeyboardInput {
+                        input:
+                            KeyboardInput {
+                                state: ElementState::Pressed,
+                                virtual_keycode: Some(VirtualKeyCode::Escape),
+                                ..
+                            },
+                        ..
+                    },
+                ..
+            } => self.exit_on_esc,
+            _ => false,
+        }
+    }
+
+    // Update input state and call the subscribed handlers for `evnt`. Window
+    // events not targeting `wid` are ignored, while replaying `wid` is None and
+    // all window events are accepted.
+    fn dispatch(
+        &mut self,
+        window: Option<&Window>,
+        wid: Option<WindowId>,
+        state: &mut S,
+        evnt: &mut Event<E>,
+    ) -> Option<ControlFlow>
+    where
+        E: fmt::Debug,
+    {
+        let is_own =
+            |window_id: &WindowId| wid.map(|wid| &wid == window_id).unwrap_or(true);
+
+        let mut no_op: Handler<S, E> = Box::new(|_, _, _| None);
+        let event_handlers = self.event_handlers.as_mut().unwrap();
+        let window_event_handlers = self.window_event_handlers.as_mut().unwrap();
+        let device_event_handlers = self.device_event_handlers.as_mut().unwrap();
+
+        match &evnt {
+            Event::WindowEvent { window_id, .. } if is_own(window_id) => {
+                self.input.write().handle_event(evnt)
+            }
+            _ => (),
+        }
+
+        (self.on_event.as_mut().unwrap())(window, state, evnt);
+
+        let handler = match &evnt {
+            Event::NewEvents(_) => &mut event_handlers.on_new_events,
+            Event::UserEvent(_) => &mut event_handlers.on_user_event,
+            Event::Suspended => &mut event_handlers.on_suspended,
+            Event::Resumed => &mut event_handlers.on_resumed,
+            Event::MainEventsCleared => &mut event_handlers.on_main_events_cleared,
+            Event::RedrawRequested(window_id) if is_own(window_id) => {
+                &mut event_handlers.on_redraw_requested
+            }
+            Event::RedrawRequested(window_id) => {
+                warn!(
+                    "mismatch in window id {:?} != {:?} for {:?}",
+                    window_id, wid, evnt
+                );
+                &mut no_op
+            }
+            Event::RedrawEventsCleared => &mut event_handlers.on_redraw_events_cleared,
+            Event::LoopDestroyed => &mut event_handlers.on_loop_destroyed,
+            Event::WindowEvent { window_id, event } if is_own(window_id) => match event {
+                WindowEvent::Resized(_) => &mut window_event_handlers.on_resized,
+                WindowEvent::Moved(_) => &mut window_event_handlers.on_moved,
+                WindowEvent::CloseRequested => {
+                    &mut window_event_handlers.on_close_requested
+                }
+                WindowEvent::Destroyed => &mut window_event_handlers.on_destroyed,
+                WindowEvent::DroppedFile(_) => &mut window_event_handlers.on_dropped_file,
+                WindowEvent::HoveredFile(_) => &mut window_event_handlers.on_hovered_file,
+                WindowEvent::HoveredFileCancelled => {
+                    &mut window_event_handlers.on_hovered_file_cancelled
+                }
+                WindowEvent::ReceivedCharacter(_) => {
+                    &mut window_event_handlers.on_received_character
+                }
+                WindowEvent::Focused(_) => &mut window_event_handlers.on_focused,
+                WindowEvent::KeyboardInput { .. } => {
+                    &mut window_event_handlers.on_keyboard_input
+                }
+                WindowEvent::ModifiersChanged(_) => {
+                    &mut window_event_handlers.on_modifiers_changed
+                }
+                WindowEvent::CursorMoved { .. } => {
+                    &mut window_event_handlers.on_cursor_moved
+                }
+                WindowEvent::CursorEntered { .. } => {
+                    &mut window_event_handlers.on_cursor_entered
+                }
+                WindowEvent::CursorLeft { .. } => {
+                    &mut window_event_handlers.on_cursor_left
+                }
+                WindowEvent::MouseWheel { .. } => {
+                    &mut window_event_handlers.on_mouse_wheel
+                }
+                WindowEvent::MouseInput { .. } => {
+                    &mut window_event_handlers.on_mouse_input
+                }
+                WindowEvent::TouchpadPressure { .. } => {
+                    &mut window_event_handlers.on_touchpad_pressure
+                }
+                WindowEvent::AxisMotion { .. } => {
+                    &mut window_event_handlers.on_axis_motion
+                }
+                WindowEvent::Touch(_) => &mut window_event_handlers.on_touch,
+                WindowEvent::ScaleFactorChanged { .. } => {
+                    &mut window_event_handlers.on_scale_factor_changed
+                }
+                WindowEvent::ThemeChanged(_) => {
+                    &mut window_event_handlers.on_theme_changed
                 }
             },
-        );
+            Event::WindowEvent { window_id, .. } => {
+                warn!(
+                    "mismatch in window id {:?} != {:?} for {:?}",
+                    window_id, wid, evnt
+                );
+                &mut no_op
+            }
+            Event::DeviceEvent { event, .. } => match event {
+                DeviceEvent::Added => &mut device_event_handlers.on_added,
+                DeviceEvent::Removed => &mut device_event_handlers.on_removed,
+                DeviceEvent::MouseMotion { .. } => {
+                    &mut device_event_handlers.on_mouse_motion
+                }
+                DeviceEvent::MouseWheel { .. } => {
+                    &mut device_event_handlers.on_mouse_wheel
+                }
+                DeviceEvent::Motion { .. } => &mut device_event_handlers.on_motion,
+                DeviceEvent::Button { .. } => &mut device_event_handlers.on_button,
+                DeviceEvent::Key(_) => &mut device_event_handlers.on_key,
+                DeviceEvent::Text { .. } => &mut device_event_handlers.on_text,
+            },
+        };
+
+        let mut cf = handler(window, state, evnt);
+
+        let action = match &evnt {
+            Event::WindowEvent { window_id, .. } if is_own(window_id) => {
+                let modifiers = self.input.read().to_modifiers();
+                self.bindings.read().to_event_action(evnt, modifiers)
+            }
+            _ => None,
+        };
+        if let Some(action) = action {
+            let action_handlers = self.action_handlers.as_mut().unwrap();
+            if let Some(handler) = action_handlers.get_mut(&action) {
+                if let Some(val) = handler(window, state, evnt) {
+                    cf = Some(val)
+                }
+            }
+        }
+
+        if let Event::RedrawEventsCleared = &evnt {
+            self.input.write().end_frame()
+        }
+
+        cf
     }
 }
 
@@ -387,6 +762,25 @@ where
         self
     }
 
+    /// Subscribe handler for `action`, called with the event that triggered the
+    /// action, refer [SingleWindow::set_bindings].
+    pub fn on_action(&mut self, action: &str, handler: Handler<S, E>) -> &mut Self {
+        debug!("subcribed to on_action {:?} ...", action);
+        self.action_handlers.as_mut().unwrap().insert(action.to_string(), handler);
+        self
+    }
+
+    /// Subscribe handler for timer `name`, called with the event that woke up
+    /// the event-loop when the timer expires, refer [SingleWindow::to_timers].
+    /// Control flow returned by the handler is applied to the event-loop, like
+    /// for any other handler. While replaying, timers expire as per a virtual
+    /// clock, refer [SingleWindow::replay].
+    pub fn on_timer(&mut self, name: &str, handler: Handler<S, E>) -> &mut Self {
+        debug!("subcribed to on_timer {:?} ...", name);
+        self.timer_handlers.as_mut().unwrap().insert(name.to_string(), handler);
+        self
+    }
+
     pub fn on_new_events(&mut self, handler: Handler<S, E>) -> &mut Self {
         debug!("subcribed to on_new_events ...");
         self.event_handlers.as_mut().unwrap().on_new_events = handler;
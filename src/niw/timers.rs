@@ -0,0 +1,145 @@
+use std::{collections::BTreeMap, time};
+
+use crate::{Error, Result};
+
+/// Redraw policy for [SingleWindow][crate::niw::SingleWindow].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RedrawMode {
+    /// Request a redraw after every [MainEventsCleared], and poll for events.
+    /// Suitable for animations and games.
+    ///
+    /// [MainEventsCleared]: winit::event::Event::MainEventsCleared
+    #[default]
+    Continuous,
+    /// Redraw only when the dirty flag is set, refer
+    /// [SingleWindow::to_dirty_flag][to_dirty_flag]. In between, the event-loop
+    /// waits for events or for the next timer deadline, refer [Timers].
+    ///
+    /// [to_dirty_flag]: crate::niw::SingleWindow::to_dirty_flag
+    OnDemand,
+}
+
+/// Type manages named one-shot and repeating timers.
+///
+/// [SingleWindow][crate::niw::SingleWindow] uses the earliest deadline to wake
+/// up the event-loop via [ControlFlow::WaitUntil], and calls handlers subscribed
+/// via [SingleWindow::on_timer][on_timer] when a timer expires. Setting a timer
+/// with an existing name replaces the older timer.
+///
+/// While replaying, refer [SingleWindow::replay][replay], timers follow a
+/// virtual clock that advances by a fixed frame time, so that deadlines set
+/// from handlers are deterministic.
+///
+/// [ControlFlow::WaitUntil]: winit::event_loop::ControlFlow::WaitUntil
+/// [on_timer]: crate::niw::SingleWindow::on_timer
+/// [replay]: crate::niw::SingleWindow::replay
+#[derive(Clone, Debug, Default)]
+pub struct Timers {
+    timers: BTreeMap<String, Timer>,
+    clock: Option<time::Instant>,
+}
+
+#[derive(Clone, Debug)]
+struct Timer {
+    deadline: time::Instant,
+    interval: Option<time::Duration>,
+}
+
+impl Timers {
+    /// Set a one-shot timer `name` that expires `after` duration from now.
+    pub fn set_timeout(&mut self, name: &str, after: time::Duration) -> Result<()> {
+        let deadline = match self.to_now().checked_add(after) {
+            Some(deadline) => deadline,
+            None => err_at!(Invalid, msg: "timeout {:?} for timer {:?}", after, name)?,
+        };
+        let timer = Timer { deadline, interval: None };
+        self.timers.insert(name.to_string(), timer);
+
+        Ok(())
+    }
+
+    /// Set a repeating timer `name` that expires `every` duration, starting
+    /// from now. Deadlines are computed from the previous deadline rather than
+    /// from the time it was handled, so that the timer does not drift.
+    pub fn set_interval(&mut self, name: &str, every: time::Duration) -> Result<()> {
+        if every.is_zero() {
+            err_at!(Invalid, msg: "zero interval for timer {:?}", name)?
+        }
+
+        let deadline = match self.to_now().checked_add(every) {
+            Some(deadline) => deadline,
+            None => err_at!(Invalid, msg: "interval {:?} for timer {:?}", every, name)?,
+        };
+        let timer = Timer { deadline, interval: Some(every) };
+        self.timers.insert(name.to_string(), timer);
+
+        Ok(())
+    }
+
+    /// Cancel timer `name`, return false if there is no such timer.
+    pub fn cancel(&mut self, name: &str) -> bool {
+        self.timers.remove(name).is_some()
+    }
+
+    pub fn is_pending(&self, name: &str) -> bool {
+        self.timers.contains_key(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    /// Return the current instant, which is the virtual clock while
+    /// replaying, else [time::Instant::now].
+    pub fn to_now(&self) -> time::Instant {
+        self.clock.unwrap_or_else(time::Instant::now)
+    }
+
+    /// Set the virtual clock, None switches back to the system clock.
+    pub(crate) fn set_clock(&mut self, clock: Option<time::Instant>) {
+        self.clock = clock
+    }
+
+    /// Return the earliest deadline among all the timers.
+    pub fn to_deadline(&self) -> Option<time::Instant> {
+        self.timers.values().map(|t| t.deadline).min()
+    }
+
+    /// Return names of timers that expired at `now`, in the order of their
+    /// deadlines. One-shot timers are removed and repeating timers are
+    /// re-armed, missed ticks of a repeating timer are skipped. Repeating timers
+    /// whose next deadline is not representable are removed.
+    pub fn expire(&mut self, now: time::Instant) -> Vec<String> {
+        let mut expired: Vec<(time::Instant, String)> = vec![];
+
+        for (name, timer) in self.timers.iter_mut() {
+            if timer.deadline > now {
+                continue;
+            }
+            expired.push((timer.deadline, name.clone()));
+
+            if let Some(every) = timer.interval {
+                // next tick after `now`, in phase with the previous deadline.
+                let late = now.duration_since(timer.deadline).as_nanos();
+                let rem = late % every.as_nanos();
+                let rem = time::Duration::new(
+                    (rem / 1_000_000_000) as u64,
+                    (rem % 1_000_000_000) as u32,
+                );
+                match now.checked_add(every - rem) {
+                    Some(deadline) => timer.deadline = deadline,
+                    None => timer.interval = None,
+                }
+            }
+        }
+
+        self.timers.retain(|_, t| t.interval.is_some() || t.deadline > now);
+
+        expired.sort();
+        expired.into_iter().map(|(_, name)| name).collect()
+    }
+}
+
+#[cfg(test)]
+#[path = "timers_test.rs"]
+mod timers_test;
@@ -0,0 +1,58 @@
+use super::*;
+
+#[test]
+fn test_timers_timeout() {
+    let mut timers = Timers::default();
+    assert_eq!(timers.to_deadline(), None);
+
+    let start = time::Instant::now();
+    timers.set_timeout("b", time::Duration::from_millis(20)).unwrap();
+    timers.set_timeout("a", time::Duration::from_millis(10)).unwrap();
+    timers.set_timeout("c", time::Duration::from_secs(10)).unwrap();
+    assert!(timers.to_deadline().unwrap() >= start + time::Duration::from_millis(10));
+    assert!(timers.expire(start).is_empty());
+
+    let now = start + time::Duration::from_secs(1);
+    assert_eq!(timers.expire(now), vec!["a".to_string(), "b".to_string()]);
+    assert!(!timers.is_pending("a"));
+    assert!(timers.is_pending("c"));
+
+    assert!(timers.cancel("c"));
+    assert!(!timers.cancel("c"));
+    assert!(timers.is_empty());
+
+    assert!(timers.set_timeout("never", time::Duration::MAX).is_err());
+    assert!(!timers.is_pending("never"));
+}
+
+#[test]
+fn test_timers_interval() {
+    let mut timers = Timers::default();
+    let every = time::Duration::from_millis(10);
+    assert!(timers.set_interval("tick", time::Duration::ZERO).is_err());
+
+    timers.set_interval("tick", every).unwrap();
+    let first = timers.to_deadline().unwrap();
+
+    // handled late, next deadline stays on the grid.
+    let now = first + time::Duration::from_millis(3);
+    assert_eq!(timers.expire(now), vec!["tick".to_string()]);
+    assert_eq!(timers.to_deadline(), Some(first + every));
+
+    // missed ticks are skipped.
+    let now = first + time::Duration::from_millis(45);
+    assert_eq!(timers.expire(now), vec!["tick".to_string()]);
+    assert_eq!(timers.to_deadline(), Some(first + every * 5));
+    assert!(timers.is_pending("tick"));
+
+    // ticks missed beyond u32::MAX.
+    let every = time::Duration::from_nanos(1);
+    timers.set_interval("fast", every).unwrap();
+    let first = timers.timers["fast"].deadline;
+    let now = first + time::Duration::from_secs(10);
+    assert_eq!(timers.expire(now), vec!["fast".to_string(), "tick".to_string()]);
+    assert_eq!(timers.timers["fast"].deadline, now + every);
+
+    assert!(timers.set_interval("never", time::Duration::MAX).is_err());
+    assert!(!timers.is_pending("never"));
+}
@@ -0,0 +1,334 @@
+use prettytable::{cell, row};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use std::{collections::BTreeMap, path};
+
+use crate::{
+    pretty::{
+        add_adapter_to_features, add_adapter_to_limits, features, limits,
+        texture_format_flags, texture_formats_info, texture_usages,
+    },
+    util::{self, JsonRow, PrettyRow},
+    Error, Result,
+};
+
+/// Capabilities of an adapter, features and texture-formats are listed by their
+/// names as in [features] and [texture_formats_info].
+///
+/// Capabilities of a texture-format is the list of its allowed
+/// [wgpu::TextureUsages] and [wgpu::TextureFormatFeatureFlags], named as in
+/// [texture_usages] and [texture_format_flags], along with `FILTERABLE` if the
+/// format is filterable.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AdapterReport {
+    pub name: String,
+    pub backend: String,
+    pub device_type: String,
+    pub features: Vec<String>,
+    pub limits: BTreeMap<String, u32>,
+    pub formats: BTreeMap<String, Vec<String>>,
+}
+
+impl<'a> From<&'a wgpu::Adapter> for AdapterReport {
+    fn from(adapter: &'a wgpu::Adapter) -> AdapterReport {
+        let info = adapter.get_info();
+
+        let mut fs = features();
+        add_adapter_to_features(&mut fs, adapter.features());
+        let mut ls = limits();
+        add_adapter_to_limits(&mut ls, adapter.limits());
+
+        let formats = texture_formats_info()
+            .into_iter()
+            .map(|tf| {
+                let tff = adapter.get_texture_format_features(tf.value);
+                (tf.name, texture_format_capabilities(&tff))
+            })
+            .collect();
+
+        AdapterReport {
+            name: info.name,
+            backend: util::wgpu_backend_to_string(info.backend),
+            device_type: format!("{:?}", info.device_type),
+            features: fs.into_iter().filter(|f| f.adapters[0]).map(|f| f.name).collect(),
+            limits: ls.into_iter().map(|l| (l.name, l.adapters[0])).collect(),
+            formats,
+        }
+    }
+}
+
+impl AdapterReport {
+    /// Return differences in capabilities from `self` to `other`.
+    pub fn diff(&self, other: &AdapterReport) -> Vec<Difference> {
+        let mut diffs = vec![];
+
+        for f in features().into_iter().map(|f| f.name) {
+            let (a, b) = (self.features.contains(&f), other.features.contains(&f));
+            if a != b {
+                diffs.push(Difference::new("feature", &f, a, b));
+            }
+        }
+        for l in limits().into_iter().map(|l| l.name) {
+            let (a, b) = (self.limits.get(&l), other.limits.get(&l));
+            if a != b {
+                diffs.push(Difference::new("limit", &l, opt(a), opt(b)));
+            }
+        }
+        for tf in texture_formats_info().into_iter().map(|tf| tf.name) {
+            let (a, b) = (self.formats.get(&tf), other.formats.get(&tf));
+            if a != b {
+                diffs.push(Difference::new("format", &tf, caps(a), caps(b)));
+            }
+        }
+
+        diffs
+    }
+}
+
+/// Minimum capabilities required from an adapter, loaded from toml file:
+///
+/// ```toml
+/// features = ["timestamp_query", "texture_compression_bc"]
+///
+/// [limits]
+/// max_bind_groups = 4
+/// min_uniform_buffer_offset_alignment = 256
+///
+/// [formats]
+/// Rgba8Unorm = ["STORAGE_BINDING", "RENDER_ATTACHMENT"]
+/// R32Uint = ["STORAGE_ATOMICS"]
+/// ```
+///
+/// Adapter limits shall be greater than or equal to the required limits,
+/// except for `min_*` limits, which shall be less than or equal.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(default)]
+    pub limits: BTreeMap<String, u32>,
+    #[serde(default)]
+    pub formats: BTreeMap<String, Vec<String>>,
+}
+
+impl Profile {
+    /// Load profile from toml file, refer [Profile] for its format.
+    pub fn from_file<P>(loc: P) -> Result<Profile>
+    where
+        P: AsRef<path::Path>,
+    {
+        let profile: Profile = util::load_toml(loc)?;
+        profile.validate()?;
+        Ok(profile)
+    }
+
+    /// Fail if profile names an unknown feature, limit, texture-format or
+    /// texture-format capability.
+    pub fn validate(&self) -> Result<()> {
+        let fs: Vec<String> = features().into_iter().map(|f| f.name).collect();
+        let ls: Vec<String> = limits().into_iter().map(|l| l.name).collect();
+        let tfs: Vec<String> =
+            texture_formats_info().into_iter().map(|tf| tf.name).collect();
+        let tcs = texture_format_capability_names();
+
+        for f in self.features.iter().filter(|f| !fs.contains(f)) {
+            err_at!(Invalid, msg: "unknown feature {:?}, valid {:?}", f, fs)?
+        }
+        for l in self.limits.keys().filter(|l| !ls.contains(l)) {
+            err_at!(Invalid, msg: "unknown limit {:?}, valid {:?}", l, ls)?
+        }
+        for (tf, cs) in self.formats.iter() {
+            if !tfs.contains(tf) {
+                err_at!(Invalid, msg: "unknown texture format {:?}", tf)?
+            }
+            for c in cs.iter().filter(|c| !tcs.contains(&c.as_str())) {
+                err_at!(Invalid, msg: "unknown {:?} for {:?}, valid {:?}", c, tf, tcs)?
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Check `report` against this profile, return the list of failures. Adapter
+    /// passes the profile if returned list is empty.
+    pub fn check(&self, report: &AdapterReport) -> Vec<Failure> {
+        let mut failures = vec![];
+
+        for f in self.features.iter() {
+            if !report.features.contains(f) {
+                failures.push(Failure::new("feature", f, "enabled", "-"))
+            }
+        }
+        for (l, required) in self.limits.iter() {
+            let ok = match report.limits.get(l) {
+                Some(found) if l.starts_with("min_") => found <= required,
+                Some(found) => found >= required,
+                None => false,
+            };
+            if !ok {
+                let required = match l.starts_with("min_") {
+                    true => format!("<= {}", required),
+                    false => format!(">= {}", required),
+                };
+                let found = opt(report.limits.get(l));
+                failures.push(Failure::new("limit", l, required, found))
+            }
+        }
+        for (tf, required) in self.formats.iter() {
+            let found = report.formats.get(tf);
+            let ok = match found {
+                Some(found) => required.iter().all(|c| found.contains(c)),
+                None => false,
+            };
+            if !ok {
+                failures.push(Failure::new("format", tf, required.join(","), caps(found)))
+            }
+        }
+
+        failures
+    }
+}
+
+/// Capability required by [Profile] and not available with the adapter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Failure {
+    pub kind: String,
+    pub name: String,
+    pub required: String,
+    pub found: String,
+}
+
+impl Failure {
+    fn new<R, F>(kind: &str, name: &str, required: R, found: F) -> Failure
+    where
+        R: ToString,
+        F: ToString,
+    {
+        Failure {
+            kind: kind.to_string(),
+            name: name.to_string(),
+            required: required.to_string(),
+            found: found.to_string(),
+        }
+    }
+}
+
+impl PrettyRow for Failure {
+    fn to_format() -> prettytable::format::TableFormat {
+        *prettytable::format::consts::FORMAT_CLEAN
+    }
+
+    fn to_head() -> prettytable::Row {
+        row![Fy => "Kind", "Name", "Required", "Found"]
+    }
+
+    fn to_row(&self) -> prettytable::Row {
+        row![self.kind, self.name, self.required, self.found]
+    }
+}
+
+impl JsonRow for Failure {
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "kind": self.kind,
+            "name": self.name,
+            "required": self.required,
+            "found": self.found,
+        })
+    }
+}
+
+/// Capability that differs between two [AdapterReport].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Difference {
+    pub kind: String,
+    pub name: String,
+    pub old: String,
+    pub new: String,
+}
+
+impl Difference {
+    fn new<O, N>(kind: &str, name: &str, old: O, new: N) -> Difference
+    where
+        O: ToString,
+        N: ToString,
+    {
+        Difference {
+            kind: kind.to_string(),
+            name: name.to_string(),
+            old: old.to_string(),
+            new: new.to_string(),
+        }
+    }
+}
+
+impl PrettyRow for Difference {
+    fn to_format() -> prettytable::format::TableFormat {
+        *prettytable::format::consts::FORMAT_CLEAN
+    }
+
+    fn to_head() -> prettytable::Row {
+        row![Fy => "Kind", "Name", "Old", "New"]
+    }
+
+    fn to_row(&self) -> prettytable::Row {
+        row![self.kind, self.name, self.old, self.new]
+    }
+}
+
+impl JsonRow for Difference {
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "kind": self.kind,
+            "name": self.name,
+            "old": self.old,
+            "new": self.new,
+        })
+    }
+}
+
+/// Return names of texture-format capabilities, refer [AdapterReport].
+pub fn texture_format_capability_names() -> Vec<&'static str> {
+    let mut names: Vec<&str> = texture_usages().into_iter().map(|u| u.2).collect();
+    names.extend(texture_format_flags().into_iter().map(|f| f.2));
+    names.push("FILTERABLE");
+    names
+}
+
+fn texture_format_capabilities(tff: &wgpu::TextureFormatFeatures) -> Vec<String> {
+    let mut caps: Vec<String> = texture_usages()
+        .into_iter()
+        .filter(|(u, _, _)| tff.allowed_usages.contains(*u))
+        .map(|(_, _, name)| name.to_string())
+        .collect();
+    caps.extend(
+        texture_format_flags()
+            .into_iter()
+            .filter(|(f, _, _)| tff.flags.contains(*f))
+            .map(|(_, _, name)| name.to_string()),
+    );
+    if tff.filterable {
+        caps.push("FILTERABLE".to_string())
+    }
+    caps
+}
+
+fn opt(val: Option<&u32>) -> String {
+    match val {
+        Some(val) => val.to_string(),
+        None => "-".to_string(),
+    }
+}
+
+fn caps(val: Option<&Vec<String>>) -> String {
+    match val {
+        Some(val) => val.join(","),
+        None => "-".to_string(),
+    }
+}
+
+#[cfg(test)]
+#[path = "capability_test.rs"]
+mod capability_test;
@@ -0,0 +1,104 @@
+use super::*;
+
+fn report() -> AdapterReport {
+    AdapterReport {
+        name: "gpu".to_string(),
+        backend: "vulkan".to_string(),
+        device_type: "DiscreteGpu".to_string(),
+        features: vec!["timestamp_query".to_string()],
+        limits: limits().into_iter().map(|l| (l.name, 256)).collect(),
+        formats: vec![(
+            "Rgba8Unorm".to_string(),
+            vec!["COPY_DST".to_string(), "STORAGE_BINDING".to_string()],
+        )]
+        .into_iter()
+        .collect(),
+    }
+}
+
+#[test]
+fn test_profile_check() {
+    let profile: Profile = toml::from_str(
+        r#"
+        features = ["timestamp_query"]
+
+        [limits]
+        max_bind_groups = 4
+        min_uniform_buffer_offset_alignment = 256
+
+        [formats]
+        Rgba8Unorm = ["STORAGE_BINDING"]
+        "#,
+    )
+    .unwrap();
+    profile.validate().unwrap();
+    assert_eq!(profile.check(&report()), vec![]);
+
+    let profile: Profile = toml::from_str(
+        r#"
+        features = ["timestamp_query", "texture_compression_bc"]
+
+        [limits]
+        max_bind_groups = 512
+        min_uniform_buffer_offset_alignment = 64
+
+        [formats]
+        Rgba8Unorm = ["STORAGE_BINDING", "STORAGE_ATOMICS"]
+        R32Uint = ["STORAGE_ATOMICS"]
+        "#,
+    )
+    .unwrap();
+    profile.validate().unwrap();
+    let failures = profile.check(&report());
+    let names: Vec<&str> = failures.iter().map(|f| f.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "texture_compression_bc",
+            "max_bind_groups",
+            "min_uniform_buffer_offset_alignment",
+            "R32Uint",
+            "Rgba8Unorm",
+        ]
+    );
+    assert_eq!(failures[1].required, ">= 512");
+    assert_eq!(failures[2].required, "<= 64");
+    assert_eq!(failures[2].found, "256");
+    assert_eq!(failures[3].found, "-");
+}
+
+#[test]
+fn test_profile_validate() {
+    let profile: Profile = toml::from_str(r#"features = ["warp_drive"]"#).unwrap();
+    assert!(profile.validate().is_err());
+
+    let profile: Profile = toml::from_str("[limits]\nmax_gizmos = 1").unwrap();
+    assert!(profile.validate().is_err());
+
+    let profile: Profile = toml::from_str("[formats]\nRgba8Unorm = [\"FAST\"]").unwrap();
+    assert!(profile.validate().is_err());
+
+    assert!(toml::from_str::<Profile>("adapters = 1").is_err());
+}
+
+#[test]
+fn test_adapter_report_diff() {
+    let a = report();
+    assert_eq!(a.diff(&a), vec![]);
+
+    let mut b = report();
+    b.features.clear();
+    b.limits.insert("max_bind_groups".to_string(), 8);
+    b.formats.clear();
+
+    let diffs = a.diff(&b);
+    assert_eq!(diffs.len(), 3);
+    assert_eq!(diffs[0], Difference::new("feature", "timestamp_query", true, false));
+    assert_eq!(diffs[1], Difference::new("limit", "max_bind_groups", 256, 8));
+    assert_eq!(diffs[2].old, "COPY_DST,STORAGE_BINDING");
+    assert_eq!(diffs[2].new, "-");
+
+    let text = serde_json::to_string(&a).unwrap();
+    let c: AdapterReport = serde_json::from_str(&text).unwrap();
+    assert_eq!(a, c);
+}
@@ -2,16 +2,17 @@
 
 use colored::Colorize;
 use prettytable::{cell, row};
+use serde_json::json;
 
-use crate::util::{format_bool, PrettyRow};
+use crate::util::{format_bool, JsonRow, PrettyRow};
 
 pub struct Feature {
-    name: String,
+    pub(crate) name: String,
     value: wgpu::Features,
     web: bool,
     native: bool,
     backends: Vec<wgpu::Backend>,
-    adapters: Vec<bool>,
+    pub(crate) adapters: Vec<bool>,
 }
 
 impl PrettyRow for Feature {
@@ -48,6 +49,20 @@ impl PrettyRow for Feature {
     }
 }
 
+impl JsonRow for Feature {
+    fn to_json(&self) -> serde_json::Value {
+        let backends: Vec<String> =
+            self.backends.iter().map(|b| format!("{:?}", b)).collect();
+        json!({
+            "name": self.name,
+            "web": self.web,
+            "native": self.native,
+            "backends": backends,
+            "adapters": self.adapters,
+        })
+    }
+}
+
 pub fn features() -> Vec<Feature> {
     use wgpu::Backend::{BrowserWebGpu, Dx11, Dx12, Gl, Metal, Vulkan};
 
@@ -314,3 +329,9 @@ pub fn add_adapter_to_features(
         f.adapters.push(adapter_features.contains(f.value))
     }
 }
+
+/// Return true if the feature `name`, as listed by [features], is enabled in
+/// `enabled`. Return false for unknown names.
+pub fn is_feature_enabled(enabled: wgpu::Features, name: &str) -> bool {
+    features().into_iter().any(|f| f.name == name && enabled.contains(f.value))
+}
@@ -1,13 +1,14 @@
 // TODO: keep this module update with wgpu implementation and web-gpu spec.
 
 use prettytable::{cell, row};
+use serde_json::json;
 
-use crate::util::PrettyRow;
+use crate::util::{JsonRow, PrettyRow};
 
 pub struct Limit {
-    name: String,
+    pub(crate) name: String,
     default: u32,
-    adapters: Vec<u32>,
+    pub(crate) adapters: Vec<u32>,
 }
 
 impl PrettyRow for Limit {
@@ -26,6 +27,16 @@ impl PrettyRow for Limit {
     }
 }
 
+impl JsonRow for Limit {
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "name": self.name,
+            "default": self.default,
+            "adapters": self.adapters,
+        })
+    }
+}
+
 pub fn limits() -> Vec<Limit> {
     let defaults = wgpu::Limits::default();
 
@@ -1,18 +1,23 @@
 //! Package implement pretty printing for [wgpu] features, limits, formats, textures etc.
 
+mod capability;
 mod features;
 mod limits;
 mod texture_formats;
 
-pub use features::{add_adapter_to_features, features, Feature};
+pub use capability::{
+    texture_format_capability_names, AdapterReport, Difference, Failure, Profile,
+};
+pub use features::{add_adapter_to_features, features, is_feature_enabled, Feature};
 pub use limits::{add_adapter_to_limits, limits, Limit};
 pub use texture_formats::{
     texture_format_flags, texture_formats_info, texture_usages, TextureFormatInfo,
 };
 
 use prettytable::{cell, row};
+use serde_json::json;
 
-use crate::util::PrettyRow;
+use crate::util::{JsonRow, PrettyRow};
 
 #[derive(Clone)]
 pub struct StorageReport {
@@ -46,6 +51,18 @@ impl PrettyRow for StorageReport {
     }
 }
 
+impl JsonRow for StorageReport {
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "name": self.name,
+            "num_occupied": self.report.num_occupied,
+            "num_vacant": self.report.num_vacant,
+            "num_error": self.report.num_error,
+            "element_size": self.report.element_size,
+        })
+    }
+}
+
 impl PrettyRow for wgpu::AdapterInfo {
     fn to_format() -> prettytable::format::TableFormat {
         *prettytable::format::consts::FORMAT_CLEAN
@@ -65,3 +82,15 @@ impl PrettyRow for wgpu::AdapterInfo {
         ]
     }
 }
+
+impl JsonRow for wgpu::AdapterInfo {
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "name": self.name,
+            "vendor": self.vendor,
+            "device": self.device,
+            "device_type": format!("{:?}", self.device_type),
+            "backend": format!("{:?}", self.backend),
+        })
+    }
+}
@@ -1,12 +1,12 @@
 use colored::Colorize;
 use prettytable::{cell, row};
+use serde_json::json;
 
-use crate::util::{format_bool, PrettyRow};
+use crate::util::{format_bool, JsonRow, PrettyRow};
 
 pub struct TextureFormatInfo {
-    name: String,
-    #[allow(dead_code)]
-    value: wgpu::TextureFormat,
+    pub(crate) name: String,
+    pub(crate) value: wgpu::TextureFormat,
     info: wgpu_types::TextureFormatInfo,
 }
 
@@ -65,6 +65,34 @@ impl PrettyRow for TextureFormatInfo {
     }
 }
 
+impl JsonRow for TextureFormatInfo {
+    fn to_json(&self) -> serde_json::Value {
+        let gff = &self.info.guaranteed_format_features;
+        let usages: Vec<&str> = texture_usages()
+            .into_iter()
+            .filter_map(|(u, _, s)| gff.allowed_usages.contains(u).then_some(s))
+            .collect();
+        let flags: Vec<&str> = texture_format_flags()
+            .into_iter()
+            .filter_map(|(u, _, s)| gff.flags.contains(u).then_some(s))
+            .collect();
+        let (w, h) = self.info.block_dimensions;
+
+        json!({
+            "name": self.name,
+            "components": self.info.components,
+            "block_size": self.info.block_size,
+            "block_dimensions": [w, h],
+            "srgb": self.info.srgb,
+            "sample_type": format!("{:?}", self.info.sample_type),
+            "required_features": format!("{:?}", self.info.required_features),
+            "usages": usages,
+            "flags": flags,
+            "filterable": gff.filterable,
+        })
+    }
+}
+
 pub fn texture_usages() -> Vec<(wgpu_types::TextureUsages, &'static str, &'static str)> {
     vec![
         (wgpu_types::TextureUsages::COPY_DST, "D", "COPY_DST"),
@@ -0,0 +1,608 @@
+use bytemuck::{Pod, Zeroable};
+use cgmath::Point2;
+use log::error;
+
+use std::{cmp, ops, sync::Arc};
+
+use crate::{
+    shader::{catch_validation, Shader, ShaderReload},
+    Blend, BoxVertex, ColorTarget, Context, Extent, PipelineTarget, Result, Transforms,
+};
+
+/// Instanced renderer for circles and rectangle sprites.
+///
+/// All shapes in a batch share one pipeline, one vertex buffer for the unit quad
+/// and one instance buffer carrying per-shape [Attributes]. The entire batch is
+/// drawn with a single draw call. Only the instances modified since the last
+/// redraw are uploaded to the GPU.
+///
+/// Sprites are solid-fill only, texture and texture-coordinates per instance
+/// are not supported.
+pub struct Batch {
+    scale_factor: f32, // default is crate::SCALE_FACTOR
+    extent: Extent,
+    instances: Instances,
+    // wgpu items
+    target: PipelineTarget,
+    pipeline: Arc<wgpu::RenderPipeline>,
+    bind_group: wgpu::BindGroup,
+    transform_buffer: wgpu::Buffer,
+    screen_buffer: wgpu::Buffer,
+    vertex_buffer: wgpu::Buffer,
+    instance_buffer: wgpu::Buffer,
+    capacity: usize,
+    // development mode, refer hot_reload().
+    reload: Option<ShaderReload>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Kind {
+    Circle,
+    /// Axis aligned square sprite, colored the same way as circles.
+    Rect,
+}
+
+// Shapes in a batch, along with the range of shapes modified since the last
+// upload.
+#[derive(Default)]
+struct Instances {
+    list: Vec<Attributes>,
+    dirty: Option<ops::Range<usize>>,
+}
+
+/// measurements are in pixels.
+#[derive(Copy, Clone, Debug)]
+pub struct Attributes {
+    pub kind: Kind,
+    pub center: Point2<f32>, // center position in screen-coordinates
+    pub radius: f32,         // in pixels, half-width for Kind::Rect
+    pub width: f32,          // in pixels
+    pub fill: bool,
+    pub fg: wgpu::Color,
+    pub bg: wgpu::Color,
+}
+
+impl Default for Attributes {
+    fn default() -> Attributes {
+        Attributes {
+            kind: Kind::Circle,
+            center: (0.0, 0.0).into(),
+            radius: 1.0,
+            width: 1.0,
+            fill: false,
+            fg: wgpu::Color::WHITE,
+            bg: wgpu::Color::BLACK,
+        }
+    }
+}
+
+impl Attributes {
+    fn to_instance(self, scale_factor: f32) -> Instance {
+        use crate::to_rgba8unorm_color;
+
+        Instance {
+            center: (self.center * scale_factor).into(),
+            radius: self.radius * scale_factor,
+            width: self.width * scale_factor,
+            fg: to_rgba8unorm_color(self.fg),
+            bg: to_rgba8unorm_color(self.bg),
+            fill: if self.fill { 1 } else { 0 },
+            kind: match self.kind {
+                Kind::Circle => 0,
+                Kind::Rect => 1,
+            },
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Default, Copy, Clone, Debug, Pod, Zeroable)]
+struct Instance {
+    center: [f32; 2],
+    radius: f32,
+    width: f32,
+    fg: [f32; 4],
+    bg: [f32; 4],
+    fill: u32,
+    kind: u32,
+}
+
+#[repr(C)]
+#[derive(Default, Copy, Clone, Debug, Pod, Zeroable)]
+struct UniformBuffer {
+    size: [f32; 2],
+    _padding: [f32; 2],
+}
+
+impl UniformBuffer {
+    const SIZE: usize = 4 * 2 + 4 * 2;
+}
+
+impl Batch {
+    const MIN_CAPACITY: usize = 64;
+
+    /// Create a batch renderer. Circles and sprites have anti-aliased edges,
+    /// hence batch content is always alpha blended, blend mode in `target` is
+    /// ignored. Shader module, layout and pipeline are fetched from
+    /// `context.cache`.
+    ///
+    /// Panics if batch.wgsl, which is baked into the binary, doesn't match
+    /// with the rust buffer layouts.
+    pub fn new<T>(context: &Context, target: T) -> Batch
+    where
+        T: Into<PipelineTarget>,
+    {
+        let device = context.device;
+        let target = PipelineTarget { blend: Blend::Alpha, ..target.into() };
+
+        let (pipeline, bind_group_layout) = match Self::to_cached(context, &target) {
+            Ok(val) => val,
+            Err(err) => panic!("primv/batch: {}", err),
+        };
+
+        let transform_buffer = Self::to_transform_buffer(device);
+        let screen_buffer = Self::to_screen_buffer(device);
+
+        let bind_group = Self::to_bind_group(
+            device,
+            &bind_group_layout,
+            &transform_buffer,
+            &screen_buffer,
+        );
+
+        let capacity = Self::MIN_CAPACITY;
+        Batch {
+            scale_factor: crate::DEFAULT_SCALE_FACTOR,
+            extent: Extent::default(),
+            instances: Instances::default(),
+            // wgpu items
+            target,
+            pipeline,
+            bind_group,
+            transform_buffer,
+            screen_buffer,
+            vertex_buffer: Self::to_vertex_buffer(device),
+            instance_buffer: Self::to_instance_buffer(device, capacity),
+            capacity,
+            reload: None,
+        }
+    }
+
+    /// Development mode, reload batch.wgsl from the source tree when it
+    /// changes, and rebuild the pipeline before the next redraw.
+    pub fn hot_reload(&mut self) -> &mut Self {
+        let loc = concat!(env!("CARGO_MANIFEST_DIR"), "/src/primv/batch/batch.wgsl");
+        self.reload = Some(ShaderReload::new("primv/batch", loc));
+        self
+    }
+
+    pub fn print(&self, prefix: &str) {
+        println!("{}primv::Batch({} instances)", prefix, self.len());
+    }
+}
+
+impl Batch {
+    /// Append a new shape to the batch, return its index.
+    pub fn push(&mut self, attrs: Attributes) -> usize {
+        self.instances.push(attrs)
+    }
+
+    /// Append shapes from `iter` to the batch.
+    pub fn extend<I>(&mut self, iter: I) -> &mut Self
+    where
+        I: IntoIterator<Item = Attributes>,
+    {
+        self.instances.extend(iter);
+        self
+    }
+
+    /// Replace the shape at `index`, only that instance is uploaded on redraw.
+    pub fn set(&mut self, index: usize, attrs: Attributes) -> &mut Self {
+        self.instances.set(index, attrs);
+        self
+    }
+
+    /// Return a mutable slice of shapes within `range`, all of them are uploaded on
+    /// the next redraw.
+    pub fn as_mut_range(&mut self, range: ops::Range<usize>) -> &mut [Attributes] {
+        self.instances.as_mut_range(range)
+    }
+
+    /// Remove the shape at `index`, the last shape in the batch takes its place.
+    pub fn swap_remove(&mut self, index: usize) -> Attributes {
+        self.instances.swap_remove(index)
+    }
+
+    pub fn clear(&mut self) -> &mut Self {
+        self.instances.clear();
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.instances.list.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instances.list.is_empty()
+    }
+
+    pub fn as_instances(&self) -> &[Attributes] {
+        &self.instances.list
+    }
+}
+
+impl Instances {
+    fn push(&mut self, attrs: Attributes) -> usize {
+        let index = self.list.len();
+        self.list.push(attrs);
+        self.mark_dirty(index..(index + 1));
+        index
+    }
+
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = Attributes>,
+    {
+        let start = self.list.len();
+        self.list.extend(iter);
+        self.mark_dirty(start..self.list.len());
+    }
+
+    fn set(&mut self, index: usize, attrs: Attributes) {
+        self.list[index] = attrs;
+        self.mark_dirty(index..(index + 1));
+    }
+
+    fn as_mut_range(&mut self, range: ops::Range<usize>) -> &mut [Attributes] {
+        self.mark_dirty(range.clone());
+        &mut self.list[range]
+    }
+
+    fn swap_remove(&mut self, index: usize) -> Attributes {
+        let attrs = self.list.swap_remove(index);
+        if index < self.list.len() {
+            self.mark_dirty(index..(index + 1));
+        }
+        attrs
+    }
+
+    fn clear(&mut self) {
+        self.list.clear();
+        self.dirty = None;
+    }
+
+    fn mark_dirty(&mut self, range: ops::Range<usize>) {
+        self.dirty = match self.dirty.take() {
+            Some(r) => Some(cmp::min(r.start, range.start)..cmp::max(r.end, range.end)),
+            None => Some(range),
+        };
+    }
+
+    // Take the range of shapes to upload into an instance buffer of `capacity`,
+    // along with the new capacity when the batch has outgrown it, in which
+    // case all shapes are to be uploaded.
+    fn take_dirty(&mut self, capacity: usize) -> (ops::Range<usize>, Option<usize>) {
+        let n = self.list.len();
+        match self.dirty.take() {
+            _ if n > capacity => (0..n, Some(n.next_power_of_two())),
+            Some(range) => (range.start..cmp::min(range.end, n), None),
+            None => (0..0, None),
+        }
+    }
+}
+
+impl Batch {
+    /// `extent` is the size of the color target in pixels, required to map
+    /// screen-coordinates into normalized-device-coordinates.
+    pub fn resize(&mut self, extent: Extent, scale_factor: Option<f32>) -> &mut Self {
+        self.extent = extent;
+        if let Some(scale_factor) = scale_factor {
+            self.scale_factor = scale_factor;
+            self.instances.mark_dirty(0..self.instances.list.len());
+        }
+        self
+    }
+
+    pub fn redraw(
+        &mut self,
+        context: &Context,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &mut ColorTarget,
+    ) -> Result<()> {
+        self.do_reload(context.device);
+
+        // overwrite the transform mvp buffer.
+        {
+            let content = context.transforms.to_bind_content();
+            context.queue.write_buffer(&self.transform_buffer, 0, &content);
+        }
+        // overwrite the screen-size uniform buffer.
+        {
+            let ub = UniformBuffer {
+                size: [self.extent.width, self.extent.height],
+                _padding: Default::default(),
+            };
+            let content: [u8; UniformBuffer::SIZE] = bytemuck::cast(ub);
+            context.queue.write_buffer(&self.screen_buffer, 0, &content);
+        }
+        self.upload_instances(context);
+
+        let mut render_pass = {
+            let desc = wgpu::RenderPassDescriptor {
+                label: Some("primv/batch:render-pass"),
+                color_attachments: &[target.to_color_attachment(wgpu::LoadOp::Load)],
+                depth_stencil_attachment: target.to_depth_attachment(false),
+            };
+            encoder.begin_render_pass(&desc)
+        };
+        if target.view_port.width > 0.0 && target.view_port.height > 0.0 {
+            target.view_port.set_viewport(&mut render_pass);
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.instance_buffer.slice(..));
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..6, 0..(self.instances.list.len() as u32));
+
+        Ok(())
+    }
+
+    // Upload dirty instances, growing the instance buffer when the batch has
+    // outgrown its capacity.
+    fn upload_instances(&mut self, context: &Context) {
+        use std::mem;
+
+        let (range, capacity) = self.instances.take_dirty(self.capacity);
+        if let Some(capacity) = capacity {
+            self.instance_buffer = Self::to_instance_buffer(context.device, capacity);
+            self.capacity = capacity;
+        }
+        if range.is_empty() {
+            return;
+        }
+
+        let instances: Vec<Instance> = self.instances.list[range.clone()]
+            .iter()
+            .map(|a| a.to_instance(self.scale_factor))
+            .collect();
+        let offset = (range.start * mem::size_of::<Instance>()) as wgpu::BufferAddress;
+        context.queue.write_buffer(
+            &self.instance_buffer,
+            offset,
+            bytemuck::cast_slice(&instances),
+        );
+    }
+}
+
+impl Batch {
+    fn do_reload(&mut self, device: &wgpu::Device) {
+        if let Some((text, shader)) = self.reload.as_mut().and_then(|r| r.poll()) {
+            let res = catch_validation(device, || {
+                let layout = shader.to_bind_group_layout(
+                    device,
+                    0,
+                    Some("primv/batch:bind-group-layout"),
+                )?;
+                let module = {
+                    let desc = wgpu::ShaderModuleDescriptor {
+                        label: Some("primv/batch"),
+                        source: wgpu::ShaderSource::Wgsl(text.into()),
+                    };
+                    device.create_shader_module(&desc)
+                };
+                let pipeline =
+                    Self::to_pipeline(&shader, &module, &layout, device, &self.target)?;
+                let bind_group = Self::to_bind_group(
+                    device,
+                    &layout,
+                    &self.transform_buffer,
+                    &self.screen_buffer,
+                );
+                Ok((pipeline, bind_group))
+            });
+            // on failure, continue with current pipeline and bind-group.
+            match res {
+                Ok((pipeline, bind_group)) => {
+                    self.pipeline = Arc::new(pipeline);
+                    self.bind_group = bind_group;
+                }
+                Err(err) => error!("primv/batch: {}", err),
+            }
+        }
+    }
+
+    // Fetch pipeline and bind-group layout for the baked batch.wgsl from
+    // cache, creating them on first use.
+    fn to_cached(
+        context: &Context,
+        target: &PipelineTarget,
+    ) -> Result<(Arc<wgpu::RenderPipeline>, Arc<wgpu::BindGroupLayout>)> {
+        let (device, cache) = (context.device, context.cache);
+
+        let permutation = cache.to_permutation(
+            device,
+            "primv/batch",
+            include_str!("batch.wgsl"),
+            &[],
+        )?;
+        let bind_group_layout = {
+            let entries = permutation.as_bind_group_layout_entries(0);
+            let label = Some("primv/batch:bind-group-layout");
+            cache.to_bind_group_layout(device, label, entries)?
+        };
+        let pipeline = cache.to_render_pipeline(
+            "primv/batch:pipeline",
+            &permutation,
+            target,
+            || {
+                let (shader, module) = (&permutation.shader, &permutation.module);
+                Self::to_pipeline(shader, module, &bind_group_layout, device, target)
+            },
+        )?;
+
+        Ok((pipeline, bind_group_layout))
+    }
+
+    // Create pipeline for batch.wgsl, after checking the shader against rust
+    // types. Location 0 is the unit quad, rest of them are per instance.
+    fn to_pipeline(
+        shader: &Shader,
+        module: &wgpu::ShaderModule,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        device: &wgpu::Device,
+        target: &PipelineTarget,
+    ) -> Result<wgpu::RenderPipeline> {
+        use std::mem::offset_of;
+
+        Transforms::check_layout(shader)?;
+        let vertex_layout = shader.to_vertex_layout_at("vs_main", 0..1)?;
+        vertex_layout.check::<BoxVertex>(&[(0, offset_of!(BoxVertex, position))])?;
+        let instance_layout = shader.to_vertex_layout_at("vs_main", 1..u32::MAX)?;
+        let offsets = [
+            (1, offset_of!(Instance, center)),
+            (2, offset_of!(Instance, radius)),
+            (3, offset_of!(Instance, width)),
+            (4, offset_of!(Instance, fg)),
+            (5, offset_of!(Instance, bg)),
+            (6, offset_of!(Instance, fill)),
+            (7, offset_of!(Instance, kind)),
+        ];
+        instance_layout.check::<Instance>(&offsets)?;
+
+        let pipeline_layout = {
+            let desc = wgpu::PipelineLayoutDescriptor {
+                label: Some("primv/batch:pipeline-layout"),
+                bind_group_layouts: &[bind_group_layout],
+                push_constant_ranges: &[],
+            };
+            device.create_pipeline_layout(&desc)
+        };
+
+        let vertex = wgpu::VertexState {
+            module,
+            entry_point: "vs_main",
+            buffers: &[
+                vertex_layout.to_vertex_buffer_layout(wgpu::VertexStepMode::Vertex),
+                instance_layout.to_vertex_buffer_layout(wgpu::VertexStepMode::Instance),
+            ],
+        };
+
+        let primitive_state = wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        };
+
+        let fragment = wgpu::FragmentState {
+            module,
+            entry_point: "fs_main",
+            targets: &[target.to_color_target_state()],
+        };
+
+        let pipeline = {
+            let desc = wgpu::RenderPipelineDescriptor {
+                label: Some("primv/batch:pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex,
+                primitive: primitive_state,
+                depth_stencil: target
+                    .to_depth_stencil_state(false, wgpu::CompareFunction::Always),
+                multisample: target.to_multisample_state(),
+                fragment: Some(fragment),
+                multiview: None,
+            };
+            device.create_render_pipeline(&desc)
+        };
+
+        Ok(pipeline)
+    }
+
+    fn to_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        transform_buffer: &wgpu::Buffer,
+        screen_buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        let desc = wgpu::BindGroupDescriptor {
+            label: Some("primv/batch:bind-group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: transform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: screen_buffer.as_entire_binding(),
+                },
+            ],
+        };
+        device.create_bind_group(&desc)
+    }
+
+    fn to_transform_buffer(device: &wgpu::Device) -> wgpu::Buffer {
+        use wgpu::{util::DeviceExt, BufferUsages};
+
+        let content = Transforms::empty().to_bind_content();
+        let desc = wgpu::util::BufferInitDescriptor {
+            label: Some("transform-buffer"),
+            contents: &content,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        };
+        device.create_buffer_init(&desc)
+    }
+
+    fn to_screen_buffer(device: &wgpu::Device) -> wgpu::Buffer {
+        use wgpu::{util::DeviceExt, BufferUsages};
+
+        let contents: [u8; UniformBuffer::SIZE] =
+            bytemuck::cast(UniformBuffer::default());
+        let desc = wgpu::util::BufferInitDescriptor {
+            label: Some("primv/batch:screen-buffer"),
+            contents: &contents,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        };
+        device.create_buffer_init(&desc)
+    }
+
+    fn to_vertex_buffer(device: &wgpu::Device) -> wgpu::Buffer {
+        use wgpu::{util::DeviceExt, BufferUsages};
+
+        let vertices = [
+            BoxVertex { position: [-1.0, 1.0, 0.0, 1.0] },
+            BoxVertex { position: [-1.0, -1.0, 0.0, 1.0] },
+            BoxVertex { position: [1.0, 1.0, 0.0, 1.0] },
+            BoxVertex { position: [1.0, 1.0, 0.0, 1.0] },
+            BoxVertex { position: [-1.0, -1.0, 0.0, 1.0] },
+            BoxVertex { position: [1.0, -1.0, 0.0, 1.0] },
+        ];
+        let contents: &[u8] = bytemuck::cast_slice(&vertices);
+        let desc = wgpu::util::BufferInitDescriptor {
+            label: Some("primv/batch:vertex-buffer"),
+            contents,
+            usage: BufferUsages::VERTEX,
+        };
+        device.create_buffer_init(&desc)
+    }
+
+    fn to_instance_buffer(device: &wgpu::Device, capacity: usize) -> wgpu::Buffer {
+        use std::mem;
+        use wgpu::BufferUsages;
+
+        let desc = wgpu::BufferDescriptor {
+            label: Some("primv/batch:instance-buffer"),
+            size: (capacity * mem::size_of::<Instance>()) as wgpu::BufferAddress,
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        };
+        device.create_buffer(&desc)
+    }
+}
+
+#[cfg(test)]
+#[path = "batch_test.rs"]
+mod batch_test;
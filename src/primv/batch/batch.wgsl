@@ -0,0 +1,79 @@
+#include "transforms.wgsl"
+#include "sdf.wgsl"
+
+struct Screen {
+    size: vec2<f32>;
+};
+
+struct VertexInput {
+    [[location(0)]] coord: vec4<f32>;
+};
+
+struct InstanceInput {
+    [[location(1)]] center: vec2<f32>;
+    [[location(2)]] radius: f32;
+    [[location(3)]] width: f32;
+    [[location(4)]] fg: vec4<f32>;
+    [[location(5)]] bg: vec4<f32>;
+    [[location(6)]] fill: u32;
+    [[location(7)]] kind: u32;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] local: vec2<f32>;
+    [[location(1)]] radius: f32;
+    [[location(2)]] width: f32;
+    [[location(3)]] fg: vec4<f32>;
+    [[location(4)]] bg: vec4<f32>;
+    [[location(5), interpolate(flat)]] fill: u32;
+    [[location(6), interpolate(flat)]] kind: u32;
+};
+
+[[binding(0), group(0)]] var<uniform> transforms: Transforms;
+[[binding(1), group(0)]] var<uniform> screen: Screen;
+
+[[stage(vertex)]]
+fn vs_main(in: VertexInput, inst: InstanceInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    // quad corner, in pixels, relative to the center of the instance.
+    let local = in.coord.xy * inst.radius;
+    let pixel = inst.center + vec2<f32>(local.x, -local.y);
+    let ndc = vec2<f32>(
+        (pixel.x / screen.size.x) * 2.0 - 1.0,
+        1.0 - (pixel.y / screen.size.y) * 2.0,
+    );
+
+    out.clip_position = transforms.mvp * vec4<f32>(ndc, 0.0, 1.0);
+    out.local = local;
+    out.radius = inst.radius;
+    out.width = inst.width;
+    out.fg = inst.fg;
+    out.bg = inst.bg;
+    out.fill = inst.fill;
+    out.kind = inst.kind;
+    return out;
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    var s: f32;
+    if (in.kind == u32(1)) {
+        // rectangle, distance is measured along the major axis.
+        s = sdf_square(in.local, 0.0);
+    } else {
+        s = sdf_circle(in.local, 0.0);
+    }
+
+    if (s > in.radius) {
+        discard;
+    }
+    if (s > (in.radius - in.width)) {
+        return in.fg;
+    }
+    if (in.fill == u32(1)) {
+        return in.fg;
+    }
+    return in.bg;
+}
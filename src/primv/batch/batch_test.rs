@@ -0,0 +1,60 @@
+use super::*;
+
+fn attrs(radius: f32) -> Attributes {
+    Attributes { radius, ..Attributes::default() }
+}
+
+#[test]
+fn test_instances_dirty() {
+    let mut instances = Instances::default();
+    assert_eq!(instances.take_dirty(64), (0..0, None));
+
+    assert_eq!(instances.push(attrs(1.0)), 0);
+    instances.extend([attrs(2.0), attrs(3.0), attrs(4.0)]);
+    assert_eq!(instances.take_dirty(64), (0..4, None));
+    assert_eq!(instances.take_dirty(64), (0..0, None));
+
+    // disjoint updates are merged into one covering range.
+    instances.set(2, attrs(5.0));
+    instances.as_mut_range(0..1)[0].radius = 6.0;
+    assert_eq!(instances.dirty, Some(0..3));
+    assert_eq!(instances.take_dirty(64), (0..3, None));
+
+    // last shape takes the place of the removed one.
+    assert_eq!(instances.swap_remove(1).radius, 2.0);
+    assert_eq!(instances.list[1].radius, 4.0);
+    assert_eq!(instances.take_dirty(64), (1..2, None));
+
+    // removing the last shape leaves nothing to upload.
+    assert_eq!(instances.swap_remove(2).radius, 5.0);
+    assert_eq!(instances.dirty, None);
+
+    // dirty range beyond the shrunk batch is clipped.
+    instances.as_mut_range(0..2);
+    instances.swap_remove(1);
+    assert_eq!(instances.take_dirty(64), (0..1, None));
+
+    instances.push(attrs(7.0));
+    instances.clear();
+    assert_eq!(instances.take_dirty(64), (0..0, None));
+}
+
+#[test]
+fn test_instances_capacity() {
+    let mut instances = Instances::default();
+
+    instances.extend((0..64).map(|i| attrs(i as f32)));
+    assert_eq!(instances.take_dirty(64), (0..64, None));
+
+    // outgrown capacity, grows to the next power of two and uploads all.
+    instances.push(attrs(64.0));
+    assert_eq!(instances.take_dirty(64), (0..65, Some(128)));
+    assert_eq!(instances.take_dirty(128), (0..0, None));
+
+    instances.set(3, attrs(3.0));
+    instances.extend((65..200).map(|i| attrs(i as f32)));
+    assert_eq!(instances.take_dirty(128), (0..200, Some(256)));
+
+    instances.set(3, attrs(3.0));
+    assert_eq!(instances.take_dirty(256), (3..4, None));
+}
@@ -1,17 +1,27 @@
 use bytemuck::{Pod, Zeroable};
 use cgmath::Point2;
 
-use crate::{BoxVertex, ColorTarget, Context, Extent, Result, Transforms};
+use log::error;
+
+use std::sync::Arc;
+
+use crate::{
+    shader::{catch_validation, Shader, ShaderReload},
+    BoxVertex, ColorTarget, Context, Extent, PipelineTarget, Result, Transforms,
+};
 
 pub struct Circle {
     scale_factor: f32, // default is crate::SCALE_FACTOR
     attrs: Attributes,
     computed_attrs: Attributes,
     // wgpu items
-    pipeline: wgpu::RenderPipeline,
+    target: PipelineTarget,
+    pipeline: Arc<wgpu::RenderPipeline>,
     bind_group: wgpu::BindGroup,
     transform_buffer: wgpu::Buffer,
     uniform_buffer: wgpu::Buffer,
+    // development mode, refer hot_reload().
+    reload: Option<ShaderReload>,
 }
 
 /// measurements are in pixels.
@@ -63,115 +73,73 @@ struct UniformBuffer {
 
 impl UniformBuffer {
     const SIZE: usize = 4 * 4 + 4 * 4 + 4 * 2 + 4 + 4 + 4 + 4 * 3;
+
+    // Check against `struct Attributes` in circle.wgsl.
+    fn check_layout(shader: &Shader) -> Result<()> {
+        use std::mem::offset_of;
+
+        let offsets = [
+            ("fg", offset_of!(UniformBuffer, fg)),
+            ("bg", offset_of!(UniformBuffer, bg)),
+            ("center", offset_of!(UniformBuffer, center)),
+            ("radius", offset_of!(UniformBuffer, radius)),
+            ("width", offset_of!(UniformBuffer, width)),
+            ("fill", offset_of!(UniformBuffer, fill)),
+        ];
+        shader.check_struct::<UniformBuffer>("Attributes", &offsets)
+    }
 }
 
 impl Circle {
-    pub fn new(
-        attrs: Attributes,
-        device: &wgpu::Device,
-        target_format: wgpu::TextureFormat,
-    ) -> Circle {
-        use std::borrow::Cow;
-
-        let bind_group_layout = Self::to_bind_group_layout(device);
-
-        let pipeline_layout = {
-            let desc = wgpu::PipelineLayoutDescriptor {
-                label: Some("primv/circle:pipeline-layout"),
-                bind_group_layouts: &[&bind_group_layout],
-                push_constant_ranges: &[],
-            };
-            device.create_pipeline_layout(&desc)
-        };
-
-        let module = {
-            let text = Cow::Borrowed(include_str!("circle.wgsl"));
-            let desc = wgpu::ShaderModuleDescriptor {
-                label: Some("primv/circle:shader"),
-                source: wgpu::ShaderSource::Wgsl(text.into()),
-            };
-            device.create_shader_module(&desc)
-        };
-
-        let vertex = wgpu::VertexState {
-            module: &module,
-            entry_point: "vs_main",
-            buffers: &[BoxVertex::to_vertex_buffer_layout()],
-        };
-
-        let primitive_state = wgpu::PrimitiveState {
-            topology: wgpu::PrimitiveTopology::TriangleList,
-            strip_index_format: None,
-            front_face: wgpu::FrontFace::Ccw,
-            cull_mode: Some(wgpu::Face::Back),
-            polygon_mode: wgpu::PolygonMode::Fill,
-            unclipped_depth: false,
-            conservative: false,
-        };
-
-        let multisample = wgpu::MultisampleState {
-            count: 1,
-            mask: !0,
-            alpha_to_coverage_enabled: false,
-        };
-
-        let fragment = wgpu::FragmentState {
-            module: &module,
-            entry_point: "fs_main",
-            targets: &[wgpu::ColorTargetState {
-                format: target_format,
-                blend: Some(wgpu::BlendState::REPLACE),
-                write_mask: wgpu::ColorWrites::ALL,
-            }],
-        };
-
-        let pipeline = {
-            let desc = wgpu::RenderPipelineDescriptor {
-                label: Some("primv/circle:pipeline"),
-                layout: Some(&pipeline_layout),
-                vertex,
-                primitive: primitive_state,
-                depth_stencil: None,
-                multisample,
-                fragment: Some(fragment),
-                multiview: None,
-            };
-            device.create_render_pipeline(&desc)
+    /// Create a circle primitive, shader module, layout and pipeline are
+    /// fetched from `context.cache`, hence shared by all circles created with
+    /// the same device.
+    ///
+    /// Panics if circle.wgsl, which is baked into the binary, doesn't match
+    /// with the rust buffer layouts.
+    pub fn new<T>(attrs: Attributes, context: &Context, target: T) -> Circle
+    where
+        T: Into<PipelineTarget>,
+    {
+        let device = context.device;
+        let target: PipelineTarget = target.into();
+
+        let (pipeline, bind_group_layout) = match Self::to_cached(context, &target) {
+            Ok(val) => val,
+            Err(err) => panic!("primv/circle: {}", err),
         };
 
         let transform_buffer = Self::to_transform_buffer(device);
         let uniform_buffer = Self::to_uniform_buffer(device);
-
-        let bind_group = {
-            let desc = wgpu::BindGroupDescriptor {
-                label: Some("primv/circle:bind-group"),
-                layout: &bind_group_layout,
-                entries: &[
-                    wgpu::BindGroupEntry {
-                        binding: 0,
-                        resource: transform_buffer.as_entire_binding(),
-                    },
-                    wgpu::BindGroupEntry {
-                        binding: 1,
-                        resource: uniform_buffer.as_entire_binding(),
-                    },
-                ],
-            };
-            device.create_bind_group(&desc)
-        };
+        let bind_group = Self::to_bind_group(
+            device,
+            &bind_group_layout,
+            &transform_buffer,
+            &uniform_buffer,
+        );
 
         Circle {
             scale_factor: crate::DEFAULT_SCALE_FACTOR,
             attrs,
             computed_attrs: attrs,
             // wgpu items
+            target,
             pipeline,
             bind_group,
             transform_buffer,
             uniform_buffer,
+            reload: None,
         }
     }
 
+    /// Development mode, reload circle.wgsl from the source tree when it
+    /// changes, and rebuild the pipeline before the next redraw.
+    pub fn hot_reload(&mut self) -> &mut Self {
+        let loc = concat!(env!("CARGO_MANIFEST_DIR"), "/src/primv/circle/circle.wgsl");
+        self.reload = Some(ShaderReload::new("primv/circle", loc));
+        self
+    }
+
     pub fn print(&self, prefix: &str) {
         println!("{}primv::Circle({},{})", prefix, self.attrs.radius, self.attrs.width);
     }
@@ -199,6 +167,41 @@ impl Circle {
     ) -> Result<()> {
         // debug!("Settings view port for circle {:?}", target.view_port);
 
+        if let Some((text, shader)) = self.reload.as_mut().and_then(|r| r.poll()) {
+            let device = context.device;
+            let res = catch_validation(device, || {
+                let layout = shader.to_bind_group_layout(
+                    device,
+                    0,
+                    Some("primv/circle:bind-group-layout"),
+                )?;
+                let module = {
+                    let desc = wgpu::ShaderModuleDescriptor {
+                        label: Some("primv/circle"),
+                        source: wgpu::ShaderSource::Wgsl(text.into()),
+                    };
+                    device.create_shader_module(&desc)
+                };
+                let pipeline =
+                    Self::to_pipeline(&shader, &module, &layout, device, &self.target)?;
+                let bind_group = Self::to_bind_group(
+                    device,
+                    &layout,
+                    &self.transform_buffer,
+                    &self.uniform_buffer,
+                );
+                Ok((pipeline, bind_group))
+            });
+            // on failure, continue with current pipeline and bind-group.
+            match res {
+                Ok((pipeline, bind_group)) => {
+                    self.pipeline = Arc::new(pipeline);
+                    self.bind_group = bind_group;
+                }
+                Err(err) => error!("primv/circle: {}", err),
+            }
+        }
+
         let vertex_buffer = self.to_vertex_buffer(&context.device);
         // overwrite the transform mvp buffer.
         {
@@ -227,12 +230,8 @@ impl Circle {
         let mut render_pass = {
             let desc = wgpu::RenderPassDescriptor {
                 label: Some("primv/circle:render-pass"),
-                color_attachments: &[wgpu::RenderPassColorAttachment {
-                    view: &target.view,
-                    resolve_target: None,
-                    ops: wgpu::Operations { load: wgpu::LoadOp::Load, store: true },
-                }],
-                depth_stencil_attachment: None,
+                color_attachments: &[target.to_color_attachment(wgpu::LoadOp::Load)],
+                depth_stencil_attachment: target.to_depth_attachment(false),
             };
             encoder.begin_render_pass(&desc)
         };
@@ -247,6 +246,128 @@ impl Circle {
 }
 
 impl Circle {
+    // Fetch pipeline and bind-group layout for the baked circle.wgsl from
+    // cache, creating them on first use.
+    fn to_cached(
+        context: &Context,
+        target: &PipelineTarget,
+    ) -> Result<(Arc<wgpu::RenderPipeline>, Arc<wgpu::BindGroupLayout>)> {
+        let (device, cache) = (context.device, context.cache);
+
+        let permutation = cache.to_permutation(
+            device,
+            "primv/circle",
+            include_str!("circle.wgsl"),
+            &[],
+        )?;
+        let bind_group_layout = {
+            let entries = permutation.as_bind_group_layout_entries(0);
+            let label = Some("primv/circle:bind-group-layout");
+            cache.to_bind_group_layout(device, label, entries)?
+        };
+        let pipeline = cache.to_render_pipeline(
+            "primv/circle:pipeline",
+            &permutation,
+            target,
+            || {
+                let (shader, module) = (&permutation.shader, &permutation.module);
+                Self::to_pipeline(shader, module, &bind_group_layout, device, target)
+            },
+        )?;
+
+        Ok((pipeline, bind_group_layout))
+    }
+
+    // Create pipeline for preprocessed `text` of circle.wgsl, after checking
+    // the shader against rust types.
+    fn to_pipeline(
+        shader: &Shader,
+        module: &wgpu::ShaderModule,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        device: &wgpu::Device,
+        target: &PipelineTarget,
+    ) -> Result<wgpu::RenderPipeline> {
+        use std::mem::offset_of;
+
+        Transforms::check_layout(shader)?;
+        UniformBuffer::check_layout(shader)?;
+        let vertex_layout = shader.to_vertex_layout("vs_main")?;
+        vertex_layout.check::<BoxVertex>(&[(0, offset_of!(BoxVertex, position))])?;
+
+        let pipeline_layout = {
+            let desc = wgpu::PipelineLayoutDescriptor {
+                label: Some("primv/circle:pipeline-layout"),
+                bind_group_layouts: &[bind_group_layout],
+                push_constant_ranges: &[],
+            };
+            device.create_pipeline_layout(&desc)
+        };
+
+        let vertex = wgpu::VertexState {
+            module,
+            entry_point: "vs_main",
+            buffers: &[
+                vertex_layout.to_vertex_buffer_layout(wgpu::VertexStepMode::Vertex)
+            ],
+        };
+
+        let primitive_state = wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        };
+
+        let fragment = wgpu::FragmentState {
+            module,
+            entry_point: "fs_main",
+            targets: &[target.to_color_target_state()],
+        };
+
+        let pipeline = {
+            let desc = wgpu::RenderPipelineDescriptor {
+                label: Some("primv/circle:pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex,
+                primitive: primitive_state,
+                depth_stencil: target
+                    .to_depth_stencil_state(false, wgpu::CompareFunction::Always),
+                multisample: target.to_multisample_state(),
+                fragment: Some(fragment),
+                multiview: None,
+            };
+            device.create_render_pipeline(&desc)
+        };
+
+        Ok(pipeline)
+    }
+
+    fn to_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        transform_buffer: &wgpu::Buffer,
+        uniform_buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        let desc = wgpu::BindGroupDescriptor {
+            label: Some("primv/circle:bind-group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: transform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        };
+        device.create_bind_group(&desc)
+    }
+
     fn to_transform_buffer(device: &wgpu::Device) -> wgpu::Buffer {
         use wgpu::{util::DeviceExt, BufferUsages};
 
@@ -294,27 +415,4 @@ impl Circle {
         };
         device.create_buffer_init(&desc)
     }
-
-    fn to_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
-        use wgpu::ShaderStages;
-
-        let desc = wgpu::BindGroupLayoutDescriptor {
-            label: Some("primv/circle:bind-group-layout"),
-            entries: &[
-                Transforms::to_bind_group_layout_entry(0),
-                // uniform-buffer
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: ShaderStages::VERTEX | ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-            ],
-        };
-        device.create_bind_group_layout(&desc)
-    }
 }
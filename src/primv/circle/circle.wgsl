@@ -1,7 +1,4 @@
-struct Transforms {
-    model: mat4x4<f32>;
-    mvp: mat4x4<f32>;
-};
+#include "transforms.wgsl"
 
 struct Attributes {
     fg: vec4<f32>;
@@ -20,17 +20,13 @@ impl Clear {
         encoder: &mut wgpu::CommandEncoder,
         target: &mut ColorTarget,
     ) -> Result<()> {
-        let ops = wgpu::Operations { load: wgpu::LoadOp::Clear(self.bg), store: true };
+        let load = wgpu::LoadOp::Clear(self.bg);
 
         let mut render_pass = {
             let desc = wgpu::RenderPassDescriptor {
                 label: Some("primv/clear:render-pass"),
-                color_attachments: &[wgpu::RenderPassColorAttachment {
-                    view: &target.view,
-                    resolve_target: None,
-                    ops,
-                }],
-                depth_stencil_attachment: None,
+                color_attachments: &[target.to_color_attachment(load)],
+                depth_stencil_attachment: target.to_depth_attachment(true),
             };
             encoder.begin_render_pass(&desc)
         };
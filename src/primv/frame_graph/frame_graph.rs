@@ -0,0 +1,311 @@
+use bytemuck::{Pod, Zeroable};
+use log::error;
+
+use std::{sync::Arc, time};
+
+use crate::{
+    shader::{catch_validation, Shader, ShaderReload},
+    util::FrameRate,
+    Blend, ColorTarget, Context, PipelineTarget, Result,
+};
+
+const BG_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+const FRAME_COLOR: [f32; 4] = [0.2, 0.8, 0.2, 0.9];
+const JANK_COLOR: [f32; 4] = [0.9, 0.2, 0.2, 0.9];
+const CPU_COLOR: [f32; 4] = [0.2, 0.4, 0.9, 0.9];
+const GPU_COLOR: [f32; 4] = [0.9, 0.6, 0.1, 0.9];
+const TARGET_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.8];
+
+/// On-screen overlay that plots recent frame times from [FrameRate].
+///
+/// Each frame is drawn as a bar, green for normal frames and red for janky
+/// frames, with CPU time overlaid in blue on the left half and GPU time in
+/// orange on the right half. A white line marks the target frame period. The
+/// vertical scale is twice the target period, or twice the average frame time
+/// when there is no target.
+///
+/// Call [FrameGraph::update] once per frame and [FrameGraph::redraw] after
+/// drawing the scene, the overlay is drawn on top of the existing content.
+pub struct FrameGraph {
+    rect: [f32; 4],
+    vertices: Vec<Vertex>,
+    target: PipelineTarget,
+    pipeline: Arc<wgpu::RenderPipeline>,
+    vertex_buffer: Option<wgpu::Buffer>,
+    capacity: usize,
+    // development mode, refer hot_reload().
+    reload: Option<ShaderReload>,
+}
+
+impl FrameGraph {
+    /// Create a frame graph, shader module and pipeline are fetched from
+    /// `context.cache`. Panics if frame_graph.wgsl, which is baked into the
+    /// binary, doesn't match with the rust vertex layout.
+    pub fn new<T>(target: T, context: &Context) -> FrameGraph
+    where
+        T: Into<PipelineTarget>,
+    {
+        let target = PipelineTarget { blend: Blend::Alpha, ..target.into() };
+
+        let pipeline = match Self::to_cached(context, &target) {
+            Ok(pipeline) => pipeline,
+            Err(err) => panic!("primv/frame_graph: {}", err),
+        };
+
+        FrameGraph {
+            rect: [-0.98, 0.6, 0.6, 0.38],
+            vertices: vec![],
+            target,
+            pipeline,
+            vertex_buffer: None,
+            capacity: 0,
+            reload: None,
+        }
+    }
+
+    /// Development mode, reload frame_graph.wgsl from the source tree when it
+    /// changes, and rebuild the pipeline before the next redraw.
+    pub fn hot_reload(&mut self) -> &mut Self {
+        let loc = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/src/primv/frame_graph/frame_graph.wgsl"
+        );
+        self.reload = Some(ShaderReload::new("primv/frame_graph", loc));
+        self
+    }
+
+    /// Set the area covered by the graph, in normalized device coordinates,
+    /// as bottom-left `x`, `y`, `width` and `height`. Default is top-left
+    /// corner of the target.
+    pub fn set_rect(&mut self, x: f32, y: f32, width: f32, height: f32) -> &mut Self {
+        self.rect = [x, y, width, height];
+        self
+    }
+
+    /// Rebuild the graph from frame times in `frame_rate`.
+    pub fn update(&mut self, frame_rate: &FrameRate) -> &mut Self {
+        let [x, y, w, h] = self.rect;
+
+        let frames: Vec<_> = frame_rate.iter_frame_times().copied().collect();
+        let gpu_times: Vec<_> = frame_rate.iter_gpu_times().copied().collect();
+
+        let reference = match frame_rate.to_period() {
+            Some(period) => period,
+            None => frame_rate.to_stats().avg,
+        };
+        let scale = (reference * 2).max(time::Duration::from_millis(1)).as_secs_f32();
+        let to_height = |d: time::Duration| (d.as_secs_f32() / scale).min(1.0) * h;
+
+        let n = frames.len().max(gpu_times.len()).max(1);
+        let bar_w = w / (n as f32);
+
+        self.vertices.clear();
+        quad(&mut self.vertices, [x, y, w, h], BG_COLOR);
+
+        // bars are right aligned, latest frame being the right most.
+        let offset = n - frames.len();
+        for (i, ft) in frames.iter().enumerate() {
+            let bx = x + ((offset + i) as f32) * bar_w;
+            let color = if ft.jank { JANK_COLOR } else { FRAME_COLOR };
+            quad(&mut self.vertices, [bx, y, bar_w, to_height(ft.frame)], color);
+            let cpu = [bx, y, bar_w / 2.0, to_height(ft.cpu)];
+            quad(&mut self.vertices, cpu, CPU_COLOR);
+        }
+        let offset = n - gpu_times.len();
+        for (i, gpu) in gpu_times.iter().enumerate() {
+            let bx = x + ((offset + i) as f32) * bar_w + bar_w / 2.0;
+            quad(&mut self.vertices, [bx, y, bar_w / 2.0, to_height(*gpu)], GPU_COLOR);
+        }
+
+        if let Some(period) = frame_rate.to_period() {
+            let ty = y + to_height(period);
+            quad(&mut self.vertices, [x, ty, w, h * 0.01], TARGET_COLOR);
+        }
+
+        self
+    }
+
+    pub fn redraw(
+        &mut self,
+        context: &Context,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &mut ColorTarget,
+    ) -> Result<()> {
+        self.do_reload(context.device);
+
+        if self.vertices.is_empty() {
+            return Ok(());
+        }
+
+        if self.capacity < self.vertices.len() {
+            self.capacity = self.vertices.len().next_power_of_two();
+            self.vertex_buffer = None;
+        }
+        let vertex_buffer = match self.vertex_buffer.take() {
+            Some(vertex_buffer) => vertex_buffer,
+            None => {
+                let desc = wgpu::BufferDescriptor {
+                    label: Some("primv/frame_graph:vertex-buffer"),
+                    size: (self.capacity * Vertex::SIZE) as wgpu::BufferAddress,
+                    usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                };
+                context.device.create_buffer(&desc)
+            }
+        };
+        context.queue.write_buffer(
+            &vertex_buffer,
+            0,
+            bytemuck::cast_slice(&self.vertices),
+        );
+
+        {
+            let mut render_pass = {
+                let desc = wgpu::RenderPassDescriptor {
+                    label: Some("primv/frame_graph:render-pass"),
+                    color_attachments: &[target.to_color_attachment(wgpu::LoadOp::Load)],
+                    depth_stencil_attachment: target.to_depth_attachment(false),
+                };
+                encoder.begin_render_pass(&desc)
+            };
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
+            render_pass.draw(0..(self.vertices.len() as u32), 0..1);
+        }
+
+        self.vertex_buffer = Some(vertex_buffer);
+
+        Ok(())
+    }
+}
+
+impl FrameGraph {
+    fn do_reload(&mut self, device: &wgpu::Device) {
+        if let Some((text, shader)) = self.reload.as_mut().and_then(|r| r.poll()) {
+            let res = catch_validation(device, || {
+                let module = {
+                    let desc = wgpu::ShaderModuleDescriptor {
+                        label: Some("primv/frame_graph"),
+                        source: wgpu::ShaderSource::Wgsl(text.into()),
+                    };
+                    device.create_shader_module(&desc)
+                };
+                Self::to_pipeline(&shader, &module, device, &self.target)
+            });
+            // on failure, continue with current pipeline.
+            match res {
+                Ok(pipeline) => self.pipeline = Arc::new(pipeline),
+                Err(err) => error!("primv/frame_graph: {}", err),
+            }
+        }
+    }
+
+    // Fetch pipeline for the baked frame_graph.wgsl from cache, creating it
+    // on first use.
+    fn to_cached(
+        context: &Context,
+        target: &PipelineTarget,
+    ) -> Result<Arc<wgpu::RenderPipeline>> {
+        let (device, cache) = (context.device, context.cache);
+
+        let permutation = cache.to_permutation(
+            device,
+            "primv/frame_graph",
+            include_str!("frame_graph.wgsl"),
+            &[],
+        )?;
+        cache.to_render_pipeline(
+            "primv/frame_graph:pipeline",
+            &permutation,
+            target,
+            || {
+                let (shader, module) = (&permutation.shader, &permutation.module);
+                Self::to_pipeline(shader, module, device, target)
+            },
+        )
+    }
+
+    // Create pipeline for frame_graph.wgsl, after checking the shader against
+    // rust types.
+    fn to_pipeline(
+        shader: &Shader,
+        module: &wgpu::ShaderModule,
+        device: &wgpu::Device,
+        target: &PipelineTarget,
+    ) -> Result<wgpu::RenderPipeline> {
+        use std::mem::offset_of;
+
+        let vertex_layout = shader.to_vertex_layout("vs_main")?;
+        let offsets = [(0, offset_of!(Vertex, position)), (1, offset_of!(Vertex, color))];
+        vertex_layout.check::<Vertex>(&offsets)?;
+
+        let pipeline_layout = {
+            let desc = wgpu::PipelineLayoutDescriptor {
+                label: Some("primv/frame_graph:pipeline-layout"),
+                bind_group_layouts: &[],
+                push_constant_ranges: &[],
+            };
+            device.create_pipeline_layout(&desc)
+        };
+
+        let vertex = wgpu::VertexState {
+            module,
+            entry_point: "vs_main",
+            buffers: &[
+                vertex_layout.to_vertex_buffer_layout(wgpu::VertexStepMode::Vertex)
+            ],
+        };
+
+        let primitive_state = wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        };
+
+        let fragment = wgpu::FragmentState {
+            module,
+            entry_point: "fs_main",
+            targets: &[target.to_color_target_state()],
+        };
+
+        let pipeline = {
+            let desc = wgpu::RenderPipelineDescriptor {
+                label: Some("primv/frame_graph:pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex,
+                primitive: primitive_state,
+                depth_stencil: target
+                    .to_depth_stencil_state(false, wgpu::CompareFunction::Always),
+                multisample: target.to_multisample_state(),
+                fragment: Some(fragment),
+                multiview: None,
+            };
+            device.create_render_pipeline(&desc)
+        };
+
+        Ok(pipeline)
+    }
+}
+
+fn quad(vertices: &mut Vec<Vertex>, rect: [f32; 4], color: [f32; 4]) {
+    let [x, y, w, h] = rect;
+    let (x1, y1) = (x + w, y + h);
+    for position in [[x, y], [x1, y], [x1, y1], [x, y], [x1, y1], [x, y1]] {
+        vertices.push(Vertex { position, color })
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct Vertex {
+    position: [f32; 2],
+    color: [f32; 4],
+}
+
+impl Vertex {
+    const SIZE: usize = std::mem::size_of::<Vertex>();
+}
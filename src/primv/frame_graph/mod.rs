@@ -0,0 +1,3 @@
+mod frame_graph;
+
+pub use frame_graph::FrameGraph;
@@ -1,87 +1,67 @@
-use crate::{ColorTarget, Context, Error, Result};
+use log::error;
+
+use std::sync::Arc;
+
+use crate::{
+    shader::{catch_validation, Shader, ShaderReload},
+    ColorTarget, Context, Error, PipelineTarget, Result,
+};
 
 pub struct Load {
     source: Option<wgpu::TextureView>,
-    bind_group_layout: wgpu::BindGroupLayout,
-    pipeline: wgpu::RenderPipeline,
+    target: PipelineTarget,
+    bind_group_layout: Arc<wgpu::BindGroupLayout>,
+    pipeline: Arc<wgpu::RenderPipeline>,
+    // development mode, refer hot_reload().
+    reload: Option<ShaderReload>,
 }
 
 impl Load {
-    pub fn new(
-        device: &wgpu::Device,
-        target_format: wgpu::TextureFormat,
-    ) -> Result<Load> {
-        let bind_group_layout = Self::to_bind_group_layout(device);
+    /// Create a load primitive, shader module, layout and pipeline are
+    /// fetched from `context.cache`.
+    pub fn new<T>(context: &Context, target: T) -> Result<Load>
+    where
+        T: Into<PipelineTarget>,
+    {
+        let (device, cache) = (context.device, context.cache);
+        let target: PipelineTarget = target.into();
 
-        let pipeline_layout = {
-            let desc = wgpu::PipelineLayoutDescriptor {
-                label: Some("primv/load:pipeline-layout"),
-                bind_group_layouts: &[&bind_group_layout],
-                push_constant_ranges: &[],
-            };
-            device.create_pipeline_layout(&desc)
+        let permutation =
+            cache.to_permutation(device, "primv/load", include_str!("load.wgsl"), &[])?;
+        let bind_group_layout = {
+            let entries = permutation.as_bind_group_layout_entries(0);
+            let label = Some("primv/load:bind-group-layout");
+            cache.to_bind_group_layout(device, label, entries)?
         };
+        let pipeline = cache.to_render_pipeline(
+            "primv/load:pipeline",
+            &permutation,
+            &target,
+            || {
+                let (shader, module) = (&permutation.shader, &permutation.module);
+                Self::to_pipeline(shader, module, &bind_group_layout, device, &target)
+            },
+        )?;
 
-        let module = {
-            let text = include_str!("load.wgsl");
-            let desc = wgpu::ShaderModuleDescriptor {
-                label: Some("primv/load:shader"),
-                source: wgpu::ShaderSource::Wgsl(text.into()),
-            };
-            device.create_shader_module(&desc)
+        let val = Load {
+            source: None,
+            target,
+            bind_group_layout,
+            pipeline,
+            reload: None,
         };
 
-        let vertex = wgpu::VertexState {
-            module: &module,
-            entry_point: "vs_main",
-            buffers: &[Vertex::to_vertex_buffer_layout()],
-        };
-
-        let primitive = wgpu::PrimitiveState {
-            topology: wgpu::PrimitiveTopology::TriangleList,
-            strip_index_format: None,
-            front_face: wgpu::FrontFace::Ccw,
-            cull_mode: Some(wgpu::Face::Back),
-            polygon_mode: wgpu::PolygonMode::Fill,
-            unclipped_depth: false,
-            conservative: false,
-        };
-
-        let multisample = wgpu::MultisampleState {
-            count: 1,
-            mask: !0,
-            alpha_to_coverage_enabled: false,
-        };
-
-        let fragment = wgpu::FragmentState {
-            module: &module,
-            entry_point: "fs_main",
-            targets: &[wgpu::ColorTargetState {
-                format: target_format,
-                blend: Some(wgpu::BlendState::REPLACE),
-                write_mask: wgpu::ColorWrites::ALL,
-            }],
-        };
-
-        let pipeline = {
-            let desc = wgpu::RenderPipelineDescriptor {
-                label: Some("primv/load:pipeline"),
-                layout: Some(&pipeline_layout),
-                vertex,
-                primitive,
-                depth_stencil: None,
-                multisample,
-                fragment: Some(fragment),
-                multiview: None,
-            };
-            device.create_render_pipeline(&desc)
-        };
-
-        let val = Load { source: None, bind_group_layout, pipeline };
-
         Ok(val)
     }
 
+    /// Development mode, reload load.wgsl from the source tree when it
+    /// changes, and rebuild the pipeline before the next redraw.
+    pub fn hot_reload(&mut self) -> &mut Self {
+        let loc = concat!(env!("CARGO_MANIFEST_DIR"), "/src/primv/load/load.wgsl");
+        self.reload = Some(ShaderReload::new("primv/load", loc));
+        self
+    }
+
     pub fn set_source(&mut self, src: wgpu::TextureView) -> Option<wgpu::TextureView> {
         self.source.replace(src)
     }
@@ -94,6 +74,8 @@ impl Load {
         encoder: &mut wgpu::CommandEncoder,
         target: &mut ColorTarget,
     ) -> Result<()> {
+        self.do_reload(context.device);
+
         let source = match self.source.as_ref() {
             Some(source) => source,
             None => err_at!(Fatal, msg: "set source frame-view for loading")?,
@@ -111,7 +93,7 @@ impl Load {
                     mipmap_filter: wgpu::FilterMode::Linear,
                     ..Default::default()
                 };
-                context.device.create_sampler(&desc)
+                context.cache.to_sampler(context.device, &desc)?
             };
             let desc = wgpu::BindGroupDescriptor {
                 label: Some("primv/load:bind-group"),
@@ -133,12 +115,8 @@ impl Load {
         let mut render_pass = {
             let desc = wgpu::RenderPassDescriptor {
                 label: Some("primv/load:render-pass"),
-                color_attachments: &[wgpu::RenderPassColorAttachment {
-                    view: &target.view,
-                    resolve_target: None,
-                    ops: wgpu::Operations { load: wgpu::LoadOp::Load, store: true },
-                }],
-                depth_stencil_attachment: None,
+                color_attachments: &[target.to_color_attachment(wgpu::LoadOp::Load)],
+                depth_stencil_attachment: target.to_depth_attachment(false),
             };
             encoder.begin_render_pass(&desc)
         };
@@ -153,36 +131,97 @@ impl Load {
 }
 
 impl Load {
-    fn to_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
-        use wgpu::{
-            BindingType, SamplerBindingType, TextureSampleType, TextureViewDimension,
+    fn do_reload(&mut self, device: &wgpu::Device) {
+        if let Some((text, shader)) = self.reload.as_mut().and_then(|r| r.poll()) {
+            let res = catch_validation(device, || {
+                let layout = shader.to_bind_group_layout(
+                    device,
+                    0,
+                    Some("primv/load:bind-group-layout"),
+                )?;
+                let module = {
+                    let desc = wgpu::ShaderModuleDescriptor {
+                        label: Some("primv/load"),
+                        source: wgpu::ShaderSource::Wgsl(text.into()),
+                    };
+                    device.create_shader_module(&desc)
+                };
+                let pipeline =
+                    Self::to_pipeline(&shader, &module, &layout, device, &self.target)?;
+                Ok((pipeline, layout))
+            });
+            // on failure, continue with current pipeline and layout.
+            match res {
+                Ok((pipeline, layout)) => {
+                    self.pipeline = Arc::new(pipeline);
+                    self.bind_group_layout = Arc::new(layout);
+                }
+                Err(err) => error!("primv/load: {}", err),
+            }
+        }
+    }
+
+    // Create pipeline for load.wgsl, after checking the shader against rust
+    // types.
+    fn to_pipeline(
+        shader: &Shader,
+        module: &wgpu::ShaderModule,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        device: &wgpu::Device,
+        target: &PipelineTarget,
+    ) -> Result<wgpu::RenderPipeline> {
+        use std::mem::offset_of;
+
+        let pipeline_layout = {
+            let desc = wgpu::PipelineLayoutDescriptor {
+                label: Some("primv/load:pipeline-layout"),
+                bind_group_layouts: &[bind_group_layout],
+                push_constant_ranges: &[],
+            };
+            device.create_pipeline_layout(&desc)
         };
 
-        let desc = wgpu::BindGroupLayoutDescriptor {
-            label: Some("primv/load:bind-group-layout"),
-            entries: &[
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Texture {
-                        multisampled: false,
-                        view_dimension: TextureViewDimension::D2,
-                        sample_type: TextureSampleType::Float { filterable: true },
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 1,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    // This should match the filterable field of the
-                    // corresponding Texture entry above.
-                    ty: BindingType::Sampler(SamplerBindingType::Filtering),
-                    count: None,
-                },
+        let vertex_layout = shader.to_vertex_layout("vs_main")?;
+        let offsets =
+            [(0, offset_of!(Vertex, position)), (1, offset_of!(Vertex, tex_coord))];
+        vertex_layout.check::<Vertex>(&offsets)?;
+
+        let vertex = wgpu::VertexState {
+            module,
+            entry_point: "vs_main",
+            buffers: &[
+                vertex_layout.to_vertex_buffer_layout(wgpu::VertexStepMode::Vertex)
             ],
         };
 
-        device.create_bind_group_layout(&desc)
+        let primitive = wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        };
+
+        let fragment = wgpu::FragmentState {
+            module,
+            entry_point: "fs_main",
+            targets: &[target.to_color_target_state()],
+        };
+
+        let desc = wgpu::RenderPipelineDescriptor {
+            label: Some("primv/load:pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex,
+            primitive,
+            depth_stencil: target
+                .to_depth_stencil_state(false, wgpu::CompareFunction::Always),
+            multisample: target.to_multisample_state(),
+            fragment: Some(fragment),
+            multiview: None,
+        };
+        Ok(device.create_render_pipeline(&desc))
     }
 
     fn to_vertex_buffer(device: &wgpu::Device) -> wgpu::Buffer {
@@ -205,10 +244,6 @@ pub struct Vertex {
 }
 
 impl Vertex {
-    const ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![
-        0 => Float32x4,
-        1 => Float32x2,
-    ];
     const NUM_VERTICES: u32 = 6;
 }
 
@@ -238,15 +273,3 @@ const VERTICES: &[Vertex] = &[
         tex_coord: [1.0, 1.0],
     },
 ];
-
-impl Vertex {
-    fn to_vertex_buffer_layout<'a>() -> wgpu::VertexBufferLayout<'a> {
-        use std::mem;
-
-        wgpu::VertexBufferLayout {
-            array_stride: mem::size_of::<Vertex>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &Self::ATTRIBUTES,
-        }
-    }
-}
@@ -0,0 +1,725 @@
+use bytemuck::{Pod, Zeroable};
+use cgmath::{InnerSpace, Point3, Vector3};
+use log::error;
+
+use std::{path, sync::Arc};
+
+use crate::{
+    shader::{catch_validation, Shader, ShaderReload},
+    util::Watcher,
+    ColorTarget, Context, Error, PipelineTarget, Result, Transforms,
+};
+
+/// Primitive to render solid 3D models, as indexed triangle-list.
+///
+/// Vertices carry position, normal, texture-coordinate and color. Optionally a
+/// texture can be set via [Mesh::set_texture] or [Mesh::set_texture_file], which
+/// is then modulated with vertex color. Shading and light source are configured
+/// via [Lighting].
+pub struct Mesh {
+    lighting: Lighting,
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+    textured: bool,
+    texture_loc: Option<path::PathBuf>,
+    // wgpu items
+    target: PipelineTarget,
+    pipeline: Arc<wgpu::RenderPipeline>,
+    bind_group: wgpu::BindGroup,
+    texture_bind_group_layout: Arc<wgpu::BindGroupLayout>,
+    texture_bind_group: wgpu::BindGroup,
+    texture: wgpu::Texture,
+    sampler: Arc<wgpu::Sampler>,
+    transform_buffer: wgpu::Buffer,
+    lighting_buffer: wgpu::Buffer,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    // development mode, refer hot_reload().
+    reload: Option<ShaderReload>,
+    assets: Option<Watcher>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Shading {
+    /// One normal per triangle, derived from screen-space derivatives.
+    Flat,
+    /// Light is computed per vertex and interpolated across the triangle.
+    Gouraud,
+    /// Normals are interpolated across the triangle and light is computed per
+    /// fragment.
+    Phong,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub enum Light {
+    /// Light travelling along `direction`, in world-coordinates.
+    Directional {
+        direction: Vector3<f32>,
+        color: wgpu::Color,
+    },
+    /// Light emitted from `position`, in world-coordinates.
+    Point {
+        position: Point3<f32>,
+        color: wgpu::Color,
+    },
+}
+
+/// Lighting configuration for [Mesh].
+#[derive(Copy, Clone, Debug)]
+pub struct Lighting {
+    pub light: Light,
+    pub ambient: wgpu::Color,
+    pub shading: Shading,
+    /// Camera position in world-coordinates, used for specular highlights.
+    pub eye: Point3<f32>,
+    /// Specular exponent, specular highlights are disabled when ZERO.
+    pub shininess: f32,
+}
+
+impl Default for Lighting {
+    fn default() -> Lighting {
+        Lighting {
+            light: Light::Directional {
+                direction: Vector3::new(-1.0, -1.0, -1.0),
+                color: wgpu::Color::WHITE,
+            },
+            ambient: wgpu::Color { r: 0.1, g: 0.1, b: 0.1, a: 1.0 },
+            shading: Shading::Phong,
+            eye: Point3::new(0.0, 0.0, 3.0),
+            shininess: 32.0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Default, Copy, Clone, Debug, Pod, Zeroable)]
+struct UniformBuffer {
+    position: [f32; 4],
+    color: [f32; 4],
+    ambient: [f32; 4],
+    eye: [f32; 4],
+    shininess: f32,
+    shading: u32,
+    textured: u32,
+    _padding: u32,
+}
+
+impl UniformBuffer {
+    const SIZE: usize = 4 * 4 * 4 + 4 + 4 + 4 + 4;
+
+    // Check against `struct Lighting` in mesh.wgsl.
+    fn check_layout(shader: &Shader) -> Result<()> {
+        use std::mem::offset_of;
+
+        let offsets = [
+            ("position", offset_of!(UniformBuffer, position)),
+            ("color", offset_of!(UniformBuffer, color)),
+            ("ambient", offset_of!(UniformBuffer, ambient)),
+            ("eye", offset_of!(UniformBuffer, eye)),
+            ("shininess", offset_of!(UniformBuffer, shininess)),
+            ("shading", offset_of!(UniformBuffer, shading)),
+            ("textured", offset_of!(UniformBuffer, textured)),
+        ];
+        shader.check_struct::<UniformBuffer>("Lighting", &offsets)
+    }
+}
+
+impl Lighting {
+    fn to_uniform_buffer(self, textured: bool) -> UniformBuffer {
+        use crate::to_rgba8unorm_color;
+
+        let (position, color) = match self.light {
+            Light::Directional { direction: d, color } => ([d.x, d.y, d.z, 0.0], color),
+            Light::Point { position: p, color } => ([p.x, p.y, p.z, 1.0], color),
+        };
+        UniformBuffer {
+            position,
+            color: to_rgba8unorm_color(color),
+            ambient: to_rgba8unorm_color(self.ambient),
+            eye: self.eye.to_homogeneous().into(),
+            shininess: self.shininess,
+            shading: match self.shading {
+                Shading::Flat => 0,
+                Shading::Gouraud => 1,
+                Shading::Phong => 2,
+            },
+            textured: if textured { 1 } else { 0 },
+            _padding: 0,
+        }
+    }
+}
+
+impl Mesh {
+    /// Create a mesh from `vertices` and triangle-list `indices`, number of indices
+    /// shall be a multiple of 3. Shader module, layouts and pipeline are fetched
+    /// from `context.cache`.
+    pub fn new<T>(
+        vertices: Vec<Vertex>,
+        indices: Vec<u32>,
+        context: &Context,
+        target: T,
+    ) -> Result<Mesh>
+    where
+        T: Into<PipelineTarget>,
+    {
+        check_indices(&vertices, &indices)?;
+
+        let (device, cache) = (context.device, context.cache);
+        let target: PipelineTarget = target.into();
+
+        let permutation =
+            cache.to_permutation(device, "primv/mesh", include_str!("mesh.wgsl"), &[])?;
+        let bind_group_layout = {
+            let entries = permutation.as_bind_group_layout_entries(0);
+            let label = Some("primv/mesh:bind-group-layout");
+            cache.to_bind_group_layout(device, label, entries)?
+        };
+        let texture_bind_group_layout = {
+            let entries = permutation.as_bind_group_layout_entries(1);
+            let label = Some("primv/mesh:texture-bind-group-layout");
+            cache.to_bind_group_layout(device, label, entries)?
+        };
+        let pipeline = cache.to_render_pipeline(
+            "primv/mesh:pipeline",
+            &permutation,
+            &target,
+            || {
+                let layouts =
+                    [bind_group_layout.as_ref(), texture_bind_group_layout.as_ref()];
+                let (shader, module) = (&permutation.shader, &permutation.module);
+                Self::to_pipeline(shader, module, &layouts, device, &target)
+            },
+        )?;
+
+        let transform_buffer = Self::to_transform_buffer(device);
+        let lighting_buffer = Self::to_lighting_buffer(device);
+
+        let bind_group = Self::to_bind_group(
+            device,
+            &bind_group_layout,
+            &transform_buffer,
+            &lighting_buffer,
+        );
+
+        // placeholder texture, not sampled until a texture is set.
+        let sampler = cache.to_sampler(device, &Self::to_sampler_descriptor())?;
+        let texture = {
+            let size = wgpu::Extent3d { width: 1, height: 1, depth_or_array_layers: 1 };
+            Self::to_texture(device, size)
+        };
+        let texture_bind_group = {
+            let layout = &texture_bind_group_layout;
+            Self::to_texture_bind_group(device, layout, &texture, &sampler)
+        };
+
+        let vertex_buffer = Self::to_vertex_buffer(device, &vertices);
+        let index_buffer = Self::to_index_buffer(device, &indices);
+
+        let val = Mesh {
+            lighting: Lighting::default(),
+            vertices,
+            indices,
+            textured: false,
+            texture_loc: None,
+            // wgpu items
+            target,
+            pipeline,
+            bind_group,
+            texture_bind_group_layout,
+            texture_bind_group,
+            texture,
+            sampler,
+            transform_buffer,
+            lighting_buffer,
+            vertex_buffer,
+            index_buffer,
+            reload: None,
+            assets: None,
+        };
+
+        Ok(val)
+    }
+
+    pub fn set_lighting(&mut self, lighting: Lighting) -> &mut Self {
+        self.lighting = lighting;
+        self
+    }
+
+    /// Set diffuse texture for this mesh, sampled using vertex texture-coordinates.
+    pub fn set_texture(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        image: &image::RgbaImage,
+    ) -> &mut Self {
+        if let Some(loc) = self.texture_loc.take() {
+            if let Some(watcher) = self.assets.as_mut() {
+                watcher.unwatch(loc);
+            }
+        }
+        self.upload_texture(device, queue, image);
+        self
+    }
+
+    /// Same as [Mesh::set_texture], with image loaded from file `loc`. In
+    /// development mode, refer [Mesh::hot_reload], the texture is reloaded when
+    /// the file changes.
+    pub fn set_texture_file<P>(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        loc: P,
+    ) -> Result<&mut Self>
+    where
+        P: AsRef<path::Path>,
+    {
+        let loc = loc.as_ref();
+        let image = Self::load_image(loc)?;
+        self.set_texture(device, queue, &image);
+        if let Some(watcher) = self.assets.as_mut() {
+            watcher.watch(loc);
+        }
+        self.texture_loc = Some(loc.to_path_buf());
+        Ok(self)
+    }
+
+    /// Development mode, reload mesh.wgsl from the source tree and the texture
+    /// file set via [Mesh::set_texture_file], when they change. Pipeline,
+    /// bind-groups and texture are rebuilt before the next redraw.
+    pub fn hot_reload(&mut self) -> &mut Self {
+        let loc = concat!(env!("CARGO_MANIFEST_DIR"), "/src/primv/mesh/mesh.wgsl");
+        self.reload = Some(ShaderReload::new("primv/mesh", loc));
+        let mut watcher = Watcher::default();
+        if let Some(loc) = self.texture_loc.as_ref() {
+            watcher.watch(loc);
+        }
+        self.assets = Some(watcher);
+        self
+    }
+
+    pub fn print(&self, prefix: &str) {
+        println!(
+            "{}primv::Mesh({} vertices, {} triangles)",
+            prefix,
+            self.vertices.len(),
+            self.indices.len() / 3
+        );
+    }
+
+    fn upload_texture(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        image: &image::RgbaImage,
+    ) {
+        use std::num::NonZeroU32;
+
+        let (width, height) = image.dimensions();
+        let size = wgpu::Extent3d { width, height, depth_or_array_layers: 1 };
+        let texture = Self::to_texture(device, size);
+        queue.write_texture(
+            texture.as_image_copy(),
+            image.as_raw(),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(4 * width),
+                rows_per_image: NonZeroU32::new(height),
+            },
+            size,
+        );
+
+        self.texture_bind_group = Self::to_texture_bind_group(
+            device,
+            &self.texture_bind_group_layout,
+            &texture,
+            &self.sampler,
+        );
+        self.texture = texture;
+        self.textured = true;
+    }
+
+    fn load_image(loc: &path::Path) -> Result<image::RgbaImage> {
+        let image = err_at!(FailConvert, image::open(loc), "file:{:?}", loc)?;
+        Ok(image.to_rgba8())
+    }
+
+    fn do_reload(&mut self, context: &Context) {
+        let device = context.device;
+
+        if let Some((text, shader)) = self.reload.as_mut().and_then(|r| r.poll()) {
+            let res = catch_validation(device, || {
+                let layout = shader.to_bind_group_layout(
+                    device,
+                    0,
+                    Some("primv/mesh:bind-group-layout"),
+                )?;
+                let texture_layout = shader.to_bind_group_layout(
+                    device,
+                    1,
+                    Some("primv/mesh:texture-bind-group-layout"),
+                )?;
+                let module = {
+                    let desc = wgpu::ShaderModuleDescriptor {
+                        label: Some("primv/mesh"),
+                        source: wgpu::ShaderSource::Wgsl(text.into()),
+                    };
+                    device.create_shader_module(&desc)
+                };
+                let pipeline = {
+                    let layouts = [&layout, &texture_layout];
+                    Self::to_pipeline(&shader, &module, &layouts, device, &self.target)?
+                };
+                let bind_group = Self::to_bind_group(
+                    device,
+                    &layout,
+                    &self.transform_buffer,
+                    &self.lighting_buffer,
+                );
+                let texture_bind_group = Self::to_texture_bind_group(
+                    device,
+                    &texture_layout,
+                    &self.texture,
+                    &self.sampler,
+                );
+                Ok((pipeline, bind_group, texture_layout, texture_bind_group))
+            });
+            // on failure, continue with current pipeline and bind-groups.
+            match res {
+                Ok((pipeline, bind_group, texture_layout, texture_bind_group)) => {
+                    self.pipeline = Arc::new(pipeline);
+                    self.bind_group = bind_group;
+                    self.texture_bind_group_layout = Arc::new(texture_layout);
+                    self.texture_bind_group = texture_bind_group;
+                }
+                Err(err) => error!("primv/mesh: {}", err),
+            }
+        }
+
+        let changed = self.assets.as_mut().map(|w| w.poll()).unwrap_or_default();
+        if let Some(loc) = changed.first() {
+            match Self::load_image(loc) {
+                Ok(image) => self.upload_texture(device, context.queue, &image),
+                Err(err) => error!("primv/mesh: {}", err),
+            }
+        }
+    }
+}
+
+impl Mesh {
+    pub fn redraw(
+        &mut self,
+        context: &Context,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &mut ColorTarget,
+    ) -> Result<()> {
+        self.do_reload(context);
+
+        // overwrite the transform mvp buffer.
+        {
+            let content = context.transforms.to_bind_content();
+            context.queue.write_buffer(&self.transform_buffer, 0, &content);
+        }
+        // overwrite the lighting buffer.
+        {
+            let ub = self.lighting.to_uniform_buffer(self.textured);
+            let content: [u8; UniformBuffer::SIZE] = bytemuck::cast(ub);
+            context.queue.write_buffer(&self.lighting_buffer, 0, &content);
+        }
+
+        let mut render_pass = {
+            let desc = wgpu::RenderPassDescriptor {
+                label: Some("primv/mesh:render-pass"),
+                color_attachments: &[target.to_color_attachment(wgpu::LoadOp::Load)],
+                depth_stencil_attachment: target.to_depth_attachment(false),
+            };
+            encoder.begin_render_pass(&desc)
+        };
+        if target.view_port.width > 0.0 && target.view_port.height > 0.0 {
+            target.view_port.set_viewport(&mut render_pass);
+        }
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass
+            .set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_bind_group(1, &self.texture_bind_group, &[]);
+        render_pass.draw_indexed(0..(self.indices.len() as u32), 0, 0..1);
+
+        Ok(())
+    }
+}
+
+impl Mesh {
+    pub fn num_vertices(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn num_triangles(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    pub fn as_vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
+    pub fn as_indices(&self) -> &[u32] {
+        &self.indices
+    }
+}
+
+impl Mesh {
+    // Create pipeline for mesh.wgsl, after checking the shader against rust
+    // types.
+    fn to_pipeline(
+        shader: &Shader,
+        module: &wgpu::ShaderModule,
+        bind_group_layouts: &[&wgpu::BindGroupLayout],
+        device: &wgpu::Device,
+        target: &PipelineTarget,
+    ) -> Result<wgpu::RenderPipeline> {
+        use std::mem::offset_of;
+
+        Transforms::check_layout(shader)?;
+        UniformBuffer::check_layout(shader)?;
+        let vertex_layout = shader.to_vertex_layout("vs_main")?;
+        let offsets = [
+            (0, offset_of!(Vertex, position)),
+            (1, offset_of!(Vertex, normal)),
+            (2, offset_of!(Vertex, uv)),
+            (3, offset_of!(Vertex, color)),
+        ];
+        vertex_layout.check::<Vertex>(&offsets)?;
+
+        let pipeline_layout = {
+            let desc = wgpu::PipelineLayoutDescriptor {
+                label: Some("primv/mesh:pipeline-layout"),
+                bind_group_layouts,
+                push_constant_ranges: &[],
+            };
+            device.create_pipeline_layout(&desc)
+        };
+
+        let vertex = wgpu::VertexState {
+            module,
+            entry_point: "vs_main",
+            buffers: &[
+                vertex_layout.to_vertex_buffer_layout(wgpu::VertexStepMode::Vertex)
+            ],
+        };
+
+        let primitive_state = wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        };
+
+        let fragment = wgpu::FragmentState {
+            module,
+            entry_point: "fs_main",
+            targets: &[target.to_color_target_state()],
+        };
+
+        let pipeline = {
+            let desc = wgpu::RenderPipelineDescriptor {
+                label: Some("primv/mesh:pipeline"),
+                layout: Some(&pipeline_layout),
+                vertex,
+                primitive: primitive_state,
+                depth_stencil: target
+                    .to_depth_stencil_state(true, wgpu::CompareFunction::Less),
+                multisample: target.to_multisample_state(),
+                fragment: Some(fragment),
+                multiview: None,
+            };
+            device.create_render_pipeline(&desc)
+        };
+
+        Ok(pipeline)
+    }
+
+    fn to_transform_buffer(device: &wgpu::Device) -> wgpu::Buffer {
+        use wgpu::{util::DeviceExt, BufferUsages};
+
+        let content = Transforms::empty().to_bind_content();
+        let desc = wgpu::util::BufferInitDescriptor {
+            label: Some("transform-buffer"),
+            contents: &content,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        };
+        device.create_buffer_init(&desc)
+    }
+
+    fn to_lighting_buffer(device: &wgpu::Device) -> wgpu::Buffer {
+        use wgpu::{util::DeviceExt, BufferUsages};
+
+        let contents: [u8; UniformBuffer::SIZE] =
+            bytemuck::cast(UniformBuffer::default());
+        let desc = wgpu::util::BufferInitDescriptor {
+            label: Some("primv/mesh:lighting-buffer"),
+            contents: &contents,
+            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+        };
+        device.create_buffer_init(&desc)
+    }
+
+    fn to_vertex_buffer(device: &wgpu::Device, vertices: &[Vertex]) -> wgpu::Buffer {
+        use wgpu::util::DeviceExt;
+
+        let desc = wgpu::util::BufferInitDescriptor {
+            label: Some("primv/mesh:vertex-buffer"),
+            contents: bytemuck::cast_slice(vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        };
+        device.create_buffer_init(&desc)
+    }
+
+    fn to_index_buffer(device: &wgpu::Device, indices: &[u32]) -> wgpu::Buffer {
+        use wgpu::util::DeviceExt;
+
+        let desc = wgpu::util::BufferInitDescriptor {
+            label: Some("primv/mesh:index-buffer"),
+            contents: bytemuck::cast_slice(indices),
+            usage: wgpu::BufferUsages::INDEX,
+        };
+        device.create_buffer_init(&desc)
+    }
+
+    fn to_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        transform_buffer: &wgpu::Buffer,
+        lighting_buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        let desc = wgpu::BindGroupDescriptor {
+            label: Some("primv/mesh:bind-group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: transform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: lighting_buffer.as_entire_binding(),
+                },
+            ],
+        };
+        device.create_bind_group(&desc)
+    }
+
+    fn to_texture(device: &wgpu::Device, size: wgpu::Extent3d) -> wgpu::Texture {
+        use wgpu::TextureUsages;
+
+        let desc = wgpu::TextureDescriptor {
+            label: Some("primv/mesh:texture"),
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            usage: TextureUsages::TEXTURE_BINDING | TextureUsages::COPY_DST,
+        };
+        device.create_texture(&desc)
+    }
+
+    fn to_sampler_descriptor<'a>() -> wgpu::SamplerDescriptor<'a> {
+        wgpu::SamplerDescriptor {
+            label: Some("primv/mesh:sampler"),
+            address_mode_u: wgpu::AddressMode::Repeat,
+            address_mode_v: wgpu::AddressMode::Repeat,
+            address_mode_w: wgpu::AddressMode::Repeat,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        }
+    }
+
+    fn to_texture_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        texture: &wgpu::Texture,
+        sampler: &wgpu::Sampler,
+    ) -> wgpu::BindGroup {
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let desc = wgpu::BindGroupDescriptor {
+            label: Some("primv/mesh:texture-bind-group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+            ],
+        };
+        device.create_bind_group(&desc)
+    }
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct Vertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    pub uv: [f32; 2],
+    pub color: [f32; 4],
+}
+
+impl Default for Vertex {
+    fn default() -> Vertex {
+        Vertex {
+            position: [0.0, 0.0, 0.0],
+            normal: [0.0, 0.0, 1.0],
+            uv: [0.0, 0.0],
+            color: [1.0, 1.0, 1.0, 1.0],
+        }
+    }
+}
+
+// Indices shall make a triangle-list within `vertices`.
+fn check_indices(vertices: &[Vertex], indices: &[u32]) -> Result<()> {
+    if !indices.len().is_multiple_of(3) {
+        err_at!(Invalid, msg: "triangle-list with {} indices", indices.len())?
+    }
+    if let Some(index) = indices.iter().find(|i| (**i as usize) >= vertices.len()) {
+        err_at!(Invalid, msg: "index {} out of {} vertices", index, vertices.len())?
+    }
+
+    Ok(())
+}
+
+/// Compute smooth per-vertex normals for a triangle-list, by accumulating
+/// area-weighted face normals of all triangles sharing a vertex.
+pub fn compute_normals(vertices: &mut [Vertex], indices: &[u32]) {
+    let mut normals = vec![Vector3::new(0.0_f32, 0.0, 0.0); vertices.len()];
+
+    for tri in indices.chunks_exact(3) {
+        let (a, b, c) = (tri[0] as usize, tri[1] as usize, tri[2] as usize);
+        let pa = Point3::from(vertices[a].position);
+        let pb = Point3::from(vertices[b].position);
+        let pc = Point3::from(vertices[c].position);
+        // magnitude of the cross product is twice the area of the triangle.
+        let normal = (pb - pa).cross(pc - pa);
+        normals[a] += normal;
+        normals[b] += normal;
+        normals[c] += normal;
+    }
+
+    for (v, n) in vertices.iter_mut().zip(normals) {
+        if n.magnitude2() > 0.0 {
+            v.normal = n.normalize().into();
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "mesh_test.rs"]
+mod mesh_test;
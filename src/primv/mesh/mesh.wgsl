@@ -0,0 +1,92 @@
+#include "transforms.wgsl"
+
+// position.w is 0.0 for directional light, where position.xyz is the direction
+// in which light travels, and 1.0 for point light.
+struct Lighting {
+    position: vec4<f32>;
+    color: vec4<f32>;
+    ambient: vec4<f32>;
+    eye: vec4<f32>;
+    shininess: f32;
+    shading: u32;
+    textured: u32;
+};
+
+struct VertexInput {
+    [[location(0)]] position: vec3<f32>;
+    [[location(1)]] normal: vec3<f32>;
+    [[location(2)]] uv: vec2<f32>;
+    [[location(3)]] color: vec4<f32>;
+};
+
+struct VertexOutput {
+    [[builtin(position)]] clip_position: vec4<f32>;
+    [[location(0)]] world_position: vec3<f32>;
+    [[location(1)]] normal: vec3<f32>;
+    [[location(2)]] uv: vec2<f32>;
+    [[location(3)]] color: vec4<f32>;
+};
+
+[[binding(0), group(0)]] var<uniform> transforms: Transforms;
+[[binding(1), group(0)]] var<uniform> lighting: Lighting;
+[[binding(0), group(1)]] var t_diffuse: texture_2d<f32>;
+[[binding(1), group(1)]] var s_diffuse: sampler;
+
+let SHADING_FLAT: u32 = 0u;
+let SHADING_GOURAUD: u32 = 1u;
+
+fn to_light_dir(world_position: vec3<f32>) -> vec3<f32> {
+    if (lighting.position.w == 0.0) {
+        return normalize(-lighting.position.xyz);
+    }
+    return normalize(lighting.position.xyz - world_position);
+}
+
+fn shade(world_position: vec3<f32>, normal: vec3<f32>, color: vec4<f32>) -> vec4<f32> {
+    let n = normalize(normal);
+    let l = to_light_dir(world_position);
+    let diffuse = max(dot(n, l), 0.0) * lighting.color.rgb;
+
+    var specular = vec3<f32>(0.0, 0.0, 0.0);
+    if (lighting.shininess > 0.0) {
+        let v = normalize(lighting.eye.xyz - world_position);
+        let h = normalize(l + v);
+        specular = pow(max(dot(n, h), 0.0), lighting.shininess) * lighting.color.rgb;
+    }
+
+    let rgb = (lighting.ambient.rgb + diffuse) * color.rgb + specular;
+    return vec4<f32>(rgb, color.a);
+}
+
+[[stage(vertex)]]
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    let world_position = transforms.model * vec4<f32>(in.position, 1.0);
+    out.clip_position = transforms.mvp * vec4<f32>(in.position, 1.0);
+    out.world_position = world_position.xyz;
+    out.normal = (transforms.model * vec4<f32>(in.normal, 0.0)).xyz;
+    out.uv = in.uv;
+    out.color = in.color;
+    if (lighting.shading == SHADING_GOURAUD) {
+        out.color = shade(out.world_position, out.normal, in.color);
+    }
+
+    return out;
+}
+
+[[stage(fragment)]]
+fn fs_main(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+    var color = in.color;
+    if (lighting.textured == 1u) {
+        color = color * textureSample(t_diffuse, s_diffuse, in.uv);
+    }
+
+    if (lighting.shading == SHADING_FLAT) {
+        let normal = cross(dpdy(in.world_position), dpdx(in.world_position));
+        return shade(in.world_position, normal, color);
+    } else if (lighting.shading == SHADING_GOURAUD) {
+        return color;
+    }
+    return shade(in.world_position, in.normal, color);
+}
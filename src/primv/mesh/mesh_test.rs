@@ -0,0 +1,96 @@
+use super::*;
+
+fn vertex(position: [f32; 3]) -> Vertex {
+    Vertex { position, ..Vertex::default() }
+}
+
+#[test]
+fn test_compute_normals() {
+    // two triangles sharing an edge, folded along the y-axis.
+    let mut vertices = vec![
+        vertex([0.0, 0.0, 0.0]),
+        vertex([0.0, 1.0, 0.0]),
+        vertex([-1.0, 0.0, 0.0]),
+        vertex([0.0, 0.0, 1.0]),
+    ];
+    let indices = vec![0, 1, 2, 0, 1, 3];
+    compute_normals(&mut vertices, &indices);
+
+    assert_eq!(vertices[2].normal, [0.0, 0.0, 1.0]);
+    assert_eq!(vertices[3].normal, [1.0, 0.0, 0.0]);
+    let h = 1.0 / 2.0_f32.sqrt();
+    for i in [0, 1] {
+        let n = vertices[i].normal;
+        assert!((n[0] - h).abs() < 1e-6 && n[1] == 0.0 && (n[2] - h).abs() < 1e-6);
+    }
+
+    // degenerate triangles leave the normal untouched.
+    let mut vertices = vec![vertex([0.0, 0.0, 0.0]); 3];
+    compute_normals(&mut vertices, &[0, 1, 2]);
+    assert_eq!(vertices[0].normal, Vertex::default().normal);
+}
+
+#[test]
+fn test_check_indices() {
+    let vertices = vec![Vertex::default(); 3];
+    check_indices(&vertices, &[]).unwrap();
+    check_indices(&vertices, &[0, 1, 2]).unwrap();
+
+    let err = check_indices(&vertices, &[0, 1]).unwrap_err();
+    assert!(err.to_string().contains("triangle-list with 2 indices"), "{}", err);
+    let err = check_indices(&vertices, &[0, 1, 3]).unwrap_err();
+    assert!(err.to_string().contains("index 3 out of 3 vertices"), "{}", err);
+}
+
+#[test]
+fn test_lighting_uniform() {
+    let lighting = Lighting::default();
+    let ub = lighting.to_uniform_buffer(false);
+    assert_eq!(ub.position, [-1.0, -1.0, -1.0, 0.0]);
+    assert_eq!((ub.shading, ub.textured), (2, 0));
+    assert_eq!(ub.eye, [0.0, 0.0, 3.0, 1.0]);
+
+    let lighting = Lighting {
+        light: Light::Point {
+            position: Point3::new(1.0, 2.0, 3.0),
+            color: wgpu::Color::RED,
+        },
+        shading: Shading::Flat,
+        ..lighting
+    };
+    let ub = lighting.to_uniform_buffer(true);
+    assert_eq!(ub.position, [1.0, 2.0, 3.0, 1.0]);
+    assert_eq!(ub.color, [1.0, 0.0, 0.0, 1.0]);
+    assert_eq!((ub.shading, ub.textured), (0, 1));
+}
+
+#[test]
+fn test_mesh_layout() {
+    use crate::shader::Shader;
+    use std::mem::size_of;
+
+    let loc = concat!(env!("CARGO_MANIFEST_DIR"), "/src/primv/mesh/mesh.wgsl");
+    let shader = Shader::from_file(loc).unwrap();
+
+    assert_eq!(size_of::<UniformBuffer>(), UniformBuffer::SIZE);
+    UniformBuffer::check_layout(&shader).unwrap();
+
+    let layout = shader.to_vertex_layout("vs_main").unwrap();
+    let offsets = [
+        (0, std::mem::offset_of!(Vertex, position)),
+        (1, std::mem::offset_of!(Vertex, normal)),
+        (2, std::mem::offset_of!(Vertex, uv)),
+        (3, std::mem::offset_of!(Vertex, color)),
+    ];
+    layout.check::<Vertex>(&offsets).unwrap();
+    assert_eq!(
+        layout.attributes,
+        wgpu::vertex_attr_array![
+            0 => Float32x3,
+            1 => Float32x3,
+            2 => Float32x2,
+            3 => Float32x4,
+        ]
+        .to_vec()
+    );
+}
@@ -0,0 +1,3 @@
+mod mesh;
+
+pub use mesh::{compute_normals, Light, Lighting, Mesh, Shading, Vertex};
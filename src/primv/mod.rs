@@ -1,5 +1,8 @@
+pub mod batch;
 pub mod circle;
 pub mod clear;
+pub mod frame_graph;
 pub mod glyph;
 pub mod load;
+pub mod mesh;
 pub mod wireframe;
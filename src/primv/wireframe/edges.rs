@@ -0,0 +1,157 @@
+use cgmath::{Deg, InnerSpace, Point3, Rad, Vector3};
+use log::warn;
+
+use std::collections::HashMap;
+
+use crate::primv::{mesh, wireframe::Vertex};
+
+/// Selects edges to extract from a triangle mesh, refer [extract_edges].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Edges {
+    /// All unique edges.
+    All,
+    /// Edges where the angle between normals of adjacent triangles exceeds
+    /// the threshold, like creases and corners of CAD models.
+    Feature(Deg<f32>),
+    /// Edges between a triangle facing the eye and a triangle facing away
+    /// from it, eye position is in model-coordinates. Silhouettes are view
+    /// dependent and shall be extracted again when the eye moves.
+    Silhouette(Point3<f32>),
+}
+
+// Triangles adjacent to an edge, a third triangle makes the edge non-manifold.
+#[derive(Default)]
+struct Adjacent {
+    triangles: [Option<usize>; 2],
+    count: usize,
+}
+
+/// Extract unique edges from an indexed triangle-list, and return an indexed
+/// line-list for [crate::primv::wireframe::Wireframe::new_indexed].
+///
+/// Vertices sharing the same position are welded, so that edges of triangles
+/// that only differ in normal, texture-coordinate or color are detected as
+/// same. Boundary edges, with a single adjacent triangle, and non-manifold
+/// edges, with more than two, are always kept.
+pub fn extract_edges(
+    vertices: &[mesh::Vertex],
+    indices: &[u32],
+    edges: Edges,
+) -> (Vec<Vertex>, Vec<u32>) {
+    // weld vertices by position.
+    let mut welded: HashMap<[u32; 3], u32> = HashMap::new();
+    let canon: Vec<u32> = vertices
+        .iter()
+        .enumerate()
+        .map(|(i, v)| *welded.entry(v.position.map(f32::to_bits)).or_insert(i as u32))
+        .collect();
+
+    let mut normals: Vec<Vector3<f32>> = vec![];
+    let mut order: Vec<(u32, u32)> = vec![];
+    let mut adjacency: HashMap<(u32, u32), Adjacent> = HashMap::new();
+
+    for tri in indices.chunks_exact(3) {
+        let [a, b, c] = [tri[0], tri[1], tri[2]].map(|i| canon[i as usize]);
+        if a == b || b == c || c == a {
+            continue;
+        }
+
+        let t = normals.len();
+        normals.push({
+            let [pa, pb, pc] =
+                [a, b, c].map(|i| Point3::from(vertices[i as usize].position));
+            let n = (pb - pa).cross(pc - pa);
+            match n.magnitude2() > 0.0 {
+                true => n.normalize(),
+                false => n,
+            }
+        });
+
+        for (x, y) in [(a, b), (b, c), (c, a)] {
+            let key = (x.min(y), x.max(y));
+            let adj = adjacency.entry(key).or_insert_with(|| {
+                order.push(key);
+                Adjacent::default()
+            });
+            if adj.count < 2 {
+                adj.triangles[adj.count] = Some(t);
+            }
+            adj.count += 1;
+        }
+    }
+
+    let faces_eye = |t: usize, eye: Point3<f32>, v: u32| -> bool {
+        let p = Point3::from(vertices[v as usize].position);
+        normals[t].dot(eye - p) > 0.0
+    };
+
+    let mut remap: HashMap<u32, u32> = HashMap::new();
+    let mut out_vertices: Vec<Vertex> = vec![];
+    let mut out_indices: Vec<u32> = vec![];
+
+    for key in order.into_iter() {
+        let adj = &adjacency[&key];
+        let keep = match (adj.count, adj.triangles, edges) {
+            (_, _, Edges::All) => true,
+            (2, [Some(t1), Some(t2)], Edges::Feature(angle)) => {
+                let cos = Rad::from(angle).0.cos();
+                normals[t1].dot(normals[t2]) < cos
+            }
+            (2, [Some(t1), Some(t2)], Edges::Silhouette(eye)) => {
+                faces_eye(t1, eye, key.0) != faces_eye(t2, eye, key.0)
+            }
+            (_, _, _) => true,
+        };
+        if !keep {
+            continue;
+        }
+
+        for i in [key.0, key.1] {
+            let index = *remap.entry(i).or_insert_with(|| {
+                let v = &vertices[i as usize];
+                let [x, y, z] = v.position;
+                out_vertices.push(Vertex { position: [x, y, z, 1.0], color: v.color });
+                (out_vertices.len() - 1) as u32
+            });
+            out_indices.push(index);
+        }
+    }
+
+    (out_vertices, out_indices)
+}
+
+/// Convert a line-list, where every segment carries its own pair of vertices,
+/// into an indexed line-list by merging identical vertices. A dangling vertex
+/// at the end of the list, that doesn't make a segment, is dropped with a
+/// warning, same as the GPU would when drawing the line-list.
+pub fn index_lines(vertices: &[Vertex]) -> (Vec<Vertex>, Vec<u32>) {
+    let mut cache: HashMap<[u32; 8], u32> = HashMap::new();
+    let mut out_vertices = vec![];
+
+    let vertices = match vertices.len() % 2 {
+        0 => vertices,
+        _ => {
+            warn!("line-list with {} vertices, dropping the last", vertices.len());
+            &vertices[..vertices.len() - 1]
+        }
+    };
+
+    let indices = vertices
+        .iter()
+        .map(|v| {
+            let mut key = [0; 8];
+            key[..4].copy_from_slice(&v.position.map(f32::to_bits));
+            key[4..].copy_from_slice(&v.color.map(f32::to_bits));
+            *cache.entry(key).or_insert_with(|| {
+                out_vertices.push(*v);
+                (out_vertices.len() - 1) as u32
+            })
+        })
+        .collect();
+
+    (out_vertices, indices)
+}
+
+#[cfg(test)]
+#[path = "edges_test.rs"]
+mod edges_test;
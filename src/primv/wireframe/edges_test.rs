@@ -0,0 +1,63 @@
+use super::*;
+
+// two triangles folded along the shared edge (0,0,0)-(1,0,0) by `fold` degree,
+// with the second triangle not sharing vertices with the first one.
+fn make_fold(fold: f32) -> (Vec<mesh::Vertex>, Vec<u32>) {
+    let (s, c) = fold.to_radians().sin_cos();
+    let positions = [
+        [0.0, 0.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0],
+        [1.0, 0.0, 0.0],
+        [0.0, 0.0, 0.0],
+        [0.0, -c, s],
+    ];
+    let vertices = positions
+        .iter()
+        .map(|p| mesh::Vertex { position: *p, ..mesh::Vertex::default() })
+        .collect();
+    (vertices, vec![0, 1, 2, 3, 4, 5])
+}
+
+#[test]
+fn test_extract_edges() {
+    let (vertices, indices) = make_fold(0.0);
+    let (vs, is) = extract_edges(&vertices, &indices, Edges::All);
+    assert_eq!(vs.len(), 4);
+    assert_eq!(is.len(), 5 * 2);
+
+    // flat, only the boundary remains.
+    let (_, is) = extract_edges(&vertices, &indices, Edges::Feature(Deg(30.0)));
+    assert_eq!(is.len(), 4 * 2);
+
+    let (vertices, indices) = make_fold(60.0);
+    let (_, is) = extract_edges(&vertices, &indices, Edges::Feature(Deg(30.0)));
+    assert_eq!(is.len(), 5 * 2);
+    let (_, is) = extract_edges(&vertices, &indices, Edges::Feature(Deg(90.0)));
+    assert_eq!(is.len(), 4 * 2);
+
+    // eye above the first triangle and below the second one.
+    let (vertices, indices) = make_fold(120.0);
+    let eye = Point3::new(0.2, 0.2, 1.0);
+    let (_, is) = extract_edges(&vertices, &indices, Edges::Silhouette(eye));
+    assert_eq!(is.len(), 5 * 2);
+    let eye = Point3::new(0.2, 0.2, -1.0);
+    let (_, is) = extract_edges(&vertices, &indices, Edges::Silhouette(eye));
+    assert_eq!(is.len(), 5 * 2);
+    let eye = Point3::new(0.2, 5.0, 5.0);
+    let (_, is) = extract_edges(&vertices, &indices, Edges::Silhouette(eye));
+    assert_eq!(is.len(), 4 * 2);
+}
+
+#[test]
+fn test_index_lines() {
+    let v = |x: f32| Vertex { position: [x, 0.0, 0.0, 1.0], color: [1.0; 4] };
+    let (vs, is) = index_lines(&[v(0.0), v(1.0), v(1.0), v(2.0), v(2.0), v(0.0)]);
+    assert_eq!(vs.len(), 3);
+    assert_eq!(is, vec![0, 1, 1, 2, 2, 0]);
+
+    // dangling vertex is dropped.
+    let (vs, is) = index_lines(&[v(0.0), v(1.0), v(2.0)]);
+    assert_eq!(vs.len(), 2);
+    assert_eq!(is, vec![0, 1]);
+}
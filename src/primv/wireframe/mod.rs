@@ -1,3 +1,5 @@
+mod edges;
 mod wireframe;
 
-pub use wireframe::{Vertex, Wireframe};
+pub use edges::{extract_edges, index_lines, Edges};
+pub use wireframe::{load_vertices, Vertex, Wireframe};
@@ -1,33 +1,88 @@
 use bytemuck::{Pod, Zeroable};
 use cgmath::{Matrix4, Point3, Vector4};
 
-use std::{fmt, path, result};
+use std::{fmt, path, result, sync::Arc};
 
-use crate::{ColorTarget, Context, Error, Result, Style, Transforms};
+use log::error;
+
+use crate::{
+    model::{Format, Model},
+    primv::wireframe::edges::{self, Edges},
+    shader::{catch_validation, Shader, ShaderReload},
+    util::Watcher,
+    ColorTarget, Context, Error, PipelineTarget, Result, Style, Transforms,
+};
 
 pub struct Wireframe {
     state: State,
-    pipeline: wgpu::RenderPipeline,
+    target: PipelineTarget,
+    pipeline: Arc<wgpu::RenderPipeline>,
     bind_group: wgpu::BindGroup,
     transform_buffer: wgpu::Buffer,
+    // created on first redraw, and recreated after transform_mut.
+    vertex_buffer: Option<wgpu::Buffer>,
+    index_buffer: Option<wgpu::Buffer>,
+    // development mode, refer hot_reload().
+    reload: Option<ShaderReload>,
+    assets: Option<Watcher>,
 }
 
 struct State {
     style: Style,
     primitive: Primitive,
+    // file from which the wireframe was loaded.
+    loc: Option<path::PathBuf>,
 }
 
 enum Primitive {
-    Lines { vertices: Vec<Vertex> },
+    Lines {
+        vertices: Vec<Vertex>,
+    },
+    IndexedLines {
+        vertices: Vec<Vertex>,
+        indices: Vec<u32>,
+    },
+}
+
+impl Primitive {
+    fn new_indexed(vertices: Vec<Vertex>, indices: Vec<u32>) -> Result<Primitive> {
+        if !indices.len().is_multiple_of(2) {
+            err_at!(Invalid, msg: "line-list with {} indices", indices.len())?
+        }
+        if let Some(index) = indices.iter().find(|i| (**i as usize) >= vertices.len()) {
+            err_at!(Invalid, msg: "index {} out of {} vertices", index, vertices.len())?
+        }
+
+        Ok(Primitive::IndexedLines { vertices, indices })
+    }
+
+    // Refer Wireframe::from_file.
+    fn from_file(loc: &path::Path) -> Result<Primitive> {
+        use std::fs;
+
+        let (vertices, indices) = match Format::from_path(loc) {
+            Some(_) => {
+                let model = Model::from_file(loc)?;
+                edges::extract_edges(&model.vertices, &model.indices, Edges::All)
+            }
+            None => {
+                let data = err_at!(IOError, fs::read(loc), "{:?}", loc)?;
+                edges::index_lines(&Vertex::from_text(&data)?)
+            }
+        };
+
+        Primitive::new_indexed(vertices, indices)
+    }
 }
 
 impl fmt::Display for Wireframe {
     fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
-        match &self.state.primitive {
-            Primitive::Lines { vertices } => {
-                for (i, v) in vertices.iter().enumerate() {
-                    write!(f, "({:4})=> {:?}\n", i, v.position)?;
-                }
+        for (i, v) in self.as_vertices().iter().enumerate() {
+            writeln!(f, "({:4})=> {:?}", i, v.position)?;
+        }
+        if let Primitive::IndexedLines { indices, .. } = &self.state.primitive {
+            for (i, line) in indices.chunks(2).enumerate() {
+                writeln!(f, "[{:4}]=> {:?}", i, line)?;
             }
         }
 
@@ -36,66 +91,261 @@ impl fmt::Display for Wireframe {
 }
 
 impl Wireframe {
-    pub fn from_file<P>(
-        loc: P,
-        format: wgpu::TextureFormat,
-        device: &wgpu::Device,
-    ) -> Result<Wireframe>
+    /// Load wireframe from file. Model files, refer [crate::model], are loaded as
+    /// unique edges of its triangles, other files are parsed as text with one
+    /// vertex per line in `x,y,z;r,g,b,a` format, every pair of lines making a
+    /// segment. Identical vertices are shared via an index buffer.
+    pub fn from_file<P, T>(loc: P, target: T, context: &Context) -> Result<Wireframe>
     where
         P: AsRef<path::Path>,
+        T: Into<PipelineTarget>,
     {
-        use std::fs;
+        let loc = loc.as_ref();
+        let primitive = Primitive::from_file(loc)?;
+        let mut val = Self::with_primitive(primitive, target.into(), context)?;
+        val.state.loc = Some(loc.to_path_buf());
 
-        let data = err_at!(IOError, fs::read(loc))?;
-        Self::from_bytes(&data, format, device)
+        Ok(val)
     }
 
-    pub fn from_bytes(
-        data: &[u8],
-        format: wgpu::TextureFormat,
-        device: &wgpu::Device,
-    ) -> Result<Wireframe> {
-        use std::str::from_utf8;
+    /// Parse `data` as text, refer [Wireframe::from_file].
+    pub fn from_bytes<T>(data: &[u8], target: T, context: &Context) -> Result<Wireframe>
+    where
+        T: Into<PipelineTarget>,
+    {
+        let (vertices, indices) = edges::index_lines(&Vertex::from_text(data)?);
+        Self::new_indexed(vertices, indices, target, context)
+    }
 
-        let txt = err_at!(IOError, from_utf8(data))?;
-        let mut vertices: Vec<Vertex> = vec![];
-        for line in txt.lines() {
-            Vertex::from_text_line(line)?.map(|v| vertices.push(v));
-        }
+    /// Create wireframe from edges of a triangle mesh, refer [Edges].
+    pub fn from_model<T>(
+        model: &Model,
+        edges: Edges,
+        target: T,
+        context: &Context,
+    ) -> Result<Wireframe>
+    where
+        T: Into<PipelineTarget>,
+    {
+        let (vertices, indices) =
+            edges::extract_edges(&model.vertices, &model.indices, edges);
+        Self::new_indexed(vertices, indices, target, context)
+    }
 
+    /// Create wireframe from a line-list of `vertices`. Panics if
+    /// wireframe.wgsl, which is baked into the binary, doesn't match with the
+    /// rust vertex layout.
+    pub fn new<T>(vertices: Vec<Vertex>, target: T, context: &Context) -> Wireframe
+    where
+        T: Into<PipelineTarget>,
+    {
         let primitive = Primitive::Lines { vertices };
+        match Self::with_primitive(primitive, target.into(), context) {
+            Ok(val) => val,
+            Err(err) => panic!("primv/wireframe: {}", err),
+        }
+    }
 
+    /// Create wireframe from an indexed line-list, every pair of `indices`
+    /// into `vertices` makes a segment.
+    pub fn new_indexed<T>(
+        vertices: Vec<Vertex>,
+        indices: Vec<u32>,
+        target: T,
+        context: &Context,
+    ) -> Result<Wireframe>
+    where
+        T: Into<PipelineTarget>,
+    {
+        let primitive = Primitive::new_indexed(vertices, indices)?;
+        Self::with_primitive(primitive, target.into(), context)
+    }
+
+    fn with_primitive(
+        primitive: Primitive,
+        target: PipelineTarget,
+        context: &Context,
+    ) -> Result<Wireframe> {
+        let (device, cache) = (context.device, context.cache);
+
+        let permutation = cache.to_permutation(
+            device,
+            "primv/wireframe",
+            include_str!("wireframe.wgsl"),
+            &[],
+        )?;
         let bind_group_layout = {
-            let entry = Transforms::to_bind_group_layout_entry(0);
-            let desc = wgpu::BindGroupLayoutDescriptor {
-                label: Some("primv/wireframe:bind-group-layout"),
-                entries: &[entry],
+            let entries = permutation.as_bind_group_layout_entries(0);
+            let label = Some("primv/wireframe:bind-group-layout");
+            cache.to_bind_group_layout(device, label, entries)?
+        };
+        let pipeline = cache.to_render_pipeline(
+            "primv/wireframe:pipeline",
+            &permutation,
+            &target,
+            || {
+                let (shader, module) = (&permutation.shader, &permutation.module);
+                Self::to_pipeline(shader, module, &bind_group_layout, device, &target)
+            },
+        )?;
+
+        let transform_buffer = Self::to_transform_buffer(device);
+        let bind_group =
+            Self::to_bind_group(device, &bind_group_layout, &transform_buffer);
+
+        let style = Style::default();
+        let val = Wireframe {
+            state: State { style, primitive, loc: None },
+            target,
+            pipeline,
+            transform_buffer,
+            bind_group,
+            vertex_buffer: None,
+            index_buffer: None,
+            reload: None,
+            assets: None,
+        };
+
+        Ok(val)
+    }
+}
+
+impl Wireframe {
+    /// Development mode, reload wireframe.wgsl from the source tree and the
+    /// file this wireframe was loaded from, when they change. Pipeline and
+    /// vertices are rebuilt before the next redraw, transformations applied
+    /// via [Wireframe::transform_mut] are lost when the file is reloaded.
+    pub fn hot_reload(&mut self) -> &mut Self {
+        let loc =
+            concat!(env!("CARGO_MANIFEST_DIR"), "/src/primv/wireframe/wireframe.wgsl");
+        self.reload = Some(ShaderReload::new("primv/wireframe", loc));
+        self.assets = self.state.loc.as_ref().map(|loc| {
+            let mut watcher = Watcher::default();
+            watcher.watch(loc);
+            watcher
+        });
+        self
+    }
+
+    fn do_reload(&mut self, device: &wgpu::Device) {
+        if let Some((text, shader)) = self.reload.as_mut().and_then(|r| r.poll()) {
+            let res = catch_validation(device, || {
+                let layout = shader.to_bind_group_layout(
+                    device,
+                    0,
+                    Some("primv/wireframe:bind-group-layout"),
+                )?;
+                let module = {
+                    let desc = wgpu::ShaderModuleDescriptor {
+                        label: Some("primv/wireframe"),
+                        source: wgpu::ShaderSource::Wgsl(text.into()),
+                    };
+                    device.create_shader_module(&desc)
+                };
+                let pipeline =
+                    Self::to_pipeline(&shader, &module, &layout, device, &self.target)?;
+                let bind_group =
+                    Self::to_bind_group(device, &layout, &self.transform_buffer);
+                Ok((pipeline, bind_group))
+            });
+            // on failure, continue with current pipeline and bind-group.
+            match res {
+                Ok((pipeline, bind_group)) => {
+                    self.pipeline = Arc::new(pipeline);
+                    self.bind_group = bind_group;
+                }
+                Err(err) => error!("primv/wireframe: {}", err),
+            }
+        }
+
+        let changed = self.assets.as_mut().map(|w| w.poll()).unwrap_or_default();
+        if let Some(loc) = changed.first() {
+            match Primitive::from_file(loc) {
+                Ok(primitive) => {
+                    self.state.primitive = primitive;
+                    self.vertex_buffer = None;
+                }
+                Err(err) => error!("primv/wireframe: {}", err),
+            }
+        }
+    }
+
+    pub fn redraw(
+        &mut self,
+        context: &Context,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &mut ColorTarget,
+    ) -> Result<()> {
+        self.do_reload(context.device);
+
+        if self.vertex_buffer.is_none() {
+            self.vertex_buffer = Some(self.to_vertex_buffer(context.device));
+            self.index_buffer = self.to_index_buffer(context.device);
+        }
+        // overwrite the transform mvp buffer.
+        {
+            let content = context.transforms.to_bind_content();
+            context.queue.write_buffer(&self.transform_buffer, 0, &content);
+        }
+
+        let mut render_pass = {
+            let desc = wgpu::RenderPassDescriptor {
+                label: Some("primv/wireframe:render-pass"),
+                color_attachments: &[
+                    target.to_color_attachment(wgpu::LoadOp::Clear(self.state.style.bg))
+                ],
+                depth_stencil_attachment: target.to_depth_attachment(true),
             };
-            device.create_bind_group_layout(&desc)
+            encoder.begin_render_pass(&desc)
         };
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.as_ref().unwrap().slice(..));
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        match (&self.state.primitive, self.index_buffer.as_ref()) {
+            (Primitive::IndexedLines { indices, .. }, Some(index_buffer)) => {
+                render_pass
+                    .set_index_buffer(index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+                render_pass.draw_indexed(0..(indices.len() as u32), 0, 0..1);
+            }
+            (_, _) => render_pass.draw(0..(self.num_vertices() as u32), 0..1),
+        }
+
+        Ok(())
+    }
+}
+
+impl Wireframe {
+    // Create pipeline for wireframe.wgsl, after checking the shader against
+    // rust types.
+    fn to_pipeline(
+        shader: &Shader,
+        module: &wgpu::ShaderModule,
+        bind_group_layout: &wgpu::BindGroupLayout,
+        device: &wgpu::Device,
+        target: &PipelineTarget,
+    ) -> Result<wgpu::RenderPipeline> {
+        use std::mem::offset_of;
+
+        Transforms::check_layout(shader)?;
+        let vertex_layout = shader.to_vertex_layout("vs_main")?;
+        let offsets = [(0, offset_of!(Vertex, position)), (1, offset_of!(Vertex, color))];
+        vertex_layout.check::<Vertex>(&offsets)?;
 
         let pipeline_layout = {
             let desc = wgpu::PipelineLayoutDescriptor {
                 label: Some("primv/wireframe:pipeline-layout"),
-                bind_group_layouts: &[&bind_group_layout],
+                bind_group_layouts: &[bind_group_layout],
                 push_constant_ranges: &[],
             };
             device.create_pipeline_layout(&desc)
         };
 
-        let module = {
-            let text = include_str!("wireframe.wgsl");
-            let desc = wgpu::ShaderModuleDescriptor {
-                label: Some("primv/wireframe:shader"),
-                source: wgpu::ShaderSource::Wgsl(text.into()),
-            };
-            device.create_shader_module(&desc)
-        };
-
         let vertex = wgpu::VertexState {
-            module: &module,
+            module,
             entry_point: "vs_main",
-            buffers: &[Vertex::to_vertex_buffer_layout()],
+            buffers: &[
+                vertex_layout.to_vertex_buffer_layout(wgpu::VertexStepMode::Vertex)
+            ],
         };
 
         let primitive_state = wgpu::PrimitiveState {
@@ -108,20 +358,10 @@ impl Wireframe {
             conservative: false,
         };
 
-        let multisample = wgpu::MultisampleState {
-            count: 1,
-            mask: !0,
-            alpha_to_coverage_enabled: false,
-        };
-
         let fragment = wgpu::FragmentState {
-            module: &module,
+            module,
             entry_point: "fs_main",
-            targets: &[wgpu::ColorTargetState {
-                format,
-                blend: Some(wgpu::BlendState::REPLACE),
-                write_mask: wgpu::ColorWrites::ALL,
-            }],
+            targets: &[target.to_color_target_state()],
         };
 
         let pipeline = {
@@ -130,80 +370,34 @@ impl Wireframe {
                 layout: Some(&pipeline_layout),
                 vertex,
                 primitive: primitive_state,
-                depth_stencil: None,
-                multisample,
+                depth_stencil: target
+                    .to_depth_stencil_state(true, wgpu::CompareFunction::Less),
+                multisample: target.to_multisample_state(),
                 fragment: Some(fragment),
                 multiview: None,
             };
             device.create_render_pipeline(&desc)
         };
 
-        let transform_buffer = Self::to_transform_buffer(device);
-
-        let bind_group = {
-            let desc = wgpu::BindGroupDescriptor {
-                label: Some("primv/wireframe:bind-group"),
-                layout: &bind_group_layout,
-                entries: &[wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: transform_buffer.as_entire_binding(),
-                }],
-            };
-            device.create_bind_group(&desc)
-        };
-
-        let style = Style::default();
-        let val = Wireframe {
-            state: State { style, primitive },
-            pipeline,
-            transform_buffer,
-            bind_group,
-        };
-
-        Ok(val)
+        Ok(pipeline)
     }
-}
 
-impl Wireframe {
-    pub fn redraw(
-        &mut self,
-        context: &Context,
-        encoder: &mut wgpu::CommandEncoder,
-        target: &mut ColorTarget,
-    ) -> Result<()> {
-        let num_vertices = self.num_vertices() as u32;
-        let vertex_buffer = self.to_vertex_buffer(context.device);
-        // overwrite the transform mvp buffer.
-        {
-            let content = context.transforms.to_bind_content();
-            context.queue.write_buffer(&self.transform_buffer, 0, &content);
-        }
-
-        let mut render_pass = {
-            let desc = wgpu::RenderPassDescriptor {
-                label: Some("primv/wireframe:render-pass"),
-                color_attachments: &[wgpu::RenderPassColorAttachment {
-                    view: &target.view,
-                    resolve_target: None,
-                    ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(self.state.style.bg),
-                        store: true,
-                    },
-                }],
-                depth_stencil_attachment: None,
-            };
-            encoder.begin_render_pass(&desc)
+    fn to_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        transform_buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        let desc = wgpu::BindGroupDescriptor {
+            label: Some("primv/wireframe:bind-group"),
+            layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: transform_buffer.as_entire_binding(),
+            }],
         };
-        render_pass.set_pipeline(&self.pipeline);
-        render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-        render_pass.set_bind_group(0, &self.bind_group, &[]);
-        render_pass.draw(0..num_vertices, 0..1);
-
-        Ok(())
+        device.create_bind_group(&desc)
     }
-}
 
-impl Wireframe {
     fn to_transform_buffer(device: &wgpu::Device) -> wgpu::Buffer {
         use wgpu::{util::DeviceExt, BufferUsages};
         let content = Transforms::empty().to_bind_content();
@@ -218,15 +412,27 @@ impl Wireframe {
     fn to_vertex_buffer(&self, device: &wgpu::Device) -> wgpu::Buffer {
         use wgpu::util::DeviceExt;
 
+        let contents: &[u8] = bytemuck::cast_slice(self.as_vertices());
+        let desc = wgpu::util::BufferInitDescriptor {
+            label: Some("primv/wireframe:vertex-buffer"),
+            contents,
+            usage: wgpu::BufferUsages::VERTEX,
+        };
+        device.create_buffer_init(&desc)
+    }
+
+    fn to_index_buffer(&self, device: &wgpu::Device) -> Option<wgpu::Buffer> {
+        use wgpu::util::DeviceExt;
+
         match &self.state.primitive {
-            Primitive::Lines { vertices } => {
-                let contents: &[u8] = bytemuck::cast_slice(vertices);
+            Primitive::Lines { .. } => None,
+            Primitive::IndexedLines { indices, .. } => {
                 let desc = wgpu::util::BufferInitDescriptor {
-                    label: Some("primv/wireframe:vertex-buffer"),
-                    contents,
-                    usage: wgpu::BufferUsages::VERTEX,
+                    label: Some("primv/wireframe:index-buffer"),
+                    contents: bytemuck::cast_slice(indices),
+                    usage: wgpu::BufferUsages::INDEX,
                 };
-                device.create_buffer_init(&desc)
+                Some(device.create_buffer_init(&desc))
             }
         }
     }
@@ -234,23 +440,41 @@ impl Wireframe {
 
 impl Wireframe {
     pub fn num_vertices(&self) -> usize {
+        self.as_vertices().len()
+    }
+
+    /// Return the number of line segments.
+    pub fn num_lines(&self) -> usize {
         match &self.state.primitive {
-            Primitive::Lines { vertices } => vertices.len(),
+            Primitive::Lines { vertices } => vertices.len() / 2,
+            Primitive::IndexedLines { indices, .. } => indices.len() / 2,
         }
     }
 
     pub fn as_vertices(&self) -> &[Vertex] {
         match &self.state.primitive {
             Primitive::Lines { vertices } => vertices,
+            Primitive::IndexedLines { vertices, .. } => vertices,
+        }
+    }
+
+    /// Return the index buffer contents, None if wireframe is not indexed.
+    pub fn as_indices(&self) -> Option<&[u32]> {
+        match &self.state.primitive {
+            Primitive::Lines { .. } => None,
+            Primitive::IndexedLines { indices, .. } => Some(indices),
         }
     }
 
     pub fn transform_mut(&mut self, mat: Matrix4<f32>) -> &mut Self {
-        match &mut self.state.primitive {
-            Primitive::Lines { vertices } => vertices
-                .iter_mut()
-                .for_each(|v| v.position = (mat * Vector4::from(v.position)).into()),
+        let vertices = match &mut self.state.primitive {
+            Primitive::Lines { vertices } => vertices,
+            Primitive::IndexedLines { vertices, .. } => vertices,
         };
+        vertices
+            .iter_mut()
+            .for_each(|v| v.position = (mat * Vector4::from(v.position)).into());
+        self.vertex_buffer = None;
         self
     }
 }
@@ -258,16 +482,11 @@ impl Wireframe {
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
 pub struct Vertex {
-    position: [f32; 4],
-    color: [f32; 4],
+    pub position: [f32; 4],
+    pub color: [f32; 4],
 }
 
 impl Vertex {
-    const ATTRIBUTES: [wgpu::VertexAttribute; 2] = wgpu::vertex_attr_array![
-        0 => Float32x4,
-        1 => Float32x4,
-    ];
-
     fn new(position: &[f32], color: &[f32]) -> Result<Vertex> {
         let position: [f32; 4] = match position {
             [x, y, z] => Point3::from((*x, *y, *z)).to_homogeneous().into(),
@@ -284,6 +503,21 @@ impl Vertex {
         Ok(Vertex { position, color })
     }
 
+    fn from_text(data: &[u8]) -> Result<Vec<Vertex>> {
+        use std::str::from_utf8;
+
+        let txt = err_at!(IOError, from_utf8(data))?;
+        let mut vertices: Vec<Vertex> = vec![];
+        for (lineno, line) in txt.lines().enumerate() {
+            match Vertex::from_text_line(line) {
+                Ok(Some(v)) => vertices.push(v),
+                Ok(None) => (),
+                Err(err) => err_at!(Invalid, msg: "line {}: {}", lineno + 1, err)?,
+            }
+        }
+        Ok(vertices)
+    }
+
     fn from_position(position: &[f32]) -> Result<Vertex> {
         Self::new(position, &[1.0, 1.0, 1.0, 1.0])
     }
@@ -304,14 +538,22 @@ impl Vertex {
     }
 }
 
-impl Vertex {
-    fn to_vertex_buffer_layout<'a>() -> wgpu::VertexBufferLayout<'a> {
-        use std::mem;
-
-        wgpu::VertexBufferLayout {
-            array_stride: mem::size_of::<Vertex>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &Self::ATTRIBUTES,
+/// Load a line-list of vertices from file, refer [Wireframe::from_file] for
+/// supported formats.
+pub fn load_vertices<P>(loc: P) -> Result<Vec<Vertex>>
+where
+    P: AsRef<path::Path>,
+{
+    use std::fs;
+
+    let loc = loc.as_ref();
+    match Format::from_path(loc) {
+        Some(_) => {
+            let model = Model::from_file(loc)?;
+            let (vertices, indices) =
+                edges::extract_edges(&model.vertices, &model.indices, Edges::All);
+            Ok(indices.into_iter().map(|i| vertices[i as usize]).collect())
         }
+        None => Vertex::from_text(&err_at!(IOError, fs::read(loc), "{:?}", loc)?),
     }
 }
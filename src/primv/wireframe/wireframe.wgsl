@@ -1,9 +1,6 @@
 // Vertex shader
 
-struct Transforms {
-    model: mat4x4<f32>;
-    mvp: mat4x4<f32>;
-};
+#include "transforms.wgsl"
 
 struct VertexInput {
     [[location(0)]] coord: vec4<f32>;
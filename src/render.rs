@@ -3,13 +3,13 @@ use winit::dpi;
 
 use std::{
     path,
-    sync::{mpsc, Arc},
+    sync::{mpsc, Arc, Mutex},
     thread,
 };
 
 use crate::{
-    primv::load, ColorTarget, Context, Error, Result, SaveFile, Screen, Transforms,
-    Viewport,
+    primv::load, util::Profiler, Cache, ColorTarget, Context, DepthTarget, Error,
+    PipelineTarget, Result, SaveFile, Screen, Transforms,
 };
 
 /// Rendering thread
@@ -18,7 +18,12 @@ pub struct Render {
     ssaa: f32,
     color_texture: Arc<wgpu::Texture>,
     color_format: wgpu::TextureFormat,
+    sample_count: u32,
+    msaa_texture: Option<wgpu::Texture>,
+    depth_format: Option<wgpu::TextureFormat>,
+    depth_texture: Option<wgpu::Texture>,
     save_file: Option<SaveFile>,
+    profiler: Option<Arc<Mutex<Profiler>>>,
     handle: Option<thread::JoinHandle<Result<()>>>,
     tx: Option<mpsc::Sender<Request>>,
 }
@@ -27,7 +32,10 @@ impl Drop for Render {
     fn drop(&mut self) {
         self.stop().ok();
         match self.save_file.as_mut() {
-            Some(sf) => sf.save_to_file(),
+            Some(sf) => match sf.save_to_file() {
+                Ok(()) => (),
+                Err(err) => error!("{}", err),
+            },
             None => (),
         }
     }
@@ -46,12 +54,18 @@ impl Render {
             size
         };
         let color_texture = Arc::new(screen.like_surface_texture(size, color_format));
+        let profiler = Profiler::new(&screen.device, &screen.queue);
         Render {
             screen: Arc::new(screen),
             ssaa,
             color_texture,
             color_format,
+            sample_count: 1,
+            msaa_texture: None,
+            depth_format: None,
+            depth_texture: None,
             save_file: None,
+            profiler: Some(Arc::new(Mutex::new(profiler))),
             handle: None,
             tx: None,
         }
@@ -95,10 +109,44 @@ impl Render {
         self
     }
 
+    /// Render into a multisampled color texture with `sample_count` samples, that is
+    /// resolved into the color texture at the end of each render-pass. Portable
+    /// values for `sample_count` are 1 and 4.
+    pub fn set_msaa(&mut self, sample_count: u32) -> &mut Self {
+        let size = self.to_extent3d();
+        self.sample_count = sample_count.max(1);
+        self.msaa_texture = self.to_msaa_texture(size);
+        // depth texture shall match the sample count of color texture.
+        self.depth_texture = self.to_depth_texture(size);
+        self
+    }
+
+    /// Attach a depth texture of `format` to color targets returned by
+    /// [Render::to_color_target].
+    pub fn set_depth(&mut self, format: wgpu::TextureFormat) -> &mut Self {
+        self.depth_format = Some(format);
+        self.depth_texture = self.to_depth_texture(self.to_extent3d());
+        self
+    }
+
+    /// Profile every frame submitted via [Render::submit], enabled by default.
+    /// Shall be called before [Render::start].
+    pub fn set_profiler(&mut self, enable: bool) -> &mut Self {
+        self.profiler = match enable {
+            true => {
+                let profiler = Profiler::new(&self.screen.device, &self.screen.queue);
+                Some(Arc::new(Mutex::new(profiler)))
+            }
+            false => None,
+        };
+        self
+    }
+
     pub fn start(&mut self) {
         let screen = Arc::clone(&self.screen);
+        let profiler = self.profiler.clone();
         let (tx, rx) = mpsc::channel();
-        self.handle = Some(thread::spawn(|| render_loop(screen, rx)));
+        self.handle = Some(thread::spawn(|| render_loop(screen, profiler, rx)));
         self.tx = Some(tx)
     }
 
@@ -127,23 +175,50 @@ impl Render {
             let texture = self.screen.like_surface_texture(size, self.color_format);
             Arc::new(texture)
         };
+        self.msaa_texture = self.to_msaa_texture(size);
+        self.depth_texture = self.to_depth_texture(size);
         self.save_file = match &self.save_file {
             Some(sf) => Some(sf.resize(&self.screen.device, size.into())),
             None => None,
         };
     }
 
+    /// Submit `encoder` and present the frame. When profiling, refer
+    /// [Render::to_profiler], the frame is profiled under the scope
+    /// `render:submit`, scopes recorded in `encoder` before calling submit are
+    /// part of the same frame.
     pub fn submit(&mut self, mut encoder: wgpu::CommandEncoder) -> Result<()> {
+        let mut cmds = vec![];
+
+        if let Some(profiler) = self.profiler.as_ref() {
+            let mut pre = self.to_command_encoder("render:profiler-begin");
+            err_at!(Fatal, profiler.lock())?.begin_scope("render:submit", &mut pre);
+            cmds.push(pre.finish());
+        }
+
         match self.save_file.as_ref() {
-            Some(sf) => sf.load_from_texture(
-                &mut encoder,
-                &self.screen.device,
-                &self.color_texture,
-            )?,
+            Some(sf) => {
+                let mut profiler = match self.profiler.as_ref() {
+                    Some(profiler) => Some(err_at!(Fatal, profiler.lock())?),
+                    None => None,
+                };
+                if let Some(p) = profiler.as_mut() {
+                    p.begin_scope("render:save-file", &mut encoder)
+                }
+                sf.load_from_texture(
+                    &mut encoder,
+                    &self.screen.device,
+                    &self.color_texture,
+                )?;
+                if let Some(p) = profiler.as_mut() {
+                    p.end_scope(&mut encoder)
+                }
+            }
             None => (),
         }
 
-        self.screen.queue.submit(vec![encoder.finish()]);
+        cmds.push(encoder.finish());
+        self.screen.queue.submit(cmds);
 
         match self.save_file.as_mut() {
             Some(sf) => sf.capture(&self.screen.device)?,
@@ -161,8 +236,23 @@ impl Render {
             None => (),
         }
 
+        if let Some(profiler) = self.profiler.as_ref() {
+            let mut post = self.to_command_encoder("render:profiler-end");
+            let mut profiler = err_at!(Fatal, profiler.lock())?;
+            profiler.end_scope(&mut post);
+            profiler.end_frame(&mut post);
+            self.screen.queue.submit(vec![post.finish()]);
+            profiler.submitted();
+            profiler.collect(&self.screen.device);
+        }
+
         Ok(())
     }
+
+    fn to_command_encoder(&self, label: &str) -> wgpu::CommandEncoder {
+        let desc = wgpu::CommandEncoderDescriptor { label: Some(label) };
+        self.screen.device.create_command_encoder(&desc)
+    }
 }
 
 impl Render {
@@ -178,6 +268,16 @@ impl Render {
         &self.screen.queue
     }
 
+    pub fn as_cache(&self) -> &Cache {
+        &self.screen.cache
+    }
+
+    /// Return the frame profiler, None if profiling is disabled. Refer
+    /// [Render::set_profiler].
+    pub fn to_profiler(&self) -> Option<Arc<Mutex<Profiler>>> {
+        self.profiler.clone()
+    }
+
     pub fn to_scale_factor(&self) -> f32 {
         self.ssaa * (self.screen.to_scale_factor() as f32)
     }
@@ -190,16 +290,50 @@ impl Render {
     }
 
     pub fn to_color_target(&self) -> ColorTarget {
-        let view = {
-            let desc = wgpu::TextureViewDescriptor::default();
-            self.color_texture.create_view(&desc)
+        let desc = wgpu::TextureViewDescriptor::default();
+
+        let view = self.color_texture.create_view(&desc);
+        let mut target = match self.msaa_texture.as_ref() {
+            Some(texture) => {
+                let mut target =
+                    ColorTarget::new(self.color_format, texture.create_view(&desc));
+                target.sample_count = self.sample_count;
+                target.resolve_target = Some(view);
+                target
+            }
+            None => ColorTarget::new(self.color_format, view),
         };
-        ColorTarget {
+        target.depth = match (self.depth_format, self.depth_texture.as_ref()) {
+            (Some(format), Some(texture)) => {
+                Some(DepthTarget { format, view: texture.create_view(&desc) })
+            }
+            _ => None,
+        };
+        target
+    }
+
+    /// Return the pipeline configuration for primitives rendering into color
+    /// targets returned by [Render::to_color_target].
+    pub fn to_pipeline_target(&self) -> PipelineTarget {
+        PipelineTarget {
             format: self.color_format,
-            view,
-            view_port: Viewport::default(),
+            sample_count: self.sample_count,
+            depth_format: self.depth_format,
+            ..PipelineTarget::default()
         }
     }
+
+    fn to_msaa_texture(&self, size: wgpu::Extent3d) -> Option<wgpu::Texture> {
+        match self.sample_count {
+            1 => None,
+            n => Some(self.screen.like_attachment_texture(size, self.color_format, n)),
+        }
+    }
+
+    fn to_depth_texture(&self, size: wgpu::Extent3d) -> Option<wgpu::Texture> {
+        let format = self.depth_format?;
+        Some(self.screen.like_attachment_texture(size, format, self.sample_count))
+    }
 }
 
 enum Request {
@@ -209,12 +343,19 @@ enum Request {
     },
 }
 
-fn render_loop(screen: Arc<Screen>, rx: mpsc::Receiver<Request>) -> Result<()> {
+fn render_loop(
+    screen: Arc<Screen>,
+    profiler: Option<Arc<Mutex<Profiler>>>,
+    rx: mpsc::Receiver<Request>,
+) -> Result<()> {
     let mut resp_txs: Vec<mpsc::Sender<bool>> = vec![];
 
     let mut surface_texture: Option<wgpu::SurfaceTexture> = None;
     let surface_format = screen.to_surface_config().format;
-    let mut load = load::Load::new(&screen.device, surface_format)?;
+    let mut load = {
+        let transforms = Transforms::empty();
+        load::Load::new(&screen.to_context(&transforms), surface_format)?
+    };
 
     debug!("entering the render_loop ..");
 
@@ -262,14 +403,18 @@ fn render_loop(screen: Arc<Screen>, rx: mpsc::Receiver<Request>) -> Result<()> {
             transforms: &Transforms::empty(),
             device: &screen.device,
             queue: &screen.queue,
+            cache: &screen.cache,
         };
-        let mut target = ColorTarget {
-            format: surface_format,
-            view: surface_view,
-            // TODO let is be same as other dom elements, should we ?
-            view_port: Viewport::default(),
-        };
+        // TODO let view_port be same as other dom elements, should we ?
+        if let Some(profiler) = profiler.as_ref() {
+            err_at!(Fatal, profiler.lock())?
+                .begin_scope("render_loop:present", &mut encoder);
+        }
+        let mut target = ColorTarget::new(surface_format, surface_view);
         load.redraw(&context, &mut encoder, &mut target)?;
+        if let Some(profiler) = profiler.as_ref() {
+            err_at!(Fatal, profiler.lock())?.end_scope(&mut encoder);
+        }
         screen.queue.submit(vec![encoder.finish()]);
 
         for tx in resp_txs.drain(..) {
@@ -1,4 +1,4 @@
-use log::{error, info};
+use log::info;
 
 use std::path;
 
@@ -138,74 +138,57 @@ impl SaveFile {
 }
 
 impl SaveFile {
-    pub fn save_to_file(&mut self) {
+    pub fn save_to_file(&mut self) -> Result<()> {
         match self.typ {
             Type::Bmp => self.save_to_bmp(),
             Type::Gif => self.save_to_gif(),
         }
     }
 
-    fn save_to_bmp(&mut self) {
-        match self.frames.pop() {
-            Some(frame) => {
-                let imgbuf: image::ImageBuffer<image::Rgba<u8>, Vec<u8>> =
-                    image::ImageBuffer::from_vec(
-                        self.extent.width,
-                        self.extent.height,
-                        frame,
-                    )
-                    .unwrap();
-
-                match imgbuf.save(&self.loc) {
-                    Ok(_) => info!("Saving to bitmap file, {:?}", self.loc),
-                    Err(err) => {
-                        error!("Fail saving to bitmap file, {:?}, {}", self.loc, err)
-                    }
-                }
-            }
-            None => error!("No frames to save to bitmap"),
-        }
+    fn save_to_bmp(&mut self) -> Result<()> {
+        let frame = match self.frames.pop() {
+            Some(frame) => frame,
+            None => err_at!(Fatal, msg: "no frames to save to bitmap {:?}", self.loc)?,
+        };
+        let imgbuf: image::ImageBuffer<image::Rgba<u8>, Vec<u8>> =
+            match image::ImageBuffer::from_vec(
+                self.extent.width,
+                self.extent.height,
+                frame,
+            ) {
+                Some(imgbuf) => imgbuf,
+                None => err_at!(Fatal, msg: "invalid image buffer {:?}", self.extent)?,
+            };
+
+        err_at!(IOError, imgbuf.save(&self.loc), "file:{:?}", self.loc)?;
+        info!("Saving to bitmap file, {:?}", self.loc);
+
+        Ok(())
     }
 
-    fn save_to_gif(&mut self) {
+    fn save_to_gif(&mut self) -> Result<()> {
         use gif::{Encoder, Frame, Repeat};
 
         let wgpu::Extent3d { width, height, .. } = self.extent.clone();
         let (width, height) = (width as u16, height as u16);
 
-        let mut image = match std::fs::File::create(&self.loc) {
-            Ok(f) => f,
-            Err(err) => {
-                error!("Fail creating Gif file {:?}, {}", self.loc, err);
-                return;
-            }
-        };
-        let mut encoder = match Encoder::new(&mut image, width, height, &[]) {
-            Ok(encoder) => encoder,
-            Err(err) => {
-                error!("Fail creating Gif encoder {:?} {}", self.loc, err);
-                return;
-            }
-        };
-        match encoder.set_repeat(Repeat::Infinite) {
-            Ok(()) => (),
-            Err(err) => {
-                error!("Fail Gif encoder::set_repeat {}", err);
-                return;
-            }
-        }
+        let mut image =
+            err_at!(IOError, std::fs::File::create(&self.loc), "file:{:?}", self.loc)?;
+        let mut encoder = err_at!(
+            Fatal,
+            Encoder::new(&mut image, width, height, &[]),
+            "gif encoder {:?}",
+            self.loc
+        )?;
+        err_at!(Fatal, encoder.set_repeat(Repeat::Infinite))?;
 
         for mut frame in self.frames.drain(..) {
             let frame = Frame::from_rgba_speed(width, height, &mut frame, 30);
-            match encoder.write_frame(&frame) {
-                Ok(()) => (),
-                Err(err) => {
-                    error!("Fail writing to Gif frame {}", err);
-                    return;
-                }
-            }
+            err_at!(IOError, encoder.write_frame(&frame), "file:{:?}", self.loc)?;
         }
 
         info!("Saving to gif file, {:?}", self.loc);
+
+        Ok(())
     }
 }
@@ -3,13 +3,19 @@ use winit::{dpi, window::Window};
 
 use std::sync::Arc;
 
-use crate::{util::Spinlock, Config, Error, Result};
+use crate::{util::Spinlock, Cache, Config, Context, Error, Result, Transforms};
 
 pub struct Screen {
     pub name: String,
     pub surface: wgpu::Surface,
-    pub device: wgpu::Device,
-    pub queue: wgpu::Queue,
+    pub device: Arc<wgpu::Device>,
+    pub queue: Arc<wgpu::Queue>,
+    /// Cache of wgpu objects created with `device`, shared by screens sharing
+    /// the device.
+    pub cache: Arc<Cache>,
+    instance: Arc<wgpu::Instance>,
+    adapter: Arc<wgpu::Adapter>,
+    present_mode: wgpu::PresentMode,
     state: Spinlock<Arc<State>>,
 }
 
@@ -22,8 +28,6 @@ impl Screen {
     /// * `win` abstracts a window instance.
     /// * `config` is configuration parameter for working with this crate.
     pub async fn new(name: String, win: &Window, config: Config) -> Result<Screen> {
-        let size: dpi::PhysicalSize<u32> = win.inner_size().into();
-
         let instance = wgpu::Instance::new(wgpu::Backends::all());
         let surface = unsafe { instance.create_surface(&win) };
         let adapter = {
@@ -33,15 +37,6 @@ impl Screen {
                 None => err_at!(Wgpu, msg: "can't find matching adapter")?,
             }
         };
-        let surface_format = surface.get_preferred_format(&adapter).unwrap();
-
-        info!(
-            "Surface created with size {}x{} format {:?} scale_factor:{}",
-            size.width,
-            size.height,
-            surface_format,
-            win.scale_factor()
-        );
 
         let desc = wgpu::DeviceDescriptor {
             label: Some(&name),
@@ -54,12 +49,63 @@ impl Screen {
         };
         device.on_uncaptured_error(uncaptured_error_handler);
 
+        Screen::with_surface(
+            name,
+            win,
+            surface,
+            Arc::new(instance),
+            Arc::new(adapter),
+            (Arc::new(device), Arc::new(queue), Arc::new(Cache::default())),
+            config.present_mode,
+        )
+    }
+
+    /// Create a screen for another window `win`, sharing the adapter, device and
+    /// queue with this screen. Resources created using this screen's device can be
+    /// used with the new screen, useful for applications with several windows.
+    pub fn to_shared_screen(&self, name: String, win: &Window) -> Result<Screen> {
+        let surface = unsafe { self.instance.create_surface(&win) };
+        Screen::with_surface(
+            name,
+            win,
+            surface,
+            Arc::clone(&self.instance),
+            Arc::clone(&self.adapter),
+            (Arc::clone(&self.device), Arc::clone(&self.queue), Arc::clone(&self.cache)),
+            self.present_mode,
+        )
+    }
+
+    fn with_surface(
+        name: String,
+        win: &Window,
+        surface: wgpu::Surface,
+        instance: Arc<wgpu::Instance>,
+        adapter: Arc<wgpu::Adapter>,
+        (device, queue, cache): (Arc<wgpu::Device>, Arc<wgpu::Queue>, Arc<Cache>),
+        present_mode: wgpu::PresentMode,
+    ) -> Result<Screen> {
+        let size: dpi::PhysicalSize<u32> = win.inner_size();
+
+        let surface_format = match surface.get_preferred_format(&adapter) {
+            Some(format) => format,
+            None => err_at!(Wgpu, msg: "surface {:?} not supported by adapter", name)?,
+        };
+
+        info!(
+            "Surface created with size {}x{} format {:?} scale_factor:{}",
+            size.width,
+            size.height,
+            surface_format,
+            win.scale_factor()
+        );
+
         let surface_config = wgpu::SurfaceConfiguration {
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             format: surface_format,
             width: size.width,
             height: size.height,
-            present_mode: config.present_mode,
+            present_mode,
         };
         surface.configure(&device, &surface_config);
 
@@ -68,6 +114,10 @@ impl Screen {
             surface,
             device,
             queue,
+            cache,
+            instance,
+            adapter,
+            present_mode,
             state: Spinlock::new(Arc::new(State {
                 surface_config,
                 scale_factor: win.scale_factor(),
@@ -77,6 +127,17 @@ impl Screen {
         Ok(val)
     }
 
+    /// Return context for creating and rendering primitives with this screen's
+    /// device.
+    pub fn to_context<'a>(&'a self, transforms: &'a Transforms) -> Context<'a> {
+        Context {
+            transforms,
+            device: &self.device,
+            queue: &self.queue,
+            cache: &self.cache,
+        }
+    }
+
     pub fn resize(&self, new_size: dpi::PhysicalSize<u32>, scale_factor: Option<f64>) {
         if new_size.width <= 0 && new_size.height <= 0 {
             warn!("screen-resize {:?}", new_size);
@@ -140,6 +201,26 @@ impl Screen {
         };
         self.device.create_texture(&desc)
     }
+
+    /// Create a render-attachment texture, like multisampled color texture or
+    /// depth texture, that is not sampled or copied from.
+    pub fn like_attachment_texture(
+        &self,
+        size: wgpu::Extent3d,
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+    ) -> wgpu::Texture {
+        let desc = wgpu::TextureDescriptor {
+            label: Some("like-attachment-texture"),
+            size,
+            mip_level_count: 1,
+            sample_count,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        };
+        self.device.create_texture(&desc)
+    }
 }
 
 impl Screen {
@@ -0,0 +1,447 @@
+use naga::{
+    proc::Layouter, ArraySize, ConstantInner, ImageClass, ImageDimension, ScalarKind,
+    ScalarValue, StorageAccess, StorageClass, StorageFormat, TypeInner,
+};
+
+use std::{mem, num::NonZeroU32, num::NonZeroU64, ops};
+
+use crate::{shader::Shader, Error, Result};
+
+/// Memory layout of a WGSL struct, refer [Shader::to_struct_layout].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructLayout {
+    pub name: String,
+    /// Size in bytes, including the trailing padding.
+    pub size: u32,
+    pub alignment: u32,
+    pub members: Vec<Member>,
+}
+
+/// Member of a WGSL struct.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Member {
+    pub name: String,
+    pub ty: String,
+    pub offset: u32,
+    pub size: u32,
+}
+
+/// Vertex buffer layout generated from vertex-stage inputs, refer
+/// [Shader::to_vertex_layout].
+#[derive(Clone, Debug, PartialEq)]
+pub struct VertexLayout {
+    pub array_stride: wgpu::BufferAddress,
+    pub attributes: Vec<wgpu::VertexAttribute>,
+}
+
+impl VertexLayout {
+    pub fn to_vertex_buffer_layout(
+        &self,
+        step_mode: wgpu::VertexStepMode,
+    ) -> wgpu::VertexBufferLayout<'_> {
+        wgpu::VertexBufferLayout {
+            array_stride: self.array_stride,
+            step_mode,
+            attributes: &self.attributes,
+        }
+    }
+
+    /// Check that Rust vertex type `T` matches the array stride of this layout,
+    /// and that each `(location, offset)` in `offsets` matches the offset of
+    /// the attribute at that shader location. Every attribute in this layout
+    /// shall be listed. Use [std::mem::offset_of] to compute the rust offsets.
+    pub fn check<T>(&self, offsets: &[(u32, usize)]) -> Result<()>
+    where
+        T: bytemuck::Pod,
+    {
+        let size = mem::size_of::<T>() as wgpu::BufferAddress;
+        if size != self.array_stride {
+            err_at!(
+                Invalid,
+                msg: "vertex {} bytes in rust, {} bytes in shader",
+                size,
+                self.array_stride
+            )?
+        }
+
+        for attr in self.attributes.iter() {
+            let location = attr.shader_location;
+            let offset = match offsets.iter().find(|(loc, _)| *loc == location) {
+                Some((_, offset)) => *offset as wgpu::BufferAddress,
+                None => {
+                    err_at!(Invalid, msg: "no rust offset for location {}", location)?
+                }
+            };
+            if offset != attr.offset {
+                err_at!(
+                    Invalid,
+                    msg: "location {} is at {} in rust, at {} in shader, format {:?}",
+                    location,
+                    offset,
+                    attr.offset,
+                    attr.format
+                )?
+            }
+        }
+        for (location, _) in offsets.iter() {
+            if !self.attributes.iter().any(|a| a.shader_location == *location) {
+                err_at!(Invalid, msg: "no location {} in shader", location)?
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Shader {
+    /// Return bind-group numbers used by this shader, in sorted order.
+    pub fn to_groups(&self) -> Vec<u32> {
+        let mut groups: Vec<u32> =
+            self.to_bindings().into_iter().map(|b| b.group).collect();
+        groups.dedup();
+        groups
+    }
+
+    /// Generate layout entries for all bindings in `group`. Textures are
+    /// assumed filterable, and samplers filtering, unless they are depth
+    /// textures and comparison samplers. Entries are visible to all stages
+    /// defined in this shader, not just the stages using them, so that the
+    /// layout stays compatible when shader code moves between stages.
+    pub fn to_bind_group_layout_entries(
+        &self,
+        group: u32,
+    ) -> Result<Vec<wgpu::BindGroupLayoutEntry>> {
+        let module = &self.module;
+        let visibility = self.to_stages();
+        let mut entries = vec![];
+
+        for (_, var) in module.global_variables.iter() {
+            let rb = match &var.binding {
+                Some(rb) if rb.group == group => rb,
+                _ => continue,
+            };
+            let (ty, count) = match &module.types[var.ty].inner {
+                TypeInner::Array { base, size, .. }
+                    if var.class == StorageClass::Handle =>
+                {
+                    match size {
+                        ArraySize::Constant(c) => match &module.constants[*c].inner {
+                            ConstantInner::Scalar {
+                                value: ScalarValue::Uint(n), ..
+                            } => (*base, NonZeroU32::new(*n as u32)),
+                            ConstantInner::Scalar {
+                                value: ScalarValue::Sint(n), ..
+                            } => (*base, NonZeroU32::new(*n as u32)),
+                            _ => (*base, None),
+                        },
+                        ArraySize::Dynamic => err_at!(
+                            Invalid,
+                            msg: "binding {}:{} is unsized binding array",
+                            rb.group,
+                            rb.binding
+                        )?,
+                    }
+                }
+                _ => (var.ty, None),
+            };
+
+            let inner = &module.types[ty].inner;
+            let ty = match (var.class, inner) {
+                (StorageClass::Uniform, inner) => wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: to_binding_size(inner, module),
+                },
+                (StorageClass::Storage { access }, inner) => wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Storage {
+                        read_only: !access.contains(StorageAccess::STORE),
+                    },
+                    has_dynamic_offset: false,
+                    min_binding_size: to_binding_size(inner, module),
+                },
+                (StorageClass::Handle, TypeInner::Image { dim, arrayed, class }) => {
+                    to_texture_binding(*dim, *arrayed, *class)?
+                }
+                (StorageClass::Handle, TypeInner::Sampler { comparison }) => {
+                    wgpu::BindingType::Sampler(match comparison {
+                        true => wgpu::SamplerBindingType::Comparison,
+                        false => wgpu::SamplerBindingType::Filtering,
+                    })
+                }
+                (class, _) => err_at!(
+                    Invalid,
+                    msg: "binding {}:{} in {:?} cannot be laid out",
+                    rb.group,
+                    rb.binding,
+                    class
+                )?,
+            };
+
+            entries.push(wgpu::BindGroupLayoutEntry {
+                binding: rb.binding,
+                visibility,
+                ty,
+                count,
+            })
+        }
+        entries.sort_by_key(|e| e.binding);
+
+        Ok(entries)
+    }
+
+    /// Create bind-group layout for `group`, refer
+    /// [Shader::to_bind_group_layout_entries].
+    pub fn to_bind_group_layout(
+        &self,
+        device: &wgpu::Device,
+        group: u32,
+        label: Option<&str>,
+    ) -> Result<wgpu::BindGroupLayout> {
+        let entries = self.to_bind_group_layout_entries(group)?;
+        let desc = wgpu::BindGroupLayoutDescriptor { label, entries: &entries };
+        Ok(device.create_bind_group_layout(&desc))
+    }
+
+    /// Generate vertex buffer layout from the location inputs of vertex
+    /// `entry_point`. Attributes are packed into a single buffer, in the
+    /// order of their locations.
+    pub fn to_vertex_layout(&self, entry_point: &str) -> Result<VertexLayout> {
+        self.to_vertex_layout_at(entry_point, 0..u32::MAX)
+    }
+
+    /// Same as [Shader::to_vertex_layout], but only for inputs whose location
+    /// falls within `locations`. Useful when vertex inputs are split across
+    /// several buffers, like per-vertex and per-instance buffers.
+    pub fn to_vertex_layout_at(
+        &self,
+        entry_point: &str,
+        locations: ops::Range<u32>,
+    ) -> Result<VertexLayout> {
+        let ep = self.to_entry_points().into_iter().find(|ep| ep.name == entry_point);
+        let ep = match ep {
+            Some(ep) if ep.stage == wgpu::ShaderStages::VERTEX => ep,
+            Some(_) => err_at!(Invalid, msg: "{:?} is not a vertex stage", entry_point)?,
+            None => err_at!(Invalid, msg: "no entry point {:?}", entry_point)?,
+        };
+
+        let mut offset = 0;
+        let mut attributes = vec![];
+        for input in ep.inputs.iter().filter(|i| locations.contains(&i.location)) {
+            let format = match to_vertex_format(&input.ty) {
+                Some(format) => format,
+                None => err_at!(
+                    Invalid,
+                    msg: "vertex input {:?} has type {}",
+                    input.name,
+                    input.ty
+                )?,
+            };
+            attributes.push(wgpu::VertexAttribute {
+                format,
+                offset,
+                shader_location: input.location,
+            });
+            offset += format.size();
+        }
+
+        Ok(VertexLayout { array_stride: offset, attributes })
+    }
+
+    /// Return memory layout of struct `name`, as laid out for uniform and
+    /// storage buffers.
+    pub fn to_struct_layout(&self, name: &str) -> Result<StructLayout> {
+        let module = &self.module;
+
+        let mut layouter = Layouter::default();
+        err_at!(Fatal, layouter.update(&module.types, &module.constants))?;
+
+        for (handle, typ) in module.types.iter() {
+            match &typ.inner {
+                TypeInner::Struct { members, span }
+                    if typ.name.as_deref() == Some(name) =>
+                {
+                    let members = members
+                        .iter()
+                        .map(|m| Member {
+                            name: m.name.clone().unwrap_or_default(),
+                            ty: self.to_type_name(m.ty),
+                            offset: m.offset,
+                            size: layouter[m.ty].size,
+                        })
+                        .collect();
+                    return Ok(StructLayout {
+                        name: name.to_string(),
+                        size: *span,
+                        alignment: layouter[handle].alignment.get(),
+                        members,
+                    });
+                }
+                _ => (),
+            }
+        }
+
+        err_at!(Invalid, msg: "no struct {:?} in shader", name)
+    }
+
+    /// Check that Rust type `T` matches the size of WGSL struct `name`, and
+    /// that each `(member, offset)` in `offsets` matches the offset of WGSL
+    /// member. Use [std::mem::offset_of] to compute the rust offsets.
+    pub fn check_struct<T>(&self, name: &str, offsets: &[(&str, usize)]) -> Result<()>
+    where
+        T: bytemuck::Pod,
+    {
+        let layout = self.to_struct_layout(name)?;
+
+        let size = mem::size_of::<T>();
+        if size != (layout.size as usize) {
+            err_at!(
+                Invalid,
+                msg: "struct {} is {} bytes in rust, {} bytes in shader, aligned to {}",
+                name,
+                size,
+                layout.size,
+                layout.alignment
+            )?
+        }
+
+        for (member, offset) in offsets.iter() {
+            match layout.members.iter().find(|m| m.name == *member) {
+                Some(m) if (m.offset as usize) == *offset => (),
+                Some(m) => err_at!(
+                    Invalid,
+                    msg: "{}.{} is at {} in rust, at {} in shader, type {}",
+                    name,
+                    member,
+                    offset,
+                    m.offset,
+                    m.ty
+                )?,
+                None => err_at!(Invalid, msg: "no member {}.{} in shader", name, member)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn to_binding_size(inner: &TypeInner, module: &naga::Module) -> Option<NonZeroU64> {
+    NonZeroU64::new(inner.span(&module.constants).into())
+}
+
+fn to_texture_binding(
+    dim: ImageDimension,
+    arrayed: bool,
+    class: ImageClass,
+) -> Result<wgpu::BindingType> {
+    use wgpu::TextureViewDimension as Tvd;
+
+    let view_dimension = match (dim, arrayed) {
+        (ImageDimension::D1, false) => Tvd::D1,
+        (ImageDimension::D2, false) => Tvd::D2,
+        (ImageDimension::D2, true) => Tvd::D2Array,
+        (ImageDimension::D3, false) => Tvd::D3,
+        (ImageDimension::Cube, false) => Tvd::Cube,
+        (ImageDimension::Cube, true) => Tvd::CubeArray,
+        (dim, arrayed) => {
+            err_at!(Invalid, msg: "texture {:?} arrayed:{} not supported", dim, arrayed)?
+        }
+    };
+
+    let ty = match class {
+        ImageClass::Sampled { kind, multi } => wgpu::BindingType::Texture {
+            sample_type: match kind {
+                ScalarKind::Float => wgpu::TextureSampleType::Float { filterable: true },
+                ScalarKind::Sint => wgpu::TextureSampleType::Sint,
+                ScalarKind::Uint => wgpu::TextureSampleType::Uint,
+                ScalarKind::Bool => err_at!(Invalid, msg: "bool texture")?,
+            },
+            view_dimension,
+            multisampled: multi,
+        },
+        ImageClass::Depth { multi } => wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Depth,
+            view_dimension,
+            multisampled: multi,
+        },
+        ImageClass::Storage { format, access } => wgpu::BindingType::StorageTexture {
+            access: match (
+                access.contains(StorageAccess::LOAD),
+                access.contains(StorageAccess::STORE),
+            ) {
+                (true, true) => wgpu::StorageTextureAccess::ReadWrite,
+                (false, true) => wgpu::StorageTextureAccess::WriteOnly,
+                (_, false) => wgpu::StorageTextureAccess::ReadOnly,
+            },
+            format: to_texture_format(format),
+            view_dimension,
+        },
+    };
+
+    Ok(ty)
+}
+
+fn to_texture_format(format: StorageFormat) -> wgpu::TextureFormat {
+    use wgpu::TextureFormat as Tf;
+
+    match format {
+        StorageFormat::R8Unorm => Tf::R8Unorm,
+        StorageFormat::R8Snorm => Tf::R8Snorm,
+        StorageFormat::R8Uint => Tf::R8Uint,
+        StorageFormat::R8Sint => Tf::R8Sint,
+        StorageFormat::R16Uint => Tf::R16Uint,
+        StorageFormat::R16Sint => Tf::R16Sint,
+        StorageFormat::R16Float => Tf::R16Float,
+        StorageFormat::Rg8Unorm => Tf::Rg8Unorm,
+        StorageFormat::Rg8Snorm => Tf::Rg8Snorm,
+        StorageFormat::Rg8Uint => Tf::Rg8Uint,
+        StorageFormat::Rg8Sint => Tf::Rg8Sint,
+        StorageFormat::R32Uint => Tf::R32Uint,
+        StorageFormat::R32Sint => Tf::R32Sint,
+        StorageFormat::R32Float => Tf::R32Float,
+        StorageFormat::Rg16Uint => Tf::Rg16Uint,
+        StorageFormat::Rg16Sint => Tf::Rg16Sint,
+        StorageFormat::Rg16Float => Tf::Rg16Float,
+        StorageFormat::Rgba8Unorm => Tf::Rgba8Unorm,
+        StorageFormat::Rgba8Snorm => Tf::Rgba8Snorm,
+        StorageFormat::Rgba8Uint => Tf::Rgba8Uint,
+        StorageFormat::Rgba8Sint => Tf::Rgba8Sint,
+        StorageFormat::Rgb10a2Unorm => Tf::Rgb10a2Unorm,
+        StorageFormat::Rg11b10Float => Tf::Rg11b10Float,
+        StorageFormat::Rg32Uint => Tf::Rg32Uint,
+        StorageFormat::Rg32Sint => Tf::Rg32Sint,
+        StorageFormat::Rg32Float => Tf::Rg32Float,
+        StorageFormat::Rgba16Uint => Tf::Rgba16Uint,
+        StorageFormat::Rgba16Sint => Tf::Rgba16Sint,
+        StorageFormat::Rgba16Float => Tf::Rgba16Float,
+        StorageFormat::Rgba32Uint => Tf::Rgba32Uint,
+        StorageFormat::Rgba32Sint => Tf::Rgba32Sint,
+        StorageFormat::Rgba32Float => Tf::Rgba32Float,
+    }
+}
+
+// Vertex format for WGSL type name `ty`, as returned by Shader::to_type_name.
+fn to_vertex_format(ty: &str) -> Option<wgpu::VertexFormat> {
+    use wgpu::VertexFormat as Vf;
+
+    let format = match ty {
+        "f32" => Vf::Float32,
+        "vec2<f32>" => Vf::Float32x2,
+        "vec3<f32>" => Vf::Float32x3,
+        "vec4<f32>" => Vf::Float32x4,
+        "u32" => Vf::Uint32,
+        "vec2<u32>" => Vf::Uint32x2,
+        "vec3<u32>" => Vf::Uint32x3,
+        "vec4<u32>" => Vf::Uint32x4,
+        "i32" => Vf::Sint32,
+        "vec2<i32>" => Vf::Sint32x2,
+        "vec3<i32>" => Vf::Sint32x3,
+        "vec4<i32>" => Vf::Sint32x4,
+        _ => return None,
+    };
+
+    Some(format)
+}
+
+#[cfg(test)]
+#[path = "layout_test.rs"]
+mod layout_test;
@@ -0,0 +1,162 @@
+use bytemuck::{Pod, Zeroable};
+
+use super::*;
+
+const CIRCLE_WGSL: &str =
+    concat!(env!("CARGO_MANIFEST_DIR"), "/src/primv/circle/circle.wgsl");
+
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+struct Attributes {
+    fg: [f32; 4],
+    bg: [f32; 4],
+    center: [f32; 2],
+    radius: f32,
+    width: f32,
+    fill: u32,
+    _padding: [f32; 3],
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+struct BadAttributes {
+    fg: [f32; 4],
+    bg: [f32; 4],
+    radius: f32,
+    center: [f32; 2],
+    width: f32,
+    fill: u32,
+    _padding: [f32; 3],
+}
+
+// per-instance inputs of batch.wgsl, same size but radius and width moved
+// after the colors.
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+struct ReorderedInstance {
+    center: [f32; 2],
+    fg: [f32; 4],
+    bg: [f32; 4],
+    radius: f32,
+    width: f32,
+    fill: u32,
+    kind: u32,
+}
+
+#[test]
+fn test_layout_struct() {
+    let shader = Shader::from_file(CIRCLE_WGSL).unwrap();
+
+    let layout = shader.to_struct_layout("Attributes").unwrap();
+    assert_eq!((layout.size, layout.alignment), (64, 16));
+    let members: Vec<(&str, u32, u32)> =
+        layout.members.iter().map(|m| (m.name.as_str(), m.offset, m.size)).collect();
+    assert_eq!(
+        members,
+        vec![
+            ("fg", 0, 16),
+            ("bg", 16, 16),
+            ("center", 32, 8),
+            ("radius", 40, 4),
+            ("width", 44, 4),
+            ("fill", 48, 4)
+        ]
+    );
+    assert!(shader.to_struct_layout("Missing").is_err());
+
+    let offsets = [
+        ("fg", mem::offset_of!(Attributes, fg)),
+        ("bg", mem::offset_of!(Attributes, bg)),
+        ("center", mem::offset_of!(Attributes, center)),
+        ("radius", mem::offset_of!(Attributes, radius)),
+        ("width", mem::offset_of!(Attributes, width)),
+        ("fill", mem::offset_of!(Attributes, fill)),
+    ];
+    shader.check_struct::<Attributes>("Attributes", &offsets).unwrap();
+
+    let offsets = [("center", mem::offset_of!(BadAttributes, center))];
+    let err = shader.check_struct::<BadAttributes>("Attributes", &offsets).unwrap_err();
+    assert!(err.to_string().contains("Attributes.center is at 36"), "{}", err);
+
+    let err = shader.check_struct::<[f32; 15]>("Attributes", &[]).unwrap_err();
+    assert!(err.to_string().contains("60 bytes in rust, 64 bytes"), "{}", err);
+}
+
+#[test]
+fn test_layout_bind_group() {
+    use wgpu::{BindingType, BufferBindingType, ShaderStages};
+
+    let shader = Shader::from_file(CIRCLE_WGSL).unwrap();
+    assert_eq!(shader.to_groups(), vec![0]);
+
+    let entries = shader.to_bind_group_layout_entries(0).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[1].binding, 1);
+    // visible to all stages in the shader, though used only by fs_main.
+    assert_eq!(entries[1].visibility, ShaderStages::VERTEX | ShaderStages::FRAGMENT);
+    assert_eq!(
+        entries[1].ty,
+        BindingType::Buffer {
+            ty: BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: NonZeroU64::new(64),
+        }
+    );
+    assert!(shader.to_bind_group_layout_entries(1).unwrap().is_empty());
+
+    let shader = Shader::from_wgsl(include_str!("../primv/load/load.wgsl")).unwrap();
+    let entries = shader.to_bind_group_layout_entries(0).unwrap();
+    assert_eq!(
+        entries[0].ty,
+        BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension: wgpu::TextureViewDimension::D2,
+            multisampled: false,
+        }
+    );
+    assert_eq!(entries[1].ty, BindingType::Sampler(wgpu::SamplerBindingType::Filtering));
+}
+
+#[test]
+fn test_layout_vertex() {
+    let shader = Shader::from_wgsl(include_str!("../primv/load/load.wgsl")).unwrap();
+
+    let layout = shader.to_vertex_layout("vs_main").unwrap();
+    assert_eq!(layout.array_stride, 24);
+    assert_eq!(
+        layout.attributes,
+        wgpu::vertex_attr_array![0 => Float32x4, 1 => Float32x2].to_vec()
+    );
+    layout.check::<[f32; 6]>(&[(0, 0), (1, 16)]).unwrap();
+    assert!(layout.check::<[f32; 8]>(&[(0, 0), (1, 16)]).is_err());
+    let err = layout.check::<[f32; 6]>(&[(0, 8), (1, 0)]).unwrap_err();
+    assert!(err.to_string().contains("location 0 is at 8 in rust, at 0"), "{}", err);
+    let err = layout.check::<[f32; 6]>(&[(0, 0)]).unwrap_err();
+    assert!(err.to_string().contains("no rust offset for location 1"), "{}", err);
+    let err = layout.check::<[f32; 6]>(&[(0, 0), (1, 16), (2, 24)]).unwrap_err();
+    assert!(err.to_string().contains("no location 2 in shader"), "{}", err);
+
+    let layout = shader.to_vertex_layout_at("vs_main", 1..2).unwrap();
+    assert_eq!(layout.array_stride, 8);
+    assert_eq!(layout.attributes, wgpu::vertex_attr_array![1 => Float32x2].to_vec());
+
+    // per-instance inputs of batch.wgsl, laid out in their own buffer.
+    let loc = concat!(env!("CARGO_MANIFEST_DIR"), "/src/primv/batch/batch.wgsl");
+    let batch = Shader::from_file(loc).unwrap();
+    let layout = batch.to_vertex_layout_at("vs_main", 1..u32::MAX).unwrap();
+    assert_eq!((layout.array_stride, layout.attributes[0].offset), (56, 0));
+    let offsets = [
+        (1, mem::offset_of!(ReorderedInstance, center)),
+        (2, mem::offset_of!(ReorderedInstance, radius)),
+        (3, mem::offset_of!(ReorderedInstance, width)),
+        (4, mem::offset_of!(ReorderedInstance, fg)),
+        (5, mem::offset_of!(ReorderedInstance, bg)),
+        (6, mem::offset_of!(ReorderedInstance, fill)),
+        (7, mem::offset_of!(ReorderedInstance, kind)),
+    ];
+    let err = layout.check::<ReorderedInstance>(&offsets).unwrap_err();
+    assert!(err.to_string().contains("location 2 is at 40 in rust, at 8"), "{}", err);
+
+    assert!(shader.to_vertex_layout("fs_main").is_err());
+    assert!(shader.to_vertex_layout("missing").is_err());
+}
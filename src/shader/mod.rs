@@ -0,0 +1,35 @@
+//! Package implement tooling for WGSL shaders, uses [naga] as backend.
+//!
+//! Start with [Shader] type. Constructing a `Shader` shall parse and validate
+//! WGSL text, reporting errors with line and column, subsequently the shader
+//! can be reflected for its entry points and resource bindings, refer
+//! [Shader::to_entry_points] and [Shader::to_bindings], and translated to
+//! SPIR-V, GLSL and MSL. This does not require a GPU, hence can be used to
+//! check shaders on CI machines.
+//!
+//! Reflected shaders can generate bind-group layouts and vertex buffer
+//! layouts, refer [Shader::to_bind_group_layout] and
+//! [Shader::to_vertex_layout]. Rust types uploaded into uniform and storage
+//! buffers shall be checked against their WGSL structs using
+//! [Shader::check_struct], and vertex types against their vertex layouts using
+//! [VertexLayout::check], before creating the pipeline.
+//!
+//! Shader source can include shared snippets and can be specialized with
+//! defines, using [Preprocessor], compiled permutations of a shader can be
+//! cached using [Permutations].
+//!
+//! For development, [ShaderReload] can watch shader source files and reload
+//! them when they change, refer `hot_reload` method on primitives. Wireframe
+//! files and mesh textures loaded via `Mesh::set_texture_file` are reloaded as
+//! well. Fonts are not watched, glyph boxes are built from in-memory glyphs
+//! that carry no source file.
+
+mod layout;
+mod preprocess;
+mod reflect;
+mod reload;
+
+pub use layout::{Member, StructLayout, VertexLayout};
+pub use preprocess::{Permutation, Permutations, Preprocessor, Source, SNIPPETS};
+pub use reflect::{Binding, EntryPoint, Location, Shader};
+pub use reload::{catch_validation, ShaderReload, SNIPPETS_DIR};
@@ -0,0 +1,438 @@
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, HashMap},
+    fs, path,
+    sync::{Arc, Mutex},
+};
+
+use crate::{shader::Shader, Error, Result};
+
+/// Snippets shipped with this package, can be included by name.
+pub const SNIPPETS: [(&str, &str); 3] = [
+    ("transforms.wgsl", include_str!("wgsl/transforms.wgsl")),
+    ("color.wgsl", include_str!("wgsl/color.wgsl")),
+    ("sdf.wgsl", include_str!("wgsl/sdf.wgsl")),
+];
+
+/// Type implement a preprocessor for WGSL source.
+///
+/// Following directives are supported, each directive must be on its own line.
+///
+/// * `#include "name"`, include snippet `name`, snippets are looked up from
+///   the registered snippets, refer [Preprocessor::add_snippet], and then
+///   from the search paths, refer [Preprocessor::add_path]. A snippet is
+///   included only once, subsequent includes are ignored.
+/// * `#define NAME [value]` and `#undef NAME`.
+/// * `#ifdef NAME`, `#ifndef NAME`, `#else`, `#endif`.
+///
+/// Identifiers in the source that are defined with a value are replaced by
+/// their value, which can be used to inject constants from application.
+#[derive(Clone)]
+pub struct Preprocessor {
+    snippets: BTreeMap<String, String>,
+    paths: Vec<path::PathBuf>,
+    defines: BTreeMap<String, String>,
+}
+
+impl Default for Preprocessor {
+    fn default() -> Preprocessor {
+        let snippets = SNIPPETS
+            .iter()
+            .map(|(name, text)| (name.to_string(), text.to_string()))
+            .collect();
+
+        Preprocessor { snippets, paths: vec![], defines: BTreeMap::new() }
+    }
+}
+
+struct State {
+    defines: BTreeMap<String, String>,
+    included: BTreeSet<String>,
+    source: Source,
+}
+
+/// Preprocessed WGSL text, refer [Preprocessor::to_source]. Included snippets
+/// shift the line numbers of the text, hence the origin of every line is
+/// remembered to report errors against the file and line they came from.
+#[derive(Clone, Debug, Default)]
+pub struct Source {
+    pub text: String,
+    names: Vec<String>,
+    // (index into names, line number) for each line in text.
+    origins: Vec<(usize, usize)>,
+    files: Vec<path::PathBuf>,
+}
+
+impl Source {
+    /// Return files read while processing, the source file itself and files
+    /// included from search paths, in the order they are read. Registered
+    /// snippets are not files, hence not listed.
+    pub fn as_files(&self) -> &[path::PathBuf] {
+        &self.files
+    }
+
+    /// Return source name and line number for `line` in preprocessed text,
+    /// line numbers start from 1.
+    pub fn to_origin(&self, line: usize) -> Option<(&str, usize)> {
+        let (name, lineno) = self.origins.get(line.checked_sub(1)?)?;
+        Some((self.names[*name].as_str(), *lineno))
+    }
+
+    /// Parse and validate the preprocessed text, refer [Shader::from_wgsl].
+    /// Errors are reported as `name:line:col`, of the original source.
+    pub fn to_shader(&self) -> Result<Shader> {
+        Shader::from_wgsl_at(&self.text, &|line, col| match self.to_origin(line) {
+            Some((name, lineno)) => format!("{}:{}:{}", name, lineno, col),
+            None => format!("{}:{}", line, col),
+        })
+    }
+
+    fn push(&mut self, name: &str, lineno: usize, line: String) {
+        let index = match self.names.iter().position(|n| n == name) {
+            Some(index) => index,
+            None => {
+                self.names.push(name.to_string());
+                self.names.len() - 1
+            }
+        };
+        if !self.origins.is_empty() {
+            self.text.push('\n');
+        }
+        self.text.push_str(&line);
+        self.origins.push((index, lineno));
+    }
+}
+
+impl Preprocessor {
+    /// Register snippet `text` under `name`, overriding a previous snippet
+    /// with the same name.
+    pub fn add_snippet(&mut self, name: &str, text: &str) -> &mut Self {
+        self.snippets.insert(name.to_string(), text.to_string());
+        self
+    }
+
+    /// Add directory `dir` to search for included files.
+    pub fn add_path<P>(&mut self, dir: P) -> &mut Self
+    where
+        P: AsRef<path::Path>,
+    {
+        self.paths.push(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Define `name` with `value` for all sources processed by this instance,
+    /// `value` can be empty.
+    pub fn define(&mut self, name: &str, value: &str) -> &mut Self {
+        self.defines.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Process WGSL `text`, with `defines` in addition to the defines on this
+    /// instance. `name` is used for reporting errors.
+    pub fn process(
+        &self,
+        name: &str,
+        text: &str,
+        defines: &[(&str, &str)],
+    ) -> Result<String> {
+        Ok(self.to_source(name, text, defines)?.text)
+    }
+
+    /// Load WGSL file from `loc` and process it, refer [Preprocessor::process].
+    /// Directory of `loc` is searched for included files, after the search
+    /// paths on this instance.
+    pub fn process_file<P>(&self, loc: P, defines: &[(&str, &str)]) -> Result<String>
+    where
+        P: AsRef<path::Path>,
+    {
+        Ok(self.to_source_file(loc, defines)?.text)
+    }
+
+    /// Same as [Preprocessor::process], but return the processed text along
+    /// with the origin of its lines.
+    pub fn to_source(
+        &self,
+        name: &str,
+        text: &str,
+        defines: &[(&str, &str)],
+    ) -> Result<Source> {
+        let mut state = State {
+            defines: self.defines.clone(),
+            included: BTreeSet::new(),
+            source: Source::default(),
+        };
+        for (key, value) in defines.iter() {
+            state.defines.insert(key.to_string(), value.to_string());
+        }
+
+        self.process_text(name, text, &mut state)?;
+
+        Ok(state.source)
+    }
+
+    /// Same as [Preprocessor::process_file], but return the processed text
+    /// along with the origin of its lines.
+    pub fn to_source_file<P>(&self, loc: P, defines: &[(&str, &str)]) -> Result<Source>
+    where
+        P: AsRef<path::Path>,
+    {
+        let loc: &path::Path = loc.as_ref();
+        let text = err_at!(IOError, fs::read_to_string(loc), "file:{:?}", loc)?;
+
+        let mut pp = self.clone();
+        if let Some(dir) = loc.parent() {
+            pp.add_path(dir);
+        }
+        let mut source = pp.to_source(&loc.to_string_lossy(), &text, defines)?;
+        source.files.insert(0, loc.to_path_buf());
+
+        Ok(source)
+    }
+
+    fn process_text(&self, name: &str, text: &str, state: &mut State) -> Result<()> {
+        // stack of (active, else_seen) for conditional blocks.
+        let mut stack: Vec<(bool, bool)> = vec![];
+
+        for (lineno, line) in text.lines().enumerate() {
+            let lineno = lineno + 1;
+            let active = stack.iter().all(|(active, _)| *active);
+
+            let directive = line.trim();
+            if !directive.starts_with('#') {
+                let line = match active {
+                    true => substitute(line, &state.defines),
+                    false => String::default(),
+                };
+                state.source.push(name, lineno, line);
+                continue;
+            }
+
+            let mut parts = directive[1..].splitn(2, char::is_whitespace);
+            let (key, arg) =
+                (parts.next().unwrap_or(""), parts.next().unwrap_or("").trim());
+            let ident = arg.split_whitespace().next();
+
+            match (key, ident) {
+                ("ifdef", Some(ident)) => {
+                    stack.push((state.defines.contains_key(ident), false));
+                }
+                ("ifndef", Some(ident)) => {
+                    stack.push((!state.defines.contains_key(ident), false));
+                }
+                ("else", _) => match stack.last_mut() {
+                    Some(top) if !top.1 => *top = (!top.0, true),
+                    Some(_) => {
+                        err_at!(Invalid, msg: "{}:{}: duplicate #else", name, lineno)?
+                    }
+                    None => {
+                        err_at!(Invalid, msg: "{}:{}: #else without #ifdef", name, lineno)?
+                    }
+                },
+                ("endif", _) => match stack.pop() {
+                    Some(_) => (),
+                    None => {
+                        err_at!(Invalid, msg: "{}:{}: #endif without #ifdef", name, lineno)?
+                    }
+                },
+                (_, _) if !active => (),
+                ("define", Some(ident)) => {
+                    let value = arg[ident.len()..].trim();
+                    state.defines.insert(ident.to_string(), value.to_string());
+                }
+                ("undef", Some(ident)) => {
+                    state.defines.remove(ident);
+                }
+                ("include", Some(_)) => {
+                    let include = arg.trim_matches(|c| matches!(c, '"' | '<' | '>'));
+                    if state.included.insert(include.to_string()) {
+                        let text = match self.to_snippet(include) {
+                            Ok((text, Some(loc))) => {
+                                state.source.files.push(loc);
+                                text
+                            }
+                            Ok((text, None)) => text,
+                            Err(err) => {
+                                err_at!(Invalid, msg: "{}:{}: {}", name, lineno, err)?
+                            }
+                        };
+                        self.process_text(include, &text, state)?;
+                    }
+                }
+                (key, _) => {
+                    err_at!(Invalid, msg: "{}:{}: invalid directive #{}", name, lineno, key)?
+                }
+            }
+
+            // directives are blanked, included text is inlined before the
+            // blank line of its #include.
+            state.source.push(name, lineno, String::default());
+        }
+
+        if !stack.is_empty() {
+            err_at!(Invalid, msg: "{}: unterminated #ifdef", name)?
+        }
+
+        Ok(())
+    }
+
+    // Return snippet text, and its file if it is read from search paths.
+    fn to_snippet(&self, name: &str) -> Result<(String, Option<path::PathBuf>)> {
+        if let Some(text) = self.snippets.get(name) {
+            return Ok((text.clone(), None));
+        }
+        for dir in self.paths.iter() {
+            let loc = dir.join(name);
+            if loc.is_file() {
+                let text = err_at!(IOError, fs::read_to_string(&loc), "file:{:?}", loc)?;
+                return Ok((text, Some(loc)));
+            }
+        }
+
+        err_at!(Invalid, msg: "cannot find include {:?}", name)
+    }
+}
+
+// Shader name, hash of its source text and its sorted defines.
+type PermutationKey = (String, u64, Vec<(String, String)>);
+
+/// Compiled permutation of a shader, refer [Permutations].
+pub struct Permutation {
+    pub shader: Shader,
+    pub module: wgpu::ShaderModule,
+    // bind-group layout entries derived from `shader`, indexed by group.
+    layouts: BTreeMap<u32, Vec<wgpu::BindGroupLayoutEntry>>,
+    // hash of the preprocessed text.
+    hash: u64,
+}
+
+impl Permutation {
+    /// Return hash of the preprocessed source, permutations compiled from
+    /// identical source return the same hash.
+    pub fn to_hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Return bind-group layout entries for `group`, derived once from the
+    /// shader when the permutation is compiled, refer
+    /// [Shader::to_bind_group_layout_entries].
+    pub fn as_bind_group_layout_entries(
+        &self,
+        group: u32,
+    ) -> &[wgpu::BindGroupLayoutEntry] {
+        self.layouts.get(&group).map(|e| e.as_slice()).unwrap_or(&[])
+    }
+}
+
+/// Cache of compiled shader permutations, shader source is processed,
+/// validated and compiled once for each unique set of defines. A cache
+/// instance shall be used with the same device.
+pub struct Permutations {
+    preprocessor: Preprocessor,
+    cache: Mutex<HashMap<PermutationKey, Arc<Permutation>>>,
+}
+
+impl Permutations {
+    pub fn new(preprocessor: Preprocessor) -> Permutations {
+        Permutations { preprocessor, cache: Mutex::new(HashMap::new()) }
+    }
+
+    /// Return compiled permutation of `text` for `defines`, `name` identifies
+    /// the shader source and is used as label for the shader module. Changed
+    /// `text` under the same `name`, like a reloaded shader, is compiled as
+    /// a new permutation.
+    pub fn to_permutation(
+        &self,
+        device: &wgpu::Device,
+        name: &str,
+        text: &str,
+        defines: &[(&str, &str)],
+    ) -> Result<Arc<Permutation>> {
+        let key = {
+            let mut defines: Vec<(String, String)> =
+                defines.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+            defines.sort();
+            (name.to_string(), to_hash(text), defines)
+        };
+
+        if let Some(val) = err_at!(Fatal, self.cache.lock())?.get(&key) {
+            return Ok(Arc::clone(val));
+        }
+
+        let source = self.preprocessor.to_source(name, text, defines)?;
+        let shader = source.to_shader()?;
+        let mut layouts = BTreeMap::new();
+        for group in shader.to_groups() {
+            match shader.to_bind_group_layout_entries(group) {
+                Ok(entries) => layouts.insert(group, entries),
+                Err(err) => err_at!(Invalid, msg: "{}: {}", name, err)?,
+            };
+        }
+        let hash = to_hash(&source.text);
+        let module = {
+            let desc = wgpu::ShaderModuleDescriptor {
+                label: Some(name),
+                source: wgpu::ShaderSource::Wgsl(source.text.into()),
+            };
+            device.create_shader_module(&desc)
+        };
+
+        let val = Arc::new(Permutation { shader, module, layouts, hash });
+        err_at!(Fatal, self.cache.lock())?.insert(key, Arc::clone(&val));
+
+        Ok(val)
+    }
+
+    /// Drop all compiled permutations.
+    pub fn clear(&self) -> Result<()> {
+        err_at!(Fatal, self.cache.lock())?.clear();
+        Ok(())
+    }
+
+    /// Return the number of compiled permutations.
+    pub fn len(&self) -> usize {
+        self.cache.lock().map(|cache| cache.len()).unwrap_or(0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn to_hash(text: &str) -> u64 {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+// Replace identifiers in `line` that are defined with a non-empty value.
+fn substitute(line: &str, defines: &BTreeMap<String, String>) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut ident = String::new();
+
+    for ch in line.chars().chain(std::iter::once('\n')) {
+        if ch.is_ascii_alphanumeric() || ch == '_' {
+            ident.push(ch);
+            continue;
+        }
+        match defines.get(&ident) {
+            Some(value)
+                if !value.is_empty()
+                    && !ident.starts_with(|c: char| c.is_ascii_digit()) =>
+            {
+                out.push_str(value)
+            }
+            _ => out.push_str(&ident),
+        }
+        ident.clear();
+        if ch != '\n' {
+            out.push(ch)
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+#[path = "preprocess_test.rs"]
+mod preprocess_test;
@@ -0,0 +1,105 @@
+use super::*;
+
+#[test]
+fn test_preprocess_include() {
+    let mut pp = Preprocessor::default();
+    pp.add_snippet("common.wgsl", "#include \"transforms.wgsl\"\nlet ONE: f32 = 1.0;");
+
+    let text =
+        "#include \"transforms.wgsl\"\n#include <common.wgsl>\nlet TWO: f32 = 2.0;";
+    let out = pp.process("test", text, &[]).unwrap();
+    assert_eq!(out.matches("struct Transforms").count(), 1, "{}", out);
+    assert!(out.contains("let ONE: f32 = 1.0;"), "{}", out);
+    assert!(out.ends_with("let TWO: f32 = 2.0;"), "{}", out);
+
+    let err = pp.process("test", "\n#include \"missing.wgsl\"", &[]).unwrap_err();
+    assert!(err.to_string().contains("test:2: "), "{}", err);
+    assert!(err.to_string().contains("missing.wgsl"), "{}", err);
+
+    // all snippets shall be valid WGSL, together.
+    let text: Vec<String> =
+        SNIPPETS.iter().map(|(name, _)| format!("#include \"{}\"", name)).collect();
+    let out = pp.process("test", &text.join("\n"), &[]).unwrap();
+    Shader::from_wgsl(&out).unwrap();
+}
+
+#[test]
+fn test_preprocess_defines() {
+    let text = r#"
+#define SIZE 64u
+#ifdef FILL
+let fill: bool = true;
+#else
+let fill: bool = false;
+#endif
+#ifndef FILL
+let size: u32 = SIZE;
+let SIZE_2: u32 = SIZE;
+#endif
+"#;
+    let pp = Preprocessor::default();
+
+    let out = pp.process("test", text, &[]).unwrap();
+    assert!(out.contains("let fill: bool = false;"), "{}", out);
+    assert!(!out.contains("let fill: bool = true;"), "{}", out);
+    assert!(out.contains("let size: u32 = 64u;"), "{}", out);
+    assert!(out.contains("let SIZE_2: u32 = 64u;"), "{}", out);
+    // directives are blanked, to preserve line numbers.
+    assert_eq!(out.split('\n').count(), text.lines().count());
+
+    let out = pp.process("test", text, &[("FILL", ""), ("SIZE", "32u")]).unwrap();
+    assert!(out.contains("let fill: bool = true;"), "{}", out);
+    assert!(!out.contains("let size"), "{}", out);
+
+    let err = pp.process("test", "#ifdef A\n#else\n#else\n#endif", &[]).unwrap_err();
+    assert!(err.to_string().contains("test:3: duplicate #else"), "{}", err);
+    assert!(pp.process("test", "#ifdef A\n", &[]).is_err());
+    assert!(pp.process("test", "#endif\n", &[]).is_err());
+    assert!(pp.process("test", "#pragma once\n", &[]).is_err());
+}
+
+#[test]
+fn test_preprocess_file() {
+    let dir = std::env::temp_dir().join("gpgpu-preprocess-test");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("util.wgsl"), "fn twice(x: f32) -> f32 { return x * 2.0; }")
+        .unwrap();
+    fs::write(dir.join("main.wgsl"), "#include \"util.wgsl\"\nlet X: f32 = 1.0;")
+        .unwrap();
+
+    let out = Preprocessor::default().process_file(dir.join("main.wgsl"), &[]).unwrap();
+    assert!(out.starts_with("fn twice"), "{}", out);
+
+    let source = Preprocessor::default().to_source_file(dir.join("main.wgsl"), &[]);
+    let files = source.unwrap().as_files().to_vec();
+    assert_eq!(files, vec![dir.join("main.wgsl"), dir.join("util.wgsl")]);
+}
+
+#[test]
+fn test_preprocess_source() {
+    let mut pp = Preprocessor::default();
+    pp.add_snippet("one.wgsl", "let ONE: f32 = 1.0;\nlet HALF: f32 = 0.5;");
+    pp.add_snippet("bad.wgsl", "let ONE: f32 = 1.0;\nlet BAD: f32 = 1u;");
+
+    let text = "#include \"one.wgsl\"\nlet TWO: f32 = 2.0;\nfn main() {\n";
+    let source = pp.to_source("test", text, &[]).unwrap();
+    assert_eq!(source.to_origin(1), Some(("one.wgsl", 1)));
+    assert_eq!(source.to_origin(3), Some(("test", 1)));
+    assert_eq!(source.to_origin(4), Some(("test", 2)));
+    assert_eq!(source.to_origin(0), None);
+
+    // parse errors are reported against the original source.
+    let err = source.to_shader().err().unwrap().to_string();
+    assert!(err.contains("test:3:12"), "{}", err);
+
+    let text = "#include \"bad.wgsl\"\nfn main() {}\n";
+    let source = pp.to_source("test", text, &[]).unwrap();
+    let err = source.to_shader().err().unwrap().to_string();
+    assert!(err.contains("bad.wgsl:2:"), "{}", err);
+
+    // inactive includes are blanked, like other directives.
+    let text = "#ifdef A\n#include \"one.wgsl\"\n#endif\nlet TWO: f32 = 2.0;";
+    let source = pp.to_source("test", text, &[]).unwrap();
+    assert_eq!(source.text.split('\n').count(), 4, "{}", source.text);
+    assert_eq!(source.to_origin(4), Some(("test", 4)));
+}
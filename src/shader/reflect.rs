@@ -0,0 +1,520 @@
+use naga::{
+    back, front, valid, ArraySize, Binding as NagaBinding, ConstantInner, ImageClass,
+    ImageDimension, ScalarKind, ScalarValue, ShaderStage, StorageAccess, StorageClass,
+    TypeInner, VectorSize,
+};
+use prettytable::{cell, row};
+use serde_json::json;
+
+use std::path;
+
+use crate::{
+    shader::Preprocessor,
+    util::{JsonRow, PrettyRow},
+    Error, Result,
+};
+
+/// Type wraps a parsed and validated WGSL module, refer [Shader::from_wgsl].
+pub struct Shader {
+    pub(super) module: naga::Module,
+    pub(super) info: valid::ModuleInfo,
+}
+
+/// Entry point in a [Shader] module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EntryPoint {
+    pub name: String,
+    pub stage: wgpu::ShaderStages,
+    /// Workgroup size, applicable for compute stage.
+    pub workgroup_size: [u32; 3],
+    /// Inputs with `location` attribute, for vertex stage these are the
+    /// vertex attributes.
+    pub inputs: Vec<Location>,
+}
+
+/// Entry point input with `location` attribute.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub location: u32,
+    pub name: String,
+    pub ty: String,
+    pub size: u32,
+}
+
+/// Resource bound to a `group` and `binding` in a [Shader] module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Binding {
+    pub group: u32,
+    pub binding: u32,
+    pub name: String,
+    /// Address space, like `uniform`, `storage,read_write` or `handle`, for
+    /// textures and samplers.
+    pub space: String,
+    pub ty: String,
+    /// Size in bytes for buffers, for runtime sized arrays this is the size
+    /// with one array element. None for textures and samplers.
+    pub size: Option<u32>,
+    /// Shader stages accessing this binding.
+    pub visibility: wgpu::ShaderStages,
+}
+
+impl Shader {
+    /// Parse and validate `text` as WGSL. Errors are reported with line and
+    /// column of the offending source.
+    pub fn from_wgsl(text: &str) -> Result<Shader> {
+        Shader::from_wgsl_at(text, &|line, col| format!("{}:{}", line, col))
+    }
+
+    // Same as from_wgsl, `at` formats the line and column of errors, used to
+    // map preprocessed text back to its source, refer Source::to_shader.
+    pub(super) fn from_wgsl_at(
+        text: &str,
+        at: &dyn Fn(usize, usize) -> String,
+    ) -> Result<Shader> {
+        let module = match front::wgsl::parse_str(text) {
+            Ok(module) => module,
+            Err(err) => {
+                let (line, col) = err.location(text);
+                err_at!(Invalid, msg: "{}: {}", at(line, col), err.emit_to_string(text))?
+            }
+        };
+
+        let flags = valid::ValidationFlags::all();
+        let caps = valid::Capabilities::all();
+        let info = match valid::Validator::new(flags, caps).validate(&module) {
+            Ok(info) => info,
+            Err(err) => {
+                let spans: Vec<String> = err
+                    .spans()
+                    .filter_map(|(span, desc)| {
+                        let (line, col) = to_location(text, span.to_range()?.start);
+                        Some(format!("{}: {}", at(line, col), desc))
+                    })
+                    .collect();
+                let err = err.into_inner();
+                err_at!(Invalid, msg: "{} {}", spans.join(" "), to_error_chain(&err))?
+            }
+        };
+
+        Ok(Shader { module, info })
+    }
+
+    /// Load WGSL file from `loc` and parse it, refer [Shader::from_wgsl].
+    /// File is preprocessed with default [Preprocessor], to resolve its
+    /// includes.
+    pub fn from_file<P>(loc: P) -> Result<Shader>
+    where
+        P: AsRef<path::Path>,
+    {
+        Preprocessor::default().to_source_file(loc, &[])?.to_shader()
+    }
+
+    pub fn as_module(&self) -> &naga::Module {
+        &self.module
+    }
+
+    /// Return entry points, in the order they are defined.
+    pub fn to_entry_points(&self) -> Vec<EntryPoint> {
+        let module = &self.module;
+        let mut entry_points = vec![];
+
+        for ep in module.entry_points.iter() {
+            let mut inputs = vec![];
+            for arg in ep.function.arguments.iter() {
+                match (&arg.binding, &module.types[arg.ty].inner) {
+                    (Some(NagaBinding::Location { location, .. }), _) => {
+                        let name = arg.name.clone().unwrap_or_default();
+                        inputs.push(self.to_location(*location, name, arg.ty))
+                    }
+                    (None, TypeInner::Struct { members, .. }) => {
+                        for m in members.iter() {
+                            if let Some(NagaBinding::Location { location, .. }) =
+                                m.binding
+                            {
+                                let name = m.name.clone().unwrap_or_default();
+                                inputs.push(self.to_location(location, name, m.ty))
+                            }
+                        }
+                    }
+                    (_, _) => (),
+                }
+            }
+            inputs.sort_by_key(|l| l.location);
+
+            entry_points.push(EntryPoint {
+                name: ep.name.clone(),
+                stage: to_shader_stages(ep.stage),
+                workgroup_size: ep.workgroup_size,
+                inputs,
+            })
+        }
+
+        entry_points
+    }
+
+    /// Return resource bindings, sorted by group and binding. Visibility of
+    /// each binding is computed from the entry points that use it.
+    pub fn to_bindings(&self) -> Vec<Binding> {
+        let module = &self.module;
+        let mut bindings = vec![];
+
+        for (handle, var) in module.global_variables.iter() {
+            let rb = match &var.binding {
+                Some(rb) => rb,
+                None => continue,
+            };
+            let inner = &module.types[var.ty].inner;
+            let size = match inner {
+                TypeInner::Image { .. } | TypeInner::Sampler { .. } => None,
+                TypeInner::Array { .. } if var.class == StorageClass::Handle => None,
+                inner => Some(inner.span(&module.constants)),
+            };
+            let visibility = self.to_visibility(handle);
+
+            bindings.push(Binding {
+                group: rb.group,
+                binding: rb.binding,
+                name: var.name.clone().unwrap_or_default(),
+                space: to_space_name(var.class),
+                ty: self.to_type_name(var.ty),
+                size,
+                visibility,
+            })
+        }
+        bindings.sort_by_key(|b| (b.group, b.binding));
+
+        bindings
+    }
+
+    /// Translate shader to SPIR-V binary.
+    pub fn to_spirv(&self) -> Result<Vec<u32>> {
+        let options = back::spv::Options::default();
+        err_at!(
+            FailConvert,
+            back::spv::write_vec(&self.module, &self.info, &options, None)
+        )
+    }
+
+    /// Translate `entry_point` to GLSL text, GLSL supports only one entry
+    /// point per shader.
+    pub fn to_glsl(&self, entry_point: &str) -> Result<String> {
+        let ep = match self.module.entry_points.iter().find(|ep| ep.name == entry_point) {
+            Some(ep) => ep,
+            None => err_at!(Invalid, msg: "no entry point {:?}", entry_point)?,
+        };
+
+        let options = back::glsl::Options::default();
+        let pipeline_options = back::glsl::PipelineOptions {
+            shader_stage: ep.stage,
+            entry_point: entry_point.to_string(),
+        };
+
+        let mut text = String::new();
+        let mut w = err_at!(
+            FailConvert,
+            back::glsl::Writer::new(
+                &mut text,
+                &self.module,
+                &self.info,
+                &options,
+                &pipeline_options
+            )
+        )?;
+        err_at!(FailConvert, w.write())?;
+
+        Ok(text)
+    }
+
+    /// Translate shader to Metal shading language text.
+    pub fn to_msl(&self) -> Result<String> {
+        let options = back::msl::Options::default();
+        let pipeline_options = back::msl::PipelineOptions::default();
+        let (text, _) = err_at!(
+            FailConvert,
+            back::msl::write_string(
+                &self.module,
+                &self.info,
+                &options,
+                &pipeline_options
+            )
+        )?;
+
+        Ok(text)
+    }
+
+    // Return union of shader stages of all entry points in this module.
+    pub(super) fn to_stages(&self) -> wgpu::ShaderStages {
+        self.module
+            .entry_points
+            .iter()
+            .fold(wgpu::ShaderStages::NONE, |acc, ep| acc | to_shader_stages(ep.stage))
+    }
+
+    // Return shader stages whose entry points access global variable `handle`.
+    fn to_visibility(
+        &self,
+        handle: naga::Handle<naga::GlobalVariable>,
+    ) -> wgpu::ShaderStages {
+        self.module
+            .entry_points
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !self.info.get_entry_point(*i)[handle].is_empty())
+            .fold(wgpu::ShaderStages::NONE, |acc, (_, ep)| {
+                acc | to_shader_stages(ep.stage)
+            })
+    }
+
+    fn to_location(
+        &self,
+        location: u32,
+        name: String,
+        ty: naga::Handle<naga::Type>,
+    ) -> Location {
+        let size = self.module.types[ty].inner.span(&self.module.constants);
+        Location { location, name, ty: self.to_type_name(ty), size }
+    }
+
+    /// Return WGSL name for type `ty`.
+    pub fn to_type_name(&self, ty: naga::Handle<naga::Type>) -> String {
+        let module = &self.module;
+        let typ = &module.types[ty];
+
+        match &typ.inner {
+            TypeInner::Struct { .. } => {
+                typ.name.clone().unwrap_or_else(|| "struct".to_string())
+            }
+            TypeInner::Scalar { kind, width } => to_scalar_name(*kind, *width),
+            TypeInner::Vector { size, kind, width } => {
+                format!("vec{}<{}>", to_size(*size), to_scalar_name(*kind, *width))
+            }
+            TypeInner::Matrix { columns, rows, width } => format!(
+                "mat{}x{}<{}>",
+                to_size(*columns),
+                to_size(*rows),
+                to_scalar_name(ScalarKind::Float, *width)
+            ),
+            TypeInner::Atomic { kind, width } => {
+                format!("atomic<{}>", to_scalar_name(*kind, *width))
+            }
+            TypeInner::Array { base, size: ArraySize::Dynamic, .. } => {
+                format!("array<{}>", self.to_type_name(*base))
+            }
+            TypeInner::Array { base, size: ArraySize::Constant(c), .. } => {
+                match &module.constants[*c].inner {
+                    ConstantInner::Scalar { value: ScalarValue::Uint(n), .. } => {
+                        format!("array<{}, {}>", self.to_type_name(*base), n)
+                    }
+                    ConstantInner::Scalar { value: ScalarValue::Sint(n), .. } => {
+                        format!("array<{}, {}>", self.to_type_name(*base), n)
+                    }
+                    _ => format!("array<{}, _>", self.to_type_name(*base)),
+                }
+            }
+            TypeInner::Image { dim, arrayed, class } => {
+                let dim = match dim {
+                    ImageDimension::D1 => "1d",
+                    ImageDimension::D2 => "2d",
+                    ImageDimension::D3 => "3d",
+                    ImageDimension::Cube => "cube",
+                };
+                let arrayed = if *arrayed { "_array" } else { "" };
+                match class {
+                    ImageClass::Sampled { kind, multi } => {
+                        let ms = if *multi { "multisampled_" } else { "" };
+                        let kind = to_scalar_name(*kind, 4);
+                        format!("texture_{}{}{}<{}>", ms, dim, arrayed, kind)
+                    }
+                    ImageClass::Depth { multi } => {
+                        let ms = if *multi { "multisampled_" } else { "" };
+                        format!("texture_depth_{}{}{}", ms, dim, arrayed)
+                    }
+                    ImageClass::Storage { format, access } => format!(
+                        "texture_storage_{}{}<{:?},{}>",
+                        dim,
+                        arrayed,
+                        format,
+                        to_access_name(*access)
+                    ),
+                }
+            }
+            TypeInner::Sampler { comparison: false } => "sampler".to_string(),
+            TypeInner::Sampler { comparison: true } => "sampler_comparison".to_string(),
+            inner => format!("{:?}", inner),
+        }
+    }
+}
+
+impl PrettyRow for EntryPoint {
+    fn to_format() -> prettytable::format::TableFormat {
+        *prettytable::format::consts::FORMAT_CLEAN
+    }
+
+    fn to_head() -> prettytable::Row {
+        row![Fy => "Name", "Stage", "Workgroup", "Inputs"]
+    }
+
+    fn to_row(&self) -> prettytable::Row {
+        let workgroup = match self.stage {
+            wgpu::ShaderStages::COMPUTE => {
+                let [x, y, z] = self.workgroup_size;
+                format!("{}x{}x{}", x, y, z)
+            }
+            _ => "-".to_string(),
+        };
+        let inputs: Vec<String> = self
+            .inputs
+            .iter()
+            .map(|l| format!("@{} {}:{}", l.location, l.name, l.ty))
+            .collect();
+
+        row![self.name, to_stages_name(self.stage), workgroup, inputs.join("\n")]
+    }
+}
+
+impl JsonRow for EntryPoint {
+    fn to_json(&self) -> serde_json::Value {
+        let inputs: Vec<serde_json::Value> = self
+            .inputs
+            .iter()
+            .map(|l| {
+                json!({
+                    "location": l.location,
+                    "name": l.name,
+                    "type": l.ty,
+                    "size": l.size,
+                })
+            })
+            .collect();
+
+        json!({
+            "name": self.name,
+            "stage": to_stages_name(self.stage),
+            "workgroup_size": self.workgroup_size,
+            "inputs": inputs,
+        })
+    }
+}
+
+impl PrettyRow for Binding {
+    fn to_format() -> prettytable::format::TableFormat {
+        *prettytable::format::consts::FORMAT_CLEAN
+    }
+
+    fn to_head() -> prettytable::Row {
+        row![Fy => "Group", "Binding", "Name", "Space", "Type", "Size", "Visibility"]
+    }
+
+    fn to_row(&self) -> prettytable::Row {
+        let size = match self.size {
+            Some(size) => size.to_string(),
+            None => "-".to_string(),
+        };
+
+        row![
+            self.group,
+            self.binding,
+            self.name,
+            self.space,
+            self.ty,
+            size,
+            to_stages_name(self.visibility),
+        ]
+    }
+}
+
+impl JsonRow for Binding {
+    fn to_json(&self) -> serde_json::Value {
+        json!({
+            "group": self.group,
+            "binding": self.binding,
+            "name": self.name,
+            "space": self.space,
+            "type": self.ty,
+            "size": self.size,
+            "visibility": to_stages_name(self.visibility),
+        })
+    }
+}
+
+fn to_shader_stages(stage: ShaderStage) -> wgpu::ShaderStages {
+    match stage {
+        ShaderStage::Vertex => wgpu::ShaderStages::VERTEX,
+        ShaderStage::Fragment => wgpu::ShaderStages::FRAGMENT,
+        ShaderStage::Compute => wgpu::ShaderStages::COMPUTE,
+    }
+}
+
+fn to_stages_name(stages: wgpu::ShaderStages) -> String {
+    let names = [
+        (wgpu::ShaderStages::VERTEX, "vertex"),
+        (wgpu::ShaderStages::FRAGMENT, "fragment"),
+        (wgpu::ShaderStages::COMPUTE, "compute"),
+    ];
+    let names: Vec<&str> =
+        names.iter().filter(|(s, _)| stages.contains(*s)).map(|(_, n)| *n).collect();
+    names.join(",")
+}
+
+fn to_space_name(class: StorageClass) -> String {
+    match class {
+        StorageClass::Function => "function".to_string(),
+        StorageClass::Private => "private".to_string(),
+        StorageClass::WorkGroup => "workgroup".to_string(),
+        StorageClass::Uniform => "uniform".to_string(),
+        StorageClass::Storage { access } => format!("storage,{}", to_access_name(access)),
+        StorageClass::Handle => "handle".to_string(),
+        StorageClass::PushConstant => "push_constant".to_string(),
+    }
+}
+
+fn to_access_name(access: StorageAccess) -> &'static str {
+    match (access.contains(StorageAccess::LOAD), access.contains(StorageAccess::STORE)) {
+        (true, true) => "read_write",
+        (false, true) => "write",
+        (_, false) => "read",
+    }
+}
+
+fn to_scalar_name(kind: ScalarKind, width: u8) -> String {
+    let kind = match kind {
+        ScalarKind::Sint => "i",
+        ScalarKind::Uint => "u",
+        ScalarKind::Float => "f",
+        ScalarKind::Bool => return "bool".to_string(),
+    };
+    format!("{}{}", kind, u32::from(width) * 8)
+}
+
+fn to_size(size: VectorSize) -> u32 {
+    match size {
+        VectorSize::Bi => 2,
+        VectorSize::Tri => 3,
+        VectorSize::Quad => 4,
+    }
+}
+
+// Return 1-based line and column for byte `offset` into `text`.
+fn to_location(text: &str, offset: usize) -> (usize, usize) {
+    let prefix = &text[..offset.min(text.len())];
+    let line = prefix.matches('\n').count() + 1;
+    let col = match prefix.rfind('\n') {
+        Some(n) => prefix[n + 1..].chars().count() + 1,
+        None => prefix.chars().count() + 1,
+    };
+    (line, col)
+}
+
+fn to_error_chain(err: &dyn std::error::Error) -> String {
+    let mut msg = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        msg.push_str(&format!(": {}", err));
+        source = err.source();
+    }
+    msg
+}
+
+#[cfg(test)]
+#[path = "reflect_test.rs"]
+mod reflect_test;
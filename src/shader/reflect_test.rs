@@ -0,0 +1,109 @@
+use super::*;
+
+const COMPUTE: &str = r#"
+struct Data {
+    values: array<vec4<f32>>;
+};
+
+struct Params {
+    scale: vec4<f32>;
+};
+
+[[group(0), binding(0)]] var<storage, read_write> data: Data;
+[[group(1), binding(2)]] var<uniform> params: Params;
+
+[[stage(compute), workgroup_size(64, 2)]]
+fn main([[builtin(global_invocation_id)]] id: vec3<u32>) {
+    data.values[id.x] = data.values[id.x] * params.scale;
+}
+"#;
+
+#[test]
+fn test_shader_repo_shaders() {
+    let shaders = [
+        include_str!("../primv/circle/circle.wgsl"),
+        include_str!("../primv/wireframe/wireframe.wgsl"),
+        include_str!("../primv/load/load.wgsl"),
+        include_str!("../primv/mesh/mesh.wgsl"),
+        include_str!("../primv/batch/batch.wgsl"),
+        include_str!("../primv/frame_graph/frame_graph.wgsl"),
+        include_str!("../../examples/bezier/shader.wgsl"),
+        include_str!("../../examples/points/shader.wgsl"),
+        include_str!("../../examples/triangle/shader.wgsl"),
+    ];
+
+    for text in shaders.iter() {
+        let text = Preprocessor::default().process("test", text, &[]).unwrap();
+        let shader = Shader::from_wgsl(&text).unwrap();
+        assert!(!shader.to_entry_points().is_empty());
+        assert!(!shader.to_spirv().unwrap().is_empty());
+        assert!(!shader.to_msl().unwrap().is_empty());
+    }
+}
+
+#[test]
+fn test_shader_reflect() {
+    let shader = Shader::from_wgsl(COMPUTE).unwrap();
+
+    let eps = shader.to_entry_points();
+    assert_eq!(eps.len(), 1);
+    assert_eq!(eps[0].name, "main");
+    assert_eq!(eps[0].stage, wgpu::ShaderStages::COMPUTE);
+    assert_eq!(eps[0].workgroup_size, [64, 2, 1]);
+    assert!(eps[0].inputs.is_empty());
+
+    let bindings = shader.to_bindings();
+    assert_eq!(bindings.len(), 2);
+    assert_eq!((bindings[0].group, bindings[0].binding), (0, 0));
+    assert_eq!(bindings[0].name, "data");
+    assert_eq!(bindings[0].space, "storage,read_write");
+    assert_eq!(bindings[0].ty, "Data");
+    assert_eq!(bindings[0].size, Some(16));
+    assert_eq!(bindings[0].visibility, wgpu::ShaderStages::COMPUTE);
+    assert_eq!((bindings[1].group, bindings[1].binding), (1, 2));
+    assert_eq!(bindings[1].space, "uniform");
+    assert_eq!(bindings[1].ty, "Params");
+    assert_eq!(bindings[1].size, Some(16));
+
+    let glsl = shader.to_glsl("main").unwrap();
+    assert!(glsl.contains("local_size_x = 64"), "{}", glsl);
+    assert!(shader.to_glsl("vs_main").is_err());
+}
+
+#[test]
+fn test_shader_vertex_inputs() {
+    let shader = Shader::from_wgsl(include_str!("../primv/load/load.wgsl")).unwrap();
+    let eps = shader.to_entry_points();
+    let vs = eps.iter().find(|ep| ep.name == "vs_main").unwrap();
+    assert_eq!(vs.stage, wgpu::ShaderStages::VERTEX);
+    let inputs: Vec<(u32, &str, &str, u32)> = vs
+        .inputs
+        .iter()
+        .map(|l| (l.location, l.name.as_str(), l.ty.as_str(), l.size))
+        .collect();
+    assert_eq!(
+        inputs,
+        vec![(0, "position", "vec4<f32>", 16), (1, "tex_coords", "vec2<f32>", 8)]
+    );
+
+    let bindings = shader.to_bindings();
+    assert_eq!(bindings[0].ty, "texture_2d<f32>");
+    assert_eq!(bindings[0].size, None);
+    assert_eq!(bindings[0].visibility, wgpu::ShaderStages::FRAGMENT);
+    assert_eq!(bindings[1].ty, "sampler");
+}
+
+#[test]
+fn test_shader_errors() {
+    let text = "fn main() {\n    let x: f32 = 1.0\n}\n";
+    let err = Shader::from_wgsl(text).err().unwrap().to_string();
+    assert!(err.contains("3:1"), "{}", err);
+
+    let text =
+        "[[stage(compute), workgroup_size(1)]]\nfn main() {\n    let x: f32 = 1u;\n}\n";
+    let err = Shader::from_wgsl(text).err().unwrap().to_string();
+    assert!(err.contains("Invalid"), "{}", err);
+
+    assert_eq!(to_location("ab\ncd", 4), (2, 2));
+    assert_eq!(to_location("ab", 0), (1, 1));
+}
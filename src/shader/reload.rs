@@ -0,0 +1,123 @@
+use log::{error, info};
+
+use std::path;
+
+use crate::{
+    shader::{Preprocessor, Shader, Source, SNIPPETS},
+    util::Watcher,
+    Error, Result,
+};
+
+/// Directory of package snippets in the source tree, refer [SNIPPETS].
+pub const SNIPPETS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/shader/wgsl");
+
+/// Type implement development mode reloading of a WGSL source file.
+///
+/// Shaders are baked into the binary with `include_str!`, in development mode
+/// primitives can instead watch the source file, the package snippets in
+/// [SNIPPETS_DIR] and files included from the source directory, and rebuild
+/// their pipeline when they change.
+pub struct ShaderReload {
+    name: String,
+    loc: path::PathBuf,
+    watcher: Watcher,
+}
+
+impl ShaderReload {
+    /// Watch WGSL file at `loc`, `name` is used for logging.
+    pub fn new<P>(name: &str, loc: P) -> ShaderReload
+    where
+        P: AsRef<path::Path>,
+    {
+        let mut watcher = Watcher::default();
+        watcher.watch(loc.as_ref());
+        for (snippet, _) in SNIPPETS.iter() {
+            watcher.watch(path::Path::new(SNIPPETS_DIR).join(snippet));
+        }
+
+        let mut val = ShaderReload {
+            name: name.to_string(),
+            loc: loc.as_ref().to_path_buf(),
+            watcher,
+        };
+        if let Ok(source) = val.to_source() {
+            val.watch_includes(&source);
+        }
+
+        val
+    }
+
+    /// Return the reloaded shader, if source file or any of the snippets have
+    /// changed since last poll. Errors are logged and None is returned, so
+    /// that callers can continue with their current pipeline.
+    pub fn poll(&mut self) -> Option<(String, Shader)> {
+        if self.watcher.poll().is_empty() {
+            return None;
+        }
+
+        let res = self.to_source().and_then(|source| {
+            // includes might have changed, watch them before validating.
+            self.watch_includes(&source);
+            let shader = source.to_shader()?;
+            Ok((source.text, shader))
+        });
+        match res {
+            Ok(val) => {
+                info!("{}: reloaded {:?}", self.name, self.loc);
+                Some(val)
+            }
+            Err(err) => {
+                error!("{}: {}", self.name, err);
+                None
+            }
+        }
+    }
+
+    /// Load source file, preprocess it with snippets from [SNIPPETS_DIR] and
+    /// validate it.
+    pub fn load(&self) -> Result<(String, Shader)> {
+        let source = self.to_source()?;
+        let shader = source.to_shader()?;
+
+        Ok((source.text, shader))
+    }
+
+    fn to_source(&self) -> Result<Source> {
+        use std::fs;
+
+        let mut pp = Preprocessor::default();
+        for (snippet, _) in SNIPPETS.iter() {
+            let loc = path::Path::new(SNIPPETS_DIR).join(snippet);
+            let text = err_at!(IOError, fs::read_to_string(&loc), "file:{:?}", loc)?;
+            pp.add_snippet(snippet, &text);
+        }
+
+        pp.to_source_file(&self.loc, &[])
+    }
+
+    // Watch files included by the source, including nested includes.
+    fn watch_includes(&mut self, source: &Source) {
+        for loc in source.as_files().iter() {
+            self.watcher.watch(loc);
+        }
+    }
+}
+
+/// Call `f` within a validation error scope on `device`, so that wgpu
+/// validation errors are returned instead of panicking. Useful while
+/// rebuilding pipelines from reloaded shaders.
+pub fn catch_validation<F, T>(device: &wgpu::Device, f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T>,
+{
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let res = f();
+    match pollster::block_on(device.pop_error_scope()) {
+        Some(err) => err_at!(Invalid, msg: "{}", err),
+        None => res,
+    }
+}
+
+#[cfg(test)]
+#[path = "reload_test.rs"]
+mod reload_test;
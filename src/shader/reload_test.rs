@@ -0,0 +1,44 @@
+use std::{fs, time};
+
+use super::*;
+
+#[test]
+fn test_shader_reload() {
+    let loc = concat!(env!("CARGO_MANIFEST_DIR"), "/src/primv/circle/circle.wgsl");
+    let mut reload = ShaderReload::new("test", loc);
+    let (text, shader) = reload.load().unwrap();
+    assert!(text.contains("struct Transforms"));
+    assert!(shader.to_struct_layout("Transforms").is_ok());
+    assert!(reload.poll().is_none());
+
+    let dir = std::env::temp_dir().join("gpgpu-reload-test");
+    fs::create_dir_all(&dir).unwrap();
+    let loc = dir.join("broken.wgsl");
+    fs::write(&loc, "#include \"transforms.wgsl\"\n").unwrap();
+
+    let mut reload = ShaderReload::new("test", &loc);
+    reload.watcher = Watcher::new(time::Duration::ZERO);
+    reload.watcher.watch(&loc);
+    reload.load().unwrap();
+
+    // compile errors are logged, and poll returns None.
+    fs::write(&loc, "fn main() {\n").unwrap();
+    let past = time::SystemTime::now() - time::Duration::from_secs(60);
+    fs::File::options().write(true).open(&loc).unwrap().set_modified(past).unwrap();
+    assert!(reload.poll().is_none());
+    assert!(reload.load().is_err());
+}
+
+#[test]
+fn test_shader_reload_includes() {
+    let dir = std::env::temp_dir().join("gpgpu-reload-includes-test");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("main.wgsl"), "#include \"outer.wgsl\"\n").unwrap();
+    fs::write(dir.join("outer.wgsl"), "#include \"inner.wgsl\"\n").unwrap();
+    fs::write(dir.join("inner.wgsl"), "let ONE: f32 = 1.0;\n").unwrap();
+
+    let reload = ShaderReload::new("test", dir.join("main.wgsl"));
+    let files = reload.watcher.to_files();
+    assert!(files.contains(&dir.join("outer.wgsl")), "{:?}", files);
+    assert!(files.contains(&dir.join("inner.wgsl")), "{:?}", files);
+}
@@ -0,0 +1,19 @@
+// Convert sRGB encoded color to linear color, alpha is left as is.
+fn srgb_to_linear(color: vec4<f32>) -> vec4<f32> {
+    let c = color.rgb;
+    let lo = c / 12.92;
+    let hi = pow((c + 0.055) / 1.055, vec3<f32>(2.4));
+    return vec4<f32>(select(hi, lo, c <= vec3<f32>(0.04045)), color.a);
+}
+
+// Convert linear color to sRGB encoded color, alpha is left as is.
+fn linear_to_srgb(color: vec4<f32>) -> vec4<f32> {
+    let c = color.rgb;
+    let lo = c * 12.92;
+    let hi = 1.055 * pow(c, vec3<f32>(1.0 / 2.4)) - 0.055;
+    return vec4<f32>(select(hi, lo, c <= vec3<f32>(0.0031308)), color.a);
+}
+
+fn premultiply(color: vec4<f32>) -> vec4<f32> {
+    return vec4<f32>(color.rgb * color.a, color.a);
+}
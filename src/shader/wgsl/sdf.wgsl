@@ -0,0 +1,21 @@
+// Distance functions, `p` is the position relative to the center of the
+// shape, and the returned distance is negative inside the shape.
+
+fn sdf_circle(p: vec2<f32>, radius: f32) -> f32 {
+    return length(p) - radius;
+}
+
+// Square with half-side `radius`, distance is measured along the major axis.
+fn sdf_square(p: vec2<f32>, radius: f32) -> f32 {
+    return max(abs(p.x), abs(p.y)) - radius;
+}
+
+fn sdf_box(p: vec2<f32>, half_size: vec2<f32>) -> f32 {
+    let d = abs(p) - half_size;
+    return length(max(d, vec2<f32>(0.0))) + min(max(d.x, d.y), 0.0);
+}
+
+// Annulus of `width` along the inner edge of circle with `radius`.
+fn sdf_ring(p: vec2<f32>, radius: f32, width: f32) -> f32 {
+    return abs(length(p) - (radius - width / 2.0)) - width / 2.0;
+}
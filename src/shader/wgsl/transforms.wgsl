@@ -0,0 +1,5 @@
+// Shall match crate::Transforms::to_bind_content.
+struct Transforms {
+    model: mat4x4<f32>;
+    mvp: mat4x4<f32>;
+};
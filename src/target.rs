@@ -0,0 +1,197 @@
+use crate::Viewport;
+
+/// Render target for primitives, a color attachment that can optionally carry a
+/// multisample resolve target and a depth-stencil attachment.
+///
+/// When `sample_count` is more than 1, `view` is the multisampled texture and
+/// `resolve_target` is the single-sampled texture it resolves into.
+pub struct ColorTarget {
+    pub format: wgpu::TextureFormat,
+    pub view: wgpu::TextureView,
+    pub view_port: Viewport,
+    pub sample_count: u32,
+    pub resolve_target: Option<wgpu::TextureView>,
+    pub depth: Option<DepthTarget>,
+}
+
+/// Depth-stencil attachment for [ColorTarget].
+pub struct DepthTarget {
+    pub format: wgpu::TextureFormat,
+    pub view: wgpu::TextureView,
+}
+
+impl ColorTarget {
+    /// Return a target without multisampling and depth-stencil attachment.
+    pub fn new(format: wgpu::TextureFormat, view: wgpu::TextureView) -> ColorTarget {
+        ColorTarget {
+            format,
+            view,
+            view_port: Viewport::default(),
+            sample_count: 1,
+            resolve_target: None,
+            depth: None,
+        }
+    }
+
+    /// Return the pipeline configuration matching this target, primitives can be
+    /// constructed with this value.
+    pub fn to_pipeline_target(&self) -> PipelineTarget {
+        PipelineTarget {
+            format: self.format,
+            sample_count: self.sample_count,
+            depth_format: self.depth.as_ref().map(|d| d.format),
+            ..PipelineTarget::default()
+        }
+    }
+
+    pub fn to_color_attachment(
+        &self,
+        load: wgpu::LoadOp<wgpu::Color>,
+    ) -> wgpu::RenderPassColorAttachment<'_> {
+        wgpu::RenderPassColorAttachment {
+            view: &self.view,
+            resolve_target: self.resolve_target.as_ref(),
+            ops: wgpu::Operations { load, store: true },
+        }
+    }
+
+    /// Return the depth-stencil attachment, if `clear` is true depth values are
+    /// cleared to 1.0 before the render-pass.
+    pub fn to_depth_attachment(
+        &self,
+        clear: bool,
+    ) -> Option<wgpu::RenderPassDepthStencilAttachment<'_>> {
+        let depth = self.depth.as_ref()?;
+        let load = match clear {
+            true => wgpu::LoadOp::Clear(1.0),
+            false => wgpu::LoadOp::Load,
+        };
+        let val = wgpu::RenderPassDepthStencilAttachment {
+            view: &depth.view,
+            depth_ops: Some(wgpu::Operations { load, store: true }),
+            stencil_ops: None,
+        };
+        Some(val)
+    }
+}
+
+/// Blend modes supported by primitives.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Blend {
+    /// Overwrite the destination color.
+    #[default]
+    Replace,
+    /// Standard alpha blending, `src * src_alpha + dst * (1 - src_alpha)`.
+    Alpha,
+    /// Add source color to destination, weighed by source alpha.
+    Additive,
+    /// Multiply source color with destination color.
+    Multiply,
+    /// Alpha blending for colors pre-multiplied with their alpha.
+    Premultiplied,
+}
+
+impl Blend {
+    pub fn to_blend_state(self) -> wgpu::BlendState {
+        use wgpu::{BlendComponent, BlendFactor, BlendOperation, BlendState};
+
+        match self {
+            Blend::Replace => BlendState::REPLACE,
+            Blend::Alpha => BlendState::ALPHA_BLENDING,
+            Blend::Premultiplied => BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+            Blend::Additive => BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::SrcAlpha,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+                alpha: BlendComponent {
+                    src_factor: BlendFactor::One,
+                    dst_factor: BlendFactor::One,
+                    operation: BlendOperation::Add,
+                },
+            },
+            Blend::Multiply => BlendState {
+                color: BlendComponent {
+                    src_factor: BlendFactor::Dst,
+                    dst_factor: BlendFactor::Zero,
+                    operation: BlendOperation::Add,
+                },
+                alpha: BlendComponent {
+                    src_factor: BlendFactor::DstAlpha,
+                    dst_factor: BlendFactor::Zero,
+                    operation: BlendOperation::Add,
+                },
+            },
+        }
+    }
+}
+
+/// Pipeline configuration that primitives need to render into a [ColorTarget].
+///
+/// Can be converted from [wgpu::TextureFormat], in which case the pipeline is
+/// single-sampled, without depth testing and with [Blend::Replace].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PipelineTarget {
+    pub format: wgpu::TextureFormat,
+    pub sample_count: u32,
+    pub depth_format: Option<wgpu::TextureFormat>,
+    pub blend: Blend,
+}
+
+impl Default for PipelineTarget {
+    fn default() -> PipelineTarget {
+        PipelineTarget {
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            sample_count: 1,
+            depth_format: None,
+            blend: Blend::default(),
+        }
+    }
+}
+
+impl From<wgpu::TextureFormat> for PipelineTarget {
+    fn from(format: wgpu::TextureFormat) -> PipelineTarget {
+        PipelineTarget { format, ..PipelineTarget::default() }
+    }
+}
+
+impl PipelineTarget {
+    pub fn to_color_target_state(self) -> wgpu::ColorTargetState {
+        wgpu::ColorTargetState {
+            format: self.format,
+            blend: Some(self.blend.to_blend_state()),
+            write_mask: wgpu::ColorWrites::ALL,
+        }
+    }
+
+    pub fn to_multisample_state(self) -> wgpu::MultisampleState {
+        wgpu::MultisampleState {
+            count: self.sample_count,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        }
+    }
+
+    /// Return depth-stencil state if this target has a depth attachment. 2D
+    /// primitives shall pass `write` as false and `compare` as Always, so that
+    /// they are drawn in submission order.
+    pub fn to_depth_stencil_state(
+        self,
+        write: bool,
+        compare: wgpu::CompareFunction,
+    ) -> Option<wgpu::DepthStencilState> {
+        let val = wgpu::DepthStencilState {
+            format: self.depth_format?,
+            depth_write_enabled: write,
+            depth_compare: compare,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        };
+        Some(val)
+    }
+}
+
+#[cfg(test)]
+#[path = "target_test.rs"]
+mod target_test;
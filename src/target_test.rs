@@ -0,0 +1,43 @@
+use super::*;
+
+#[test]
+fn test_pipeline_target_color_state() {
+    let target = PipelineTarget::from(wgpu::TextureFormat::Bgra8UnormSrgb);
+    let state = target.to_color_target_state();
+    assert_eq!(state.format, wgpu::TextureFormat::Bgra8UnormSrgb);
+    assert_eq!(state.blend, Some(wgpu::BlendState::REPLACE));
+    assert_eq!(state.write_mask, wgpu::ColorWrites::ALL);
+
+    let testcases = [
+        (Blend::Replace, wgpu::BlendState::REPLACE),
+        (Blend::Alpha, wgpu::BlendState::ALPHA_BLENDING),
+        (Blend::Premultiplied, wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+    ];
+    for (blend, refb) in testcases.iter() {
+        let target = PipelineTarget { blend: *blend, ..target };
+        assert_eq!(target.to_color_target_state().blend, Some(*refb), "{:?}", blend);
+    }
+
+    let target = PipelineTarget { blend: Blend::Additive, ..target };
+    let state = target.to_color_target_state().blend.unwrap();
+    assert_eq!(state.color.dst_factor, wgpu::BlendFactor::One);
+}
+
+#[test]
+fn test_pipeline_target_states() {
+    let target = PipelineTarget::default();
+    assert_eq!(target.to_multisample_state().count, 1);
+    assert!(target.to_depth_stencil_state(true, wgpu::CompareFunction::Less).is_none());
+
+    let target = PipelineTarget {
+        sample_count: 4,
+        depth_format: Some(wgpu::TextureFormat::Depth32Float),
+        ..target
+    };
+    assert_eq!(target.to_multisample_state().count, 4);
+    let state =
+        target.to_depth_stencil_state(false, wgpu::CompareFunction::Always).unwrap();
+    assert_eq!(state.format, wgpu::TextureFormat::Depth32Float);
+    assert!(!state.depth_write_enabled);
+    assert_eq!(state.depth_compare, wgpu::CompareFunction::Always);
+}
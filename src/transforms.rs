@@ -1,5 +1,9 @@
 use bytemuck::{Pod, Zeroable};
-use cgmath::{Matrix4, One, Point3, Rad, Vector3};
+use cgmath::{Deg, Matrix4, One, Point3, Quaternion, Rad, Vector3};
+
+use std::f32::consts::PI;
+
+use crate::{shader::Shader, Error, Result};
 
 #[rustfmt::skip]
 #[allow(unused)]
@@ -233,6 +237,18 @@ impl Transforms {
         contents.to_vec()
     }
 
+    /// Check that `Transforms` struct in `shader` matches the layout of
+    /// [Transforms::to_bind_content].
+    pub fn check_layout(shader: &Shader) -> Result<()> {
+        use std::mem::offset_of;
+
+        let offsets = [
+            ("model", offset_of!(UniformBuffer, model)),
+            ("mvp", offset_of!(UniformBuffer, mvp)),
+        ];
+        shader.check_struct::<UniformBuffer>("Transforms", &offsets)
+    }
+
     pub fn to_bind_group_layout_entry(binding: u32) -> wgpu::BindGroupLayoutEntry {
         wgpu::BindGroupLayoutEntry {
             binding,
@@ -246,3 +262,304 @@ impl Transforms {
         }
     }
 }
+
+/// Translation, rotation and scale components of an affine transform, refer
+/// [decompose].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Trs {
+    pub translate: Vector3<f32>,
+    pub rotate: Quaternion<f32>,
+    pub scale: Vector3<f32>,
+}
+
+impl Trs {
+    pub fn to_matrix(&self) -> Matrix4<f32> {
+        let (s, t) = (self.scale, self.translate);
+        Matrix4::from_translation(t)
+            * Matrix4::from(self.rotate)
+            * Matrix4::from_nonuniform_scale(s.x, s.y, s.z)
+    }
+}
+
+/// Decompose affine transform `mat` into translation, rotation and scale.
+/// Shear is not represented, [Trs::to_matrix] reproduces `mat` only when it
+/// has none. Fails for projective and singular matrices.
+pub fn decompose(mat: &Matrix4<f32>) -> Result<Trs> {
+    use cgmath::{InnerSpace, Matrix, Matrix3, SquareMatrix, Vector4};
+
+    let epsilon = 1e-6;
+
+    if (mat.row(3) - Vector4::unit_w()).magnitude() > epsilon {
+        err_at!(Invalid, msg: "not an affine transform, last row {:?}", mat.row(3))?
+    }
+
+    let m3 = Matrix3::from_cols(mat.x.truncate(), mat.y.truncate(), mat.z.truncate());
+    let mut scale = Vector3::new(m3.x.magnitude(), m3.y.magnitude(), m3.z.magnitude());
+    if scale.x < epsilon || scale.y < epsilon || scale.z < epsilon {
+        err_at!(Invalid, msg: "singular transform, scale {:?}", scale)?
+    }
+    // a reflection is folded into the x-axis scale.
+    if m3.determinant() < 0.0 {
+        scale.x = -scale.x
+    }
+
+    let rotate = {
+        let m = Matrix3::from_cols(m3.x / scale.x, m3.y / scale.y, m3.z / scale.z);
+        Quaternion::from(m)
+    };
+
+    let val = Trs { translate: mat.w.truncate(), rotate, scale };
+    Ok(val)
+}
+
+/// Parse transform expression `expr` and return the composed matrix.
+///
+/// Expression is a product of transforms, like
+/// `translate(1,0,0) * rotate_y(30deg) * perspective(60deg,1.6,0.1,100)`,
+/// multiplied in the order they are written, so that the right most
+/// transform is applied first. Parenthesis can be used for grouping.
+/// Angles are in degrees, unless suffixed with `rad`. Supported transforms:
+///
+/// * `identity()`
+/// * `translate(x)`, `translate(x,y)`, `translate(x,y,z)`
+/// * `scale(ratio)`, `scale(x,y,z)`
+/// * `rotate_x(angle)`, `rotate_y(angle)`, `rotate_z(angle)`
+/// * `rotate(x,y,z)`, rotate about z, y and x axis, same as [Transforms::model]
+/// * `perspective(fov,aspect,near,far)`, same as [Transforms::perspective_by]
+/// * `ortho(left,right,bottom,top,near,far)`, same as [Transforms::orthogonal_by]
+/// * `look_at(eye_x,eye_y,eye_z,center_x,center_y,center_z,up_x,up_y,up_z)`
+pub fn compose(expr: &str) -> Result<Matrix4<f32>> {
+    let mut parser = Parser { chars: expr.chars().collect(), pos: 0 };
+
+    let mat = parser.parse_expr()?;
+    parser.skip_ws();
+    match parser.peek() {
+        Some(ch) => parser.to_error(&format!("unexpected {:?}", ch)),
+        None => Ok(mat),
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Unit {
+    None,
+    Deg,
+    Rad,
+}
+
+// Argument to a transform, with its position in the expression.
+#[derive(Clone, Copy)]
+struct Arg {
+    value: f32,
+    unit: Unit,
+    pos: usize,
+}
+
+impl Arg {
+    fn to_angle(self) -> Rad<f32> {
+        match self.unit {
+            Unit::None | Unit::Deg => Deg(self.value).into(),
+            Unit::Rad => Rad(self.value),
+        }
+    }
+
+    fn to_scalar(self) -> Result<f32> {
+        match self.unit {
+            Unit::None => Ok(self.value),
+            _ => err_at!(Invalid, msg: "unexpected angle at {}", self.pos),
+        }
+    }
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn parse_expr(&mut self) -> Result<Matrix4<f32>> {
+        let mut mat = self.parse_term()?;
+        loop {
+            self.skip_ws();
+            match self.peek() {
+                Some('*') => {
+                    self.pos += 1;
+                    mat = mat * self.parse_term()?;
+                }
+                _ => break Ok(mat),
+            }
+        }
+    }
+
+    fn parse_term(&mut self) -> Result<Matrix4<f32>> {
+        self.skip_ws();
+        if self.peek() == Some('(') {
+            self.pos += 1;
+            let mat = self.parse_expr()?;
+            self.expect(')')?;
+            return Ok(mat);
+        }
+
+        let pos = self.pos;
+        let name = self.take_while(|ch| ch.is_ascii_alphanumeric() || ch == '_');
+        if name.is_empty() {
+            return self.to_error("expected transform");
+        }
+
+        self.expect('(')?;
+        let mut args = vec![];
+        self.skip_ws();
+        if self.peek() == Some(')') {
+            self.pos += 1;
+        } else {
+            loop {
+                args.push(self.parse_arg()?);
+                self.skip_ws();
+                match self.peek() {
+                    Some(',') => self.pos += 1,
+                    Some(')') => {
+                        self.pos += 1;
+                        break;
+                    }
+                    _ => return self.to_error("expected ',' or ')'"),
+                }
+            }
+        }
+
+        to_matrix(&name, &args, pos)
+    }
+
+    fn parse_arg(&mut self) -> Result<Arg> {
+        self.skip_ws();
+        let pos = self.pos;
+
+        let mut text = String::default();
+        if let Some(ch @ ('+' | '-')) = self.peek() {
+            text.push(ch);
+            self.pos += 1;
+        }
+        text.push_str(&self.take_while(|ch| ch.is_ascii_digit() || ch == '.'));
+        if let Some('e' | 'E') = self.peek() {
+            let n = match self.chars.get(self.pos + 1) {
+                Some('+' | '-') => 2,
+                _ => 1,
+            };
+            if matches!(self.chars.get(self.pos + n), Some(d) if d.is_ascii_digit()) {
+                text.extend(&self.chars[self.pos..self.pos + n]);
+                self.pos += n;
+                text.push_str(&self.take_while(|ch| ch.is_ascii_digit()));
+            }
+        }
+
+        let value: f32 = match text.parse() {
+            Ok(value) => value,
+            Err(_) => {
+                self.pos = pos;
+                return self.to_error("expected number");
+            }
+        };
+
+        let unit = match self.take_while(|ch| ch.is_ascii_alphabetic()).as_str() {
+            "" => Unit::None,
+            "deg" => Unit::Deg,
+            "rad" => Unit::Rad,
+            unit => err_at!(Invalid, msg: "invalid unit {:?} at {}", unit, pos)?,
+        };
+
+        Ok(Arg { value, unit, pos })
+    }
+
+    fn skip_ws(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn take_while<F>(&mut self, f: F) -> String
+    where
+        F: Fn(char) -> bool,
+    {
+        let start = self.pos;
+        while matches!(self.peek(), Some(ch) if f(ch)) {
+            self.pos += 1;
+        }
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    fn expect(&mut self, want: char) -> Result<()> {
+        self.skip_ws();
+        match self.peek() {
+            Some(ch) if ch == want => {
+                self.pos += 1;
+                Ok(())
+            }
+            _ => self.to_error(&format!("expected {:?}", want)),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn to_error<T>(&self, msg: &str) -> Result<T> {
+        err_at!(Invalid, msg: "{} at {}", msg, self.pos)
+    }
+}
+
+fn to_matrix(name: &str, args: &[Arg], pos: usize) -> Result<Matrix4<f32>> {
+    let s = |i: usize| args[i].to_scalar();
+    let a = |i: usize| args[i].to_angle();
+
+    let mat = match (name, args.len()) {
+        ("identity", 0) => Matrix4::one(),
+        ("translate", n @ 1..=3) => {
+            let mut xyz = [0.0; 3];
+            for (i, v) in xyz.iter_mut().take(n).enumerate() {
+                *v = s(i)?;
+            }
+            Matrix4::from_translation(xyz.into())
+        }
+        ("scale", 1) => Matrix4::from_scale(s(0)?),
+        ("scale", 3) => Matrix4::from_nonuniform_scale(s(0)?, s(1)?, s(2)?),
+        ("rotate_x", 1) => Matrix4::from_angle_x(a(0)),
+        ("rotate_y", 1) => Matrix4::from_angle_y(a(0)),
+        ("rotate_z", 1) => Matrix4::from_angle_z(a(0)),
+        ("rotate", 3) => {
+            Matrix4::from_angle_z(a(2))
+                * Matrix4::from_angle_y(a(1))
+                * Matrix4::from_angle_x(a(0))
+        }
+        ("perspective", 4) => {
+            // cgmath::perspective asserts on these, report them as errors instead.
+            let (fov, aspect, near, far) = (a(0), s(1)?, s(2)?, s(3)?);
+            let checks = [
+                (fov > Rad(0.0), 0, "fov must be greater than 0deg"),
+                (fov < Rad(PI), 0, "fov must be less than 180deg"),
+                (aspect.abs() > f32::EPSILON, 1, "aspect must be non-zero"),
+                (near > 0.0, 2, "near must be positive"),
+                (far > 0.0, 3, "far must be positive"),
+                ((far - near).abs() > f32::EPSILON, 3, "far must differ from near"),
+            ];
+            if let Some((_, i, msg)) = checks.iter().find(|(ok, _, _)| !ok) {
+                err_at!(Invalid, msg: "{} at {}", msg, args[*i].pos)?
+            }
+            let projection = cgmath::perspective(fov, aspect, near, far);
+            OPENGL_TO_WGPU_MATRIX * projection
+        }
+        ("ortho", 6) => {
+            let projection = cgmath::ortho(s(0)?, s(1)?, s(2)?, s(3)?, s(4)?, s(5)?);
+            OPENGL_TO_WGPU_MATRIX * projection
+        }
+        ("look_at", 9) => Matrix4::look_at_rh(
+            Point3::new(s(0)?, s(1)?, s(2)?),
+            Point3::new(s(3)?, s(4)?, s(5)?),
+            Vector3::new(s(6)?, s(7)?, s(8)?),
+        ),
+        (name, n) => {
+            err_at!(Invalid, msg: "invalid transform {}() with {} args at {}", name, n, pos)?
+        }
+    };
+
+    Ok(mat)
+}
+
+#[cfg(test)]
+#[path = "transforms_test.rs"]
+mod transforms_test;
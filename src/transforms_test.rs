@@ -0,0 +1,82 @@
+use cgmath::{abs_diff_eq, Deg, Matrix4, SquareMatrix, Vector3};
+
+use super::*;
+
+#[test]
+fn test_compose() {
+    let mat = compose("translate(1,0,0) * rotate_y(30deg) * scale(2)").unwrap();
+    let refm = Matrix4::from_translation(Vector3::new(1.0, 0.0, 0.0))
+        * Matrix4::from_angle_y(Deg(30.0))
+        * Matrix4::from_scale(2.0);
+    assert!(abs_diff_eq!(mat, refm, epsilon = 1e-6));
+
+    // angles default to degrees, grouping and whitespace.
+    let a = compose(" ( rotate_z(90) * identity() ) * translate(-1.5e0, 2)").unwrap();
+    let b = compose("rotate_z(1.5707964rad)*translate(-1.5,2,0)").unwrap();
+    assert!(abs_diff_eq!(a, b, epsilon = 1e-6));
+
+    let a = compose("rotate(10,20,30)").unwrap();
+    let mut transforms = Transforms::empty();
+    transforms.rotate_by(Some(Deg(10.0)), Some(Deg(20.0)), Some(Deg(30.0)));
+    assert!(abs_diff_eq!(a, transforms.model(), epsilon = 1e-6));
+
+    let a = compose("perspective(60deg,1.6,0.1,100)").unwrap();
+    let mut transforms = Transforms::empty();
+    transforms.perspective_by(Perspective {
+        fov: Deg(60.0),
+        aspect: 1.6,
+        near: 0.1,
+        far: 100.0,
+    });
+    assert!(abs_diff_eq!(a, transforms.mvp(), epsilon = 1e-6));
+}
+
+#[test]
+fn test_compose_errors() {
+    let testcases = [
+        ("", "expected transform at 0"),
+        ("translate(1,0,0) *", "expected transform at 18"),
+        ("translate(1,0,0", "expected ',' or ')' at 15"),
+        ("translate(1,x)", "expected number at 12"),
+        ("scale(1,2)", "invalid transform scale() with 2 args at 0"),
+        ("skew(1)", "invalid transform skew() with 1 args at 0"),
+        ("scale(2deg)", "unexpected angle at 6"),
+        ("rotate_x(2grad)", "invalid unit \"grad\" at 9"),
+        ("identity() identity()", "unexpected 'i' at 11"),
+        ("perspective(0deg,1.6,0.1,100)", "fov must be greater than 0deg at 12"),
+        ("perspective(-10deg,1.6,0.1,100)", "fov must be greater than 0deg at 12"),
+        ("perspective(180deg,1.6,0.1,100)", "fov must be less than 180deg at 12"),
+        ("perspective(60deg,0,0.1,100)", "aspect must be non-zero at 18"),
+        ("perspective(60deg,1.6,0,100)", "near must be positive at 22"),
+        ("perspective(60deg,1.6,-1,100)", "near must be positive at 22"),
+        ("perspective(60deg,1.6,0.1,0)", "far must be positive at 26"),
+        ("perspective(60deg,1.6,0.1,-100)", "far must be positive at 26"),
+        ("perspective(60deg,1.6,10,10)", "far must differ from near at 25"),
+    ];
+    for (expr, msg) in testcases.iter() {
+        let err = compose(expr).unwrap_err();
+        assert!(err.to_string().contains(msg), "{:?} {}", expr, err);
+    }
+}
+
+#[test]
+fn test_decompose() {
+    let mat = compose("translate(1,2,3) * rotate_y(30) * scale(2,3,4)").unwrap();
+    let trs = decompose(&mat).unwrap();
+    assert!(abs_diff_eq!(trs.translate, Vector3::new(1.0, 2.0, 3.0), epsilon = 1e-6));
+    assert!(abs_diff_eq!(trs.scale, Vector3::new(2.0, 3.0, 4.0), epsilon = 1e-5));
+    let rotate = Quaternion::from(cgmath::Matrix3::from_angle_y(Deg(30.0)));
+    assert!(abs_diff_eq!(trs.rotate, rotate, epsilon = 1e-6));
+    assert!(abs_diff_eq!(trs.to_matrix(), mat, epsilon = 1e-5));
+
+    let mat = compose("scale(-1,1,1) * rotate_x(45)").unwrap();
+    let trs = decompose(&mat).unwrap();
+    assert!(trs.scale.x < 0.0);
+    assert!(abs_diff_eq!(trs.to_matrix(), mat, epsilon = 1e-5));
+    assert!(mat.invert().is_some());
+
+    let mat = compose("perspective(60,1.6,0.1,100)").unwrap();
+    assert!(decompose(&mat).is_err());
+    let mat = compose("scale(1,0,1)").unwrap();
+    assert!(decompose(&mat).is_err());
+}
@@ -0,0 +1,292 @@
+use std::{collections::VecDeque, fmt, result, time};
+
+/// Default number of frames over which rolling statistics are computed.
+pub const FRAME_WINDOW: usize = 120;
+
+/// A frame is considered janky if it takes longer than this factor times the
+/// target frame period, or times the rolling average when there is no target.
+pub const JANK_FACTOR: f64 = 1.5;
+
+/// Type to pace frames and to compute frame-time statistics.
+///
+/// Typical usage in a redraw handler:
+///
+/// * Return early if [FrameRate::is_redraw] is false.
+/// * Call [FrameRate::begin_frame] before encoding commands.
+/// * Call [FrameRate::end_frame] after submitting the commands.
+///
+/// With a target FPS, refer [FrameRate::set_target_fps], deadlines for
+/// subsequent frames are computed from the previous deadline rather than from
+/// the time the frame was drawn, so that the frame rate does not drift.
+/// GPU time for frames can be added via [FrameRate::add_gpu_time], refer
+/// [GpuTimer][crate::util::GpuTimer].
+pub struct FrameRate {
+    next_frame: time::Instant,
+    start_time: time::Instant,
+    n_frames: u64,
+    n_janks: u64,
+    period: Option<time::Duration>,
+    window: usize,
+    last_frame: Option<time::Instant>,
+    frame_begin: Option<time::Instant>,
+    frames: VecDeque<FrameTime>,
+    gpu_times: VecDeque<time::Duration>,
+}
+
+/// Time taken by a single frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FrameTime {
+    /// Time elapsed since the previous frame.
+    pub frame: time::Duration,
+    /// Time spent on CPU, between [FrameRate::begin_frame] and
+    /// [FrameRate::end_frame].
+    pub cpu: time::Duration,
+    pub jank: bool,
+}
+
+/// Rolling frame-time statistics, refer [FrameRate::to_stats].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FrameStats {
+    pub frames: usize,
+    pub fps: f64,
+    pub min: time::Duration,
+    pub avg: time::Duration,
+    pub p95: time::Duration,
+    pub p99: time::Duration,
+    pub max: time::Duration,
+    /// Number of janky frames in the window.
+    pub jank: usize,
+    pub cpu_avg: time::Duration,
+    /// Average GPU time, if GPU times are added to [FrameRate].
+    pub gpu_avg: Option<time::Duration>,
+}
+
+impl Default for FrameRate {
+    fn default() -> FrameRate {
+        FrameRate::new()
+    }
+}
+
+impl fmt::Display for FrameStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
+        let ms = |d: time::Duration| d.as_secs_f64() * 1000.0;
+        write!(
+            f,
+            "fps:{:.1} frame min/avg/p95/p99/max {:.2}/{:.2}/{:.2}/{:.2}/{:.2}ms jank:{}",
+            self.fps,
+            ms(self.min),
+            ms(self.avg),
+            ms(self.p95),
+            ms(self.p99),
+            ms(self.max),
+            self.jank
+        )?;
+        write!(f, " cpu:{:.2}ms", ms(self.cpu_avg))?;
+        match self.gpu_avg {
+            Some(gpu) => write!(f, " gpu:{:.2}ms", ms(gpu)),
+            None => write!(f, " gpu:-"),
+        }
+    }
+}
+
+impl FrameRate {
+    pub fn new() -> FrameRate {
+        let now = time::Instant::now();
+        FrameRate {
+            next_frame: now,
+            start_time: now,
+            n_frames: 0,
+            n_janks: 0,
+            period: None,
+            window: FRAME_WINDOW,
+            last_frame: None,
+            frame_begin: None,
+            frames: VecDeque::with_capacity(FRAME_WINDOW),
+            gpu_times: VecDeque::with_capacity(FRAME_WINDOW),
+        }
+    }
+
+    /// Pace frames at `fps` frames per second, None shall disable pacing.
+    pub fn set_target_fps(&mut self, fps: Option<f64>) -> &mut Self {
+        self.period = fps
+            .filter(|fps| *fps > 0.0)
+            .map(|fps| time::Duration::from_secs_f64(1.0 / fps));
+        self
+    }
+
+    /// Set the number of recent frames over which statistics are computed.
+    pub fn set_window(&mut self, window: usize) -> &mut Self {
+        self.window = window.max(1);
+        while self.frames.len() > self.window {
+            self.frames.pop_front();
+        }
+        while self.gpu_times.len() > self.window {
+            self.gpu_times.pop_front();
+        }
+        self
+    }
+
+    /// Return true if it is time to draw the next frame.
+    pub fn is_redraw(&self) -> bool {
+        time::Instant::now() >= self.next_frame
+    }
+
+    /// Mark the beginning of a frame, for measuring CPU time.
+    pub fn begin_frame(&mut self) {
+        self.frame_begin = Some(time::Instant::now())
+    }
+
+    /// Mark the end of a frame, and schedule the next frame if a target FPS
+    /// is set.
+    pub fn end_frame(&mut self) {
+        self.end_frame_at(time::Instant::now())
+    }
+
+    /// Mark the end of a frame and schedule the next frame after `micros`,
+    /// irrespective of the target FPS.
+    pub fn next_frame_after(&mut self, micros: u64) {
+        let now = time::Instant::now();
+        self.end_frame_at(now);
+        self.next_frame = now + time::Duration::from_micros(micros);
+    }
+
+    /// Add GPU time taken by a frame. GPU times are typically available a few
+    /// frames later, and are tracked separately over the same window.
+    pub fn add_gpu_time(&mut self, gpu: time::Duration) {
+        if self.gpu_times.len() >= self.window {
+            self.gpu_times.pop_front();
+        }
+        self.gpu_times.push_back(gpu)
+    }
+
+    fn end_frame_at(&mut self, now: time::Instant) {
+        let cpu = self
+            .frame_begin
+            .take()
+            .map(|begin| now.saturating_duration_since(begin))
+            .unwrap_or_default();
+
+        if let Some(last) = self.last_frame {
+            let frame = now.saturating_duration_since(last);
+            let jank = match self.period {
+                Some(period) => frame.as_secs_f64() > period.as_secs_f64() * JANK_FACTOR,
+                None if self.frames.len() > 1 => {
+                    let avg = self.to_average_frame_time().as_secs_f64();
+                    frame.as_secs_f64() > avg * JANK_FACTOR
+                }
+                None => false,
+            };
+            if jank {
+                self.n_janks += 1;
+            }
+
+            if self.frames.len() >= self.window {
+                self.frames.pop_front();
+            }
+            self.frames.push_back(FrameTime { frame, cpu, jank });
+        }
+        self.last_frame = Some(now);
+        self.n_frames += 1;
+
+        if let Some(period) = self.period {
+            self.next_frame += period;
+            // fell behind by more than a frame, re-sync instead of bursting.
+            if self.next_frame + period < now {
+                self.next_frame = now + period;
+            }
+        }
+    }
+
+    fn to_average_frame_time(&self) -> time::Duration {
+        match self.frames.len() {
+            0 => time::Duration::ZERO,
+            n => self.frames.iter().map(|f| f.frame).sum::<time::Duration>() / (n as u32),
+        }
+    }
+}
+
+impl FrameRate {
+    /// Return the deadline for the next frame, useful with
+    /// [ControlFlow::WaitUntil][winit::event_loop::ControlFlow::WaitUntil].
+    pub fn to_deadline(&self) -> time::Instant {
+        self.next_frame
+    }
+
+    /// Return the target frame period, if a target FPS is set.
+    pub fn to_period(&self) -> Option<time::Duration> {
+        self.period
+    }
+
+    /// Return the total number of frames.
+    pub fn total(&self) -> u64 {
+        self.n_frames
+    }
+
+    /// Return the total number of janky frames.
+    pub fn to_jank_count(&self) -> u64 {
+        self.n_janks
+    }
+
+    /// Return the average frame rate since start.
+    pub fn rate(&self) -> u64 {
+        match self.start_time.elapsed().as_secs() {
+            secs if secs > 0 && self.n_frames > 0 => self.n_frames / secs,
+            _ => 0,
+        }
+    }
+
+    /// Iterate over frame times in the window, oldest first.
+    pub fn iter_frame_times(&self) -> impl Iterator<Item = &FrameTime> + '_ {
+        self.frames.iter()
+    }
+
+    /// Iterate over GPU times in the window, oldest first.
+    pub fn iter_gpu_times(&self) -> impl Iterator<Item = &time::Duration> + '_ {
+        self.gpu_times.iter()
+    }
+
+    /// Return statistics over the recent window of frames.
+    pub fn to_stats(&self) -> FrameStats {
+        let n = self.frames.len();
+        if n == 0 {
+            return FrameStats::default();
+        }
+
+        let mut frames: Vec<time::Duration> =
+            self.frames.iter().map(|f| f.frame).collect();
+        frames.sort();
+        let percentile = |p: f64| {
+            let i = ((n as f64) * p / 100.0).ceil() as usize;
+            frames[i.clamp(1, n) - 1]
+        };
+
+        let avg = self.to_average_frame_time();
+        let cpu_avg =
+            self.frames.iter().map(|f| f.cpu).sum::<time::Duration>() / (n as u32);
+        let gpu_avg = match self.gpu_times.len() {
+            0 => None,
+            m => Some(self.gpu_times.iter().sum::<time::Duration>() / (m as u32)),
+        };
+        let fps = match avg.as_secs_f64() {
+            secs if secs > 0.0 => 1.0 / secs,
+            _ => 0.0,
+        };
+
+        FrameStats {
+            frames: n,
+            fps,
+            min: frames[0],
+            avg,
+            p95: percentile(95.0),
+            p99: percentile(99.0),
+            max: frames[n - 1],
+            jank: self.frames.iter().filter(|f| f.jank).count(),
+            cpu_avg,
+            gpu_avg,
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "frame_rate_test.rs"]
+mod frame_rate_test;
@@ -0,0 +1,62 @@
+use super::*;
+
+fn ms(millis: u64) -> time::Duration {
+    time::Duration::from_millis(millis)
+}
+
+#[test]
+fn test_frame_rate_pacing() {
+    let mut fr = FrameRate::new();
+    fr.set_target_fps(Some(50.0));
+    assert_eq!(fr.to_period(), Some(ms(20)));
+
+    let start = fr.to_deadline();
+    // frames that are drawn late do not push subsequent deadlines.
+    fr.end_frame_at(start + ms(5));
+    assert_eq!(fr.to_deadline(), start + ms(20));
+    fr.end_frame_at(start + ms(27));
+    assert_eq!(fr.to_deadline(), start + ms(40));
+
+    // falling behind by more than a frame re-syncs with current time.
+    fr.end_frame_at(start + ms(200));
+    assert_eq!(fr.to_deadline(), start + ms(220));
+
+    fr.set_target_fps(None);
+    assert_eq!(fr.to_period(), None);
+    assert_eq!(fr.total(), 3);
+}
+
+#[test]
+fn test_frame_rate_stats() {
+    let mut fr = FrameRate::new();
+    fr.set_target_fps(Some(100.0)).set_window(100);
+
+    let mut now = time::Instant::now();
+    fr.end_frame_at(now);
+    for i in 1..=100 {
+        // every 25th frame takes 3 periods, others take 1 period.
+        now += if i % 25 == 0 { ms(30) } else { ms(10) };
+        fr.frame_begin = Some(now - ms(2));
+        fr.end_frame_at(now);
+    }
+    fr.add_gpu_time(ms(4));
+    fr.add_gpu_time(ms(6));
+
+    let stats = fr.to_stats();
+    assert_eq!(stats.frames, 100);
+    assert_eq!(stats.min, ms(10));
+    assert_eq!(stats.max, ms(30));
+    assert_eq!(stats.p95, ms(10));
+    assert_eq!(stats.p99, ms(30));
+    assert_eq!(stats.avg, time::Duration::from_micros(10_800));
+    assert_eq!(stats.jank, 4);
+    assert_eq!(stats.cpu_avg, ms(2));
+    assert_eq!(stats.gpu_avg, Some(ms(5)));
+    assert_eq!(fr.to_jank_count(), 4);
+
+    // window slides over older frames.
+    fr.set_window(10);
+    assert_eq!(fr.iter_frame_times().count(), 10);
+    assert_eq!(fr.to_stats().jank, 1);
+    assert!(fr.to_stats().to_string().starts_with("fps:"));
+}
@@ -0,0 +1,73 @@
+use std::time;
+
+use crate::util::Profiler;
+
+/// Name of the single scope timed by [GpuTimer].
+const SCOPE: &str = "util/gpu-timer";
+
+/// Type measures GPU time taken by command buffers, using timestamp queries.
+///
+/// Available only when the device is created with
+/// [wgpu::Features::TIMESTAMP_QUERY]. Timestamps are read back asynchronously,
+/// without stalling the CPU, hence GPU times are reported a few frames late.
+/// This is a single-scope use of [Profiler], frames are not timed while all
+/// its read back slots are in flight.
+///
+/// * Call [GpuTimer::begin] after creating the command encoder.
+/// * Call [GpuTimer::end] before submitting the encoder.
+/// * Call [GpuTimer::submitted] after submitting the encoder.
+/// * Call [GpuTimer::collect] to gather times of completed frames.
+pub struct GpuTimer {
+    profiler: Profiler,
+    // frames before this are already collected.
+    next: u64,
+}
+
+impl GpuTimer {
+    /// Create a GPU timer, return None if `device` does not support
+    /// [wgpu::Features::TIMESTAMP_QUERY].
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<GpuTimer> {
+        let profiler = Profiler::new(device, queue);
+        match profiler.is_timestamp() {
+            true => Some(GpuTimer { profiler, next: 0 }),
+            false => None,
+        }
+    }
+
+    /// Write the begin timestamp for this frame.
+    pub fn begin(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        self.profiler.begin_scope(SCOPE, encoder)
+    }
+
+    /// Write the end timestamp for this frame, and resolve both timestamps
+    /// for read back.
+    pub fn end(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        self.profiler.end_scope(encoder);
+        self.profiler.end_frame(encoder);
+    }
+
+    /// Request read back of timestamps, shall be called after submitting the
+    /// encoder passed to [GpuTimer::end].
+    pub fn submitted(&mut self) {
+        self.profiler.submitted()
+    }
+
+    /// Return GPU times of frames that completed since the last call, oldest
+    /// first. Does not block.
+    pub fn collect(&mut self, device: &wgpu::Device) -> Vec<time::Duration> {
+        self.profiler.collect(device);
+
+        // GPU-timed frames complete in the order they were submitted.
+        let spans: Vec<(u64, time::Duration)> = self
+            .profiler
+            .iter_frames()
+            .filter(|f| f.frame >= self.next)
+            .filter_map(|f| Some((f.frame, f.scopes.first()?.gpu?.to_duration())))
+            .collect();
+        if let Some((frame, _)) = spans.last() {
+            self.next = frame + 1;
+        }
+
+        spans.into_iter().map(|(_, d)| d).collect()
+    }
+}
@@ -1,13 +1,24 @@
 mod backend;
+mod frame_rate;
+mod gpu_timer;
+mod profiler;
 mod spinlock;
+mod watch;
 
 pub use backend::{wgpu_backend, wgpu_backend_to_string};
+pub use frame_rate::{FrameRate, FrameStats, FrameTime, FRAME_WINDOW, JANK_FACTOR};
+pub use gpu_timer::GpuTimer;
+pub use profiler::{
+    PipelineStatistics, ProfileFrame, ProfileScope, ProfileTarget, Profiler, Span,
+    MAX_SCOPES, PROFILE_HISTORY,
+};
 pub use spinlock::Spinlock;
+pub use watch::Watcher;
 
 use log::error;
 use serde::de::DeserializeOwned;
 
-use std::{fmt, fs, path, str::FromStr, time};
+use std::{fmt, fs, path, str::FromStr};
 
 use crate::{Error, Result};
 macro_rules! format_bool {
@@ -65,6 +76,19 @@ where
     }
 }
 
+/// Companion to [PrettyRow], for machine-readable output of the same rows.
+pub trait JsonRow {
+    fn to_json(&self) -> serde_json::Value;
+}
+
+/// Return `rows` as JSON array, refer [JsonRow].
+pub fn make_json<R>(rows: &[R]) -> serde_json::Value
+where
+    R: JsonRow,
+{
+    serde_json::Value::Array(rows.iter().map(|r| r.to_json()).collect())
+}
+
 pub trait PrettyPrint {
     fn print(&self);
 }
@@ -195,36 +219,3 @@ where
 
     Ok(())
 }
-
-pub struct FrameRate {
-    next_frame: time::Instant,
-    start_time: time::Instant,
-    n_frames: u64,
-}
-
-impl FrameRate {
-    pub fn new() -> FrameRate {
-        let now = time::Instant::now();
-        FrameRate { next_frame: now, start_time: now, n_frames: 0 }
-    }
-
-    pub fn is_redraw(&self) -> bool {
-        time::Instant::now() > self.next_frame
-    }
-
-    pub fn next_frame_after(&mut self, micros: u64) {
-        self.next_frame = time::Instant::now() + time::Duration::from_micros(micros);
-        self.n_frames += 1;
-    }
-
-    pub fn total(&self) -> u64 {
-        self.n_frames
-    }
-
-    pub fn rate(&self) -> u64 {
-        match self.start_time.elapsed().as_secs() {
-            secs if secs > 0 && self.n_frames > 0 => self.n_frames / secs,
-            _ => 0,
-        }
-    }
-}
@@ -0,0 +1,784 @@
+use log::warn;
+
+use std::{
+    collections::VecDeque,
+    fmt, fs,
+    future::Future,
+    path,
+    pin::Pin,
+    result,
+    task::{Context, Poll, Waker},
+    time,
+};
+
+use crate::{pretty, Error, Result};
+
+/// Maximum number of scopes, per frame, that are timed on the GPU. Scopes
+/// beyond this limit are timed only on the CPU.
+pub const MAX_SCOPES: u32 = 64;
+
+/// Default number of completed frames retained by [Profiler].
+pub const PROFILE_HISTORY: usize = 300;
+
+/// Number of frames that can be in flight before their queries are read
+/// back. Frames are timed only on the CPU while all slots are in flight.
+const SLOTS: usize = 4;
+
+/// Number of 64-bit counters written by each pipeline-statistics query.
+const N_STATISTICS: usize = 5;
+
+type MapFuture =
+    Pin<Box<dyn Future<Output = result::Result<(), wgpu::BufferAsyncError>> + Send>>;
+
+/// Command recorders that can be instrumented by [Profiler], implemented for
+/// [wgpu::CommandEncoder], [wgpu::RenderPass] and [wgpu::ComputePass].
+pub trait ProfileTarget {
+    fn write_profile_timestamp(&mut self, query_set: &wgpu::QuerySet, index: u32);
+
+    /// Begin a pipeline-statistics query, return false if the recorder does
+    /// not support pipeline-statistics.
+    fn begin_profile_statistics(
+        &mut self,
+        query_set: &wgpu::QuerySet,
+        index: u32,
+    ) -> bool;
+
+    fn end_profile_statistics(&mut self);
+}
+
+impl ProfileTarget for wgpu::CommandEncoder {
+    fn write_profile_timestamp(&mut self, query_set: &wgpu::QuerySet, index: u32) {
+        self.write_timestamp(query_set, index)
+    }
+
+    fn begin_profile_statistics(&mut self, _: &wgpu::QuerySet, _: u32) -> bool {
+        false
+    }
+
+    fn end_profile_statistics(&mut self) {}
+}
+
+impl<'a> ProfileTarget for wgpu::RenderPass<'a> {
+    fn write_profile_timestamp(&mut self, query_set: &wgpu::QuerySet, index: u32) {
+        self.write_timestamp(query_set, index)
+    }
+
+    fn begin_profile_statistics(
+        &mut self,
+        query_set: &wgpu::QuerySet,
+        index: u32,
+    ) -> bool {
+        self.begin_pipeline_statistics_query(query_set, index);
+        true
+    }
+
+    fn end_profile_statistics(&mut self) {
+        self.end_pipeline_statistics_query()
+    }
+}
+
+impl<'a> ProfileTarget for wgpu::ComputePass<'a> {
+    fn write_profile_timestamp(&mut self, query_set: &wgpu::QuerySet, index: u32) {
+        self.write_timestamp(query_set, index)
+    }
+
+    fn begin_profile_statistics(
+        &mut self,
+        query_set: &wgpu::QuerySet,
+        index: u32,
+    ) -> bool {
+        self.begin_pipeline_statistics_query(query_set, index);
+        true
+    }
+
+    fn end_profile_statistics(&mut self) {
+        self.end_pipeline_statistics_query()
+    }
+}
+
+/// Type to profile frames using named, nested scopes.
+///
+/// Every scope is timed on the CPU. When the device supports
+/// `timestamp_query`, refer [pretty::features], scopes are also timed on the
+/// GPU, and when the device supports `pipeline_statistics_query`, scopes
+/// opened on a render-pass or compute-pass also count shader invocations.
+/// Queries are read back asynchronously, hence a frame's profile is available
+/// a few frames after it was submitted.
+///
+/// * Call [Profiler::begin_scope] and [Profiler::end_scope] around commands,
+///   scopes can be nested. The first scope after [Profiler::end_frame] begins
+///   a new frame.
+/// * Call [Profiler::end_frame] after the last scope, with the last encoder
+///   of the frame.
+/// * Call [Profiler::submitted] after submitting that encoder.
+/// * Call [Profiler::collect] to gather profiles of completed frames.
+///
+/// A scope opened on a pass must be closed on the same pass.
+/// [Render][crate::Render] profiles every frame by default.
+pub struct Profiler {
+    timestamps: Option<wgpu::QuerySet>,
+    statistics: Option<wgpu::QuerySet>,
+    period: f32,
+    origin: time::Instant,
+    slots: Vec<Slot>,
+    current: usize,
+    recording: Option<Recording>,
+    ended: Option<Recording>,
+    frames: VecDeque<ProfileFrame>,
+    history: usize,
+    n_frames: u64,
+}
+
+struct Slot {
+    timestamps: Option<wgpu::Buffer>,
+    statistics: Option<wgpu::Buffer>,
+    pending: Option<Pending>,
+}
+
+struct Pending {
+    recording: Recording,
+    timestamps: Request,
+    statistics: Request,
+}
+
+enum Request {
+    None,
+    Waiting(MapFuture),
+    Mapped,
+    Failed,
+}
+
+struct Recording {
+    frame: u64,
+    slot: Option<usize>,
+    records: Vec<Record>,
+    stack: Vec<usize>,
+    n_timestamps: u32,
+    n_statistics: u32,
+    statistics_open: bool,
+}
+
+struct Record {
+    name: String,
+    parent: Option<usize>,
+    begin: time::Duration,
+    end: time::Duration,
+    timestamp: Option<u32>,
+    statistics: Option<u32>,
+    ticks: Option<(u64, u64)>,
+    counters: Option<[u64; N_STATISTICS]>,
+}
+
+/// Profile of a single frame, as a tree of scopes.
+#[derive(Clone, Debug, Default)]
+pub struct ProfileFrame {
+    pub frame: u64,
+    /// Top level scopes, in the order they were opened.
+    pub scopes: Vec<ProfileScope>,
+}
+
+/// Profile of a single scope and its nested scopes.
+#[derive(Clone, Debug)]
+pub struct ProfileScope {
+    pub name: String,
+    pub cpu: Span,
+    /// GPU span is aligned with the CPU clock at the beginning of the frame's
+    /// first GPU-timed scope, hence comparable with CPU spans only within a
+    /// frame.
+    pub gpu: Option<Span>,
+    pub statistics: Option<PipelineStatistics>,
+    pub children: Vec<ProfileScope>,
+}
+
+/// Time span, relative to the creation of [Profiler].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Span {
+    pub begin: time::Duration,
+    pub end: time::Duration,
+}
+
+/// Counters from a pipeline-statistics query.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PipelineStatistics {
+    pub vertex_shader_invocations: u64,
+    pub clipper_invocations: u64,
+    pub clipper_primitives_out: u64,
+    pub fragment_shader_invocations: u64,
+    pub compute_shader_invocations: u64,
+}
+
+impl Span {
+    pub fn to_duration(&self) -> time::Duration {
+        self.end.saturating_sub(self.begin)
+    }
+}
+
+impl From<[u64; N_STATISTICS]> for PipelineStatistics {
+    fn from(counters: [u64; N_STATISTICS]) -> PipelineStatistics {
+        PipelineStatistics {
+            vertex_shader_invocations: counters[0],
+            clipper_invocations: counters[1],
+            clipper_primitives_out: counters[2],
+            fragment_shader_invocations: counters[3],
+            compute_shader_invocations: counters[4],
+        }
+    }
+}
+
+impl fmt::Display for ProfileFrame {
+    fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
+        writeln!(f, "frame {}", self.frame)?;
+        for scope in self.scopes.iter() {
+            scope.fmt_tree(f, 1)?
+        }
+        Ok(())
+    }
+}
+
+impl ProfileScope {
+    fn fmt_tree(
+        &self,
+        f: &mut fmt::Formatter,
+        depth: usize,
+    ) -> result::Result<(), fmt::Error> {
+        let ms = |d: time::Duration| d.as_secs_f64() * 1000.0;
+
+        let name = format!("{:width$}{}", "", self.name, width = depth * 2);
+        write!(f, "{:<40} cpu:{:.3}ms", name, ms(self.cpu.to_duration()))?;
+        match self.gpu {
+            Some(gpu) => write!(f, " gpu:{:.3}ms", ms(gpu.to_duration()))?,
+            None => write!(f, " gpu:-")?,
+        }
+        if let Some(s) = self.statistics {
+            write!(
+                f,
+                " vs:{} prims:{}/{} fs:{} cs:{}",
+                s.vertex_shader_invocations,
+                s.clipper_primitives_out,
+                s.clipper_invocations,
+                s.fragment_shader_invocations,
+                s.compute_shader_invocations
+            )?;
+        }
+        writeln!(f)?;
+
+        for child in self.children.iter() {
+            child.fmt_tree(f, depth + 1)?
+        }
+        Ok(())
+    }
+}
+
+impl Profiler {
+    /// Create a profiler for `device`, GPU queries are used only if the
+    /// device supports them.
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Profiler {
+        let features = device.features();
+
+        let timestamps = match pretty::is_feature_enabled(features, "timestamp_query") {
+            true => {
+                let desc = wgpu::QuerySetDescriptor {
+                    label: Some("util/profiler:timestamps"),
+                    ty: wgpu::QueryType::Timestamp,
+                    count: (SLOTS as u32) * MAX_SCOPES * 2,
+                };
+                Some(device.create_query_set(&desc))
+            }
+            false => None,
+        };
+        let statistics =
+            match pretty::is_feature_enabled(features, "pipeline_statistics_query") {
+                true => {
+                    let desc = wgpu::QuerySetDescriptor {
+                        label: Some("util/profiler:statistics"),
+                        ty: wgpu::QueryType::PipelineStatistics(
+                            wgpu::PipelineStatisticsTypes::all(),
+                        ),
+                        count: (SLOTS as u32) * MAX_SCOPES,
+                    };
+                    Some(device.create_query_set(&desc))
+                }
+                false => None,
+            };
+
+        let readback = |label, size: usize| {
+            let desc = wgpu::BufferDescriptor {
+                label: Some(label),
+                size: size as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            };
+            device.create_buffer(&desc)
+        };
+        let slots = (0..SLOTS)
+            .map(|_| Slot {
+                timestamps: timestamps.as_ref().map(|_| {
+                    let size = (MAX_SCOPES as usize) * 2 * (wgpu::QUERY_SIZE as usize);
+                    readback("util/profiler:timestamps-readback", size)
+                }),
+                statistics: statistics.as_ref().map(|_| {
+                    let size = (MAX_SCOPES as usize) * N_STATISTICS * 8;
+                    readback("util/profiler:statistics-readback", size)
+                }),
+                pending: None,
+            })
+            .collect();
+
+        Profiler {
+            timestamps,
+            statistics,
+            period: queue.get_timestamp_period(),
+            origin: time::Instant::now(),
+            slots,
+            current: 0,
+            recording: None,
+            ended: None,
+            frames: VecDeque::with_capacity(PROFILE_HISTORY),
+            history: PROFILE_HISTORY,
+            n_frames: 0,
+        }
+    }
+
+    /// Set the number of completed frames to retain.
+    pub fn set_history(&mut self, history: usize) -> &mut Self {
+        self.history = history.max(1);
+        while self.frames.len() > self.history {
+            self.frames.pop_front();
+        }
+        self
+    }
+
+    /// Open a scope `name`, nested within the currently open scope if any.
+    pub fn begin_scope<E>(&mut self, name: &str, encoder: &mut E)
+    where
+        E: ProfileTarget,
+    {
+        if self.recording.is_none() {
+            self.begin_frame()
+        }
+
+        let begin = self.origin.elapsed();
+        let rec = self.recording.as_mut().unwrap();
+        let index = rec.records.len();
+
+        let timestamp = match (self.timestamps.as_ref(), rec.slot) {
+            (Some(qs), Some(slot)) if rec.n_timestamps < MAX_SCOPES * 2 => {
+                let q = rec.n_timestamps;
+                encoder.write_profile_timestamp(qs, to_query(slot, MAX_SCOPES * 2, q));
+                rec.n_timestamps += 2;
+                Some(q)
+            }
+            _ => None,
+        };
+        let statistics = match (self.statistics.as_ref(), rec.slot) {
+            (Some(qs), Some(slot))
+                if !rec.statistics_open && rec.n_statistics < MAX_SCOPES =>
+            {
+                let q = rec.n_statistics;
+                match encoder.begin_profile_statistics(qs, to_query(slot, MAX_SCOPES, q))
+                {
+                    true => {
+                        rec.n_statistics += 1;
+                        rec.statistics_open = true;
+                        Some(q)
+                    }
+                    false => None,
+                }
+            }
+            _ => None,
+        };
+
+        rec.records.push(Record {
+            name: name.to_string(),
+            parent: rec.stack.last().copied(),
+            begin,
+            end: begin,
+            timestamp,
+            statistics,
+            ticks: None,
+            counters: None,
+        });
+        rec.stack.push(index);
+    }
+
+    /// Close the most recently opened scope.
+    pub fn end_scope<E>(&mut self, encoder: &mut E)
+    where
+        E: ProfileTarget,
+    {
+        let end = self.origin.elapsed();
+        let rec = match self.recording.as_mut() {
+            Some(rec) if !rec.stack.is_empty() => rec,
+            _ => {
+                warn!("profiler end_scope without a matching begin_scope");
+                return;
+            }
+        };
+
+        let index = rec.stack.pop().unwrap();
+        let record = &mut rec.records[index];
+        record.end = end;
+
+        if let (Some(qs), Some(slot), Some(q)) =
+            (self.timestamps.as_ref(), rec.slot, record.timestamp)
+        {
+            encoder.write_profile_timestamp(qs, to_query(slot, MAX_SCOPES * 2, q + 1));
+        }
+        if record.statistics.is_some() {
+            encoder.end_profile_statistics();
+            rec.statistics_open = false;
+        }
+    }
+
+    /// End the current frame, scopes that are still open are closed on
+    /// `encoder`. Queries are resolved into read back buffers using `encoder`,
+    /// which shall be the last encoder submitted for the frame.
+    pub fn end_frame(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let n = match self.recording.as_ref() {
+            Some(rec) => rec.stack.len(),
+            None => return,
+        };
+        if n > 0 {
+            warn!("profiler end_frame with {} open scopes", n);
+        }
+        (0..n).for_each(|_| self.end_scope(encoder));
+
+        let rec = self.recording.take().unwrap();
+        if let Some(slot) = rec.slot {
+            let buffers = &self.slots[slot];
+            if let (Some(qs), Some(buf)) =
+                (self.timestamps.as_ref(), buffers.timestamps.as_ref())
+            {
+                if rec.n_timestamps > 0 {
+                    let base = to_query(slot, MAX_SCOPES * 2, 0);
+                    encoder.resolve_query_set(
+                        qs,
+                        base..(base + rec.n_timestamps),
+                        buf,
+                        0,
+                    );
+                }
+            }
+            if let (Some(qs), Some(buf)) =
+                (self.statistics.as_ref(), buffers.statistics.as_ref())
+            {
+                if rec.n_statistics > 0 {
+                    let base = to_query(slot, MAX_SCOPES, 0);
+                    encoder.resolve_query_set(
+                        qs,
+                        base..(base + rec.n_statistics),
+                        buf,
+                        0,
+                    );
+                }
+            }
+        }
+        self.ended = Some(rec);
+    }
+
+    /// Request read back of queries for the frame ended by
+    /// [Profiler::end_frame], shall be called after submitting its encoder.
+    pub fn submitted(&mut self) {
+        let rec = match self.ended.take() {
+            Some(rec) => rec,
+            None => return,
+        };
+
+        let slot = match rec.slot {
+            Some(slot) if rec.n_timestamps > 0 || rec.n_statistics > 0 => slot,
+            _ => return self.complete(rec),
+        };
+
+        let buffers = &mut self.slots[slot];
+        let request = |buf: Option<&wgpu::Buffer>, n: u32| match buf {
+            Some(buf) if n > 0 => {
+                let fut = buf.slice(..).map_async(wgpu::MapMode::Read);
+                Request::Waiting(Box::pin(fut))
+            }
+            _ => Request::None,
+        };
+        buffers.pending = Some(Pending {
+            timestamps: request(buffers.timestamps.as_ref(), rec.n_timestamps),
+            statistics: request(buffers.statistics.as_ref(), rec.n_statistics),
+            recording: rec,
+        });
+
+        self.current = (self.current + 1) % SLOTS;
+    }
+
+    /// Gather profiles of frames whose queries are read back since the last
+    /// call, return the number of completed frames. Does not block.
+    pub fn collect(&mut self, device: &wgpu::Device) -> usize {
+        device.poll(wgpu::Maintain::Poll);
+
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut n = 0;
+
+        // visit slots starting from the oldest submission.
+        for i in 0..SLOTS {
+            let slot = (self.current + i) % SLOTS;
+            let buffers = &mut self.slots[slot];
+            let pending = match buffers.pending.as_mut() {
+                Some(pending) => pending,
+                None => continue,
+            };
+
+            pending.timestamps.poll(&mut cx);
+            pending.statistics.poll(&mut cx);
+            if pending.timestamps.is_waiting() || pending.statistics.is_waiting() {
+                continue;
+            }
+
+            let Pending { mut recording, timestamps, statistics } =
+                buffers.pending.take().unwrap();
+            if let (Request::Mapped, Some(buf)) =
+                (timestamps, buffers.timestamps.as_ref())
+            {
+                {
+                    let data = buf.slice(..).get_mapped_range();
+                    recording.set_timestamps(bytemuck::cast_slice(&data));
+                }
+                buf.unmap();
+            }
+            if let (Request::Mapped, Some(buf)) =
+                (statistics, buffers.statistics.as_ref())
+            {
+                {
+                    let data = buf.slice(..).get_mapped_range();
+                    recording.set_statistics(bytemuck::cast_slice(&data));
+                }
+                buf.unmap();
+            }
+
+            self.complete(recording);
+            n += 1;
+        }
+
+        n
+    }
+
+    fn begin_frame(&mut self) {
+        if let Some(rec) = self.ended.take() {
+            warn!("profiler frame {} ended but not submitted", rec.frame);
+            let rec = Recording { slot: None, ..rec };
+            self.complete(rec);
+        }
+
+        let gpu = self.timestamps.is_some() || self.statistics.is_some();
+        let slot = match self.slots[self.current].pending {
+            None if gpu => Some(self.current),
+            _ => None,
+        };
+        self.recording = Some(Recording::new(self.n_frames, slot));
+        self.n_frames += 1;
+    }
+
+    fn complete(&mut self, rec: Recording) {
+        if self.frames.len() >= self.history {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(rec.into_frame(self.period))
+    }
+}
+
+impl Profiler {
+    /// Return true if scopes are timed on the GPU.
+    pub fn is_timestamp(&self) -> bool {
+        self.timestamps.is_some()
+    }
+
+    /// Return true if scopes opened on passes count shader invocations.
+    pub fn is_statistics(&self) -> bool {
+        self.statistics.is_some()
+    }
+
+    /// Iterate over completed frames, oldest first.
+    pub fn iter_frames(&self) -> impl Iterator<Item = &ProfileFrame> + '_ {
+        self.frames.iter()
+    }
+
+    /// Return the most recently completed frame.
+    pub fn to_last_frame(&self) -> Option<ProfileFrame> {
+        self.frames.back().cloned()
+    }
+
+    /// Return completed frames in Chrome trace-event JSON format, viewable via
+    /// chrome://tracing or Perfetto. CPU and GPU spans are shown as separate
+    /// threads.
+    pub fn to_chrome_trace(&self) -> Result<String> {
+        to_chrome_trace(self.frames.iter())
+    }
+
+    /// Save completed frames in Chrome trace-event JSON format, refer
+    /// [Profiler::to_chrome_trace].
+    pub fn save_chrome_trace<P>(&self, loc: P) -> Result<()>
+    where
+        P: AsRef<path::Path>,
+    {
+        let text = self.to_chrome_trace()?;
+        err_at!(IOError, fs::write(loc.as_ref(), text), "{:?}", loc.as_ref())
+    }
+}
+
+impl Request {
+    fn poll(&mut self, cx: &mut Context) {
+        if let Request::Waiting(fut) = self {
+            *self = match fut.as_mut().poll(cx) {
+                Poll::Ready(Ok(())) => Request::Mapped,
+                Poll::Ready(Err(err)) => {
+                    warn!("profiler read back {}", err);
+                    Request::Failed
+                }
+                Poll::Pending => return,
+            }
+        }
+    }
+
+    fn is_waiting(&self) -> bool {
+        matches!(self, Request::Waiting(_))
+    }
+}
+
+impl Recording {
+    fn new(frame: u64, slot: Option<usize>) -> Recording {
+        Recording {
+            frame,
+            slot,
+            records: vec![],
+            stack: vec![],
+            n_timestamps: 0,
+            n_statistics: 0,
+            statistics_open: false,
+        }
+    }
+
+    fn set_timestamps(&mut self, ticks: &[u64]) {
+        for r in self.records.iter_mut() {
+            if let Some(q) = r.timestamp {
+                r.ticks = Some((ticks[q as usize], ticks[q as usize + 1]));
+            }
+        }
+    }
+
+    fn set_statistics(&mut self, counters: &[u64]) {
+        for r in self.records.iter_mut() {
+            if let Some(q) = r.statistics {
+                let off = (q as usize) * N_STATISTICS;
+                let mut cs = [0; N_STATISTICS];
+                cs.copy_from_slice(&counters[off..(off + N_STATISTICS)]);
+                r.counters = Some(cs);
+            }
+        }
+    }
+
+    /// Build the scope tree, `period` is nanoseconds per timestamp tick.
+    fn into_frame(self, period: f32) -> ProfileFrame {
+        // align GPU ticks with the CPU clock at the first GPU-timed scope.
+        let origin = self
+            .records
+            .iter()
+            .filter_map(|r| r.ticks.map(|(t, _)| (t, r.begin)))
+            .min_by_key(|(t, _)| *t);
+        let to_span = |(begin, end): (u64, u64)| {
+            let (t0, cpu) = origin.unwrap();
+            let nanos = |t: u64| (t.saturating_sub(t0) as f64 * period as f64) as u64;
+            Span {
+                begin: cpu + time::Duration::from_nanos(nanos(begin)),
+                end: cpu + time::Duration::from_nanos(nanos(end.max(begin))),
+            }
+        };
+
+        let mut scopes: Vec<(Option<usize>, ProfileScope)> = self
+            .records
+            .into_iter()
+            .map(|r| {
+                let scope = ProfileScope {
+                    name: r.name,
+                    cpu: Span { begin: r.begin, end: r.end },
+                    gpu: r.ticks.map(to_span),
+                    statistics: r.counters.map(PipelineStatistics::from),
+                    children: vec![],
+                };
+                (r.parent, scope)
+            })
+            .collect();
+
+        // parents precede their children, attach children in reverse order.
+        let mut roots = vec![];
+        while let Some((parent, scope)) = scopes.pop() {
+            match parent {
+                Some(parent) => scopes[parent].1.children.insert(0, scope),
+                None => roots.insert(0, scope),
+            }
+        }
+
+        ProfileFrame { frame: self.frame, scopes: roots }
+    }
+}
+
+fn to_query(slot: usize, per_slot: u32, index: u32) -> u32 {
+    (slot as u32) * per_slot + index
+}
+
+fn to_chrome_trace<'a, I>(frames: I) -> Result<String>
+where
+    I: Iterator<Item = &'a ProfileFrame>,
+{
+    use serde_json::{json, Value};
+
+    fn add_events(events: &mut Vec<Value>, frame: u64, scope: &ProfileScope) {
+        let micros = |d: time::Duration| d.as_secs_f64() * 1_000_000.0;
+
+        events.push(json!({
+            "name": scope.name,
+            "cat": "cpu",
+            "ph": "X",
+            "pid": 1,
+            "tid": 1,
+            "ts": micros(scope.cpu.begin),
+            "dur": micros(scope.cpu.to_duration()),
+            "args": { "frame": frame },
+        }));
+        if let Some(gpu) = scope.gpu {
+            let mut args = json!({ "frame": frame });
+            if let Some(s) = scope.statistics {
+                args["vertex_shader_invocations"] = s.vertex_shader_invocations.into();
+                args["clipper_invocations"] = s.clipper_invocations.into();
+                args["clipper_primitives_out"] = s.clipper_primitives_out.into();
+                args["fragment_shader_invocations"] =
+                    s.fragment_shader_invocations.into();
+                args["compute_shader_invocations"] = s.compute_shader_invocations.into();
+            }
+            events.push(json!({
+                "name": scope.name,
+                "cat": "gpu",
+                "ph": "X",
+                "pid": 1,
+                "tid": 2,
+                "ts": micros(gpu.begin),
+                "dur": micros(gpu.to_duration()),
+                "args": args,
+            }));
+        }
+        for child in scope.children.iter() {
+            add_events(events, frame, child)
+        }
+    }
+
+    let mut events = vec![
+        json!({"name": "thread_name", "ph": "M", "pid": 1, "tid": 1, "args": {"name": "cpu"}}),
+        json!({"name": "thread_name", "ph": "M", "pid": 1, "tid": 2, "args": {"name": "gpu"}}),
+    ];
+    for frame in frames {
+        for scope in frame.scopes.iter() {
+            add_events(&mut events, frame.frame, scope)
+        }
+    }
+
+    let trace = json!({ "traceEvents": events, "displayTimeUnit": "ms" });
+    err_at!(FailConvert, serde_json::to_string(&trace))
+}
+
+#[cfg(test)]
+#[path = "profiler_test.rs"]
+mod profiler_test;
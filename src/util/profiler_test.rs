@@ -0,0 +1,120 @@
+use super::*;
+
+fn record(name: &str, parent: Option<usize>, begin: u64, end: u64) -> Record {
+    Record {
+        name: name.to_string(),
+        parent,
+        begin: time::Duration::from_micros(begin),
+        end: time::Duration::from_micros(end),
+        timestamp: None,
+        statistics: None,
+        ticks: None,
+        counters: None,
+    }
+}
+
+fn make_recording() -> Recording {
+    let mut rec = Recording::new(7, Some(0));
+    rec.records = vec![
+        record("frame", None, 100, 900),
+        record("clear", Some(0), 110, 200),
+        record("mesh", Some(0), 200, 700),
+        record("mesh:pass", Some(2), 210, 690),
+        record("present", None, 950, 990),
+    ];
+    rec.records[0].timestamp = Some(0);
+    rec.records[3].timestamp = Some(2);
+    rec.records[3].statistics = Some(0);
+    rec.n_timestamps = 4;
+    rec.n_statistics = 1;
+    rec
+}
+
+#[test]
+fn test_profile_tree() {
+    let frame = Recording::new(3, None).into_frame(1.0);
+    assert_eq!(frame.frame, 3);
+    assert!(frame.scopes.is_empty());
+
+    let frame = make_recording().into_frame(1.0);
+    assert_eq!(frame.frame, 7);
+
+    let names: Vec<&str> = frame.scopes.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["frame", "present"]);
+
+    let root = &frame.scopes[0];
+    let names: Vec<&str> = root.children.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, vec!["clear", "mesh"]);
+    assert_eq!(root.children[1].children.len(), 1);
+    assert_eq!(root.children[1].children[0].name, "mesh:pass");
+    assert!(frame.scopes[1].children.is_empty());
+
+    assert_eq!(root.cpu.to_duration(), time::Duration::from_micros(800));
+    assert!(root.gpu.is_none());
+    assert!(root.statistics.is_none());
+}
+
+#[test]
+fn test_profile_gpu() {
+    let mut rec = make_recording();
+    rec.set_timestamps(&[1000, 1800, 1200, 1600]);
+    rec.set_statistics(&[36, 12, 10, 4096, 0]);
+
+    // 2 nanoseconds per tick.
+    let frame = rec.into_frame(2.0);
+    let root = &frame.scopes[0];
+    let pass = &root.children[1].children[0];
+
+    let gpu = root.gpu.unwrap();
+    assert_eq!(gpu.begin, time::Duration::from_micros(100));
+    assert_eq!(gpu.to_duration(), time::Duration::from_nanos(1600));
+
+    let gpu = pass.gpu.unwrap();
+    assert_eq!(gpu.begin, time::Duration::from_nanos(100_400));
+    assert_eq!(gpu.to_duration(), time::Duration::from_nanos(800));
+
+    let stats = pass.statistics.unwrap();
+    assert_eq!(stats.vertex_shader_invocations, 36);
+    assert_eq!(stats.clipper_invocations, 12);
+    assert_eq!(stats.clipper_primitives_out, 10);
+    assert_eq!(stats.fragment_shader_invocations, 4096);
+    assert_eq!(stats.compute_shader_invocations, 0);
+
+    assert!(root.children[0].gpu.is_none());
+    assert!(frame.scopes[1].gpu.is_none());
+
+    let text = frame.to_string();
+    assert!(text.starts_with("frame 7\n"), "{}", text);
+    assert_eq!(text.lines().count(), 6, "{}", text);
+    assert!(text.contains("fs:4096"), "{}", text);
+    assert!(text.contains("      mesh:pass"), "{}", text);
+}
+
+#[test]
+fn test_chrome_trace() {
+    let mut rec = make_recording();
+    rec.set_timestamps(&[1000, 1800, 1200, 1600]);
+    rec.set_statistics(&[36, 12, 10, 4096, 0]);
+    let frames = vec![rec.into_frame(1.0), make_recording().into_frame(1.0)];
+
+    let text = to_chrome_trace(frames.iter()).unwrap();
+    let trace: serde_json::Value = serde_json::from_str(&text).unwrap();
+    let events = trace["traceEvents"].as_array().unwrap();
+
+    // 2 metadata events, 5 cpu scopes per frame, 2 gpu scopes in first frame.
+    assert_eq!(events.len(), 2 + 5 + 2 + 5);
+
+    let gpu: Vec<&serde_json::Value> =
+        events.iter().filter(|e| e["cat"] == "gpu").collect();
+    assert_eq!(gpu.len(), 2);
+    assert_eq!(gpu[1]["name"], "mesh:pass");
+    assert_eq!(gpu[1]["tid"], 2);
+    assert_eq!(gpu[1]["args"]["fragment_shader_invocations"], 4096);
+
+    let cpu = &events[2];
+    assert_eq!(cpu["name"], "frame");
+    assert_eq!(cpu["ph"], "X");
+    assert_eq!(cpu["ts"], 100.0);
+    assert_eq!(cpu["dur"], 800.0);
+    assert_eq!(cpu["args"]["frame"], 7);
+}
@@ -0,0 +1,85 @@
+use std::{fs, path, time};
+
+/// Type implement a polling file watcher, used for hot-reload in development
+/// mode. Files are checked for modification time no more than once every
+/// `interval`, hence polling can be done on every frame.
+pub struct Watcher {
+    interval: time::Duration,
+    last_poll: Option<time::Instant>,
+    files: Vec<(path::PathBuf, Option<time::SystemTime>)>,
+}
+
+impl Default for Watcher {
+    fn default() -> Watcher {
+        Watcher::new(Watcher::INTERVAL)
+    }
+}
+
+impl Watcher {
+    /// Default interval between polls.
+    pub const INTERVAL: time::Duration = time::Duration::from_millis(250);
+
+    pub fn new(interval: time::Duration) -> Watcher {
+        Watcher { interval, last_poll: None, files: vec![] }
+    }
+
+    /// Watch file at `loc`, it is okay for the file to not exist yet, in which
+    /// case its creation is reported as a change.
+    pub fn watch<P>(&mut self, loc: P) -> &mut Self
+    where
+        P: AsRef<path::Path>,
+    {
+        let loc = loc.as_ref().to_path_buf();
+        if !self.files.iter().any(|(f, _)| f == &loc) {
+            let modified = to_modified(&loc);
+            self.files.push((loc, modified));
+        }
+        self
+    }
+
+    /// Stop watching file at `loc`.
+    pub fn unwatch<P>(&mut self, loc: P) -> &mut Self
+    where
+        P: AsRef<path::Path>,
+    {
+        self.files.retain(|(f, _)| f != loc.as_ref());
+        self
+    }
+
+    pub fn to_files(&self) -> Vec<path::PathBuf> {
+        self.files.iter().map(|(f, _)| f.clone()).collect()
+    }
+
+    /// Return files modified, created or removed since last poll. Return
+    /// empty list if called again within `interval`.
+    pub fn poll(&mut self) -> Vec<path::PathBuf> {
+        match self.last_poll {
+            Some(last_poll) if last_poll.elapsed() < self.interval => vec![],
+            _ => {
+                self.last_poll = Some(time::Instant::now());
+                self.poll_now()
+            }
+        }
+    }
+
+    /// Same as [Watcher::poll], but ignores the interval.
+    pub fn poll_now(&mut self) -> Vec<path::PathBuf> {
+        let mut changed = vec![];
+        for (loc, modified) in self.files.iter_mut() {
+            let latest = to_modified(loc);
+            if latest != *modified {
+                *modified = latest;
+                changed.push(loc.clone());
+            }
+        }
+        changed
+    }
+}
+
+fn to_modified(loc: &path::Path) -> Option<time::SystemTime> {
+    fs::metadata(loc).and_then(|m| m.modified()).ok()
+}
+
+#[cfg(test)]
+#[path = "watch_test.rs"]
+mod watch_test;
@@ -0,0 +1,29 @@
+use std::{fs, time};
+
+use super::*;
+
+#[test]
+fn test_watcher() {
+    let dir = std::env::temp_dir().join("gpgpu-watcher-test");
+    fs::create_dir_all(&dir).unwrap();
+    let (a, b) = (dir.join("a.wgsl"), dir.join("b.txt"));
+    fs::write(&a, "a").unwrap();
+    fs::remove_file(&b).ok();
+
+    let mut watcher = Watcher::new(time::Duration::from_secs(3600));
+    watcher.watch(&a).watch(&b).watch(&a);
+    assert_eq!(watcher.to_files(), vec![a.clone(), b.clone()]);
+    assert!(watcher.poll().is_empty());
+
+    let past = time::SystemTime::now() - time::Duration::from_secs(60);
+    fs::File::options().write(true).open(&a).unwrap().set_modified(past).unwrap();
+    fs::write(&b, "b").unwrap();
+    // within interval.
+    assert!(watcher.poll().is_empty());
+    assert_eq!(watcher.poll_now(), vec![a.clone(), b.clone()]);
+    assert!(watcher.poll_now().is_empty());
+
+    fs::remove_file(&b).unwrap();
+    watcher.unwatch(&a);
+    assert_eq!(watcher.poll_now(), vec![b]);
+}